@@ -2,20 +2,59 @@
 
 use clap::{value_parser, Arg, ArgAction, Command};
 use notify::{DebouncedEvent, RecursiveMode, Watcher};
-use piqued::workspace::workspace::Workspace;
+use piqued::workspace::workspace::{error_message, GenerationMode, Workspace};
 use std::sync::mpsc::channel;
 use std::time::Duration;
-use std::{env, path::PathBuf, sync::Arc};
+use std::{env, path::PathBuf, process, sync::Arc};
 use tokio::fs;
 
 use piqued::config::config::Config;
+use piqued::query::query::Query;
+
+#[derive(Debug)]
+enum CliCommand {
+    Explain { name: String, analyze: bool },
+    ConfigSchema,
+    List { json: bool },
+    Schema,
+}
+
+// What the process exits with, which CI can rely on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExitStatus {
+    Success = 0,
+    // Code couldn't be generated, or a query didn't typecheck
+    GenerationError = 1,
+    // `--check` found generated files that are out of date
+    Drift = 2,
+}
 
 #[derive(Debug)]
 struct CliOptions {
-    pub config_path: Option<String>,
+    pub command: Option<CliCommand>,
+    pub config_path: Option<PathBuf>,
     pub watch: bool,
     pub no_emit: bool,
+    pub check: bool,
+    pub schema_only: bool,
+    pub no_cache: bool,
     pub verbose: bool,
+    pub quiet: bool,
+}
+
+// Progress goes to stderr, so that it never ends up in output like `list --format json`
+impl CliOptions {
+    fn info(&self, message: &str) {
+        if !self.quiet {
+            eprintln!("{}", message);
+        }
+    }
+
+    fn debug(&self, message: &str) {
+        if self.verbose && !self.quiet {
+            eprintln!("{}", message);
+        }
+    }
 }
 
 fn get_args() -> CliOptions {
@@ -44,6 +83,25 @@ fn get_args() -> CliOptions {
                 .required(false)
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .required(false)
+                .conflicts_with_all(["watch", "no-emit"])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("schema-only")
+                .long("schema-only")
+                .required(false)
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("no-cache")
+                .long("no-cache")
+                .required(false)
+                .action(ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("verbose")
                 .short('v')
@@ -51,40 +109,282 @@ fn get_args() -> CliOptions {
                 .required(false)
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .required(false)
+                .conflicts_with("verbose")
+                .action(ArgAction::SetTrue),
+        )
+        .subcommand(
+            Command::new("explain")
+                .about("Print the query plan for a named query")
+                .arg(
+                    Arg::new("name")
+                        .short('n')
+                        .long("name")
+                        .required(true)
+                        .num_args(1)
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("analyze")
+                        .long("analyze")
+                        .required(false)
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("config-schema").about("Print a JSON Schema describing piqued.toml"),
+        )
+        .subcommand(
+            Command::new("list")
+                .about("List every query in the workspace")
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .required(false)
+                        .num_args(1)
+                        .default_value("text")
+                        .value_parser(["text", "json"])
+                        .action(ArgAction::Set),
+                ),
+        )
+        .subcommand(
+            Command::new("schema")
+                .about("Print the tables and types piqued loaded from the database as JSON"),
+        )
         .get_matches();
 
-    let config_path = matches.get_one::<String>("config").map(|x| x.to_owned());
+    let command = match matches.subcommand() {
+        Some(("explain", sub_matches)) => Some(CliCommand::Explain {
+            name: sub_matches.get_one::<String>("name").unwrap().to_owned(),
+            analyze: sub_matches.get_flag("analyze"),
+        }),
+        Some(("config-schema", _)) => Some(CliCommand::ConfigSchema),
+        Some(("list", sub_matches)) => Some(CliCommand::List {
+            json: sub_matches.get_one::<String>("format").unwrap() == "json",
+        }),
+        Some(("schema", _)) => Some(CliCommand::Schema),
+        _ => None,
+    };
+
+    let config_path = matches.get_one::<PathBuf>("config").map(|x| x.to_owned());
     let watch = matches.get_one::<bool>("watch").unwrap_or(&false);
     let no_emit = matches.get_one::<bool>("no-emit").unwrap_or(&false);
+    let check = matches.get_one::<bool>("check").unwrap_or(&false);
+    let schema_only = matches.get_one::<bool>("schema-only").unwrap_or(&false);
+    let no_cache = matches.get_one::<bool>("no-cache").unwrap_or(&false);
     let verbose = matches.get_one::<bool>("verbose").unwrap_or(&false);
+    let quiet = matches.get_one::<bool>("quiet").unwrap_or(&false);
 
     CliOptions {
+        command,
         config_path,
         watch: watch.clone(),
         no_emit: no_emit.clone(),
+        check: check.clone(),
+        schema_only: schema_only.clone(),
+        no_cache: no_cache.clone(),
         verbose: verbose.clone(),
+        quiet: quiet.clone(),
+    }
+}
+
+// Returns whether every query typechecked
+async fn typecheck(workspace: &Workspace, options: &CliOptions) -> bool {
+    let failures = match workspace.typecheck().await {
+        Ok(failures) => failures,
+        Err(e) => {
+            eprintln!("Error typechecking queries: {:?}", e);
+            return false;
+        }
+    };
+
+    for failure in failures.iter() {
+        eprintln!(
+            "{}:{}: {}",
+            failure.path.display(),
+            failure.line,
+            error_message(&failure.error)
+        );
+    }
+
+    if failures.is_empty() {
+        options.info("All queries typechecked");
+    }
+
+    failures.is_empty()
+}
+
+// Warnings are printed even with `--quiet`, since they point at something that needs fixing
+fn print_warnings(warnings: &[String]) {
+    for warning in warnings {
+        eprintln!("Warning: {}", warning);
+    }
+}
+
+fn generation_mode(options: &CliOptions) -> GenerationMode {
+    if options.schema_only {
+        GenerationMode::SchemaOnly
+    } else {
+        GenerationMode::Full
+    }
+}
+
+async fn check(workspace: &Workspace, options: &CliOptions) -> ExitStatus {
+    let stale = match workspace.check_code(generation_mode(options)).await {
+        Ok(report) => {
+            print_warnings(&report.warnings);
+            report.changed_files
+        }
+        Err(e) => {
+            eprintln!("Error generating code: {:?}", e);
+            return ExitStatus::GenerationError;
+        }
+    };
+
+    for path in stale.iter() {
+        let path = path.strip_prefix(&workspace.root_dir).unwrap_or(path);
+        eprintln!("{} is out of date", path.display());
+    }
+
+    if !stale.is_empty() {
+        return ExitStatus::Drift;
     }
+
+    options.info("Generated code is up to date");
+    ExitStatus::Success
 }
 
-async fn compile_one(workspace: &Workspace, options: &CliOptions) {
+async fn compile_one(workspace: &Workspace, options: &CliOptions) -> ExitStatus {
     if options.no_emit {
-        if options.verbose {
-            println!("Not emitting code");
-            println!("Typecheck-only-mode not currently supported");
+        options.debug("Not emitting code");
+
+        return match typecheck(workspace, options).await {
+            true => ExitStatus::Success,
+            false => ExitStatus::GenerationError,
+        };
+    }
+
+    match workspace.gen_code(generation_mode(options)).await {
+        Ok(report) => {
+            print_warnings(&report.warnings);
+            ExitStatus::Success
         }
+        Err(e) => {
+            eprintln!("Error generating code: {:?}", e);
+            ExitStatus::GenerationError
+        }
+    }
+}
 
-        return;
+async fn explain(workspace: &Workspace, name: &str, analyze: bool) -> ExitStatus {
+    let query = match &workspace.query {
+        Ok(query) => query,
+        Err(e) => {
+            eprintln!("Error connecting to database: {:?}", e);
+            return ExitStatus::GenerationError;
+        }
+    };
+
+    let stmt = match workspace.find_query(name).await {
+        Ok(stmt) => stmt,
+        Err(e) => {
+            eprintln!("Error loading query: {:?}", e);
+            return ExitStatus::GenerationError;
+        }
+    };
+
+    if analyze {
+        eprintln!(
+            "Warning: --analyze executes the query (with all parameters set to null), then rolls back what it changed"
+        );
+    }
+
+    match query.explain(&stmt, analyze).await {
+        Ok(plan) => println!("{}", plan),
+        Err(e) => {
+            eprintln!("Error explaining query: {:?}", e);
+            return ExitStatus::GenerationError;
+        }
     }
 
-    let result = workspace.gen_code().await;
-    if let Err(e) = result {
-        eprintln!("Error generating code: {:?}", e);
+    if let Some(index) = &stmt.details.index {
+        match query.plan_indexes(&stmt).await {
+            Ok(indexes) if !indexes.contains(index) => {
+                eprintln!(
+                    "Warning: the query plan doesn't use the expected index {}",
+                    index
+                )
+            }
+            Ok(_) => (),
+            Err(e) => {
+                eprintln!("Error checking the expected index: {:?}", e);
+                return ExitStatus::GenerationError;
+            }
+        }
     }
 
-    return;
+    ExitStatus::Success
 }
 
-async fn compile_on_change(workspace: &mut Workspace, options: &CliOptions) {
+async fn list(workspace: &Workspace, json: bool) -> ExitStatus {
+    let queries = match workspace.list_queries().await {
+        Ok(queries) => queries,
+        Err(e) => {
+            eprintln!("Error loading queries: {:?}", e);
+            return ExitStatus::GenerationError;
+        }
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&queries).unwrap());
+        return ExitStatus::Success;
+    }
+
+    for query in queries.iter() {
+        let path = query
+            .path
+            .strip_prefix(&workspace.root_dir)
+            .unwrap_or(&query.path);
+
+        println!(
+            "{}\t{}:{}\t{} param(s)",
+            query.name,
+            path.display(),
+            query.line,
+            query.params
+        );
+    }
+
+    ExitStatus::Success
+}
+
+fn schema(workspace: &Workspace) -> ExitStatus {
+    let query = match &workspace.query {
+        Ok(query) => query,
+        Err(e) => {
+            eprintln!("Error connecting to database: {:?}", e);
+            return ExitStatus::GenerationError;
+        }
+    };
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&query.dump_schema()).unwrap()
+    );
+
+    ExitStatus::Success
+}
+
+async fn compile_on_change(
+    workspace: &mut Workspace,
+    options: &CliOptions,
+    config_path: &PathBuf,
+    working_dir: &PathBuf,
+) {
     let (tx, rx) = channel();
     let mut watcher = notify::watcher(tx, Duration::from_millis(200)).unwrap();
 
@@ -95,12 +395,38 @@ async fn compile_on_change(workspace: &mut Workspace, options: &CliOptions) {
         .clone()
         .unwrap_or_else(|| workspace.root_dir.clone());
 
+    // Editors tend to save by replacing the file, so watch the directory it lives in instead
+    if let Some(config_dir) = config_path.parent() {
+        if !config_dir.starts_with(&dir_to_watch) {
+            watcher
+                .watch(config_dir, RecursiveMode::NonRecursive)
+                .unwrap();
+        }
+    }
+
     watcher
         .watch(dir_to_watch, RecursiveMode::Recursive)
         .unwrap();
 
     loop {
         match rx.recv() {
+            Ok(
+                DebouncedEvent::Write(p) | DebouncedEvent::Create(p) | DebouncedEvent::Rename(_, p),
+            ) if &p == config_path => {
+                match Config::load(&Some(config_path.clone()), working_dir).await {
+                    Ok(config) => {
+                        options.info("Config changed, recompiling...");
+                        workspace.reload_config(Arc::new(config)).await;
+                        if let Ok(query) = &workspace.query {
+                            print_warnings(&query.warnings);
+                        }
+                        compile_one(workspace, options).await;
+                    }
+                    Err(e) => {
+                        eprintln!("Error loading config, keeping the previous one:\n{:?}", e);
+                    }
+                }
+            }
             Ok(
                 DebouncedEvent::Write(p)
                 | DebouncedEvent::Create(p)
@@ -108,54 +434,96 @@ async fn compile_on_change(workspace: &mut Workspace, options: &CliOptions) {
                 | DebouncedEvent::Rename(p, _),
             ) => {
                 if workspace.is_compile_target(&p).await {
-                    println!("Change detected, recompiling...");
+                    options.info("Change detected, recompiling...");
                     compile_one(workspace, options).await;
                 }
             }
             Ok(e) => {
-                if options.verbose {
-                    println!("Ignoring event: {:?}", e);
-                }
+                options.debug(&format!("Ignoring event: {:?}", e));
             }
             Err(e) => {
-                println!("Error while watching for changes:\n{:?}", e);
+                eprintln!("Error while watching for changes:\n{:?}", e);
                 return;
             }
         }
     }
 }
 
+// Anything that stops piqued before it gets going is reported the same way a failed generation is
+fn exit_with_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(ExitStatus::GenerationError as i32);
+}
+
 #[tokio::main]
 async fn main() {
     let args = get_args();
 
+    if let Some(CliCommand::ConfigSchema) = &args.command {
+        println!("{}", Config::json_schema());
+        return;
+    }
+
     let working_dir = fs::canonicalize(env::current_dir().unwrap()).await.unwrap();
     let base_path = if let Some(config_path) = &args.config_path {
-        Config::find_file(&fs::canonicalize(config_path).await.unwrap()).await
+        match fs::canonicalize(config_path).await {
+            Ok(config_path) => Config::find_file(&config_path).await,
+            Err(e) => exit_with_error(&format!(
+                "Error finding config {}: {:?}",
+                config_path.display(),
+                e
+            )),
+        }
     } else {
         Config::find_dir(&working_dir).await
     };
 
     let Some(path) = &base_path else {
-        panic!("No config file found in working directory or parent directories");
+        exit_with_error("No config file found in working directory or parent directories");
     };
 
-    if args.verbose {
-        println!("Working directory: {:?}", base_path);
-        println!("Found config path: {:?}", path);
-    }
+    args.debug(&format!("Working directory: {:?}", base_path));
+    args.debug(&format!("Found config path: {:?}", path));
 
-    let config = Config::load(&Some(path.clone()), &working_dir)
-        .await
-        .unwrap();
+    let config = match Config::load(&Some(path.clone()), &working_dir).await {
+        Ok(config) => config,
+        Err(e) => exit_with_error(&format!("Error loading config: {:?}", e)),
+    };
+
+    if args.no_cache {
+        if let Err(e) = Query::invalidate_schema_cache_file(&config).await {
+            eprintln!("Error removing schema cache: {:?}", e);
+        }
+    }
 
     let root = config.workspace.root.as_ref().unwrap().clone();
     let mut workspace = Workspace::new(Arc::new(config), root).await;
+    if let Ok(query) = &workspace.query {
+        print_warnings(&query.warnings);
+    }
 
-    if args.watch {
-        compile_on_change(&mut workspace, &args).await;
+    let status = if let Some(CliCommand::Explain { name, analyze }) = &args.command {
+        explain(&workspace, name, *analyze).await
+    } else if let Some(CliCommand::List { json }) = &args.command {
+        list(&workspace, *json).await
+    } else if let Some(CliCommand::Schema) = &args.command {
+        schema(&workspace)
+    } else if args.watch {
+        compile_on_change(&mut workspace, &args, path, &working_dir).await;
+        ExitStatus::Success
+    } else if args.check {
+        args.info("Checking generated code...");
+        check(&workspace, &args).await
     } else {
-        println!("Compiling...");
-        compile_one(&workspace, &args).await;
+        args.info(if args.no_emit {
+            "Typechecking..."
+        } else {
+            "Compiling..."
+        });
+        compile_one(&workspace, &args).await
+    };
+
+    if status != ExitStatus::Success {
+        process::exit(status as i32);
     }
 }
@@ -23,7 +23,8 @@ impl<'a> CodegenHelper<'a> {
             indent: 0,
             buffer: String::new(),
             pending_token: false,
-            pending_newline: false,
+            // The buffer starts out at the beginning of a line, which may need indenting too
+            pending_newline: true,
         }
     }
 
@@ -81,7 +82,9 @@ impl<'a> CodegenHelper<'a> {
             self.write_helper(data, true);
         }
 
-        self.write_symbol(self.line_terminator);
+        // Written directly, so that blank lines don't pick up the indentation
+        self.buffer.push_str(self.line_terminator);
+        self.pending_token = false;
         self.pending_newline = true;
     }
 
@@ -90,6 +93,10 @@ impl<'a> CodegenHelper<'a> {
     }
 
     fn write_helper(&mut self, data: &str, respect_token: bool) {
+        if data.is_empty() {
+            return;
+        }
+
         if self.pending_newline {
             for _ in 0..self.indent {
                 self.buffer.push_str(self.indent_with);
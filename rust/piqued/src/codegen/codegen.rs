@@ -1,17 +1,28 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
+    env,
     path::{Path, PathBuf},
-    sync::Arc,
+    process,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
 };
 
 use async_recursion::async_recursion;
-use string_builder::Builder;
-use tokio::fs;
+use pg_query::protobuf::ScanToken;
+use tokio::{
+    fs,
+    io::{AsyncReadExt, AsyncWriteExt, BufReader, BufWriter},
+};
 
 use crate::{
-    config::config::Config,
-    parser::parser::{self, ParsedPreparedQuery},
-    query::query::{CustomType, ProbeResponse, Query},
+    codegen::utils::{contains_identifier, to_camel_case},
+    config::config::{Config, ConfigWorkspace},
+    parser::parser::{self, ParsedPreparedQuery, RelocatedStmt},
+    query::query::{Column, CustomType, ProbeResponse, Query},
+    utils::result::{PiquedError, Result},
+    workspace::workspace::error_message,
 };
 
 pub struct ImportResult {
@@ -26,7 +37,29 @@ pub struct SerializationResult {
 
 pub struct QueryContext(pub ParsedPreparedQuery, pub ProbeResponse);
 
-pub trait CodeGenerator {
+impl QueryContext {
+    // Pairs a statement with a probe that didn't necessarily come from a database, which is how
+    // generators are tested offline
+    pub fn from_stmt(
+        stmt: &RelocatedStmt,
+        tokens: &Vec<ScanToken>,
+        contents: &str,
+        probe: ProbeResponse,
+    ) -> Result<QueryContext> {
+        let prepared =
+            parser::get_prepared_statement(stmt, tokens, contents, || "query".to_string())?;
+
+        Ok(QueryContext(prepared, probe))
+    }
+}
+
+// A query as seen from the registry file, which has to import it from wherever it was generated
+pub struct RegistryEntry {
+    pub name: String,
+    pub import_path: PathBuf,
+}
+
+pub trait CodeGenerator: Send + Sync {
     fn serialize_import(
         &self,
         ctx: &CodeGenerationContext,
@@ -54,6 +87,14 @@ pub trait CodeGenerator {
     ) -> Option<String> {
         None
     }
+    // The alias for one of the types in `emit.brandedScalars`, for languages that have them
+    fn serialize_branded_scalar(
+        &self,
+        _ctx: &CodeGenerationContext,
+        _type_name: &String,
+    ) -> Option<SerializationResult> {
+        None
+    }
 
     fn serialize_query_prefix(
         &self,
@@ -91,12 +132,30 @@ pub trait CodeGenerator {
     ) -> Option<String> {
         None
     }
+
+    // Produced a segment at a time, so that the registry never has to be held in memory whole
+    fn serialize_registry<'a>(
+        &'a self,
+        _ctx: &'a CodeGenerationContext,
+        _entries: &'a Vec<RegistryEntry>,
+    ) -> Option<Box<dyn Iterator<Item = String> + Send + 'a>> {
+        None
+    }
 }
 
 pub struct CodeGenerationContext<'a> {
     pub config: Arc<Config>,
     pub working_dir: PathBuf,
-    pub query: &'a Query,
+    pub tables: Arc<HashMap<String, Vec<Column>>>,
+    pub custom_types_by_name: Arc<HashMap<String, Arc<CustomType>>>,
+    // Only needed to probe queries, so generators can be driven by a schema alone
+    query: Option<&'a Query>,
+    // Compare against what's on disk instead of writing
+    check_only: bool,
+    // The files whose contents changed (or would have, when only checking)
+    changed_files: Mutex<Vec<PathBuf>>,
+    // Problems that didn't stop generation, left for the caller to report
+    warnings: Mutex<Vec<String>>,
 }
 
 impl<'a> CodeGenerationContext<'a> {
@@ -106,126 +165,311 @@ impl<'a> CodeGenerationContext<'a> {
         CodeGenerationContext {
             working_dir,
             config: config.clone(),
-            query,
+            tables: query.tables.clone(),
+            custom_types_by_name: query.custom_types_by_name.clone(),
+            query: Some(query),
+            check_only: false,
+            changed_files: Mutex::new(vec![]),
+            warnings: Mutex::new(vec![]),
         }
     }
 
-    pub async fn generate_system_types(&self, generator: &dyn CodeGenerator) -> () {
-        let mut b = Builder::default();
-        let mut imports: Vec<String> = vec![];
+    pub fn from_schema(
+        config: Arc<Config>,
+        tables: HashMap<String, Vec<Column>>,
+        custom_types_by_name: HashMap<String, Arc<CustomType>>,
+    ) -> CodeGenerationContext<'static> {
+        let working_dir = config.workspace.root.as_ref().unwrap().clone();
+
+        CodeGenerationContext {
+            working_dir,
+            config,
+            tables: Arc::new(tables),
+            custom_types_by_name: Arc::new(custom_types_by_name),
+            query: None,
+            check_only: false,
+            changed_files: Mutex::new(vec![]),
+            warnings: Mutex::new(vec![]),
+        }
+    }
 
-        let all_types = self
-            .query
+    // Leaves every file alone, so that `changed_files` reports the ones that are out of date
+    pub fn check_only(mut self) -> Self {
+        self.check_only = true;
+        self
+    }
+
+    pub fn changed_files(&self) -> Vec<PathBuf> {
+        self.changed_files.lock().unwrap().clone()
+    }
+
+    pub fn warnings(&self) -> Vec<String> {
+        self.warnings.lock().unwrap().clone()
+    }
+
+    // Custom types are emitted in their own right, so they're never branded over
+    pub fn is_branded_scalar(&self, type_name: &str) -> bool {
+        !self.custom_types_by_name.contains_key(type_name)
+            && self
+                .config
+                .emit
+                .branded_scalars
+                .iter()
+                .any(|scalar| scalar == type_name)
+    }
+
+    // Sorted, so that the types file comes out the same every time
+    pub fn branded_scalars(&self) -> Vec<String> {
+        let mut scalars = self
+            .config
+            .emit
+            .branded_scalars
+            .iter()
+            .filter(|scalar| self.is_branded_scalar(scalar))
+            .cloned()
+            .collect::<Vec<_>>();
+        scalars.sort();
+        scalars.dedup();
+        scalars
+    }
+
+    pub async fn generate_system_types(&self, generator: &dyn CodeGenerator) -> Result<()> {
+        let base_path = self.get_root_path();
+        let source_path = generator.resolve_file_path(self, &base_path);
+
+        let mut file = self.create_file(source_path).await?;
+
+        // Sorted, so that the file comes out the same every time
+        let mut all_types = self
             .custom_types_by_name
             .values()
             .into_iter()
             .map(|refrence| refrence.clone())
             .collect::<Vec<Arc<CustomType>>>();
+        all_types.sort_by(|a, b| a.name().cmp(b.name()));
 
         if let Some(prefix) = generator.serialize_type_prefix(self, &all_types) {
-            b.append(prefix.as_bytes());
+            file.append(&prefix).await?;
         }
 
-        b.append("\n");
+        file.append("\n").await?;
 
-        for type_ in self.query.custom_types_by_name.values() {
+        for type_ in &all_types {
             let res = generator.serialize_type(self, type_);
-            b.append(res.generated_code);
-            b.append("\n\n");
-            imports.extend(res.requires_import);
+            file.append(&res.generated_code).await?;
+            file.append("\n\n").await?;
         }
 
-        if let Some(prefix) = generator.serialize_type_suffix(self, &all_types) {
-            b.append(prefix.as_bytes());
+        for type_name in self.branded_scalars() {
+            if let Some(res) = generator.serialize_branded_scalar(self, &type_name) {
+                file.append(&res.generated_code).await?;
+                file.append("\n\n").await?;
+            }
         }
 
-        let base_path = self.get_root_path();
-        let source_path = generator.resolve_file_path(self, &base_path);
+        if let Some(prefix) = generator.serialize_type_suffix(self, &all_types) {
+            file.append(&prefix).await?;
+        }
 
-        fs::write(source_path, b.string().unwrap()).await.unwrap();
+        self.finish_file(file).await
     }
 
-    pub async fn generate_table_file(&self, generator: &dyn CodeGenerator) -> () {
+    pub async fn generate_table_file(&self, generator: &dyn CodeGenerator) -> Result<()> {
         let table_file = match &self.config.emit.table_file {
             Some(table_file) => table_file,
-            None => return,
+            None => return Ok(()),
         };
 
-        let mut code_segments: Vec<String> = vec![];
-        let mut imports: Vec<String> = vec![];
+        // The table builder is typed by the table's composite type, so there's nothing useful to
+        // emit for tables without columns (or whose type we never loaded)
+        let mut tables = self
+            .tables
+            .iter()
+            .filter(|(name, columns)| {
+                !columns.is_empty() && self.custom_types_by_name.contains_key(*name)
+            })
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        tables.sort();
+
+        let prefix = generator.serialize_table_prefix(self, &tables);
+        let suffix = generator.serialize_table_suffix(self, &tables);
 
-        let tables = self.query.tables.keys().into_iter().collect::<Vec<_>>();
+        let base_path = self.working_dir.join(&table_file);
+        let source_path = PathBuf::from(generator.resolve_file_path(self, &base_path));
 
-        if let Some(prefix) = generator.serialize_table_prefix(&self, &tables) {
-            code_segments.push(prefix);
-        }
+        self.write_segments(
+            generator,
+            &source_path,
+            "",
+            prefix
+                .iter()
+                .map(|prefix| (prefix.clone(), vec![]))
+                .chain(tables.iter().map(|table_name| {
+                    let res = generator.serialize_table(self, table_name);
+                    (res.generated_code, res.requires_import)
+                }))
+                .chain(suffix.iter().map(|suffix| (suffix.clone(), vec![]))),
+        )
+        .await
+    }
 
-        for table_name in &tables {
-            let res = generator.serialize_table(&self, table_name);
-            imports.extend(res.requires_import);
-            code_segments.push(res.generated_code);
-        }
+    pub async fn generate_queries(&self, generator: &dyn CodeGenerator) -> Result<()> {
+        let query_files = self.locate_query_files().await;
+
+        self.check_duplicate_names(&query_files).await?;
 
-        if let Some(suffix) = generator.serialize_table_suffix(&self, &tables) {
-            code_segments.push(suffix);
+        let Some(query) = self.query else {
+            return Err(PiquedError::OtherError(
+                "Generating queries requires a database connection".to_string(),
+            ));
+        };
+
+        let mut generated: Vec<(PathBuf, Vec<String>)> = vec![];
+        for query_file in query_files {
+            let dst_file = PathBuf::from(generator.resolve_file_path(self, &query_file));
+
+            let names = self
+                .generate_query_file(generator, query, &query_file, &dst_file)
+                .await?;
+            generated.push((dst_file, names));
         }
 
-        let mut b = Builder::default();
+        self.generate_registry(generator, &generated).await
+    }
 
-        let base_path = self.working_dir.join(&table_file);
-        let source_path = generator.resolve_file_path(self, &base_path);
+    // The registry is built last, since it needs to know about every query in the workspace
+    async fn generate_registry(
+        &self,
+        generator: &dyn CodeGenerator,
+        generated: &Vec<(PathBuf, Vec<String>)>,
+    ) -> Result<()> {
+        let registry = match &self.config.emit.registry {
+            Some(registry) => registry,
+            None => return Ok(()),
+        };
 
-        b.append(self.generate_import_statements(
-            &PathBuf::from(source_path.clone()),
-            &imports,
-            generator,
-        ));
+        let registry_file =
+            PathBuf::from(generator.resolve_file_path(self, &self.working_dir.join(registry)));
+
+        let mut entries = generated
+            .iter()
+            .flat_map(|(dst_file, names)| {
+                let import_path =
+                    self.relative_import_path(&registry_file, &dst_file.with_extension(""));
+
+                names.iter().map(move |name| RegistryEntry {
+                    name: name.clone(),
+                    import_path: import_path.clone(),
+                })
+            })
+            .collect::<Vec<RegistryEntry>>();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let Some(segments) = generator.serialize_registry(self, &entries) else {
+            return Ok(());
+        };
 
-        for chunk in code_segments {
-            b.append(chunk);
+        let mut file = self.create_file(registry_file).await?;
+        for segment in segments {
+            file.append(&segment).await?;
         }
-
-        fs::write(source_path, b.string().unwrap()).await.unwrap();
+        self.finish_file(file).await
     }
 
-    pub async fn generate_queries(&self, generator: &dyn CodeGenerator) -> () {
-        let query_files = self.locate_query_files().await;
+    // Queries are exported under their (camel cased) name, so two queries sharing a name will
+    // collide as soon as they end up in the same module. The `query_N` fallbacks are only unique
+    // within their own file, so they're only compared across files once the registry puts every
+    // query side by side.
+    async fn check_duplicate_names(&self, query_files: &Vec<PathBuf>) -> Result<()> {
+        let mut query_files = query_files.clone();
+        query_files.sort();
+
+        let mut first_declarations: HashMap<String, (String, String)> = HashMap::new();
+        let mut duplicates = vec![];
+
+        for query_file in &query_files {
+            let contents = fs::read_to_string(query_file).await?;
+            let Ok(data) = parser::load_file(&contents) else {
+                continue;
+            };
+
+            let mut file_declarations: HashMap<String, (String, String)> = HashMap::new();
+            for (name, fallback, stmt) in parser::statement_names(&data, &contents) {
+                let location = format!(
+                    "{}:{}",
+                    query_file.to_string_lossy(),
+                    stmt.range.start.line + 1
+                );
+
+                let key = to_camel_case(&name, false);
+                let shared = !fallback || self.config.emit.registry.is_some();
+                let first = file_declarations
+                    .get(&key)
+                    .or(shared.then(|| first_declarations.get(&key)).flatten());
+
+                match first {
+                    Some((first_name, first_location)) if first_name == &name => {
+                        duplicates.push(format!(
+                            "Query name \"{}\" at {} is already used at {}",
+                            name, location, first_location
+                        ))
+                    }
+                    Some((first_name, first_location)) => duplicates.push(format!(
+                        "Query name \"{}\" at {} collides with \"{}\" at {}",
+                        name, location, first_name, first_location
+                    )),
+                    None => {
+                        if shared {
+                            first_declarations
+                                .insert(key.clone(), (name.clone(), location.clone()));
+                        }
+                        file_declarations.insert(key, (name.clone(), location));
+                    }
+                }
+            }
+        }
 
-        for query_file in query_files {
-            let mut dst_file = query_file.clone();
-            dst_file.set_extension("ts");
+        if duplicates.is_empty() {
+            return Ok(());
+        }
+
+        if self.config.emit.allow_duplicate_names {
+            self.warnings.lock().unwrap().extend(duplicates);
 
-            self.generate_query_file(generator, &query_file, &dst_file)
-                .await;
+            Ok(())
+        } else {
+            Err(PiquedError::OtherError(duplicates.join("\n")))
         }
     }
 
-    async fn locate_query_files(&self) -> Vec<PathBuf> {
+    pub async fn locate_query_files(&self) -> Vec<PathBuf> {
         let mut files: Vec<PathBuf> = vec![];
 
         #[async_recursion]
-        async fn walk_dir(dir: &Path, file_results: &mut Vec<PathBuf>) {
+        async fn walk_dir(
+            dir: &Path,
+            workspace: &ConfigWorkspace,
+            file_results: &mut Vec<PathBuf>,
+        ) {
             let mut files = fs::read_dir(dir).await.unwrap();
 
             while let Ok(Some(entry)) = files.next_entry().await {
                 let path = entry.path();
 
-                if path.is_dir() {
-                    walk_dir(path.as_path(), file_results).await;
+                if workspace.is_excluded(&path) {
+                    continue;
                 }
 
-                match path.extension() {
-                    Some(ext) => {
-                        if ext == "sql" || ext == "psql" || ext == "pgsql" || ext == "pg" {
-                            file_results.push(path);
-                        }
-                    }
-                    _ => (),
+                if path.is_dir() {
+                    walk_dir(path.as_path(), workspace, file_results).await;
+                } else if workspace.is_query_file(&path) {
+                    file_results.push(path);
                 }
             }
         }
 
-        walk_dir(&self.working_dir, &mut files).await;
+        walk_dir(&self.working_dir, &self.config.workspace, &mut files).await;
         files
     }
 
@@ -236,95 +480,290 @@ impl<'a> CodeGenerationContext<'a> {
     async fn generate_query_file(
         &self,
         generator: &dyn CodeGenerator,
+        query: &Query,
         src_file: &PathBuf,
         dst_file: &PathBuf,
-    ) {
-        let contents = fs::read_to_string(src_file).await.unwrap();
-        let data = parser::load_file(&contents);
-
-        let mut imports: Vec<String> = vec![];
-        let mut code_segments: Vec<String> = vec![];
-
-        let statements = match data {
-            Ok(data) => {
-                let mut results = vec![];
-                for (i, stmt) in data.statements.iter().enumerate() {
-                    let prep_result =
-                        parser::get_prepared_statement(&stmt, &data.tokens, &contents, || {
-                            format!("query_{i}")
-                        });
-
-                    if let Ok(prepared_statement) = prep_result {
-                        let probed_type = self.query.probe_type(&prepared_statement).await.unwrap();
-
-                        results.push(QueryContext(prepared_statement, probed_type))
-                    }
-                }
-
-                results
-            }
-
-            Err(e) => {
-                println!("Error: {:#?}", e);
-                return;
-            }
-        };
-
-        if let Some(prefix) = generator.serialize_query_prefix(self, &statements) {
-            code_segments.push(prefix);
+    ) -> Result<Vec<String>> {
+        let contents = fs::read_to_string(src_file).await?;
+        let data = parser::load_file(&contents).map_err(|e| located_error(src_file, 1, &e))?;
+
+        // A query that doesn't typecheck has no type to generate, so the file is left as it was
+        let mut statements = vec![];
+        for (i, stmt) in data.statements.iter().enumerate() {
+            let line = stmt.range.start.line + 1;
+            let prepared_statement =
+                parser::get_prepared_statement(stmt, &data.tokens, &contents, || {
+                    format!("query_{i}")
+                })
+                .map_err(|e| located_error(src_file, line, &e))?;
+            let probed_type = query
+                .probe_type(&prepared_statement)
+                .await
+                .map_err(|e| located_error(src_file, line, &e))?;
+
+            statements.push(QueryContext(prepared_statement, probed_type))
         }
 
-        for stmt in &statements {
-            let res = generator.serialize_query(self, stmt);
-            imports.extend(res.requires_import);
-            code_segments.push(res.generated_code);
-        }
-
-        if let Some(suffix) = generator.serialize_query_suffix(self, &statements) {
-            code_segments.push(suffix);
-        }
+        let prefix = generator.serialize_query_prefix(self, &statements);
+        let suffix = generator.serialize_query_suffix(self, &statements);
 
-        let mut b = Builder::default();
-
-        b.append(self.generate_import_statements(&dst_file, &imports, generator));
+        self.write_segments(
+            generator,
+            dst_file,
+            "\n\n",
+            prefix
+                .iter()
+                .map(|prefix| (prefix.clone(), vec![]))
+                .chain(statements.iter().map(|stmt| {
+                    let res = generator.serialize_query(self, stmt);
+                    (res.generated_code, res.requires_import)
+                }))
+                .chain(suffix.iter().map(|suffix| (suffix.clone(), vec![]))),
+        )
+        .await?;
+
+        Ok(statements
+            .iter()
+            .map(|QueryContext(query, _)| query.details.name.clone())
+            .collect())
+    }
 
-        for segment in code_segments {
-            b.append(segment);
-            b.append("\n\n");
+    // Streams each segment's code to a scratch file as it's produced, noting the imports it needs.
+    // Imports go first, so the code is only copied into the generated file once they're all known.
+    async fn write_segments<I>(
+        &self,
+        generator: &dyn CodeGenerator,
+        dst_file: &PathBuf,
+        separator: &str,
+        segments: I,
+    ) -> Result<()>
+    where
+        I: Iterator<Item = (String, Vec<String>)>,
+    {
+        let mut body = ScratchFile::create().await?;
+
+        // Generators may over-report what they need, so only import the symbols that are actually
+        // referenced by the code asking for them. Otherwise, linters will complain.
+        let mut needed_imports = HashSet::new();
+        for (code, imports) in segments {
+            needed_imports.extend(
+                imports
+                    .into_iter()
+                    .filter(|import| contains_identifier(&code, import)),
+            );
+
+            body.write(&code).await?;
+            body.write(separator).await?;
         }
 
-        fs::write(dst_file, b.string().unwrap()).await.unwrap();
+        let import_statements =
+            self.generate_import_statements(dst_file, &needed_imports, generator);
+
+        let mut file = self.create_file(dst_file).await?;
+        file.append(&import_statements).await?;
+        body.copy_to(&mut file).await?;
+        self.finish_file(file).await
     }
 
     fn generate_import_statements(
         &self,
         dst_file: &PathBuf,
-        imports: &Vec<String>,
+        needed_imports: &HashSet<String>,
         generator: &dyn CodeGenerator,
     ) -> String {
-        let mut b = Builder::default();
-
-        let needed_imports = imports
-            .into_iter()
-            .collect::<HashSet<&String>>()
-            .into_iter()
-            .map(|s| s.clone())
+        let mut needed_imports = needed_imports
+            .iter()
+            .map(|s| s.to_string())
             .collect::<Vec<String>>();
+        needed_imports.sort();
+
+        if needed_imports.is_empty() {
+            return String::new();
+        }
 
-        let type_file_path = self.working_dir.join(&self.config.emit.type_file);
+        let import_path = match &self.config.emit.type_file_module {
+            Some(module) => PathBuf::from(module),
+            None => self.relative_import_path(
+                dst_file,
+                &self.working_dir.join(&self.config.emit.type_file),
+            ),
+        };
+
+        let import = generator.serialize_import(self, &import_path, &needed_imports);
+
+        format!("{}\n\n", import.generated_code)
+    }
+
+    async fn create_file(&self, path: impl AsRef<Path>) -> Result<GeneratedFile> {
+        GeneratedFile::create(path.as_ref().to_path_buf(), self.check_only).await
+    }
+
+    // Leaves the file alone when its contents haven't changed, so that anything watching it doesn't
+    // rebuild for nothing
+    async fn finish_file(&self, file: GeneratedFile) -> Result<()> {
+        let path = file.path.clone();
+        if file.finish().await? {
+            self.changed_files.lock().unwrap().push(path);
+        }
+
+        Ok(())
+    }
+
+    // How `dst_file` should refer to `target` in an import
+    fn relative_import_path(&self, dst_file: &PathBuf, target: &PathBuf) -> PathBuf {
         let mut start_file_path = dst_file.clone();
         start_file_path.pop();
 
-        let mut relative_path = pathdiff::diff_paths(type_file_path, start_file_path).unwrap();
+        let mut relative_path = pathdiff::diff_paths(target, start_file_path).unwrap();
         if !relative_path.starts_with("../") {
             relative_path = Path::new("./").join(relative_path);
         }
 
-        let import = generator.serialize_import(self, &relative_path, &needed_imports);
+        relative_path
+    }
+}
+
+// Points an error at the line of the query file it came from, the way compilers print them
+fn located_error(path: &Path, line: u32, err: &PiquedError) -> PiquedError {
+    PiquedError::OtherError(format!(
+        "{}:{}: {}",
+        path.to_string_lossy(),
+        line,
+        error_message(err)
+    ))
+}
 
-        b.append(import.generated_code);
-        b.append("\n\n");
+// Somewhere to put generated code while it waits on something that has to come before it
+struct ScratchFile {
+    path: PathBuf,
+    writer: BufWriter<fs::File>,
+}
+
+static SCRATCH_FILES: AtomicUsize = AtomicUsize::new(0);
+
+impl ScratchFile {
+    async fn create() -> Result<ScratchFile> {
+        let path = env::temp_dir().join(format!(
+            "piqued-{}-{}.tmp",
+            process::id(),
+            SCRATCH_FILES.fetch_add(1, Ordering::Relaxed)
+        ));
+        let writer = BufWriter::new(fs::File::create(&path).await?);
 
-        return b.string().unwrap();
+        Ok(ScratchFile { path, writer })
+    }
+
+    async fn write(&mut self, code: &str) -> Result<()> {
+        self.writer.write_all(code.as_bytes()).await?;
+        Ok(())
+    }
+
+    // Appends everything written so far to `file`, a buffer at a time
+    async fn copy_to(mut self, file: &mut GeneratedFile) -> Result<()> {
+        self.writer.flush().await?;
+
+        let mut reader = fs::File::open(&self.path).await?;
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            let read = reader.read(&mut buffer).await?;
+            if read == 0 {
+                return Ok(());
+            }
+
+            file.append(&buffer[..read]).await?;
+        }
+    }
+}
+
+impl Drop for ScratchFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+// A generated file, written out a segment at a time instead of being built up in memory first.
+// Segments go to a temporary file beside the destination, which only replaces it once we know the
+// contents changed. When only checking, nothing is written at all. Either way, each segment is
+// compared against the bytes already on disk as it comes in.
+struct GeneratedFile {
+    path: PathBuf,
+    temp_path: PathBuf,
+    writer: Option<BufWriter<fs::File>>,
+    check_only: bool,
+    // The file as it is now, read alongside what's appended for as long as the two still match
+    existing: Option<BufReader<fs::File>>,
+}
+
+impl GeneratedFile {
+    async fn create(path: PathBuf, check_only: bool) -> Result<GeneratedFile> {
+        let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+        temp_name.push(".piqued-tmp");
+        let temp_path = path.with_file_name(temp_name);
+
+        let writer = if check_only {
+            None
+        } else {
+            Some(BufWriter::new(fs::File::create(&temp_path).await?))
+        };
+        let existing = fs::File::open(&path).await.ok().map(BufReader::new);
+
+        Ok(GeneratedFile {
+            path,
+            temp_path,
+            writer,
+            check_only,
+            existing,
+        })
+    }
+
+    async fn append(&mut self, segment: impl AsRef<[u8]>) -> Result<()> {
+        let segment = segment.as_ref();
+
+        if let Some(existing) = &mut self.existing {
+            let mut on_disk = vec![0; segment.len()];
+            if existing.read_exact(&mut on_disk).await.is_err() || on_disk != segment {
+                self.existing = None;
+            }
+        }
+
+        if let Some(writer) = &mut self.writer {
+            writer.write_all(segment).await?;
+        }
+
+        Ok(())
+    }
+
+    // Whether the file's contents changed (or would have, when only checking)
+    async fn finish(mut self) -> Result<bool> {
+        // Everything matched so far, so it's only changed if there's more on disk than we wrote
+        let changed = match &mut self.existing {
+            Some(existing) => existing
+                .read(&mut [0; 1])
+                .await
+                .map_or(true, |read| read > 0),
+            None => true,
+        };
+        self.existing = None;
+
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush().await?;
+            drop(writer);
+
+            if changed {
+                fs::rename(&self.temp_path, &self.path).await?;
+            }
+        }
+
+        Ok(changed)
+    }
+}
+
+// Whatever's left of the temporary file goes along with it, whether it went unused or a write to it
+// failed halfway
+impl Drop for GeneratedFile {
+    fn drop(&mut self) {
+        if !self.check_only {
+            let _ = std::fs::remove_file(&self.temp_path);
+        }
     }
 }
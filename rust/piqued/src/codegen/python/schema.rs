@@ -0,0 +1,532 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::{Component, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    code_builder::codegen_helper::CodegenHelper,
+    codegen::{
+        codegen::{
+            CodeGenerationContext, CodeGenerator, ImportResult, QueryContext, SerializationResult,
+        },
+        utils::{
+            comment_lines, contains_identifier, to_camel_case, to_const_name, to_snake_case,
+            to_snake_identifier, unique_name, unique_names, ResolvedType,
+        },
+    },
+    query::query::{ColumnType, CompositeType, CustomType, EnumType},
+};
+use string_builder::Builder;
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+// The names the generated code may take from the standard library, along with their modules
+const STANDARD_IMPORTS: &[(&str, &str)] = &[
+    ("datetime", "date"),
+    ("datetime", "datetime"),
+    ("datetime", "time"),
+    ("datetime", "timedelta"),
+    ("decimal", "Decimal"),
+    ("enum", "Enum"),
+    ("typing", "Any"),
+    ("typing", "Optional"),
+    ("uuid", "UUID"),
+];
+
+fn list_of(inner: String) -> String {
+    format!("list[{}]", inner)
+}
+
+pub struct PythonGenerator {}
+
+impl PythonGenerator {
+    pub fn new() -> Self {
+        return PythonGenerator {};
+    }
+
+    fn resolve_type(&self, ctx: &CodeGenerationContext, type_: &ColumnType) -> ResolvedType {
+        let type_ = match type_ {
+            ColumnType::ArrayOf(element) => {
+                return ResolvedType::Array(Box::new(self.resolve_type(ctx, element)))
+            }
+            ColumnType::Named(name) => name.as_str(),
+        };
+
+        if ctx.custom_types_by_name.contains_key(type_) {
+            return ResolvedType::Import(to_camel_case(&type_.to_string(), true));
+        }
+
+        let native_type = match type_ {
+            "int2" | "int4" | "int8" => "int",
+            "float4" | "float8" => "float",
+            "numeric" => "Decimal",
+            "bool" => "bool",
+            "bytea" => "bytes",
+            "uuid" => "UUID",
+            "json" | "jsonb" => "Any",
+            "date" => "date",
+            "time" => "time",
+            "timestamp" | "timestamptz" => "datetime",
+            "interval" => "timedelta",
+            _ => "str",
+        };
+
+        return ResolvedType::Native(native_type.to_string());
+    }
+
+    fn resolve_field_type(
+        &self,
+        ctx: &CodeGenerationContext,
+        type_: &ColumnType,
+        nullable: bool,
+    ) -> ResolvedType {
+        let resolved = self.resolve_type(ctx, type_);
+
+        if nullable {
+            ResolvedType::Native(format!("Optional[{}]", resolved.get_type(list_of)))
+        } else {
+            resolved
+        }
+    }
+
+    // Pydantic takes anything starting with an underscore to be private, so those are avoided
+    fn to_field_name(&self, name: &str) -> String {
+        let name = to_snake_case(&name.to_string());
+
+        to_snake_identifier(name.trim_start_matches('_'), "field_", |field| {
+            if PYTHON_KEYWORDS.contains(&field.as_str()) {
+                format!("{}_", field)
+            } else {
+                field
+            }
+        })
+    }
+
+    fn to_docstring(&self, comment: &str) -> Option<String> {
+        // A stray `"""` would end the docstring early
+        let lines = comment_lines(comment)
+            .iter()
+            .map(|line| line.replace('\\', "\\\\").replace("\"\"\"", "\\\"\\\"\\\""))
+            .collect::<Vec<String>>();
+
+        match lines.as_slice() {
+            [] => None,
+            [line] => Some(format!("\"\"\"{}\"\"\"", line)),
+            [first, rest @ ..] => Some(format!("\"\"\"{}\n{}\n\"\"\"", first, rest.join("\n"))),
+        }
+    }
+
+    // Enum members are upper case, which keeps them clear of keywords and of `_sunder_` names
+    fn to_member_name(&self, value: &str) -> String {
+        let member = to_snake_case(&value.to_string()).to_ascii_uppercase();
+        let member = member.trim_start_matches('_');
+
+        if member.is_empty() || member.starts_with(|c: char| c.is_ascii_digit()) {
+            format!("V_{}", member)
+        } else {
+            member.to_string()
+        }
+    }
+
+    fn to_string_literal(&self, contents: &str) -> String {
+        format!(
+            "\"{}\"",
+            contents
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+        )
+    }
+
+    // Queries keep their line breaks, so that they read the same as they do in the `.sql` file
+    fn to_block_string(&self, contents: &str) -> String {
+        format!(
+            "\"\"\"{}\"\"\"",
+            contents.replace('\\', "\\\\").replace('"', "\\\"")
+        )
+    }
+
+    // The `from ... import ...` lines for everything in the standard library that `types` use
+    fn standard_imports(&self, types: &[String]) -> String {
+        let mut modules: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (module, name) in STANDARD_IMPORTS {
+            if types.iter().any(|type_| contains_identifier(type_, name)) {
+                modules.entry(module).or_default().push(name);
+            }
+        }
+
+        modules
+            .iter()
+            .map(|(module, names)| format!("from {} import {}\n", module, names.join(", ")))
+            .collect()
+    }
+}
+
+impl CodeGenerator for PythonGenerator {
+    fn serialize_import(
+        &self,
+        ctx: &CodeGenerationContext,
+        path: &PathBuf,
+        identifiers: &Vec<String>,
+    ) -> ImportResult {
+        // An explicit module path is used verbatim. Otherwise, the import is relative to the
+        // package, so `./types` is `.types` and `../types` is `..types`.
+        let module_path = match &ctx.config.emit.type_file_module {
+            Some(module) => module.clone(),
+            None => {
+                let mut dots = ".".to_string();
+                let mut segments = vec![];
+
+                for component in path.components() {
+                    match component {
+                        Component::ParentDir => dots.push('.'),
+                        Component::Normal(segment) => segments.push(
+                            PathBuf::from(segment)
+                                .with_extension("")
+                                .to_string_lossy()
+                                .to_string(),
+                        ),
+                        _ => (),
+                    }
+                }
+
+                format!("{}{}", dots, segments.join("."))
+            }
+        };
+
+        ImportResult {
+            generated_code: format!("from {} import {}", module_path, identifiers.join(", ")),
+        }
+    }
+
+    fn serialize_type_prefix(
+        &self,
+        ctx: &CodeGenerationContext,
+        types: &Vec<Arc<CustomType>>,
+    ) -> Option<String> {
+        let mut used_types = vec![];
+        let mut renamed_fields = false;
+        for type_ in types {
+            match type_.as_ref() {
+                CustomType::Composite(CompositeType { fields, .. }) => {
+                    for field in fields {
+                        used_types.push(
+                            self.resolve_field_type(ctx, &field.column_type, field.nullable)
+                                .get_type(list_of),
+                        );
+                        renamed_fields |= self.to_field_name(&field.name) != field.name;
+                    }
+                }
+                CustomType::Enum(_) => used_types.push("Enum".to_string()),
+            }
+        }
+
+        let mut b = Builder::default();
+        b.append(
+            "# This file was generated by piqued.
+# You can edit it if you want, but you'll probably be sad
+# If you have some changes you want to make, check out the configuration options
+# Or file a bug report on our definitely-extant github
+
+# Composite types can refer to ones declared further down
+from __future__ import annotations
+
+",
+        );
+        b.append(self.standard_imports(&used_types));
+        if renamed_fields {
+            b.append("\nfrom pydantic import BaseModel, Field\n\n");
+        } else {
+            b.append("\nfrom pydantic import BaseModel\n\n");
+        }
+
+        Some(b.string().unwrap())
+    }
+
+    fn serialize_type(
+        &self,
+        ctx: &CodeGenerationContext,
+        type_: &CustomType,
+    ) -> SerializationResult {
+        let mut c = CodegenHelper::new("    ", "\n");
+
+        match type_ {
+            CustomType::Composite(CompositeType { name, fields, .. }) => {
+                let identifier = to_camel_case(name, true);
+
+                c.write_line(Some(&format!("class {}(BaseModel):", identifier)));
+                c.with_indent(|c| {
+                    if fields.is_empty() {
+                        c.write_line(Some(&"pass"));
+                    }
+
+                    for field in fields {
+                        let field_name = self.to_field_name(&field.name);
+                        let field_type =
+                            self.resolve_field_type(ctx, &field.column_type, field.nullable);
+
+                        // Composites come back keyed by their own field names
+                        if field_name != field.name {
+                            c.write_line(Some(&format!(
+                                "{}: {} = Field(alias=\"{}\")",
+                                field_name,
+                                field_type.get_type(list_of),
+                                field.name
+                            )));
+                        } else {
+                            c.write_line(Some(&format!(
+                                "{}: {}",
+                                field_name,
+                                field_type.get_type(list_of)
+                            )));
+                        }
+                    }
+                });
+
+                SerializationResult {
+                    generated_code: c.serialize(),
+                    identifier,
+                    requires_import: vec![],
+                }
+            }
+
+            CustomType::Enum(EnumType { name, values, .. }) => {
+                let identifier = to_camel_case(name, true);
+
+                // Mixing in `str` lets members be passed straight to asyncpg, and compared to the
+                // strings it hands back
+                c.write_line(Some(&format!("class {}(str, Enum):", identifier)));
+                c.with_indent(|c| {
+                    if values.is_empty() {
+                        c.write_line(Some(&"pass"));
+                    }
+
+                    // Values that only differ by case or punctuation would otherwise be the same
+                    // member twice
+                    let mut seen_members: HashSet<String> = HashSet::new();
+                    for value in values {
+                        let member =
+                            unique_name(&mut seen_members, self.to_member_name(value), "_");
+
+                        c.write_line(Some(&format!(
+                            "{} = {}",
+                            member,
+                            self.to_string_literal(value)
+                        )));
+                    }
+                });
+
+                SerializationResult {
+                    generated_code: c.serialize(),
+                    identifier,
+                    requires_import: vec![],
+                }
+            }
+        }
+    }
+
+    fn serialize_query_prefix(
+        &self,
+        ctx: &CodeGenerationContext,
+        queries: &Vec<QueryContext>,
+    ) -> Option<String> {
+        let mut used_types = vec![];
+        for QueryContext(_, probe_result) in queries {
+            for arg in &probe_result.args {
+                used_types.push(self.resolve_type(ctx, arg).get_type(list_of));
+            }
+
+            for (i, type_) in probe_result.column_types.iter().enumerate() {
+                let nullable = ctx.config.emit.force_nullable_results
+                    || probe_result
+                        .column_nullable
+                        .get(i)
+                        .copied()
+                        .unwrap_or(false);
+                used_types.push(
+                    self.resolve_field_type(ctx, type_, nullable)
+                        .get_type(list_of),
+                );
+            }
+        }
+
+        let mut b = Builder::default();
+        b.append("import asyncpg\n");
+        b.append(self.standard_imports(&used_types));
+
+        let has_rows = queries
+            .iter()
+            .any(|QueryContext(_, probe_result)| !probe_result.column_names.is_empty());
+        if has_rows {
+            b.append("\nfrom pydantic import BaseModel\n");
+        }
+
+        Some(b.string().unwrap())
+    }
+
+    fn serialize_query(
+        &self,
+        ctx: &CodeGenerationContext,
+        QueryContext(parsed_query, probe_result): &QueryContext,
+    ) -> SerializationResult {
+        let name = &parsed_query.details.name;
+
+        let identifier = to_camel_case(name, true);
+        let row_identifier = format!("{}Row", identifier);
+        let const_identifier = format!("{}_QUERY", to_const_name(&self.to_field_name(name)));
+        let fn_identifier = self.to_field_name(name);
+        let mut imports: Vec<String> = vec![];
+
+        // The connection comes first, so no parameter can take its name
+        let mut seen_args: HashSet<String> = HashSet::from(["conn".to_string()]);
+        let args = probe_result
+            .args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| {
+                let arg_name = match &parsed_query.details.params {
+                    Some(param) if param.len() > i && !param[i].starts_with('$') => {
+                        self.to_field_name(&param[i])
+                    }
+                    _ => format!("arg{}", i + 1),
+                };
+                let arg_name = unique_name(&mut seen_args, arg_name, "_");
+
+                let type_ = self.resolve_type(ctx, arg);
+                imports.append(&mut type_.get_imports());
+
+                (arg_name, type_.get_type(list_of))
+            })
+            .collect::<Vec<(String, String)>>();
+
+        let mut c = CodegenHelper::new("    ", "\n");
+
+        let has_rows = !probe_result.column_names.is_empty();
+        // Postgres is happy to return several columns with the same name, but a class isn't, so
+        // rows are filled in by position
+        let fields = unique_names(
+            probe_result
+                .column_names
+                .iter()
+                .map(|name| self.to_field_name(name)),
+            "_",
+        );
+
+        if has_rows {
+            c.write_line(Some(&format!("class {}(BaseModel):", row_identifier)));
+            c.with_indent(|c| {
+                c.write_line(Some(&format!(
+                    "\"\"\"A row returned by `{}`\"\"\"",
+                    fn_identifier
+                )));
+                c.write_line(None);
+
+                for (i, type_) in probe_result.column_types.iter().enumerate() {
+                    let nullable = ctx.config.emit.force_nullable_results
+                        || probe_result
+                            .column_nullable
+                            .get(i)
+                            .copied()
+                            .unwrap_or(false);
+                    let resolved = self.resolve_field_type(ctx, type_, nullable);
+                    imports.append(&mut self.resolve_type(ctx, type_).get_imports());
+
+                    c.write_line(Some(&format!(
+                        "{}: {}",
+                        fields[i],
+                        resolved.get_type(list_of)
+                    )));
+                }
+            });
+            c.write_line(None);
+            c.write_line(None);
+        }
+
+        c.write_line(Some(&format!(
+            "{} = {}",
+            const_identifier,
+            self.to_block_string(&parsed_query.contents)
+        )));
+        c.write_line(None);
+        c.write_line(None);
+
+        let return_type = if has_rows {
+            format!("list[{}]", row_identifier)
+        } else {
+            "str".to_string()
+        };
+
+        c.write_line(Some(&format!(
+            "async def {}({}) -> {}:",
+            fn_identifier,
+            ["conn: asyncpg.Connection".to_string()]
+                .into_iter()
+                .chain(
+                    args.iter()
+                        .map(|(arg_name, type_)| format!("{}: {}", arg_name, type_))
+                )
+                .collect::<Vec<String>>()
+                .join(", "),
+            return_type
+        )));
+        c.with_indent(|c| {
+            if let Some(docstring) = self.to_docstring(&parsed_query.details.comment) {
+                for line in docstring.lines() {
+                    c.write_line(Some(line));
+                }
+            }
+
+            let bound = [const_identifier.clone()]
+                .into_iter()
+                .chain(args.iter().map(|(arg_name, _)| arg_name.clone()))
+                .collect::<Vec<String>>()
+                .join(", ");
+
+            if !has_rows {
+                c.write_line(Some(&format!("return await conn.execute({})", bound)));
+                return;
+            }
+
+            c.write_line(Some(&format!("rows = await conn.fetch({})", bound)));
+            c.write_line(Some(&"return ["));
+            c.with_indent(|c| {
+                c.write_line(Some(&format!("{}(", row_identifier)));
+                c.with_indent(|c| {
+                    for (i, field) in fields.iter().enumerate() {
+                        c.write_line(Some(&format!("{}=row[{}],", field, i)));
+                    }
+                });
+                c.write_line(Some(&")"));
+                c.write_line(Some(&"for row in rows"));
+            });
+            c.write_line(Some(&"]"));
+        });
+
+        SerializationResult {
+            generated_code: c.serialize(),
+            identifier: fn_identifier,
+            requires_import: imports,
+        }
+    }
+
+    fn serialize_table(&self, _ctx: &CodeGenerationContext, table: &String) -> SerializationResult {
+        let export_name = format!("{}_TABLE", to_const_name(&self.to_field_name(table)));
+
+        SerializationResult {
+            generated_code: format!("{} = \"{}\"\n", export_name, table),
+            identifier: export_name,
+            requires_import: vec![],
+        }
+    }
+
+    fn resolve_file_path(&self, _ctx: &CodeGenerationContext, path: &PathBuf) -> String {
+        let result = path.clone();
+        result.with_extension("py").to_str().unwrap().to_string()
+    }
+}
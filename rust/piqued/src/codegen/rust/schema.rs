@@ -0,0 +1,403 @@
+use std::{
+    collections::HashSet,
+    path::{Component, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    code_builder::codegen_helper::CodegenHelper,
+    codegen::{
+        codegen::{
+            CodeGenerationContext, CodeGenerator, ImportResult, QueryContext, SerializationResult,
+        },
+        utils::{
+            comment_lines, to_camel_case, to_const_name, to_snake_identifier, unique_name,
+            unique_names, ResolvedType,
+        },
+    },
+    query::query::{ColumnType, CompositeType, CustomType, EnumType},
+};
+use string_builder::Builder;
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while",
+];
+
+// Keywords that can't be raw identifiers either
+const RESERVED_PATH_KEYWORDS: &[&str] = &["crate", "self", "Self", "super"];
+
+fn vec_of(inner: String) -> String {
+    format!("Vec<{}>", inner)
+}
+
+pub struct RustGenerator {}
+
+impl RustGenerator {
+    pub fn new() -> Self {
+        return RustGenerator {};
+    }
+
+    fn resolve_type(&self, ctx: &CodeGenerationContext, type_: &ColumnType) -> ResolvedType {
+        let type_ = match type_ {
+            ColumnType::ArrayOf(element) => {
+                return ResolvedType::Array(Box::new(self.resolve_type(ctx, element)))
+            }
+            ColumnType::Named(name) => name.as_str(),
+        };
+
+        if ctx.custom_types_by_name.contains_key(type_) {
+            return ResolvedType::Import(to_camel_case(&type_.to_string(), true));
+        }
+
+        let native_type = match type_ {
+            "int2" => "i16",
+            "int4" => "i32",
+            "int8" => "i64",
+            "float4" => "f32",
+            "float8" => "f64",
+            "numeric" => "sqlx::types::BigDecimal",
+            "bool" => "bool",
+            "bytea" => "Vec<u8>",
+            "uuid" => "sqlx::types::Uuid",
+            "json" | "jsonb" => "sqlx::types::JsonValue",
+            "date" => "sqlx::types::chrono::NaiveDate",
+            "time" => "sqlx::types::chrono::NaiveTime",
+            "timestamp" => "sqlx::types::chrono::NaiveDateTime",
+            "timestamptz" => "sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc>",
+            _ => "String",
+        };
+
+        return ResolvedType::Native(native_type.to_string());
+    }
+
+    fn resolve_field_type(
+        &self,
+        ctx: &CodeGenerationContext,
+        type_: &ColumnType,
+        nullable: bool,
+    ) -> ResolvedType {
+        let resolved = self.resolve_type(ctx, type_);
+
+        if nullable {
+            ResolvedType::Native(format!("Option<{}>", resolved.get_type(vec_of)))
+        } else {
+            resolved
+        }
+    }
+
+    fn to_field_name(&self, name: &str) -> String {
+        to_snake_identifier(name, "_", |field| {
+            if RESERVED_PATH_KEYWORDS.contains(&field.as_str()) {
+                format!("{}_", field)
+            } else if RUST_KEYWORDS.contains(&field.as_str()) {
+                format!("r#{}", field)
+            } else {
+                field
+            }
+        })
+    }
+
+    fn to_variant_name(&self, value: &String) -> String {
+        let variant = to_camel_case(value, true);
+
+        if variant.is_empty()
+            || variant.starts_with(|c: char| c.is_ascii_digit())
+            || RUST_KEYWORDS.contains(&variant.as_str())
+        {
+            format!("V{}", variant)
+        } else {
+            variant
+        }
+    }
+
+    fn to_doc_comment(&self, comment: &str) -> String {
+        comment_lines(comment)
+            .iter()
+            .map(|line| {
+                if line.is_empty() {
+                    "///\n".to_string()
+                } else {
+                    format!("/// {}\n", line)
+                }
+            })
+            .collect()
+    }
+
+    // Picks enough `#`s that the query can't terminate the raw string early
+    fn to_raw_string(&self, contents: &str) -> String {
+        let mut hashes = "#".to_string();
+        while contents.contains(&format!("\"{}", hashes)) {
+            hashes.push('#');
+        }
+
+        format!("r{}\"{}\"{}", hashes, contents, hashes)
+    }
+}
+
+impl CodeGenerator for RustGenerator {
+    fn serialize_import(
+        &self,
+        ctx: &CodeGenerationContext,
+        path: &PathBuf,
+        identifiers: &Vec<String>,
+    ) -> ImportResult {
+        // An explicit module path is used verbatim. Otherwise, we assume the module tree mirrors
+        // the directory layout, so `./types` is a sibling module and `../types` is one level up.
+        let module_path = match &ctx.config.emit.type_file_module {
+            Some(module) => module.clone(),
+            None => {
+                let mut segments = vec!["super".to_string()];
+
+                for component in path.components() {
+                    match component {
+                        Component::ParentDir => segments.push("super".to_string()),
+                        Component::Normal(segment) => segments.push(
+                            PathBuf::from(segment)
+                                .with_extension("")
+                                .to_string_lossy()
+                                .to_string(),
+                        ),
+                        _ => (),
+                    }
+                }
+
+                segments.join("::")
+            }
+        };
+
+        ImportResult {
+            generated_code: format!("use {}::{{{}}};", module_path, identifiers.join(", ")),
+        }
+    }
+
+    fn serialize_type_prefix(
+        &self,
+        _ctx: &CodeGenerationContext,
+        _types: &Vec<Arc<CustomType>>,
+    ) -> Option<String> {
+        Some(
+            "// This file was generated by piqued.
+// You can edit it if you want, but you'll probably be sad
+// If you have some changes you want to make, check out the configuration options
+// Or file a bug report on our definitely-extant github
+
+"
+            .to_string(),
+        )
+    }
+
+    fn serialize_type(
+        &self,
+        ctx: &CodeGenerationContext,
+        type_: &CustomType,
+    ) -> SerializationResult {
+        let mut c = CodegenHelper::new("    ", "\n");
+
+        match type_ {
+            CustomType::Composite(CompositeType { name, fields, .. }) => {
+                let identifier = to_camel_case(name, true);
+
+                c.write_line(Some(&"#[derive(Debug, Clone, sqlx::Type)]"));
+                c.write_line(Some(&format!("#[sqlx(type_name = \"{}\")]", name)));
+                c.write_line(Some(&format!("pub struct {} {{", identifier)));
+                c.with_indent(|c| {
+                    for field in fields {
+                        let field_name = self.to_field_name(&field.name);
+                        let field_type =
+                            self.resolve_field_type(ctx, &field.column_type, field.nullable);
+
+                        if field_name.trim_start_matches("r#") != field.name {
+                            c.write_line(Some(&format!("#[sqlx(rename = \"{}\")]", field.name)));
+                        }
+                        c.write_line(Some(&format!(
+                            "pub {}: {},",
+                            field_name,
+                            field_type.get_type(vec_of)
+                        )));
+                    }
+                });
+                c.write_symbol("}");
+
+                SerializationResult {
+                    generated_code: c.serialize(),
+                    identifier,
+                    requires_import: vec![],
+                }
+            }
+
+            CustomType::Enum(EnumType { name, values, .. }) => {
+                let identifier = to_camel_case(name, true);
+
+                c.write_line(Some(
+                    &"#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]",
+                ));
+                c.write_line(Some(&format!("#[sqlx(type_name = \"{}\")]", name)));
+                c.write_line(Some(&format!("pub enum {} {{", identifier)));
+                c.with_indent(|c| {
+                    // Values that only differ by case or punctuation would otherwise be the same
+                    // variant twice
+                    let mut seen_variants: HashSet<String> = HashSet::new();
+                    for value in values {
+                        let variant =
+                            unique_name(&mut seen_variants, self.to_variant_name(value), "");
+
+                        c.write_line(Some(&format!("#[sqlx(rename = \"{}\")]", value)));
+                        c.write_line(Some(&format!("{},", variant)));
+                    }
+                });
+                c.write_symbol("}");
+
+                SerializationResult {
+                    generated_code: c.serialize(),
+                    identifier,
+                    requires_import: vec![],
+                }
+            }
+        }
+    }
+
+    fn serialize_query(
+        &self,
+        ctx: &CodeGenerationContext,
+        QueryContext(parsed_query, probe_result): &QueryContext,
+    ) -> SerializationResult {
+        let name = &parsed_query.details.name;
+
+        let identifier = to_camel_case(&name, true);
+        let row_identifier = format!("{}Row", identifier);
+        let const_identifier = format!("{}_QUERY", to_const_name(&self.to_field_name(name)));
+        let fn_identifier = self.to_field_name(name);
+        let mut imports: Vec<String> = vec![];
+
+        let mut seen_args: HashSet<String> = HashSet::new();
+        let args = probe_result
+            .args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| {
+                let arg_name = match &parsed_query.details.params {
+                    Some(param) if param.len() > i && !param[i].starts_with('$') => {
+                        self.to_field_name(&param[i])
+                    }
+                    _ => format!("arg{}", i + 1),
+                };
+                let arg_name = unique_name(&mut seen_args, arg_name, "_");
+
+                let type_ = self.resolve_type(ctx, arg);
+                imports.append(&mut type_.get_imports());
+
+                (arg_name, type_.get_type(vec_of))
+            })
+            .collect::<Vec<(String, String)>>();
+
+        let mut b = Builder::default();
+
+        let doc_comment = self.to_doc_comment(&parsed_query.details.comment);
+        let has_rows = !probe_result.column_names.is_empty();
+
+        if has_rows {
+            // Postgres is happy to return several columns with the same name, but a struct isn't
+            let field_names = unique_names(
+                probe_result
+                    .column_names
+                    .iter()
+                    .map(|column| self.to_field_name(column)),
+                "_",
+            );
+
+            b.append(format!(
+                "/// A row returned by [`{}`]\n#[derive(Debug, Clone, sqlx::FromRow)]\n",
+                fn_identifier
+            ));
+            b.append(format!("pub struct {} {{\n", row_identifier));
+
+            for (i, ((column, field_name), type_)) in probe_result
+                .column_names
+                .iter()
+                .zip(&field_names)
+                .zip(&probe_result.column_types)
+                .enumerate()
+            {
+                let nullable = ctx.config.emit.force_nullable_results
+                    || probe_result
+                        .column_nullable
+                        .get(i)
+                        .copied()
+                        .unwrap_or(false);
+                let resolved = self.resolve_field_type(ctx, type_, nullable);
+                imports.append(&mut self.resolve_type(ctx, type_).get_imports());
+
+                if field_name.trim_start_matches("r#") != column {
+                    b.append(format!("    #[sqlx(rename = \"{}\")]\n", column));
+                }
+                b.append(format!(
+                    "    pub {}: {},\n",
+                    field_name,
+                    resolved.get_type(vec_of)
+                ));
+            }
+
+            b.append("}\n\n");
+        }
+
+        b.append(format!(
+            "pub const {}: &str = {};\n\n",
+            const_identifier,
+            self.to_raw_string(&parsed_query.contents)
+        ));
+
+        let return_type = if has_rows {
+            format!(
+                "sqlx::query::QueryAs<'static, sqlx::Postgres, {}, sqlx::postgres::PgArguments>",
+                row_identifier
+            )
+        } else {
+            "sqlx::query::Query<'static, sqlx::Postgres, sqlx::postgres::PgArguments>".to_string()
+        };
+        let constructor = if has_rows {
+            "sqlx::query_as"
+        } else {
+            "sqlx::query"
+        };
+
+        b.append(doc_comment);
+        b.append(format!(
+            "pub fn {}({}) -> {} {{\n",
+            fn_identifier,
+            args.iter()
+                .map(|(arg_name, type_)| format!("{}: {}", arg_name, type_))
+                .collect::<Vec<String>>()
+                .join(", "),
+            return_type
+        ));
+        b.append(format!("    {}({})", constructor, const_identifier));
+        for (arg_name, _) in &args {
+            b.append(format!("\n        .bind({})", arg_name));
+        }
+        b.append("\n}");
+
+        SerializationResult {
+            generated_code: b.string().unwrap(),
+            identifier: fn_identifier,
+            requires_import: imports,
+        }
+    }
+
+    fn serialize_table(&self, _ctx: &CodeGenerationContext, table: &String) -> SerializationResult {
+        let export_name = format!("{}_TABLE", to_const_name(&self.to_field_name(table)));
+
+        SerializationResult {
+            generated_code: format!("pub const {}: &str = \"{}\";\n", export_name, table),
+            identifier: export_name,
+            requires_import: vec![],
+        }
+    }
+
+    fn resolve_file_path(&self, _ctx: &CodeGenerationContext, path: &PathBuf) -> String {
+        let result = path.clone();
+        result.with_extension("rs").to_str().unwrap().to_string()
+    }
+}
@@ -1,41 +1,47 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
 use crate::{
     code_builder::codegen_helper::CodegenHelper,
     codegen::{
         codegen::{
-            CodeGenerationContext, CodeGenerator, ImportResult, QueryContext, SerializationResult,
+            CodeGenerationContext, CodeGenerator, ImportResult, QueryContext, RegistryEntry,
+            SerializationResult,
         },
-        utils::{indent_block, to_camel_case},
+        utils::{comment_lines, indent_block, to_camel_case, ResolvedType},
     },
-    query::query::{CompositeType, CustomType, EnumType},
+    config::config::EnumStyle,
+    query::query::{ColumnType, CompositeType, CustomType, EnumType},
 };
 use string_builder::Builder;
 
-enum ResolvedType {
-    Native(String),
-    Import(String),
-    Array(Box<ResolvedType>),
+// How TypeScript spells a resolved type. Imported types are namespaces, with the type itself at `.t`
+// and its parse spec at `.spec`
+trait TSType {
+    fn get_ts_type(&self) -> String;
+    fn get_readonly_type(&self) -> String;
+    fn get_spec(&self) -> String;
 }
 
-impl ResolvedType {
-    fn _get_imports(&self) -> Vec<String> {
+impl TSType for ResolvedType {
+    fn get_ts_type(&self) -> String {
         match self {
-            ResolvedType::Native(_) => vec![],
-            ResolvedType::Import(path) => vec![path.to_string()],
-            ResolvedType::Array(inner) => inner._get_imports(),
+            ResolvedType::Native(val) => val.to_string(),
+            ResolvedType::Import(val) => format!("{}.t", val.to_string()),
+            ResolvedType::Array(inner) => format!("{}[]", inner.get_ts_type()),
         }
     }
 
-    pub fn get_type(&self) -> String {
+    fn get_readonly_type(&self) -> String {
         match self {
-            ResolvedType::Native(val) => val.to_string(),
-            ResolvedType::Import(val) => format!("{}.t", val.to_string()),
-            ResolvedType::Array(inner) => format!("{}[]", inner.get_type()),
+            ResolvedType::Array(inner) => match inner.as_ref() {
+                ResolvedType::Array(_) => format!("readonly ({})[]", inner.get_readonly_type()),
+                _ => format!("readonly {}[]", inner.get_readonly_type()),
+            },
+            _ => self.get_ts_type(),
         }
     }
 
-    pub fn get_spec(&self) -> String {
+    fn get_spec(&self) -> String {
         match self {
             ResolvedType::Native(val) if val == "number" => "Number".to_string(),
             ResolvedType::Native(val) if val == "string" => "String".to_string(),
@@ -48,24 +54,6 @@ impl ResolvedType {
             }
         }
     }
-
-    pub fn is_ultimately_custom_type(&self) -> bool {
-        match self {
-            ResolvedType::Native(_) => false,
-            ResolvedType::Import(_) => true,
-            ResolvedType::Array(inner) => inner.is_ultimately_custom_type(),
-        }
-    }
-}
-
-impl ToString for ResolvedType {
-    fn to_string(&self) -> String {
-        match self {
-            ResolvedType::Native(native) => native.to_string(),
-            ResolvedType::Import(import) => import.to_string(),
-            ResolvedType::Array(array) => format!("{}[]", array.to_string()),
-        }
-    }
 }
 
 pub struct TSGenerator {}
@@ -75,25 +63,74 @@ impl TSGenerator {
         return TSGenerator {};
     }
 
-    fn resolve_type(&self, ctx: &CodeGenerationContext, type_: &str) -> ResolvedType {
-        if ctx.query.custom_types_by_name.contains_key(type_) {
+    fn resolve_type(&self, ctx: &CodeGenerationContext, type_: &ColumnType) -> ResolvedType {
+        let type_ = match type_ {
+            ColumnType::ArrayOf(element) => {
+                return ResolvedType::Array(Box::new(self.resolve_type(ctx, element)))
+            }
+            ColumnType::Named(name) => name.as_str(),
+        };
+
+        if ctx.custom_types_by_name.contains_key(type_) || ctx.is_branded_scalar(type_) {
             return ResolvedType::Import(to_camel_case(&type_.to_string(), true));
         }
 
-        if type_.starts_with('_') {
-            return ResolvedType::Array(Box::new(self.resolve_type(ctx, &type_[1..])));
-        }
+        return ResolvedType::Native(self.native_type(type_).to_string());
+    }
 
-        let native_type = match type_ {
-            "int4" | "int8" | "float4" | "float8" | "numeric" => "number",
+    fn native_type(&self, type_: &str) -> &'static str {
+        match type_ {
+            "int2" | "int4" | "int8" | "float4" | "float8" | "numeric" => "number",
             "text" | "bytea" | "varchar" | "char" | "uuid" => "string",
             "bool" => "boolean",
             "date" | "timestamp" | "time" | "timestamptz" => "Date",
             "json" | "jsonb" => "any",
             _ => "string",
-        };
+        }
+    }
 
-        return ResolvedType::Native(native_type.to_string());
+    // The type of a property that's read back out of the database, per `emit.readonly`
+    fn output_type(&self, ctx: &CodeGenerationContext, type_: &ResolvedType) -> String {
+        if ctx.config.emit.readonly {
+            type_.get_readonly_type()
+        } else {
+            type_.get_ts_type()
+        }
+    }
+
+    fn readonly_prefix(&self, ctx: &CodeGenerationContext) -> &'static str {
+        if ctx.config.emit.readonly {
+            "readonly "
+        } else {
+            ""
+        }
+    }
+
+    // Any tags follow the comment's own lines, but don't stand in for a missing comment
+    fn to_doc_comment(&self, comment: &str, tags: &[String]) -> Option<String> {
+        let lines = comment_lines(comment);
+        if lines.is_empty() {
+            return None;
+        }
+
+        let mut b = Builder::default();
+        b.append("/**\n");
+
+        let tag_lines = tags.iter().map(|tag| tag.as_str());
+        let separator = if tags.is_empty() { None } else { Some("") };
+        for line in lines.iter().copied().chain(separator).chain(tag_lines) {
+            // A stray `*/` would end the comment early
+            let escaped = line.replace("*/", "*\\/");
+
+            if escaped.is_empty() {
+                b.append(" *\n");
+            } else {
+                b.append(format!(" * {}\n", escaped));
+            }
+        }
+        b.append(" */\n");
+
+        Some(b.string().unwrap())
     }
 
     fn to_safe_identifier(&self, type_: &String) -> Option<String> {
@@ -132,7 +169,10 @@ impl CodeGenerator for TSGenerator {
         b.append(" } from \"");
         b.append(path.as_path().to_str().unwrap());
 
-        if ctx.config.emit.module_type.to_lowercase() == "esm" {
+        // Module aliases are resolved by the bundler, so they don't get an extension
+        if ctx.config.emit.module_type.to_lowercase() == "esm"
+            && ctx.config.emit.type_file_module.is_none()
+        {
             b.append(".js");
         }
 
@@ -159,16 +199,54 @@ impl CodeGenerator for TSGenerator {
         )
     }
 
+    fn serialize_branded_scalar(
+        &self,
+        _ctx: &CodeGenerationContext,
+        type_name: &String,
+    ) -> Option<SerializationResult> {
+        let mut c = CodegenHelper::new("    ", "\n");
+
+        let identifier = to_camel_case(type_name, true);
+        let native_type = ResolvedType::Native(self.native_type(type_name).to_string());
+
+        c.write_token("export namespace");
+        c.write_token(&identifier);
+        c.write_line(Some(&"{"));
+        c.with_indent(|c| {
+            c.write_line(Some(&format!(
+                "export type t = {} & {{ readonly __brand: \"{}\" }};",
+                native_type.get_ts_type(),
+                type_name
+            )));
+            // Branded values are parsed just like the type they're branding
+            c.write_line(Some(&format!(
+                "export const spec = {};",
+                native_type.get_spec()
+            )));
+        });
+        c.write_symbol("}");
+
+        Some(SerializationResult {
+            generated_code: c.serialize(),
+            identifier,
+            requires_import: vec![],
+        })
+    }
+
     fn serialize_query_prefix(
         &self,
         _ctx: &CodeGenerationContext,
-        _queries: &Vec<crate::codegen::codegen::QueryContext>,
+        queries: &Vec<crate::codegen::codegen::QueryContext>,
     ) -> Option<String> {
-        Some(
-            "import { Query, EntityQueries } from \"@piqued/client\";
-"
-            .to_string(),
-        )
+        let mut imports = vec!["Query", "EntityQueries", "SmartClient", "parseRow"];
+        if queries.iter().any(|query| query.0.details.cache.is_some()) {
+            imports.push("memoize");
+        }
+
+        Some(format!(
+            "import {{ {} }} from \"@piqued/client\";\n",
+            imports.join(", ")
+        ))
     }
 
     fn serialize_type(
@@ -190,19 +268,36 @@ impl CodeGenerator for TSGenerator {
                     c.write_line(Some(&"export type t = {"));
                     c.with_indent(|c| {
                         for field in fields {
-                            let native_type = self.resolve_type(ctx, &field.type_name);
+                            let native_type = self.resolve_type(ctx, &field.column_type);
 
+                            c.write(&self.readonly_prefix(ctx));
                             c.with_duouble_quote(|c| c.write(&field.name));
                             c.write_symbol(": ");
-                            c.write(&&native_type.get_type());
+                            c.write(&self.output_type(ctx, &native_type));
                             c.write_symbol(";");
                             c.write_line(None);
 
-                            imports.push(field.type_name.clone());
+                            imports.append(&mut native_type.get_imports());
                         }
                     });
                     c.write_line(Some(&"};"));
 
+                    // Generated columns can be read, but never written to
+                    let generated_fields = fields
+                        .iter()
+                        .filter(|field| field.generated)
+                        .map(|field| format!("\"{}\"", field.name))
+                        .collect::<Vec<String>>();
+
+                    if generated_fields.is_empty() {
+                        c.write_line(Some(&"export type writable = t;"));
+                    } else {
+                        c.write_line(Some(&format!(
+                            "export type writable = Omit<t, {}>;",
+                            generated_fields.join(" | ")
+                        )));
+                    }
+
                     c.write_line(None);
 
                     c.write_line(Some(&"export const spec = {"));
@@ -211,7 +306,7 @@ impl CodeGenerator for TSGenerator {
                         c.write_line(Some(&"fields: () => ["));
                         c.with_indent(|c| {
                             for field in fields {
-                                let native_type = self.resolve_type(ctx, &field.type_name);
+                                let native_type = self.resolve_type(ctx, &field.column_type);
 
                                 c.write_symbol("[");
                                 c.with_duouble_quote(|c| c.write(&field.name));
@@ -220,7 +315,7 @@ impl CodeGenerator for TSGenerator {
                                 c.write_symbol("],");
                                 c.write_line(None);
 
-                                imports.push(field.type_name.clone());
+                                imports.append(&mut native_type.get_imports());
                             }
                         });
                         c.write_line(Some(&"] as const,"));
@@ -241,22 +336,79 @@ impl CodeGenerator for TSGenerator {
                 let mut c = CodegenHelper::new("    ", "\n");
 
                 let identifier = to_camel_case(name, true);
+                let enum_style = ctx.config.emit.enum_style;
+
+                // The enum and the namespace below merge into a single declaration
+                if enum_style == EnumStyle::Enum {
+                    c.write_token(&"export enum");
+                    c.write_token(&identifier);
+                    c.write_line(Some(&"{"));
+                    c.with_indent(|c| {
+                        for value in values {
+                            let member = to_camel_case(value, true);
+                            match self.to_safe_identifier(&member) {
+                                Some(safe) if safe == member => c.write(&member),
+                                _ => c.with_duouble_quote(|c| c.write(&value)),
+                            }
+                            c.write_symbol(" = ");
+                            c.with_duouble_quote(|c| c.write(&value));
+                            c.write_symbol(",");
+                            c.write_line(None);
+                        }
+                    });
+                    c.write_line(Some(&"}"));
+                    c.write_line(None);
+                }
 
                 c.write_token(&"export namespace");
                 c.write_token(&identifier);
                 c.write_line(Some(&"{"));
 
                 c.with_indent(|c| {
-                    c.write_line(Some(&"export type t ="));
-                    c.with_indent(|c| {
-                        for value in values {
-                            c.write(&"| ");
-                            c.with_duouble_quote(|c| c.write(&value));
+                    match enum_style {
+                        EnumStyle::Union => {
+                            c.write_line(Some(&"export type t ="));
+                            c.with_indent(|c| {
+                                for value in values {
+                                    c.write(&"| ");
+                                    c.with_duouble_quote(|c| c.write(&value));
+                                    c.write_line(None);
+                                }
+                                c.write(&";");
+                                c.write_line(None);
+                            });
+                        }
+                        EnumStyle::Enum => {
+                            c.write_line(Some(&format!("export type t = {};", identifier)));
+                        }
+                        EnumStyle::BrandedUnion => {
+                            c.write_line(Some(&"export type t = ("));
+                            c.with_indent(|c| {
+                                for value in values {
+                                    c.write(&"| ");
+                                    c.with_duouble_quote(|c| c.write(&value));
+                                    c.write_line(None);
+                                }
+                            });
+                            c.write_line(Some(&format!(
+                                ") & {{ readonly __brand: \"{}\" }};",
+                                identifier
+                            )));
+
                             c.write_line(None);
+
+                            // Branded values can't be written as literals, so hand them out here
+                            c.write_line(Some(&"export const values = ["));
+                            c.with_indent(|c| {
+                                for value in values {
+                                    c.with_duouble_quote(|c| c.write(&value));
+                                    c.write_symbol(",");
+                                    c.write_line(None);
+                                }
+                            });
+                            c.write_line(Some(&"] as unknown as readonly t[];"));
                         }
-                        c.write(&";");
-                        c.write_line(None);
-                    });
+                    }
 
                     c.write_line(None);
 
@@ -304,7 +456,9 @@ impl CodeGenerator for TSGenerator {
             .iter()
             .enumerate()
             .map(|(i, _arg)| match &parsed_query.details.params {
-                Some(param) if param.len() > i => to_camel_case(&param[i], false),
+                Some(param) if param.len() > i && !param[i].starts_with('$') => {
+                    to_camel_case(&param[i], false)
+                }
                 _ => format!("${}", i),
             })
             .collect::<Vec<String>>();
@@ -325,11 +479,9 @@ impl CodeGenerator for TSGenerator {
                 .iter()
                 .map(|arg| {
                     let type_ = self.resolve_type(ctx, arg);
-                    if let ResolvedType::Import(import) = &type_ {
-                        imports.push(import.clone());
-                    }
+                    imports.append(&mut type_.get_imports());
 
-                    type_.get_type()
+                    type_.get_ts_type()
                 })
                 .zip(arg_names)
                 .collect::<Vec<(String, String)>>();
@@ -359,18 +511,30 @@ impl CodeGenerator for TSGenerator {
             )
         };
 
+        let scalar =
+            ctx.config.emit.scalarize_single_column && probe_result.column_names.len() == 1;
+
         let (output_array_type, output_object_type) = {
             let resolved_types = probe_result
                 .column_names
                 .iter()
                 .zip(&probe_result.column_types)
-                .map(|(name, type_)| {
+                .enumerate()
+                .map(|(i, (name, type_))| {
                     let resolved = self.resolve_type(ctx, type_);
-                    if let ResolvedType::Import(import) = &resolved {
-                        imports.push(import.clone());
+                    imports.append(&mut resolved.get_imports());
+
+                    let nullable = probe_result
+                        .column_nullable
+                        .get(i)
+                        .copied()
+                        .unwrap_or(false);
+                    let type_ = self.output_type(ctx, &resolved);
+                    if ctx.config.emit.force_nullable_results || nullable {
+                        return (name, format!("{} | null", type_));
                     }
 
-                    return (name, resolved.get_type());
+                    return (name, type_);
                 })
                 .collect::<Vec<(&String, String)>>();
 
@@ -389,13 +553,25 @@ impl CodeGenerator for TSGenerator {
 
             let object_types = resolved_types
                 .iter()
-                .map(|(name, type_)| format!("    \"{}\": {},\n", name, type_))
+                .map(|(name, type_)| {
+                    format!(
+                        "    {}\"{}\": {},\n",
+                        self.readonly_prefix(ctx),
+                        name,
+                        type_
+                    )
+                })
                 .collect::<Vec<String>>()
                 .join("");
 
+            let object_type = match resolved_types.as_slice() {
+                [(_, type_)] if scalar => type_.clone(),
+                _ => format!("{{\n{}}}", object_types),
+            };
+
             (
-                format!("[\n{}]", array_types),
-                format!("{{\n{}}}", object_types),
+                format!("{}[\n{}]", self.readonly_prefix(ctx), array_types),
+                object_type,
             )
         };
 
@@ -408,11 +584,11 @@ impl CodeGenerator for TSGenerator {
                     let resolved = self.resolve_type(ctx, type_);
                     match &resolved {
                         ResolvedType::Array(inner) if inner.is_ultimately_custom_type() => {
-                            imports.append(&mut resolved._get_imports());
+                            imports.append(&mut resolved.get_imports());
                             (name, resolved.get_spec())
                         }
                         ResolvedType::Import(_) => {
-                            imports.append(&mut resolved._get_imports());
+                            imports.append(&mut resolved.get_imports());
                             (name, resolved.get_spec())
                         }
                         _ => (name, "undefined".to_string()),
@@ -432,11 +608,23 @@ impl CodeGenerator for TSGenerator {
         let escaped_query = parsed_query.contents.replace('`', "\\`");
 
         let mut b = Builder::default();
+
+        let comment = &parsed_query.details.comment;
+        if let Some(doc_comment) = self.to_doc_comment(comment, &[]) {
+            b.append(doc_comment);
+        }
+
         b.append(format!("export const {}: Query<{}.InputArray, {}.InputObject, {}.OutputArray, {}.OutputObject> = {{\n", identifier, identifier, identifier, identifier, identifier));
         b.append(format!("    name: \"{}\",\n", name));
         b.append(format!("    query: `{}`,\n", escaped_query));
         b.append(format!("    params: {},\n", param_names));
         b.append(format!("    spec: {},\n", parse_spec));
+        if scalar {
+            b.append("    scalar: true,\n");
+        }
+        if let Some(cache) = &parsed_query.details.cache {
+            b.append(format!("    cache: {{ ttl: {} }},\n", cache.ttl));
+        }
         b.append("    _brand: undefined as any,\n");
         b.append("};\n\n");
 
@@ -453,11 +641,123 @@ impl CodeGenerator for TSGenerator {
             &format!("export type OutputArray = {};\n", output_array_type),
             1,
         ));
+        if let Some(doc_comment) = self.to_doc_comment(comment, &[]) {
+            b.append(indent_block(&doc_comment, 1));
+        }
         b.append(indent_block(
             &format!("export type OutputObject = {};\n", output_object_type),
             1,
         ));
-        b.append("}");
+        b.append("}\n\n");
+
+        // A runner that issues the query directly, for callers that don't go through `EntityQueries`
+        let runner_params = probe_result
+            .args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| {
+                let param = match &parsed_query.details.params {
+                    Some(params) if params.len() > i && !params[i].starts_with('$') => {
+                        to_camel_case(&params[i], false)
+                    }
+                    _ => format!("arg{}", i + 1),
+                };
+                let accessor = match self.to_safe_identifier(&param) {
+                    Some(safe) if safe == param => format!("params.{}", param),
+                    _ => format!("params[\"{}\"]", param),
+                };
+
+                (param, self.resolve_type(ctx, arg).get_ts_type(), accessor)
+            })
+            .collect::<Vec<(String, String, String)>>();
+
+        let runner_signature = if runner_params.is_empty() {
+            "client: SmartClient".to_string()
+        } else if ctx.config.emit.export_param_types {
+            let params_identifier = format!("{}Params", identifier);
+
+            b.append(format!("export interface {} {{\n", params_identifier));
+            for (param, type_, _) in runner_params.iter() {
+                b.append(format!("    \"{}\": {};\n", param, type_));
+            }
+            b.append("}\n\n");
+
+            format!("client: SmartClient, params: {}", params_identifier)
+        } else {
+            let fields = runner_params
+                .iter()
+                .map(|(param, type_, _)| format!("\"{}\": {}", param, type_))
+                .collect::<Vec<String>>()
+                .join(", ");
+
+            format!("client: SmartClient, params: {{ {} }}", fields)
+        };
+
+        let runner_values = runner_params
+            .iter()
+            .map(|(_, _, accessor)| accessor.clone())
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let param_tags = runner_params
+            .iter()
+            .map(|(param, type_, _)| match self.to_safe_identifier(param) {
+                Some(safe) if &safe == param => format!("@param {{{}}} params.{}", type_, param),
+                _ => format!("@param {{{}}} params[\"{}\"]", type_, param),
+            })
+            .collect::<Vec<String>>();
+        if let Some(doc_comment) = self.to_doc_comment(comment, &param_tags) {
+            b.append(doc_comment);
+        }
+
+        let runner = format!(
+            "async function {}({}): Promise<{}.OutputObject[]> {{\n",
+            to_camel_case(&name, false),
+            runner_signature,
+            identifier
+        );
+        // `@cache` queries are memoized by their parameters, outside of transactions
+        match &parsed_query.details.cache {
+            Some(cache) => b.append(format!(
+                "export const {} = memoize({}, {}",
+                to_camel_case(&name, false),
+                cache.ttl,
+                runner
+            )),
+            None => b.append(format!("export {}", runner)),
+        }
+        if ctx.config.emit.runtime_checks {
+            // Templates and positional parameters make it easy to lose track of how many there are
+            let provided = if runner_params.is_empty() {
+                "arguments.length - 1"
+            } else {
+                "params == null ? 0 : Object.keys(params).length"
+            };
+
+            b.append(format!("    const provided = {};\n", provided));
+            b.append(format!(
+                "    if (provided !== {}) {{\n",
+                runner_params.len()
+            ));
+            b.append(format!(
+                "        throw new Error(`Query \"{}\" expects {} parameter(s), but was given ${{provided}}`);\n",
+                name,
+                runner_params.len()
+            ));
+            b.append("    }\n");
+        }
+        b.append(format!(
+            "    const result = await client.query(`{}`, [{}]);\n",
+            escaped_query, runner_values
+        ));
+        b.append(format!(
+            "    return result.rows.map((row) => parseRow({}, row));\n",
+            identifier
+        ));
+        b.append(match parsed_query.details.cache {
+            Some(_) => "});",
+            None => "}",
+        });
 
         SerializationResult {
             generated_code: b.string().unwrap(),
@@ -516,6 +816,98 @@ impl CodeGenerator for TSGenerator {
         };
     }
 
+    fn serialize_registry<'a>(
+        &'a self,
+        ctx: &'a CodeGenerationContext,
+        entries: &'a Vec<RegistryEntry>,
+    ) -> Option<Box<dyn Iterator<Item = String> + Send + 'a>> {
+        // Each query file is imported once, under a name derived from the file
+        let mut aliases: HashMap<&PathBuf, String> = HashMap::new();
+        let mut imports = vec![];
+        for entry in entries {
+            if aliases.contains_key(&entry.import_path) {
+                continue;
+            }
+
+            let stem = entry
+                .import_path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let base = format!("{}Queries", to_camel_case(&stem, true));
+
+            let mut alias = base.clone();
+            let mut suffix = 2;
+            while aliases.values().any(|existing| existing == &alias) {
+                alias = format!("{}{}", base, suffix);
+                suffix += 1;
+            }
+
+            imports.push((&entry.import_path, alias.clone()));
+            aliases.insert(&entry.import_path, alias);
+        }
+
+        let extension = if ctx.config.emit.module_type.to_lowercase() == "esm" {
+            ".js"
+        } else {
+            ""
+        };
+
+        let mut header = Builder::default();
+        header.append("import { EntityQueries, QueryExecutor } from \"@piqued/client\";\n");
+        for (path, alias) in &imports {
+            header.append(format!(
+                "import * as {} from \"{}{}\";\n",
+                alias,
+                path.to_str().unwrap(),
+                extension
+            ));
+        }
+        header.append("\n");
+        header.append("export interface Database {\n");
+
+        let aliases = Arc::new(aliases);
+        let query_for = move |entry: &RegistryEntry| {
+            format!(
+                "{}.{}",
+                aliases[&entry.import_path],
+                to_camel_case(&entry.name, true)
+            )
+        };
+        let interface_query_for = query_for.clone();
+
+        let interface = entries.iter().map(move |entry| {
+            let query = interface_query_for(entry);
+            format!(
+                "    \"{}\": QueryExecutor<{}.InputArray, {}.InputObject, {}.OutputArray, {}.OutputObject>;\n",
+                to_camel_case(&entry.name, false),
+                query,
+                query,
+                query,
+                query
+            )
+        });
+        let queries = entries.iter().map(move |entry| {
+            format!(
+                "    \"{}\": {},\n",
+                to_camel_case(&entry.name, false),
+                query_for(entry)
+            )
+        });
+
+        Some(Box::new(
+            std::iter::once(header.string().unwrap())
+                .chain(interface)
+                .chain(std::iter::once(
+                    "}\n\nexport const queries = {\n".to_string(),
+                ))
+                .chain(queries)
+                .chain(std::iter::once(
+                    "};\n\nexport default EntityQueries(queries);\n".to_string(),
+                )),
+        ))
+    }
+
     fn resolve_file_path(&self, _ctx: &CodeGenerationContext, path: &PathBuf) -> String {
         let result = path.clone();
         result.with_extension("ts").to_str().unwrap().to_string()
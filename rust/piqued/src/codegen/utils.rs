@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 pub fn to_camel_case(identifier: &String, pascal_mode: bool) -> String {
     let mut chars: Vec<char> = vec![];
 
@@ -21,30 +23,126 @@ pub fn to_camel_case(identifier: &String, pascal_mode: bool) -> String {
     chars.iter().collect()
 }
 
+// Underscores and anything else that can't be in an identifier stay put as underscores, and a new
+// word starts wherever the case changes, so `getHTTPStatus` becomes `get_http_status`
 pub fn to_snake_case(identifier: &String) -> String {
-    let mut chars: Vec<char> = vec![];
+    let chars: Vec<char> = identifier.chars().collect();
+    let mut snake = String::new();
 
-    let mut has_pending_underscore = false;
-    for char_ in identifier.chars() {
-        if char_ == '_' {
-            has_pending_underscore = true;
+    for (i, char_) in chars.iter().enumerate() {
+        if !char_.is_ascii_alphanumeric() {
+            snake.push('_');
             continue;
         }
 
-        if !char_.is_alphanumeric() {
-            continue;
-        }
+        if char_.is_ascii_uppercase() && i > 0 && !snake.ends_with('_') {
+            let previous = chars[i - 1];
+            let next = chars.get(i + 1);
 
-        if char_.is_uppercase() {
-            if has_pending_underscore {
-                chars.push('_');
-                has_pending_underscore = false;
+            if previous.is_ascii_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_ascii_uppercase() && next.is_some_and(|c| c.is_ascii_lowercase()))
+            {
+                snake.push('_');
             }
-            chars.push(char_.to_ascii_lowercase())
         }
+
+        snake.push(char_.to_ascii_lowercase());
     }
 
-    chars.iter().collect()
+    snake
+}
+
+// `name`, or when that's already been taken, `name` numbered from 2 onwards
+pub fn unique_name(seen: &mut HashSet<String>, name: String, separator: &str) -> String {
+    let mut unique = name.clone();
+    let mut suffix = 2;
+    while !seen.insert(unique.clone()) {
+        unique = format!("{}{}{}", name, separator, suffix);
+        suffix += 1;
+    }
+
+    unique
+}
+
+// The names in order, with any repeats numbered like `unique_name` does
+pub fn unique_names(names: impl Iterator<Item = String>, separator: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+
+    names
+        .map(|name| unique_name(&mut seen, name, separator))
+        .collect()
+}
+
+// `name` in snake case, with `prefix` in front if that leaves it empty or starting with a digit.
+// `escape` then gets to steer it clear of the language's keywords.
+pub fn to_snake_identifier(name: &str, prefix: &str, escape: impl Fn(String) -> String) -> String {
+    let mut identifier = to_snake_case(&name.to_string());
+
+    if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier = format!("{}{}", prefix, identifier);
+    }
+
+    escape(identifier)
+}
+
+// A field name in upper case, without anything that was added to it to get around a keyword
+pub fn to_const_name(field_name: &str) -> String {
+    field_name
+        .trim_start_matches("r#")
+        .trim_end_matches('_')
+        .to_ascii_uppercase()
+}
+
+// The lines of a comment, without trailing whitespace or the blank lines around them
+pub fn comment_lines(comment: &str) -> Vec<&str> {
+    let lines = comment
+        .lines()
+        .map(|line| line.trim_end())
+        .skip_while(|line| line.is_empty())
+        .collect::<Vec<&str>>();
+    let len = lines.len()
+        - lines
+            .iter()
+            .rev()
+            .take_while(|line| line.is_empty())
+            .count();
+
+    lines[..len].to_vec()
+}
+
+// A type in the generated code, along with whatever has to be imported for it
+pub enum ResolvedType {
+    Native(String),
+    Import(String),
+    Array(Box<ResolvedType>),
+}
+
+impl ResolvedType {
+    pub fn get_imports(&self) -> Vec<String> {
+        match self {
+            ResolvedType::Native(_) => vec![],
+            ResolvedType::Import(path) => vec![path.to_string()],
+            ResolvedType::Array(inner) => inner.get_imports(),
+        }
+    }
+
+    // Languages only differ in how they spell an array of something
+    pub fn get_type(&self, array_of: fn(String) -> String) -> String {
+        match self {
+            ResolvedType::Native(val) => val.to_string(),
+            ResolvedType::Import(val) => val.to_string(),
+            ResolvedType::Array(inner) => array_of(inner.get_type(array_of)),
+        }
+    }
+
+    pub fn is_ultimately_custom_type(&self) -> bool {
+        match self {
+            ResolvedType::Native(_) => false,
+            ResolvedType::Import(_) => true,
+            ResolvedType::Array(inner) => inner.is_ultimately_custom_type(),
+        }
+    }
 }
 
 pub fn indent_block(block: &String, indent: usize) -> String {
@@ -59,3 +157,14 @@ pub fn indent_block(block: &String, indent: usize) -> String {
 
     lines.join("\n")
 }
+
+pub fn contains_identifier(code: &str, identifier: &str) -> bool {
+    let is_identifier_char = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+
+    code.match_indices(identifier).any(|(i, _)| {
+        let before = code[..i].chars().last();
+        let after = code[i + identifier.len()..].chars().next();
+
+        !before.map_or(false, is_identifier_char) && !after.map_or(false, is_identifier_char)
+    })
+}
@@ -1,42 +1,185 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::utils::result::Result;
+use crate::utils::result::{PiquedError, Result};
+use glob::{MatchOptions, Pattern};
+use schemars::{schema_for, JsonSchema};
 use serde_derive::Deserialize;
 
-#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Default)]
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, JsonSchema, Default)]
 #[serde(rename_all = "camelCase", default)]
 pub struct Config {
     #[serde(default = "default_postgres_obj")]
     pub postgres: PostgresConfig,
     #[serde(default = "default_emit_obj")]
     pub emit: EmitConfig,
+    #[serde(default = "default_lsp_obj")]
+    pub lsp: LspConfig,
+    #[serde(default = "default_workspace_obj")]
     pub workspace: ConfigWorkspace,
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Default)]
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, JsonSchema, Default)]
 #[serde(rename_all = "camelCase", default)]
 pub struct PostgresConfig {
+    // Either a `postgresql://` URL or a key-value string (`host=... dbname=...`)
     #[serde(default = "default_postgres_uri")]
     pub uri: String,
     #[serde(default = "default_schema")]
     pub schema: String,
+    // A file (relative to the workspace root) to keep the loaded schema in between runs
+    #[serde(default)]
+    pub schema_cache: Option<String>,
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Default)]
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, JsonSchema, Default)]
 #[serde(rename_all = "camelCase", default)]
 pub struct EmitConfig {
+    #[serde(default)]
+    pub language: Language,
     #[serde(default = "default_type_file")]
     pub type_file: String,
     #[serde(default = "default_module_type")]
     pub module_type: String,
     #[serde(default)]
+    pub type_file_module: Option<String>,
+    #[serde(default)]
     pub table_file: Option<String>,
+    #[serde(default)]
+    pub registry: Option<String>,
+    #[serde(default)]
+    pub force_nullable_results: bool,
+    #[serde(default)]
+    pub scalarize_single_column: bool,
+    #[serde(default)]
+    pub allow_duplicate_names: bool,
+    #[serde(default)]
+    pub enum_style: EnumStyle,
+    // Marks the properties of rows and composite types as `readonly`, and their arrays too
+    #[serde(default)]
+    pub readonly: bool,
+    // Exports each query's parameters as an interface of their own, e.g. `GetUserParams`
+    #[serde(default)]
+    pub export_param_types: bool,
+    // Has each query's function throw when it's called with the wrong number of parameters
+    #[serde(default)]
+    pub runtime_checks: bool,
+    // Postgres types, e.g. `uuid`, to give a branded alias of their own rather than their plain
+    // TypeScript type, so that they can't be mixed up with any other string
+    #[serde(default)]
+    pub branded_scalars: Vec<String>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum Language {
+    #[default]
+    Typescript,
+    Rust,
+    Python,
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Default)]
+// How postgres enums are emitted in TypeScript
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum EnumStyle {
+    #[default]
+    Union,
+    Enum,
+    BrandedUnion,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LspConfig {
+    #[serde(default)]
+    pub min_severity: Severity,
+    // A `.sql` file with the `CREATE TABLE`s for the schema, used to jump to definitions
+    #[serde(default)]
+    pub schema_file: Option<String>,
+    // Flags a bare `SELECT *`, whose generated type silently changes along with the schema
+    #[serde(default)]
+    pub warn_select_star: bool,
+    // The channel to `LISTEN` on for schema changes, or nothing to not listen at all
+    #[serde(default = "default_schema_channel")]
+    pub schema_channel: String,
+    // Runs `EXPLAIN` on queries with an `@index` hint and warns when the plan doesn't use it
+    #[serde(default)]
+    pub check_index_hints: bool,
+}
+
+// Ordered from most to least severe
+#[derive(Debug, Eq, PartialEq, PartialOrd, Ord, Clone, Copy, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+    #[default]
+    Hint,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, JsonSchema, Default)]
 #[serde(rename_all = "camelCase", default)]
 pub struct ConfigWorkspace {
     pub root: Option<PathBuf>,
+    // Extensions of the files holding queries, e.g. `sql` or `sql.tmpl`, matched case-insensitively
+    #[serde(default = "default_query_extensions")]
+    pub query_extensions: Vec<String>,
+    // Globs of files and directories to leave out, relative to the root. One without a `/` matches
+    // the name of a file or directory anywhere, e.g. `node_modules`
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl ConfigWorkspace {
+    pub fn is_query_file(&self, path: &Path) -> bool {
+        let Some(file_name) = path.file_name() else {
+            return false;
+        };
+        let file_name = file_name.to_string_lossy().to_lowercase();
+
+        self.query_extensions.iter().any(|extension| {
+            let extension = extension.trim_start_matches('.').to_lowercase();
+            file_name.ends_with(&format!(".{}", extension))
+        })
+    }
+
+    // Whether `path`, or any directory it's in, is excluded
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let relative = match &self.root {
+            Some(root) => path.strip_prefix(root).unwrap_or(path),
+            None => path,
+        };
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::new()
+        };
+        // A leading `/` only anchors the pattern to the root
+        let patterns = self
+            .exclude
+            .iter()
+            .filter_map(|pattern| {
+                let anywhere = !pattern.contains('/');
+                Some((
+                    anywhere,
+                    Pattern::new(pattern.trim_start_matches('/')).ok()?,
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        relative
+            .ancestors()
+            .filter(|ancestor| !ancestor.as_os_str().is_empty())
+            .any(|ancestor| {
+                patterns.iter().any(|(anywhere, pattern)| {
+                    pattern.matches_path_with(ancestor, options)
+                        || (*anywhere
+                            && ancestor.file_name().is_some_and(|name| {
+                                pattern.matches_with(&name.to_string_lossy(), options)
+                            }))
+                })
+            })
+    }
 }
 
 #[derive(Debug)]
@@ -73,14 +216,55 @@ fn default_postgres_obj() -> PostgresConfig {
     PostgresConfig {
         uri: default_postgres_uri(),
         schema: default_schema(),
+        schema_cache: None,
     }
 }
 
 fn default_emit_obj() -> EmitConfig {
     EmitConfig {
+        language: Language::default(),
         type_file: default_type_file(),
         module_type: default_module_type().to_string(),
+        type_file_module: None,
         table_file: None,
+        registry: None,
+        force_nullable_results: false,
+        scalarize_single_column: false,
+        allow_duplicate_names: false,
+        enum_style: EnumStyle::default(),
+        readonly: false,
+        export_param_types: false,
+        runtime_checks: false,
+        branded_scalars: vec![],
+    }
+}
+
+fn default_schema_channel() -> String {
+    "piqued_ddl".to_string()
+}
+
+fn default_lsp_obj() -> LspConfig {
+    LspConfig {
+        min_severity: Severity::default(),
+        schema_file: None,
+        warn_select_star: false,
+        schema_channel: default_schema_channel(),
+        check_index_hints: false,
+    }
+}
+
+fn default_query_extensions() -> Vec<String> {
+    ["sql", "psql", "pgsql", "pg"]
+        .iter()
+        .map(|extension| extension.to_string())
+        .collect()
+}
+
+fn default_workspace_obj() -> ConfigWorkspace {
+    ConfigWorkspace {
+        root: None,
+        query_extensions: default_query_extensions(),
+        exclude: vec![],
     }
 }
 
@@ -89,6 +273,10 @@ fn default_module_type() -> String {
 }
 
 impl Config {
+    pub fn json_schema() -> String {
+        serde_json::to_string_pretty(&schema_for!(Config)).unwrap()
+    }
+
     pub async fn find_dir(dir: &PathBuf) -> Option<PathBuf> {
         let mut file_buf = dir.to_path_buf();
         file_buf.push("piqued.toml");
@@ -115,13 +303,31 @@ impl Config {
     }
 
     pub async fn load(file: &Option<PathBuf>, working_dir: &PathBuf) -> Result<Self> {
-        let mut ruulang_config: Config = if let Some(path) = file {
-            let contents = tokio::fs::read(path).await?;
-            let str_contents = std::str::from_utf8(contents.as_slice()).unwrap();
-            toml::from_str(str_contents)?
-        } else {
-            toml::from_str("")?
-        };
+        match file {
+            Some(path) => {
+                let contents = tokio::fs::read(path).await?;
+                let str_contents = std::str::from_utf8(contents.as_slice()).unwrap();
+                Config::from_str(str_contents, working_dir)
+            }
+            None => Config::from_str("", working_dir),
+        }
+    }
+
+    /// Parses a `piqued.toml` that's already in memory, with the same defaults as [`Config::load`].
+    /// Without a `workspace.root`, the workspace is rooted at `working_dir`, which has to exist.
+    ///
+    /// ```
+    /// use piqued::config::config::Config;
+    ///
+    /// let working_dir = std::env::temp_dir();
+    /// let config = Config::from_str("[emit]\ntypeFile = \"./types\"", &working_dir).unwrap();
+    ///
+    /// assert_eq!(config.emit.type_file, "./types");
+    /// assert_eq!(config.postgres.schema, "public");
+    /// assert_eq!(config.workspace.root, Some(working_dir.canonicalize().unwrap()));
+    /// ```
+    pub fn from_str(contents: &str, working_dir: &PathBuf) -> Result<Self> {
+        let mut ruulang_config: Config = toml::from_str(contents)?;
 
         if ruulang_config.workspace.root.is_none() {
             ruulang_config.workspace.root = Some(working_dir.clone());
@@ -133,6 +339,15 @@ impl Config {
             ruulang_config.workspace.root = Some(root);
         }
 
+        for pattern in &ruulang_config.workspace.exclude {
+            if let Err(e) = Pattern::new(pattern.trim_start_matches('/')) {
+                return Err(PiquedError::OtherError(format!(
+                    "Invalid pattern \"{}\" in workspace.exclude: {}",
+                    pattern, e
+                )));
+            }
+        }
+
         Ok(ruulang_config)
     }
 }
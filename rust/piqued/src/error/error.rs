@@ -0,0 +1,71 @@
+use std::{error, fmt, io, sync::Arc};
+
+use crate::utils::result::PiquedError;
+
+/// An error from piqued, for code using it as a library.
+///
+/// Every fallible function returns piqued's internal error, which converts into this with `?` or
+/// `Error::from`. That one implements `std::error::Error` as well, so it can also go straight into
+/// `anyhow` and the like. New variants may be added, so matches need a wildcard arm.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A statement couldn't be parsed. `near` is the text the parser stopped at.
+    Parse { near: String },
+    /// Postgres rejected a statement. `position` is the 1-based character offset into the
+    /// statement as sent, if Postgres gave one.
+    Postgres {
+        message: String,
+        position: Option<u32>,
+    },
+    /// The configuration couldn't be read. The underlying error is its `source()`.
+    Config {
+        source: Box<dyn error::Error + Send + Sync>,
+    },
+    /// A file couldn't be read or written. The `std::io::Error` is its `source()`.
+    Io { source: Arc<io::Error> },
+    /// Postgres couldn't be reached or talked to, as opposed to rejecting a statement. The
+    /// `tokio_postgres::Error` is its `source()`.
+    Connection { source: Arc<tokio_postgres::Error> },
+    /// Anything else.
+    Other { message: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse { near } => write!(f, "error parsing query at \"{}\"", near),
+            Error::Postgres { message, .. } => write!(f, "{}", message),
+            Error::Config { source } => write!(f, "invalid configuration: {}", source),
+            Error::Io { source } => write!(f, "{}", source),
+            Error::Connection { source } => write!(f, "{}", source),
+            Error::Other { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Config { source } => Some(source.as_ref()),
+            Error::Io { source } => Some(source.as_ref()),
+            Error::Connection { source } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<PiquedError> for Error {
+    fn from(err: PiquedError) -> Self {
+        match err {
+            PiquedError::ParseErrorAt(near) => Error::Parse { near },
+            PiquedError::PostgresError(message, position) => Error::Postgres { message, position },
+            PiquedError::OtherError(message) => Error::Other { message },
+            PiquedError::SerdeParseError(err) => Error::Config {
+                source: Box::new(err),
+            },
+            PiquedError::IoError(source) => Error::Io { source },
+            PiquedError::ConnectionError(source) => Error::Connection { source },
+        }
+    }
+}
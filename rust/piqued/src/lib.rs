@@ -4,8 +4,10 @@ pub mod parser {
     pub mod parser;
 }
 pub mod lsp {
+    mod definition;
+    pub mod formatting;
     pub mod lsp;
-    mod lsp_fmt;
+    pub mod lsp_fmt;
     mod utils;
 }
 pub mod query {
@@ -17,6 +19,12 @@ pub mod config {
 pub mod codegen {
     pub mod codegen;
     pub mod utils;
+    pub mod python {
+        pub mod schema;
+    }
+    pub mod rust {
+        pub mod schema;
+    }
     pub mod ts {
         pub mod schema;
     }
@@ -34,6 +42,10 @@ pub mod workspace {
     pub mod workspace;
 }
 
+pub mod error {
+    pub mod error;
+}
+
 mod utils {
     pub mod result;
 }
@@ -31,9 +31,12 @@ impl Operator {
             })),
             Token::ExclamationMarkTilde
             | Token::ExclamationMarkTildeAsterisk
+            | Token::Arrow
+            | Token::LongArrow
             | Token::HashArrow
             | Token::HashLongArrow
-            | Token::LongArrow
+            | Token::AtArrow
+            | Token::ArrowAt
             | Token::Pipe
             | Token::RArrow
             | Token::Sharp
@@ -46,6 +49,12 @@ impl Operator {
                 token: tok.clone(),
                 precedence: 8,
             })),
+            // `sqlparser` doesn't know about the `?` existence operators, and hands them to us as a
+            // placeholder. `?|` and `?&` are stitched back together by `reduce_existence_operator`
+            Token::Placeholder(placeholder) if placeholder == "?" => Some(Operator::Binop(Binop {
+                token: tok.clone(),
+                precedence: 8,
+            })),
             Token::Word(Word {
                 keyword:
                     Keyword::LIKE | Keyword::ILIKE | Keyword::BETWEEN | Keyword::IN | Keyword::SIMILAR,
@@ -65,7 +74,7 @@ impl Operator {
                 ..
             }) => Some(Operator::Binop(Binop {
                 token: tok.clone(),
-                precedence: 12,
+                precedence: 11,
             })),
             Token::Word(Word {
                 keyword: Keyword::AND,
@@ -100,14 +109,29 @@ impl Operator {
                 ..
             }) => Some(Operator::Unop(Unop {
                 token: token.clone(),
-                precedence: 11,
+                precedence: 12,
             })),
             _ => None,
         }
     }
 
+    // `AT TIME ZONE` spans three tokens, so it's only turned into an operator once all of them
+    // have been shifted. Its precedence sits between unary operators and `^`.
+    pub fn at_time_zone(token: &Token) -> Operator {
+        Operator::Binop(Binop {
+            token: token.clone(),
+            precedence: 5,
+        })
+    }
+
     pub fn precedence_from_token(token: &Token) -> Option<u8> {
-        if let Some(Operator::Binop(op)) = Self::binop_from_token(token) {
+        if let Token::Word(Word {
+            keyword: Keyword::AT,
+            ..
+        }) = token
+        {
+            Some(5)
+        } else if let Some(Operator::Binop(op)) = Self::binop_from_token(token) {
             Some(op.precedence)
         } else if let Some(Operator::Unop(op)) = Self::unop_from_token(token) {
             Some(op.precedence)
@@ -116,3 +140,158 @@ impl Operator {
         }
     }
 }
+
+// Numeric types in the order Postgres promotes them when the two sides of an operator differ,
+// along with the other names they go by
+const NUMERIC_TYPES: [(&str, &[&str]); 6] = [
+    ("int2", &["smallint"]),
+    ("int4", &["integer", "int"]),
+    ("int8", &["bigint"]),
+    ("numeric", &["decimal"]),
+    ("float4", &["real"]),
+    ("float8", &["double precision", "float"]),
+];
+
+fn numeric_rank(type_name: &str) -> Option<usize> {
+    NUMERIC_TYPES
+        .iter()
+        .position(|(name, aliases)| *name == type_name || aliases.contains(&type_name))
+}
+
+fn is_integer(type_name: &str) -> bool {
+    numeric_rank(type_name).is_some_and(|rank| rank <= 2)
+}
+
+fn is_timestamp(type_name: &str) -> bool {
+    matches!(
+        type_name,
+        "timestamp" | "timestamptz" | "timestamp without time zone" | "timestamp with time zone"
+    )
+}
+
+fn is_json(type_name: &str) -> bool {
+    matches!(type_name, "json" | "jsonb")
+}
+
+fn is_boolean_operator(token: &Token) -> bool {
+    match token {
+        Token::Lt
+        | Token::LtEq
+        | Token::Gt
+        | Token::GtEq
+        | Token::Eq
+        | Token::Neq
+        | Token::Tilde
+        | Token::TildeAsterisk
+        | Token::ExclamationMarkTilde
+        | Token::ExclamationMarkTildeAsterisk
+        | Token::AtArrow
+        | Token::ArrowAt => true,
+        Token::Placeholder(placeholder) => placeholder.starts_with('?'),
+        Token::Word(word) => matches!(
+            word.keyword,
+            Keyword::LIKE
+                | Keyword::ILIKE
+                | Keyword::SIMILAR
+                | Keyword::IN
+                | Keyword::BETWEEN
+                | Keyword::IS
+                | Keyword::AND
+                | Keyword::OR
+        ),
+        _ => false,
+    }
+}
+
+impl Binop {
+    /// A best-effort guess at the type of `left <op> right`, going by the types of its operands
+    /// alone rather than the operators Postgres actually has. Gives up (with `None`) whenever the
+    /// answer isn't obvious.
+    pub fn result_type(&self, left: Option<&str>, right: Option<&str>) -> Option<String> {
+        if is_boolean_operator(&self.token) {
+            return Some("bool".to_string());
+        }
+
+        let (left, right) = (left?, right?);
+        let result = match &self.token {
+            Token::StringConcat => match (left, right) {
+                (array, _) | (_, array) if array.ends_with("[]") => array,
+                ("jsonb", "jsonb") => "jsonb",
+                _ => "text",
+            },
+            Token::Arrow | Token::HashArrow if is_json(left) => left,
+            Token::LongArrow | Token::HashLongArrow if is_json(left) => "text",
+            // `AT TIME ZONE` swaps between the two flavours of timestamp
+            Token::Word(word) if word.keyword == Keyword::AT => match left {
+                "timestamptz" | "timestamp with time zone" => "timestamp",
+                "timestamp" | "timestamp without time zone" => "timestamptz",
+                _ => return None,
+            },
+            Token::Plus | Token::Minus => match (left, right) {
+                ("date", "date") if self.token == Token::Minus => "int4",
+                (left, right) if is_timestamp(left) && is_timestamp(right) => {
+                    if self.token == Token::Minus {
+                        "interval"
+                    } else {
+                        return None;
+                    }
+                }
+                ("date", integer) if is_integer(integer) => "date",
+                ("date", "interval") | ("interval", "date") => "timestamp",
+                (timestamp, "interval") | ("interval", timestamp) if is_timestamp(timestamp) => {
+                    timestamp
+                }
+                ("interval", "interval") => "interval",
+                _ => return self.numeric_result_type(left, right),
+            },
+            Token::Mul | Token::Div => match (left, right) {
+                ("interval", number) | (number, "interval") if numeric_rank(number).is_some() => {
+                    "interval"
+                }
+                _ => return self.numeric_result_type(left, right),
+            },
+            Token::Mod | Token::Caret => return self.numeric_result_type(left, right),
+            Token::Pipe | Token::Sharp | Token::ShiftLeft | Token::ShiftRight
+                if is_integer(left) =>
+            {
+                left
+            }
+            _ => return None,
+        };
+
+        Some(result.to_string())
+    }
+
+    fn numeric_result_type(&self, left: &str, right: &str) -> Option<String> {
+        let (left, right) = (numeric_rank(left)?, numeric_rank(right)?);
+
+        // Integers are raised to a power as doubles
+        if self.token == Token::Caret && left.max(right) <= 2 {
+            return Some("float8".to_string());
+        }
+
+        Some(NUMERIC_TYPES[left.max(right)].0.to_string())
+    }
+}
+
+impl Unop {
+    /// The type of applying this operator to something of type `operand`, on the same terms as
+    /// [`Binop::result_type`].
+    pub fn result_type(&self, operand: Option<&str>) -> Option<String> {
+        match &self.token {
+            Token::Word(word) if word.keyword == Keyword::NOT => Some("bool".to_string()),
+            Token::PGSquareRoot | Token::PGCubeRoot => Some("float8".to_string()),
+            Token::Plus | Token::Minus => {
+                let operand = operand?;
+                let rank = numeric_rank(operand);
+
+                match rank {
+                    Some(rank) => Some(NUMERIC_TYPES[rank].0.to_string()),
+                    None if operand == "interval" => Some(operand.to_string()),
+                    None => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
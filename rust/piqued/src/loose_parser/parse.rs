@@ -5,11 +5,13 @@ use sqlparser::{
     keywords::Keyword,
     tokenizer::{Token, Tokenizer, Word},
 };
-use tower_lsp::lsp_types::Position;
+use tower_lsp::lsp_types::{Position, Range};
 
 use super::parse_cf::{
-    BinopExpression, ColumnExpression, Expression, FromExpression, LR1Kind, LR1State, Operator,
-    ParseCF, SelectQuery, TableLike, UnopExpression,
+    BetweenExpression, Binop, BinopExpression, CastExpression, ColumnExpression,
+    CommonTableExpression, CommonTableQuery, CompoundQuery, Expression, FromExpression,
+    FunctionCall, InsertStatement, IsExpression, IsPredicate, JoinExpression, JoinKind, LR1Kind,
+    LR1State, Operator, ParseCF, SelectQuery, SetOperation, SetOperator, TableLike, UnopExpression,
 };
 
 pub struct ParserContext {
@@ -17,6 +19,8 @@ pub struct ParserContext {
 
     next_token: usize,
     stack: Vec<Arc<LR1State>>,
+    // The states of the statements before the last `;`, which nothing after it can reduce with
+    finished: Vec<Arc<LR1State>>,
 }
 
 impl ParserContext {
@@ -28,6 +32,7 @@ impl ParserContext {
             tokens,
             next_token: 0,
             stack: vec![],
+            finished: vec![],
         }
     }
 
@@ -95,29 +100,50 @@ impl ParserContext {
                     self.stack.push(Arc::new(new_state));
                     self.next_token += 1;
                 }
+                ParseCF::EndStatement => {
+                    self.finished.append(&mut self.stack);
+                    self.next_token += 1;
+                }
             }
         }
 
-        ParserResult::new(&self.tokens, self.stack.clone())
+        let states = [self.finished.as_slice(), self.stack.as_slice()].concat();
+        ParserResult::new(&self.tokens, states)
     }
 
     fn iterate_once(&mut self) -> ParseCF {
         let tok = self.tokens.get(self.next_token).unwrap_or(&Token::EOF);
 
-        self.reduce_expression_1(tok)?;
-        self.reduce_operators(tok)?;
-        self.reduce_wildcard(tok)?;
+        // Reductions look past any whitespace or comments, so that they make the same decision
+        // regardless of how the query happens to be formatted.
+        let lookahead = self.lookahead();
 
+        self.reduce_expression_1(lookahead)?;
+        self.reduce_type_name(lookahead)?;
+        self.reduce_cast(lookahead)?;
+        self.reduce_distinct_on(lookahead)?;
+        self.reduce_operators(lookahead)?;
+        self.reduce_scoped_wildcard(lookahead)?;
+        self.reduce_wildcard(lookahead)?;
+
+        self.shift_type_name(tok)?;
         self.shift_operators(tok)?;
 
-        self.reduce_expression_list(tok)?;
-        self.reduce_from_expression_list(tok)?;
-        self.reduce_select_stmt(tok)?;
-        self.reduce_from_stmt(tok)?;
-        self.reduce_select_query(tok)?;
+        self.reduce_from_expression_list(lookahead)?;
+        self.reduce_expression_list(lookahead)?;
+        self.reduce_join_stmt(lookahead)?;
+        self.reduce_ordering_modifier(lookahead)?;
+        self.reduce_select_stmt(lookahead)?;
+        self.reduce_from_stmt(lookahead)?;
+        self.reduce_grouping_clause(lookahead)?;
+        self.reduce_with_clause(lookahead)?;
+        self.reduce_select_query(lookahead)?;
+        self.reduce_compound_query(lookahead)?;
+        self.reduce_insert_stmt(lookahead)?;
 
         self.shift_identifier(tok)?;
         self.shift_keyword(tok)?;
+        self.shift_statement_separator(tok)?;
         self.shift_whitespace(tok)?;
 
         if let Token::EOF = tok {
@@ -127,24 +153,57 @@ impl ParserContext {
         }
     }
 
+    fn lookahead(&self) -> &Token {
+        self.tokens[self.next_token.min(self.tokens.len())..]
+            .iter()
+            .find(|tok| !matches!(tok, Token::Whitespace(_)))
+            .unwrap_or(&Token::EOF)
+    }
+
+    fn lookahead_after_next(&self) -> &Token {
+        self.tokens[(self.next_token + 1).min(self.tokens.len())..]
+            .iter()
+            .find(|tok| !matches!(tok, Token::Whitespace(_)))
+            .unwrap_or(&Token::EOF)
+    }
+
+    // Every reduction has had its chance to see the `;` as lookahead by now, so the statement is
+    // as complete as it's going to get
+    fn shift_statement_separator(&self, tok: &Token) -> ParseCF {
+        match tok {
+            Token::SemiColon => ParseCF::EndStatement,
+            _ => ParseCF::NotApplicable,
+        }
+    }
+
     fn shift_whitespace(&self, tok: &Token) -> ParseCF {
         match tok {
             Token::Whitespace(_) => ParseCF::ReduceWhitespace,
-            Token::SemiColon => ParseCF::ReduceWhitespace,
             _ => ParseCF::NotApplicable,
         }
     }
 
     fn shift_identifier(&self, tok: &Token) -> ParseCF {
         match tok {
-            Token::Word(Word {
-                keyword: Keyword::NoKeyword,
-                value,
-                ..
-            }) => ParseCF::Shifted(LR1Kind::Expression(Arc::new(Expression::Identifier(
-                value.clone(),
+            Token::Word(
+                word @ Word {
+                    keyword: Keyword::NoKeyword,
+                    ..
+                },
+            ) => ParseCF::Shifted(LR1Kind::Expression(Arc::new(Expression::Identifier(
+                identifier_name(word),
             )))),
 
+            // Plenty of functions (`count`, `lower`, `coalesce`, ...) share their name with a keyword
+            Token::Word(word)
+                if self.lookahead_after_next() == &Token::LParen
+                    && keyword_may_name_function(&word.keyword) =>
+            {
+                ParseCF::Shifted(LR1Kind::Expression(Arc::new(Expression::Identifier(
+                    identifier_name(word),
+                ))))
+            }
+
             _ => ParseCF::NotApplicable,
         }
     }
@@ -236,6 +295,18 @@ impl ParserContext {
                 LR1Kind::Operator(Operator::Binop(binop)),
                 LR1Kind::Expression(right),
             ) => {
+                // The right hand side is the name of a function that's about to be called
+                if tok == &Token::LParen && matches!(right.as_ref(), Expression::Identifier(_)) {
+                    return ParseCF::NotApplicable;
+                }
+
+                // The `AND` belongs to the `BETWEEN`, which is reduced by `reduce_between`
+                if token_is_keyword(&binop.token, Keyword::BETWEEN)
+                    && token_is_keyword(tok, Keyword::AND)
+                {
+                    return ParseCF::NotApplicable;
+                }
+
                 let current_precedence = binop.precedence;
                 if current_precedence > upcoming_precedence {
                     ParseCF::NotApplicable
@@ -263,6 +334,10 @@ impl ParserContext {
 
         match (&second.kind, &first.kind) {
             (LR1Kind::Operator(Operator::Unop(unop)), LR1Kind::Expression(expr)) => {
+                if tok == &Token::LParen && matches!(expr.as_ref(), Expression::Identifier(_)) {
+                    return ParseCF::NotApplicable;
+                }
+
                 let current_precedence = unop.precedence;
                 if current_precedence > upcoming_precedence {
                     ParseCF::NotApplicable
@@ -281,7 +356,278 @@ impl ParserContext {
         }
     }
 
+    fn reduce_at_time_zone(&self, _tok: &Token) -> ParseCF {
+        let (third, second, first) = self.get_3()?;
+
+        match (&third.kind, &second.kind, &first.kind) {
+            (
+                LR1Kind::Token(
+                    at @ Token::Word(Word {
+                        keyword: Keyword::AT,
+                        ..
+                    }),
+                ),
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::TIME,
+                    ..
+                })),
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::ZONE,
+                    ..
+                })),
+            ) => ParseCF::Reduced((3, LR1Kind::Operator(Operator::at_time_zone(at)))),
+
+            _ => ParseCF::NotApplicable,
+        }
+    }
+
+    fn reduce_existence_operator(&self, _tok: &Token) -> ParseCF {
+        let (second, first) = self.get_2()?;
+
+        match (&second.kind, &first.kind) {
+            (
+                LR1Kind::Operator(Operator::Binop(binop)),
+                LR1Kind::Token(suffix @ (Token::Pipe | Token::Ampersand)),
+            ) if binop.token == Token::Placeholder("?".to_string()) => {
+                let operator = Operator::Binop(Binop {
+                    token: Token::Placeholder(format!("?{}", suffix)),
+                    precedence: binop.precedence,
+                });
+
+                ParseCF::Reduced((2, LR1Kind::Operator(operator)))
+            }
+
+            _ => ParseCF::NotApplicable,
+        }
+    }
+
+    fn reduce_parenthesized_expression(&self, _tok: &Token) -> ParseCF {
+        let (third, second, first) = self.get_3()?;
+
+        // Parentheses following an expression are a function call, not a grouping
+        let preceding = self.stack.len().checked_sub(4).map(|i| &self.stack[i].kind);
+        if let Some(LR1Kind::Expression(_)) = preceding {
+            return ParseCF::NotApplicable;
+        }
+
+        match (&third.kind, &second.kind, &first.kind) {
+            (
+                LR1Kind::Token(Token::LParen),
+                LR1Kind::ExpressionList(list),
+                LR1Kind::Token(Token::RParen),
+            ) if list.len() == 1 => match list[0].as_ref() {
+                ColumnExpression::Unnamed(expr) => {
+                    ParseCF::Reduced((3, LR1Kind::Expression(expr.clone())))
+                }
+                ColumnExpression::Named(..) => ParseCF::NotApplicable,
+            },
+
+            _ => ParseCF::NotApplicable,
+        }
+    }
+
+    // `expression [NOT] BETWEEN low AND high`. Since `BETWEEN` isn't reduced as a binop while an
+    // `AND` follows it, the bounds are left on the stack for us, and the `AND` between them
+    // binds looser than `BETWEEN`, so they're never reduced on their own before we get here.
+    fn reduce_between(&self, tok: &Token) -> ParseCF {
+        let len = self.stack.len();
+        if len < 5 {
+            return ParseCF::NotApplicable;
+        }
+
+        let (
+            LR1Kind::Expression(low),
+            LR1Kind::Operator(Operator::Binop(and)),
+            LR1Kind::Expression(high),
+        ) = (
+            &self.stack[len - 3].kind,
+            &self.stack[len - 2].kind,
+            &self.stack[len - 1].kind,
+        )
+        else {
+            return ParseCF::NotApplicable;
+        };
+
+        if !token_is_keyword(&and.token, Keyword::AND) {
+            return ParseCF::NotApplicable;
+        }
+
+        let (used, between, negated) = match &self.stack[len - 4].kind {
+            LR1Kind::Operator(Operator::Binop(binop))
+                if token_is_keyword(&binop.token, Keyword::BETWEEN) =>
+            {
+                (5, &binop.token, false)
+            }
+            // `NOT` is shifted as a unop, which leaves `BETWEEN` as a plain token
+            LR1Kind::Token(between) if token_is_keyword(between, Keyword::BETWEEN) => {
+                match len.checked_sub(5).map(|i| &self.stack[i].kind) {
+                    Some(LR1Kind::Operator(Operator::Unop(unop)))
+                        if token_is_keyword(&unop.token, Keyword::NOT) =>
+                    {
+                        (6, between, true)
+                    }
+                    _ => return ParseCF::NotApplicable,
+                }
+            }
+            _ => return ParseCF::NotApplicable,
+        };
+
+        let Some(LR1Kind::Expression(expression)) =
+            len.checked_sub(used).map(|i| &self.stack[i].kind)
+        else {
+            return ParseCF::NotApplicable;
+        };
+
+        // Wait for the upper bound to be complete, e.g. `BETWEEN 1 AND 2 + 3`
+        let precedence = Operator::precedence_from_token(between).map_or(0, |p| p);
+        let upcoming_precedence = Operator::precedence_from_token(tok).map_or(255, |p| p);
+        if precedence > upcoming_precedence {
+            return ParseCF::NotApplicable;
+        }
+
+        let between_expression = BetweenExpression {
+            expression: expression.clone(),
+            low: low.clone(),
+            high: high.clone(),
+            negated,
+        };
+
+        ParseCF::Reduced((
+            used as u32,
+            LR1Kind::Expression(Arc::new(Expression::BetweenExpression(between_expression))),
+        ))
+    }
+
+    // `expression IS [NOT] NULL`, and likewise for `TRUE`, `FALSE` and `UNKNOWN`. These are
+    // postfix, so there's nothing to wait for once the predicate has been shifted. `NULL` has
+    // already become a literal by then, while the others are still plain tokens.
+    fn reduce_is_predicate(&self, _tok: &Token) -> ParseCF {
+        let len = self.stack.len();
+        if len < 3 {
+            return ParseCF::NotApplicable;
+        }
+
+        let predicate = match &self.stack[len - 1].kind {
+            LR1Kind::Expression(expr) if matches!(expr.as_ref(), Expression::NullLiteral) => {
+                IsPredicate::Null
+            }
+            LR1Kind::Token(Token::Word(word)) => match word.keyword {
+                Keyword::TRUE => IsPredicate::True,
+                Keyword::FALSE => IsPredicate::False,
+                Keyword::UNKNOWN => IsPredicate::Unknown,
+                _ => return ParseCF::NotApplicable,
+            },
+            _ => return ParseCF::NotApplicable,
+        };
+
+        let (used, negated) = match &self.stack[len - 2].kind {
+            LR1Kind::Operator(Operator::Unop(unop))
+                if token_is_keyword(&unop.token, Keyword::NOT) =>
+            {
+                (4, true)
+            }
+            _ => (3, false),
+        };
+
+        let is_operator = len.checked_sub(used - 1).map(|i| &self.stack[i].kind);
+        let expression = len.checked_sub(used).map(|i| &self.stack[i].kind);
+        match (expression, is_operator) {
+            (
+                Some(LR1Kind::Expression(expression)),
+                Some(LR1Kind::Operator(Operator::Binop(binop))),
+            ) if token_is_keyword(&binop.token, Keyword::IS) => ParseCF::Reduced((
+                used as u32,
+                LR1Kind::Expression(Arc::new(Expression::IsExpression(IsExpression {
+                    expression: expression.clone(),
+                    predicate,
+                    negated,
+                }))),
+            )),
+
+            _ => ParseCF::NotApplicable,
+        }
+    }
+
+    // `name ( [args] )`, where the arguments have already been reduced to a list of their own
+    fn reduce_function_call(&self, _tok: &Token) -> ParseCF {
+        let len = self.stack.len();
+        let args_len = match self.stack.last().map(|state| &state.kind) {
+            Some(LR1Kind::Token(Token::RParen)) => match self.stack.get(len.wrapping_sub(2)) {
+                Some(state) if matches!(state.kind, LR1Kind::Token(Token::LParen)) => 0,
+                Some(state) if matches!(state.kind, LR1Kind::ExpressionList(_)) => 1,
+                _ => return ParseCF::NotApplicable,
+            },
+            _ => return ParseCF::NotApplicable,
+        };
+
+        let used = args_len + 3;
+        if len < used {
+            return ParseCF::NotApplicable;
+        }
+
+        let (name, lparen) = (&self.stack[len - used], &self.stack[len - used + 1]);
+        let (LR1Kind::Expression(name), LR1Kind::Token(Token::LParen)) = (&name.kind, &lparen.kind)
+        else {
+            return ParseCF::NotApplicable;
+        };
+        let Expression::Identifier(name) = name.as_ref() else {
+            return ParseCF::NotApplicable;
+        };
+
+        // `INSERT INTO table [AS alias] (columns)` looks just like a call
+        let preceding = |offset: usize| {
+            len.checked_sub(used + offset)
+                .map(|i| &self.stack[i].kind)
+                .and_then(|kind| match kind {
+                    LR1Kind::Token(Token::Word(word)) => Some(word.keyword),
+                    _ => None,
+                })
+        };
+        // ...as does `INSERT INTO schema.table (columns)`, where the schema hasn't been reduced yet
+        let qualified = matches!(
+            len.checked_sub(used + 1).map(|i| &self.stack[i].kind),
+            Some(LR1Kind::Operator(Operator::Binop(Binop {
+                token: Token::Period,
+                ..
+            })))
+        );
+        if preceding(1) == Some(Keyword::INTO)
+            || preceding(1) == Some(Keyword::AS) && preceding(3) == Some(Keyword::INTO)
+            || qualified && preceding(3) == Some(Keyword::INTO)
+        {
+            return ParseCF::NotApplicable;
+        }
+
+        let args = match &self.stack[len - 2].kind {
+            LR1Kind::ExpressionList(list) => list
+                .iter()
+                .map(|column| match column.as_ref() {
+                    ColumnExpression::Unnamed(expr) => expr.clone(),
+                    // e.g. `cast(value AS type)`
+                    ColumnExpression::Named(_, expr) => expr.clone(),
+                })
+                .collect(),
+            _ => vec![],
+        };
+
+        let call = FunctionCall {
+            name: name.clone(),
+            args,
+        };
+
+        ParseCF::Reduced((
+            used as u32,
+            LR1Kind::Expression(Arc::new(Expression::FunctionCall(call))),
+        ))
+    }
+
     fn reduce_operators(&self, tok: &Token) -> ParseCF {
+        self.reduce_function_call(tok)?;
+        self.reduce_parenthesized_expression(tok)?;
+        self.reduce_at_time_zone(tok)?;
+        self.reduce_existence_operator(tok)?;
+        self.reduce_between(tok)?;
+        self.reduce_is_predicate(tok)?;
         self.reduce_binop(tok)?;
         self.reduce_unop(tok)?;
         self.reduce_binop_expression(tok)?;
@@ -290,6 +636,117 @@ impl ParserContext {
         ParseCF::NotApplicable
     }
 
+    // Type names can run on for a few words, and be followed by modifiers or array brackets
+    fn reduce_type_name(&self, _tok: &Token) -> ParseCF {
+        let len = self.stack.len();
+        let kinds = |count: usize| {
+            len.checked_sub(count).map(|start| {
+                self.stack[start..]
+                    .iter()
+                    .map(|state| &state.kind)
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        if let Some([LR1Kind::TypeName(name), LR1Kind::Token(Token::Word(word))]) =
+            kinds(2).as_deref()
+        {
+            if continues_type_name(name, word) {
+                let name = format!("{} {}", name, identifier_name(word));
+                return ParseCF::Reduced((2, LR1Kind::TypeName(name)));
+            }
+        }
+
+        // `text[]`, or the equivalent `text[3]`
+        if let Some(
+            [LR1Kind::TypeName(name), LR1Kind::Token(Token::LBracket), LR1Kind::Token(Token::RBracket)],
+        ) = kinds(3).as_deref()
+        {
+            return ParseCF::Reduced((3, LR1Kind::TypeName(format!("{}[]", name))));
+        }
+
+        if let Some(
+            [LR1Kind::TypeName(name), LR1Kind::Token(Token::LBracket), LR1Kind::Expression(_) | LR1Kind::ExpressionList(_), LR1Kind::Token(Token::RBracket)],
+        ) = kinds(4).as_deref()
+        {
+            return ParseCF::Reduced((4, LR1Kind::TypeName(format!("{}[]", name))));
+        }
+
+        // Modifiers like the precision in `numeric(10, 2)` don't change the type
+        if let Some(
+            [LR1Kind::TypeName(name), LR1Kind::Token(Token::LParen), LR1Kind::Expression(_) | LR1Kind::ExpressionList(_), LR1Kind::Token(Token::RParen)],
+        ) = kinds(4).as_deref()
+        {
+            return ParseCF::Reduced((4, LR1Kind::TypeName(name.clone())));
+        }
+
+        ParseCF::NotApplicable
+    }
+
+    fn reduce_cast(&self, tok: &Token) -> ParseCF {
+        let (third, second, first) = self.get_3()?;
+
+        match (&third.kind, &second.kind, &first.kind) {
+            (
+                LR1Kind::Expression(expression),
+                LR1Kind::Operator(Operator::Binop(Binop {
+                    token: Token::DoubleColon,
+                    ..
+                })),
+                LR1Kind::TypeName(type_name),
+            ) => {
+                // Wait for the rest of the type name
+                let continues = match tok {
+                    Token::LParen | Token::LBracket => true,
+                    Token::Word(word) => continues_type_name(type_name, word),
+                    _ => false,
+                };
+                if continues {
+                    return ParseCF::NotApplicable;
+                }
+
+                ParseCF::Reduced((
+                    3,
+                    LR1Kind::Expression(Arc::new(Expression::Cast(CastExpression {
+                        expression: expression.clone(),
+                        type_name: type_name.clone(),
+                    }))),
+                ))
+            }
+
+            _ => ParseCF::NotApplicable,
+        }
+    }
+
+    // `relation.*`
+    fn reduce_scoped_wildcard(&self, _tok: &Token) -> ParseCF {
+        let (third, second, first) = self.get_3()?;
+
+        match (&third.kind, &second.kind, &first.kind) {
+            (
+                LR1Kind::Expression(relation),
+                LR1Kind::Operator(Operator::Binop(Binop {
+                    token: Token::Period,
+                    ..
+                })),
+                LR1Kind::Token(Token::Mul),
+            ) => {
+                let Expression::Identifier(relation) = relation.as_ref() else {
+                    return ParseCF::NotApplicable;
+                };
+
+                ParseCF::Reduced((
+                    3,
+                    LR1Kind::Expression(Arc::new(Expression::ScopedWildcardLiteral(
+                        relation.clone(),
+                    ))),
+                ))
+            }
+
+            _ => ParseCF::NotApplicable,
+        }
+    }
+
     fn reduce_wildcard(&self, _tok: &Token) -> ParseCF {
         let (second, first) = self.get_2()?;
 
@@ -322,14 +779,11 @@ impl ParserContext {
         let (second, first) = self.get_2()?;
 
         match (&second.kind, &first.kind) {
-            (
-                LR1Kind::Expression(e),
-                LR1Kind::Token(Token::Word(Word {
-                    keyword: Keyword::NoKeyword,
-                    value,
-                    ..
-                })),
-            ) => {
+            (LR1Kind::Expression(e), LR1Kind::Expression(alias)) => {
+                let Expression::Identifier(value) = alias.as_ref() else {
+                    return ParseCF::NotApplicable;
+                };
+
                 let column_expression = ColumnExpression::Named(value.clone(), e.clone());
                 ParseCF::Reduced((2, LR1Kind::ColumnExpression(Arc::new(column_expression))))
             }
@@ -348,12 +802,12 @@ impl ParserContext {
                     keyword: Keyword::AS,
                     ..
                 })),
-                LR1Kind::Token(Token::Word(Word {
-                    keyword: Keyword::NoKeyword,
-                    value: name,
-                    ..
-                })),
+                LR1Kind::Expression(alias),
             ) => {
+                let Expression::Identifier(name) = alias.as_ref() else {
+                    return ParseCF::NotApplicable;
+                };
+
                 let column_expression = ColumnExpression::Named(name.clone(), e.clone());
                 ParseCF::Reduced((3, LR1Kind::ColumnExpression(Arc::new(column_expression))))
             }
@@ -410,6 +864,18 @@ impl ParserContext {
             return ParseCF::NotApplicable;
         }
 
+        // Nor if the expression is about to be continued by an operator
+        if Operator::precedence_from_token(tok).is_some() {
+            return ParseCF::NotApplicable;
+        }
+
+        // Or is the name of a function that's about to be called
+        if let (Token::LParen, Some(LR1Kind::Expression(_))) =
+            (tok, self.get_1().map(|state| &state.kind))
+        {
+            return ParseCF::NotApplicable;
+        }
+
         self.reduce_column_expression_3(tok)?;
         self.reduce_column_expression_2(tok)?;
         self.reduce_column_expression_1(tok)?;
@@ -426,14 +892,15 @@ impl ParserContext {
                 LR1Kind::Token(Token::Word(Word {
                     keyword: Keyword::FROM,
                     ..
-                })),
-                LR1Kind::Token(Token::Word(Word {
-                    keyword: Keyword::NoKeyword,
-                    value,
-                    ..
-                })),
+                }))
+                | LR1Kind::JoinKind(_),
+                LR1Kind::Expression(table),
             ) => {
-                let table_like = TableLike::Table(value.clone());
+                let Some(value) = table.table_name() else {
+                    return ParseCF::NotApplicable;
+                };
+
+                let table_like = TableLike::Table(value);
                 ParseCF::Reduced((1, LR1Kind::TableLike(Arc::new(table_like))))
             }
 
@@ -448,13 +915,13 @@ impl ParserContext {
             (
                 LR1Kind::FromExpressionList(_),
                 LR1Kind::Token(Token::Comma),
-                LR1Kind::Token(Token::Word(Word {
-                    keyword: Keyword::NoKeyword,
-                    value,
-                    ..
-                })),
+                LR1Kind::Expression(table),
             ) => {
-                let table_like = TableLike::Table(value.clone());
+                let Some(value) = table.table_name() else {
+                    return ParseCF::NotApplicable;
+                };
+
+                let table_like = TableLike::Table(value);
                 ParseCF::Reduced((1, LR1Kind::TableLike(Arc::new(table_like))))
             }
 
@@ -462,6 +929,50 @@ impl ParserContext {
         }
     }
 
+    fn reduce_table_like_subquery(&self, _tok: &Token) -> ParseCF {
+        let (third, second, first) = self.get_3()?;
+
+        let select_query = match (&third.kind, &second.kind, &first.kind) {
+            (
+                LR1Kind::Token(Token::LParen),
+                LR1Kind::SelectQuery(select_query),
+                LR1Kind::Token(Token::RParen),
+            ) => select_query,
+
+            // The columns of a compound query are those of its first branch
+            (
+                LR1Kind::Token(Token::LParen),
+                LR1Kind::CompoundQuery(compound_query),
+                LR1Kind::Token(Token::RParen),
+            ) => compound_query.branches.first()?,
+
+            _ => return ParseCF::NotApplicable,
+        };
+
+        // Only a subquery in the place of a table is a derived table, otherwise it's a scalar
+        // subquery within some expression
+        let len = self.stack.len();
+        let in_from_position = match len.checked_sub(4).map(|i| &self.stack[i].kind) {
+            Some(LR1Kind::Token(Token::Word(Word {
+                keyword: Keyword::FROM,
+                ..
+            })))
+            | Some(LR1Kind::JoinKind(_)) => true,
+            Some(LR1Kind::Token(Token::Comma)) => matches!(
+                len.checked_sub(5).map(|i| &self.stack[i].kind),
+                Some(LR1Kind::FromExpressionList(_))
+            ),
+            _ => false,
+        };
+
+        if !in_from_position {
+            return ParseCF::NotApplicable;
+        }
+
+        let table_like = TableLike::Subquery(select_query.clone());
+        ParseCF::Reduced((3, LR1Kind::TableLike(Arc::new(table_like))))
+    }
+
     fn reduce_from_expression_1(&self, _tok: &Token) -> ParseCF {
         let first = self.get_1()?;
 
@@ -482,14 +993,11 @@ impl ParserContext {
         let (second, first) = self.get_2()?;
 
         match (&second.kind, &first.kind) {
-            (
-                LR1Kind::TableLike(table_like),
-                LR1Kind::Token(Token::Word(Word {
-                    keyword: Keyword::NoKeyword,
-                    value: alias,
-                    ..
-                })),
-            ) => {
+            (LR1Kind::TableLike(table_like), LR1Kind::Expression(alias)) => {
+                let Expression::Identifier(alias) = alias.as_ref() else {
+                    return ParseCF::NotApplicable;
+                };
+
                 let from_expression = FromExpression {
                     table: table_like.clone(),
                     alias: Some(alias.clone()),
@@ -511,12 +1019,12 @@ impl ParserContext {
                     keyword: Keyword::AS,
                     ..
                 })),
-                LR1Kind::Token(Token::Word(Word {
-                    keyword: Keyword::NoKeyword,
-                    value: alias,
-                    ..
-                })),
+                LR1Kind::Expression(alias),
             ) => {
+                let Expression::Identifier(alias) = alias.as_ref() else {
+                    return ParseCF::NotApplicable;
+                };
+
                 let from_expression = FromExpression {
                     table: table_like.clone(),
                     alias: Some(alias.clone()),
@@ -531,6 +1039,13 @@ impl ParserContext {
     fn reduce_from_expression_list_1(&self, _tok: &Token) -> ParseCF {
         let first = self.get_1()?;
 
+        // The table being joined belongs to the join, rather than starting a list of its own
+        if let Some((second, _)) = self.get_2() {
+            if let LR1Kind::JoinKind(_) = second.kind {
+                return ParseCF::NotApplicable;
+            }
+        }
+
         match &first.kind {
             LR1Kind::FromExpression(e) => {
                 ParseCF::Reduced((1, LR1Kind::FromExpressionList(vec![e.clone()])))
@@ -561,6 +1076,7 @@ impl ParserContext {
     fn reduce_from_expression_list(&self, tok: &Token) -> ParseCF {
         self.reduce_table_like_2(tok)?;
         self.reduce_table_like_3(tok)?;
+        self.reduce_table_like_subquery(tok)?;
 
         // Don't attempt to reduce any lists if we have an upcoming `AS` (or name without AS)
         if let Token::Word(Word {
@@ -588,15 +1104,256 @@ impl ParserContext {
         ParseCF::NotApplicable
     }
 
-    fn shift_operators(&self, tok: &Token) -> ParseCF {
-        let first = self.get_1()?;
+    // `[NATURAL] [INNER | CROSS | { LEFT | RIGHT | FULL } [OUTER]] JOIN`
+    fn reduce_join_kind(&self, _tok: &Token) -> ParseCF {
+        let keyword_at = |offset: usize| match self.stack.len().checked_sub(offset + 1) {
+            Some(i) => match &self.stack[i].kind {
+                LR1Kind::Token(Token::Word(word)) => Some(word.keyword),
+                _ => None,
+            },
+            None => None,
+        };
 
-        if let Some(op) = Operator::binop_from_token(tok) {
-            // This is weird, we do a bit of "lexer hack"ing here
-            // We only want to shift in a binop if there's an expression to the left.
-            // Otherwise, we want to shift it as a unop
-            if let LR1Kind::Expression(_) = first.kind {
-                return ParseCF::Shifted(LR1Kind::Operator(op));
+        if keyword_at(0) != Some(Keyword::JOIN) {
+            return ParseCF::NotApplicable;
+        }
+
+        let mut len = 1;
+        if keyword_at(len) == Some(Keyword::OUTER) {
+            len += 1;
+        }
+
+        let mut kind = match keyword_at(len) {
+            Some(Keyword::LEFT) => JoinKind::Left,
+            Some(Keyword::RIGHT) => JoinKind::Right,
+            Some(Keyword::FULL) => JoinKind::Full,
+            Some(Keyword::INNER) if len == 1 => JoinKind::Inner,
+            Some(Keyword::CROSS) if len == 1 => JoinKind::Cross,
+            _ if len == 1 => {
+                len -= 1;
+                JoinKind::Inner
+            }
+            _ => return ParseCF::NotApplicable,
+        };
+        len += 1;
+
+        if keyword_at(len) == Some(Keyword::NATURAL) {
+            kind = JoinKind::Natural;
+            len += 1;
+        }
+
+        ParseCF::Reduced((len as u32, LR1Kind::JoinKind(kind)))
+    }
+
+    // A join with nothing after the table, which is a cartesian product unless it's a `CROSS` or
+    // `NATURAL` join
+    fn reduce_join_stmt_2(&self, tok: &Token) -> ParseCF {
+        let (second, first) = self.get_2()?;
+
+        if !token_is_select_clause_boundary(tok)
+            && !token_is_join_keyword(tok)
+            && *tok != Token::Comma
+        {
+            return ParseCF::NotApplicable;
+        }
+
+        match (&second.kind, &first.kind) {
+            (LR1Kind::JoinKind(kind), LR1Kind::FromExpression(table)) => {
+                let join = JoinExpression {
+                    kind: kind.clone(),
+                    table: table.clone(),
+                    condition: None,
+                    using: vec![],
+                };
+
+                ParseCF::Reduced((2, LR1Kind::JoinExpression(Arc::new(join))))
+            }
+
+            _ => ParseCF::NotApplicable,
+        }
+    }
+
+    fn reduce_join_stmt_4(&self, tok: &Token) -> ParseCF {
+        let len = self.stack.len();
+        if len < 4 {
+            return ParseCF::NotApplicable;
+        }
+        let (fourth, third, second, first) = (
+            &self.stack[len - 4],
+            &self.stack[len - 3],
+            &self.stack[len - 2],
+            &self.stack[len - 1],
+        );
+
+        if !token_is_select_clause_boundary(tok)
+            && !token_is_join_keyword(tok)
+            && *tok != Token::Comma
+        {
+            return ParseCF::NotApplicable;
+        }
+
+        match (&fourth.kind, &third.kind, &second.kind, &first.kind) {
+            (
+                LR1Kind::JoinKind(kind),
+                LR1Kind::FromExpression(table),
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::ON,
+                    ..
+                })),
+                LR1Kind::ExpressionList(list),
+            ) => {
+                let [condition] = list.as_slice() else {
+                    return ParseCF::NotApplicable;
+                };
+                let ColumnExpression::Unnamed(condition) = condition.as_ref() else {
+                    return ParseCF::NotApplicable;
+                };
+
+                let join = JoinExpression {
+                    kind: kind.clone(),
+                    table: table.clone(),
+                    condition: Some(condition.clone()),
+                    using: vec![],
+                };
+
+                ParseCF::Reduced((4, LR1Kind::JoinExpression(Arc::new(join))))
+            }
+
+            // A single parenthesized column has already lost its parentheses
+            (
+                LR1Kind::JoinKind(kind),
+                LR1Kind::FromExpression(table),
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::USING,
+                    ..
+                })),
+                LR1Kind::ExpressionList(list),
+            ) => {
+                let Some(using) = using_columns(list) else {
+                    return ParseCF::NotApplicable;
+                };
+
+                let join = JoinExpression {
+                    kind: kind.clone(),
+                    table: table.clone(),
+                    condition: None,
+                    using,
+                };
+
+                ParseCF::Reduced((4, LR1Kind::JoinExpression(Arc::new(join))))
+            }
+
+            _ => ParseCF::NotApplicable,
+        }
+    }
+
+    // `JoinKind FromExpression USING ( column, ... )`
+    fn reduce_join_stmt_using(&self, _tok: &Token) -> ParseCF {
+        let len = self.stack.len();
+        if len < 6 {
+            return ParseCF::NotApplicable;
+        }
+
+        let states = &self.stack[len - 6..];
+        let (kind, table, list) = match (
+            &states[0].kind,
+            &states[1].kind,
+            &states[2].kind,
+            &states[3].kind,
+            &states[4].kind,
+            &states[5].kind,
+        ) {
+            (
+                LR1Kind::JoinKind(kind),
+                LR1Kind::FromExpression(table),
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::USING,
+                    ..
+                })),
+                LR1Kind::Token(Token::LParen),
+                LR1Kind::ExpressionList(list),
+                LR1Kind::Token(Token::RParen),
+            ) => (kind, table, list),
+
+            _ => return ParseCF::NotApplicable,
+        };
+
+        let Some(using) = using_columns(list) else {
+            return ParseCF::NotApplicable;
+        };
+
+        let join = JoinExpression {
+            kind: kind.clone(),
+            table: table.clone(),
+            condition: None,
+            using,
+        };
+
+        ParseCF::Reduced((6, LR1Kind::JoinExpression(Arc::new(join))))
+    }
+
+    // Joins attach to the table on their left, which is the last one in the list
+    fn reduce_join_from_expression(&self, _tok: &Token) -> ParseCF {
+        let (second, first) = self.get_2()?;
+
+        match (&second.kind, &first.kind) {
+            (LR1Kind::FromExpressionList(list), LR1Kind::JoinExpression(join)) => {
+                let mut new_list = list.clone();
+                let Some(left) = new_list.pop() else {
+                    return ParseCF::NotApplicable;
+                };
+
+                let joined = FromExpression {
+                    table: Arc::new(TableLike::Join(left, join.clone())),
+                    alias: None,
+                };
+                new_list.push(Arc::new(joined));
+
+                ParseCF::Reduced((2, LR1Kind::FromExpressionList(new_list)))
+            }
+
+            _ => ParseCF::NotApplicable,
+        }
+    }
+
+    fn reduce_join_stmt(&self, tok: &Token) -> ParseCF {
+        self.reduce_join_kind(tok)?;
+        self.reduce_join_stmt_using(tok)?;
+        self.reduce_join_stmt_4(tok)?;
+        self.reduce_join_stmt_2(tok)?;
+        self.reduce_join_from_expression(tok)?;
+
+        ParseCF::NotApplicable
+    }
+
+    // Whatever follows a `::` names a type, even if it's a keyword like `text` or `int`. That's
+    // still true when what's being cast isn't an expression we know, like a `$1` parameter.
+    fn shift_type_name(&self, tok: &Token) -> ParseCF {
+        let first = self.get_1()?;
+
+        match (&first.kind, tok) {
+            (
+                LR1Kind::Operator(Operator::Binop(Binop {
+                    token: Token::DoubleColon,
+                    ..
+                }))
+                | LR1Kind::Token(Token::DoubleColon),
+                Token::Word(word),
+            ) => ParseCF::Shifted(LR1Kind::TypeName(identifier_name(word))),
+
+            _ => ParseCF::NotApplicable,
+        }
+    }
+
+    fn shift_operators(&self, tok: &Token) -> ParseCF {
+        let first = self.get_1()?;
+
+        if let Some(op) = Operator::binop_from_token(tok) {
+            // This is weird, we do a bit of "lexer hack"ing here
+            // We only want to shift in a binop if there's an expression to the left.
+            // Otherwise, we want to shift it as a unop
+            if let LR1Kind::Expression(_) = first.kind {
+                return ParseCF::Shifted(LR1Kind::Operator(op));
             }
         }
 
@@ -607,20 +1364,70 @@ impl ParserContext {
         ParseCF::NotApplicable
     }
 
-    fn reduce_select_stmt(&self, tok: &Token) -> ParseCF {
-        let (second, first) = self.get_2()?;
+    // `DISTINCT ON ( expression, ... )`, which has to be taken before the parentheses are read as
+    // a grouping of the first column
+    fn reduce_distinct_on(&self, _tok: &Token) -> ParseCF {
+        let len = self.stack.len();
+        if len < 5 {
+            return ParseCF::NotApplicable;
+        }
 
-        match (&second.kind, &first.kind, tok) {
+        let states = &self.stack[len - 5..];
+        match (
+            &states[0].kind,
+            &states[1].kind,
+            &states[2].kind,
+            &states[3].kind,
+            &states[4].kind,
+        ) {
             (
                 LR1Kind::Token(Token::Word(Word {
-                    keyword: Keyword::SELECT,
+                    keyword: Keyword::DISTINCT,
                     ..
                 })),
-                LR1Kind::ExpressionList(from_expression_list),
-                next,
-            ) if token_is_select_clause_boundary(next) => {
-                ParseCF::Reduced((2, LR1Kind::SelectStmt(from_expression_list.clone())))
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::ON,
+                    ..
+                })),
+                LR1Kind::Token(Token::LParen),
+                LR1Kind::ExpressionList(list),
+                LR1Kind::Token(Token::RParen),
+            ) => ParseCF::Reduced((5, LR1Kind::DistinctClause(list.clone()))),
+
+            _ => ParseCF::NotApplicable,
+        }
+    }
+
+    fn reduce_select_stmt(&self, tok: &Token) -> ParseCF {
+        let (second, first) = self.get_2()?;
+
+        let LR1Kind::ExpressionList(from_expression_list) = &first.kind else {
+            return ParseCF::NotApplicable;
+        };
+        if !token_is_select_clause_boundary(tok) {
+            return ParseCF::NotApplicable;
+        }
+
+        // A `DISTINCT`, `DISTINCT ON (...)` or `ALL` doesn't change the columns being selected
+        let (select, len) = match &second.kind {
+            LR1Kind::Token(Token::Word(Word {
+                keyword: Keyword::DISTINCT | Keyword::ALL,
+                ..
+            }))
+            | LR1Kind::DistinctClause(_) => {
+                let Some((select, _, _)) = self.get_3() else {
+                    return ParseCF::NotApplicable;
+                };
+                (select, 3)
             }
+            _ => (second, 2),
+        };
+
+        match &select.kind {
+            LR1Kind::Token(Token::Word(Word {
+                keyword: Keyword::SELECT,
+                ..
+            })) => ParseCF::Reduced((len, LR1Kind::SelectStmt(from_expression_list.clone()))),
 
             _ => ParseCF::NotApplicable,
         }
@@ -645,14 +1452,120 @@ impl ParserContext {
         }
     }
 
+    // `ASC`/`DESC` and `NULLS FIRST`/`NULLS LAST` belong to the ordering term before them, so they're
+    // folded into the list rather than being left around to start a new expression
+    fn reduce_ordering_modifier(&self, _tok: &Token) -> ParseCF {
+        let len = self.stack.len();
+        let modifier_len = match self.get_2()? {
+            (
+                _,
+                LR1State {
+                    kind:
+                        LR1Kind::Token(Token::Word(Word {
+                            keyword: Keyword::ASC | Keyword::DESC,
+                            ..
+                        })),
+                    ..
+                },
+            ) => 1,
+
+            (
+                LR1State {
+                    kind:
+                        LR1Kind::Token(Token::Word(Word {
+                            keyword: Keyword::NULLS,
+                            ..
+                        })),
+                    ..
+                },
+                LR1State {
+                    kind:
+                        LR1Kind::Token(Token::Word(Word {
+                            keyword: Keyword::FIRST | Keyword::LAST,
+                            ..
+                        })),
+                    ..
+                },
+            ) => 2,
+
+            _ => return ParseCF::NotApplicable,
+        };
+
+        if len < modifier_len + 3 {
+            return ParseCF::NotApplicable;
+        }
+
+        match (
+            &self.stack[len - modifier_len - 3].kind,
+            &self.stack[len - modifier_len - 2].kind,
+            &self.stack[len - modifier_len - 1].kind,
+        ) {
+            (
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::ORDER,
+                    ..
+                })),
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::BY,
+                    ..
+                })),
+                LR1Kind::ExpressionList(list),
+            ) => ParseCF::Reduced((
+                (modifier_len + 1) as u32,
+                LR1Kind::ExpressionList(list.clone()),
+            )),
+
+            _ => ParseCF::NotApplicable,
+        }
+    }
+
+    fn reduce_grouping_clause(&self, tok: &Token) -> ParseCF {
+        let (third, second, first) = self.get_3()?;
+
+        if !token_is_select_clause_boundary(tok) {
+            return ParseCF::NotApplicable;
+        }
+
+        match (&third.kind, &second.kind, &first.kind) {
+            (
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::GROUP,
+                    ..
+                })),
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::BY,
+                    ..
+                })),
+                LR1Kind::ExpressionList(list),
+            ) => ParseCF::Reduced((3, LR1Kind::GroupByClause(list.clone()))),
+
+            (
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::ORDER,
+                    ..
+                })),
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::BY,
+                    ..
+                })),
+                LR1Kind::ExpressionList(list),
+            ) => ParseCF::Reduced((3, LR1Kind::OrderByClause(list.clone()))),
+
+            _ => ParseCF::NotApplicable,
+        }
+    }
+
     fn reduce_select_query_1(&self, _tok: &Token) -> ParseCF {
         let first = self.get_1()?;
 
         match &first.kind {
             LR1Kind::SelectStmt(select_stmt) => {
                 let select_query = SelectQuery {
+                    with: vec![],
                     columns: select_stmt.clone(),
                     from: None,
+                    group_by: None,
+                    order_by: None,
                 };
 
                 ParseCF::Reduced((1, LR1Kind::SelectQuery(Arc::new(select_query))))
@@ -673,17 +1586,524 @@ impl ParserContext {
                 ParseCF::Reduced((2, LR1Kind::SelectQuery(Arc::new(new_query))))
             }
 
+            (LR1Kind::SelectQuery(select_query), LR1Kind::GroupByClause(group_by)) => {
+                let mut new_query = select_query.as_ref().clone();
+                new_query.group_by = Some(group_by.clone());
+
+                ParseCF::Reduced((2, LR1Kind::SelectQuery(Arc::new(new_query))))
+            }
+
+            (LR1Kind::SelectQuery(select_query), LR1Kind::OrderByClause(order_by)) => {
+                let mut new_query = select_query.as_ref().clone();
+                new_query.order_by = Some(order_by.clone());
+
+                ParseCF::Reduced((2, LR1Kind::SelectQuery(Arc::new(new_query))))
+            }
+
+            _ => ParseCF::NotApplicable,
+        }
+    }
+
+    fn reduce_select_query_3(&self, _tok: &Token) -> ParseCF {
+        let (second, first) = self.get_2()?;
+
+        match (&second.kind, &first.kind) {
+            (LR1Kind::WithClause(with), LR1Kind::SelectQuery(select_query)) => {
+                let mut new_query = select_query.as_ref().clone();
+                new_query.with = with.clone();
+
+                ParseCF::Reduced((2, LR1Kind::SelectQuery(Arc::new(new_query))))
+            }
+
             _ => ParseCF::NotApplicable,
         }
     }
 
     fn reduce_select_query(&self, _tok: &Token) -> ParseCF {
+        self.reduce_select_query_3(_tok)?;
         self.reduce_select_query_2(_tok)?;
         self.reduce_select_query_1(_tok)?;
 
         ParseCF::NotApplicable
     }
 
+    // `query (UNION | EXCEPT | INTERSECT) [ALL | DISTINCT] query`
+    fn reduce_compound_query(&self, tok: &Token) -> ParseCF {
+        let first = self.get_1()?;
+        let LR1Kind::SelectQuery(right) = &first.kind else {
+            return ParseCF::NotApplicable;
+        };
+
+        // Wait for the right branch to take in all of its clauses. An `ORDER BY` applies to the
+        // whole compound query, so it's left for after.
+        if token_is_keyword(tok, Keyword::FROM) || token_is_keyword(tok, Keyword::GROUP) {
+            return ParseCF::NotApplicable;
+        }
+
+        let len = self.stack.len();
+        let (all, operator_index) = match len.checked_sub(2).map(|i| &self.stack[i].kind) {
+            Some(LR1Kind::Token(Token::Word(Word {
+                keyword: Keyword::ALL,
+                ..
+            }))) => (true, len.checked_sub(3)?),
+            Some(LR1Kind::Token(Token::Word(Word {
+                keyword: Keyword::DISTINCT,
+                ..
+            }))) => (false, len.checked_sub(3)?),
+            _ => (false, len.checked_sub(2)?),
+        };
+
+        let operator = match &self.stack.get(operator_index)?.kind {
+            LR1Kind::Token(Token::Word(Word {
+                keyword: Keyword::UNION,
+                ..
+            })) => SetOperator::Union,
+            LR1Kind::Token(Token::Word(Word {
+                keyword: Keyword::EXCEPT,
+                ..
+            })) => SetOperator::Except,
+            LR1Kind::Token(Token::Word(Word {
+                keyword: Keyword::INTERSECT,
+                ..
+            })) => SetOperator::Intersect,
+            _ => return ParseCF::NotApplicable,
+        };
+
+        // The left branch may be trailed by clauses we don't reduce into it (e.g. `WHERE`), so
+        // look back for the closest query at the same depth
+        let mut depth = 0;
+        let left_index = self.stack[..operator_index].iter().rposition(|state| {
+            match state.kind {
+                LR1Kind::Token(Token::RParen) => depth += 1,
+                LR1Kind::Token(Token::LParen) => depth -= 1,
+                _ => (),
+            };
+
+            // An unmatched paren means the compound query is itself parenthesized
+            depth < 0
+                || depth == 0
+                    && matches!(
+                        state.kind,
+                        LR1Kind::SelectQuery(_) | LR1Kind::CompoundQuery(_)
+                    )
+        })?;
+
+        let left = &self.stack[left_index];
+
+        let operation = SetOperation { operator, all };
+        let compound_query = match &left.kind {
+            LR1Kind::SelectQuery(left) => CompoundQuery {
+                branches: vec![left.clone(), right.clone()],
+                operations: vec![operation],
+            },
+            LR1Kind::CompoundQuery(left) => {
+                let mut compound_query = left.as_ref().clone();
+                compound_query.branches.push(right.clone());
+                compound_query.operations.push(operation);
+                compound_query
+            }
+            _ => return ParseCF::NotApplicable,
+        };
+
+        ParseCF::Reduced((
+            (len - left_index) as u32,
+            LR1Kind::CompoundQuery(Arc::new(compound_query)),
+        ))
+    }
+
+    // `INSERT INTO table [AS alias] [(columns)]`, once it's followed by the rows to insert
+    fn reduce_insert_target(&self, tok: &Token) -> ParseCF {
+        let rows_follow = [
+            Keyword::VALUES,
+            Keyword::DEFAULT,
+            Keyword::SELECT,
+            Keyword::WITH,
+        ]
+        .iter()
+        .any(|keyword| token_is_keyword(tok, *keyword));
+
+        if !rows_follow && !matches!(tok, Token::EOF | Token::SemiColon) {
+            return ParseCF::NotApplicable;
+        }
+
+        let len = self.stack.len();
+        let (columns, end) = match &self.stack.last()?.kind {
+            LR1Kind::Token(Token::RParen) => {
+                let (LR1Kind::Token(Token::LParen), LR1Kind::ExpressionList(list)) = (
+                    &self.stack.get(len.checked_sub(3)?)?.kind,
+                    &self.stack[len - 2].kind,
+                ) else {
+                    return ParseCF::NotApplicable;
+                };
+
+                (using_columns(list)?, len - 3)
+            }
+            _ => (vec![], len),
+        };
+
+        let identifier = |state: &LR1State| match &state.kind {
+            LR1Kind::Expression(expr) => match expr.as_ref() {
+                Expression::Identifier(name) => Some(name.clone()),
+                _ => None,
+            },
+            _ => None,
+        };
+        let table_name = |state: &LR1State| match &state.kind {
+            LR1Kind::Expression(expr) => expr.table_name(),
+            _ => None,
+        };
+
+        // The table may have been taken for a column, aliased or not
+        let (table, alias, start) = match &self.stack.get(end.checked_sub(1)?)?.kind {
+            LR1Kind::ExpressionList(list) => match list.as_slice() {
+                [column] => match column.as_ref() {
+                    ColumnExpression::Unnamed(expr) => (expr.table_name()?, None, end - 1),
+                    ColumnExpression::Named(alias, expr) => {
+                        (expr.table_name()?, Some(alias.clone()), end - 1)
+                    }
+                },
+                _ => None?,
+            },
+            _ => {
+                let aliased = end >= 3
+                    && matches!(
+                        &self.stack[end - 2].kind,
+                        LR1Kind::Token(Token::Word(Word {
+                            keyword: Keyword::AS,
+                            ..
+                        }))
+                    );
+                // A column list keeps `schema . table` from reducing, just as it does a call
+                let qualified = end >= 3
+                    && matches!(
+                        &self.stack[end - 2].kind,
+                        LR1Kind::Operator(Operator::Binop(Binop {
+                            token: Token::Period,
+                            ..
+                        }))
+                    );
+
+                match aliased.then(|| table_name(&self.stack[end - 3])).flatten() {
+                    Some(table) => (table, Some(identifier(&self.stack[end - 1])?), end - 3),
+                    None if qualified => {
+                        let schema = identifier(&self.stack[end - 3])?;
+                        let table = identifier(&self.stack[end - 1])?;
+                        (format!("{}.{}", schema, table), None, end - 3)
+                    }
+                    None => (table_name(&self.stack[end - 1])?, None, end - 1),
+                }
+            }
+        };
+
+        match (
+            &self.stack.get(start.checked_sub(2)?)?.kind,
+            &self.stack[start - 1].kind,
+        ) {
+            (
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::INSERT,
+                    ..
+                })),
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::INTO,
+                    ..
+                })),
+            ) => (),
+            _ => return ParseCF::NotApplicable,
+        }
+
+        let insert = InsertStatement {
+            table,
+            alias,
+            columns,
+            values: vec![],
+            returning: None,
+        };
+
+        ParseCF::Reduced((
+            (len - (start - 2)) as u32,
+            LR1Kind::InsertStmt(Arc::new(insert)),
+        ))
+    }
+
+    // Folds the rest of an `INSERT` into it once the statement ends, picking up its `RETURNING`
+    // list along the way
+    fn reduce_insert_body(&self, tok: &Token) -> ParseCF {
+        if !matches!(tok, Token::EOF | Token::SemiColon | Token::RParen) {
+            return ParseCF::NotApplicable;
+        }
+
+        let mut depth = 0;
+        let insert_index = self.stack.iter().rposition(|state| {
+            match state.kind {
+                LR1Kind::Token(Token::RParen) => depth += 1,
+                LR1Kind::Token(Token::LParen) => depth -= 1,
+                _ => (),
+            };
+
+            depth < 0 || depth == 0 && matches!(state.kind, LR1Kind::InsertStmt(_))
+        })?;
+
+        let LR1Kind::InsertStmt(insert) = &self.stack[insert_index].kind else {
+            return ParseCF::NotApplicable;
+        };
+
+        let len = self.stack.len();
+        if insert_index + 1 == len {
+            return ParseCF::NotApplicable;
+        }
+
+        let end = self.stack[len - 1].end as usize;
+        let body = &self.stack[insert_index + 1..];
+        let returning = body
+            .iter()
+            .position(|state| {
+                matches!(
+                    state.kind,
+                    LR1Kind::Token(Token::Word(Word {
+                        keyword: Keyword::RETURNING,
+                        ..
+                    }))
+                )
+            })
+            .and_then(|index| match body.get(index + 1).map(|state| &state.kind) {
+                Some(LR1Kind::ExpressionList(columns)) => Some(columns.clone()),
+                _ => None,
+            });
+
+        let values = body
+            .iter()
+            .find(|state| match &state.kind {
+                LR1Kind::Token(tok) => token_is_keyword(tok, Keyword::VALUES),
+                _ => false,
+            })
+            .map(|state| self.values_rows(state.end as usize, end))
+            .unwrap_or_default();
+
+        let mut insert = insert.as_ref().clone();
+        insert.values = values;
+        insert.returning = returning;
+
+        ParseCF::Reduced((
+            (len - insert_index) as u32,
+            LR1Kind::InsertStmt(Arc::new(insert)),
+        ))
+    }
+
+    // Splits the rows following `VALUES` into the token range of each of their values. The values
+    // themselves may not have reduced to anything (a bare `$1` stays a token), so this works off
+    // the tokens rather than the stack.
+    fn values_rows(&self, start: usize, end: usize) -> Vec<Vec<(u32, u32)>> {
+        let mut rows = vec![];
+        let mut index = start;
+
+        loop {
+            while matches!(self.tokens.get(index), Some(Token::Whitespace(_))) && index < end {
+                index += 1;
+            }
+
+            if index >= end || self.tokens[index] != Token::LParen {
+                return rows;
+            }
+
+            let mut row = vec![];
+            let mut value_start = index + 1;
+            let mut depth = 0;
+            index += 1;
+
+            while index < end {
+                match &self.tokens[index] {
+                    Token::LParen | Token::LBracket => depth += 1,
+                    Token::RParen if depth == 0 => break,
+                    Token::RParen | Token::RBracket => depth -= 1,
+                    Token::Comma if depth == 0 => {
+                        row.push((value_start as u32, index as u32));
+                        value_start = index + 1;
+                    }
+                    _ => (),
+                }
+
+                index += 1;
+            }
+
+            row.push((value_start as u32, index as u32));
+            rows.push(row);
+            index += 1;
+
+            while matches!(self.tokens.get(index), Some(Token::Whitespace(_))) && index < end {
+                index += 1;
+            }
+
+            if index >= end || self.tokens[index] != Token::Comma {
+                return rows;
+            }
+
+            index += 1;
+        }
+    }
+
+    fn reduce_insert_stmt(&self, tok: &Token) -> ParseCF {
+        self.reduce_insert_target(tok)?;
+        self.reduce_insert_body(tok)?;
+
+        ParseCF::NotApplicable
+    }
+
+    fn reduce_common_table_expression(&self, _tok: &Token) -> ParseCF {
+        let first = self.get_1()?;
+        let LR1Kind::Token(Token::RParen) = first.kind else {
+            return ParseCF::NotApplicable;
+        };
+
+        // Find the paren that opens the CTE's body
+        let mut depth = 0;
+        let open_index = self.stack.iter().rposition(|state| {
+            match state.kind {
+                LR1Kind::Token(Token::RParen) => depth += 1,
+                LR1Kind::Token(Token::LParen) => depth -= 1,
+                _ => (),
+            };
+
+            depth == 0
+        })?;
+
+        if open_index < 3 {
+            return ParseCF::NotApplicable;
+        }
+
+        let name = match (
+            &self.stack[open_index - 3].kind,
+            &self.stack[open_index - 2].kind,
+            &self.stack[open_index - 1].kind,
+        ) {
+            (
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::WITH | Keyword::RECURSIVE,
+                    ..
+                }))
+                | LR1Kind::Token(Token::Comma),
+                LR1Kind::Expression(name),
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::AS,
+                    ..
+                })),
+            ) => match name.as_ref() {
+                Expression::Identifier(name) => name.clone(),
+                _ => None?,
+            },
+
+            _ => None?,
+        };
+
+        let body = &self.stack[open_index + 1..self.stack.len() - 1];
+        let query = match body.first().map(|state| &state.kind) {
+            Some(LR1Kind::SelectQuery(select_query)) if body.len() == 1 => {
+                CommonTableQuery::Select(select_query.clone())
+            }
+            Some(LR1Kind::CompoundQuery(compound_query)) if body.len() == 1 => {
+                CommonTableQuery::Select(compound_query.branches.first()?.clone())
+            }
+            Some(LR1Kind::InsertStmt(insert)) if body.len() == 1 => {
+                CommonTableQuery::DataModifying(
+                    Token::make_keyword("INSERT"),
+                    insert.returning.clone().unwrap_or(vec![]),
+                )
+            }
+
+            // We don't model data-modifying statements, but we do want to keep their `RETURNING`
+            // list around, since that's what the rest of the query gets to see
+            Some(LR1Kind::Token(
+                keyword @ Token::Word(Word {
+                    keyword: Keyword::INSERT | Keyword::UPDATE | Keyword::DELETE,
+                    ..
+                }),
+            )) => {
+                let returning = body
+                    .iter()
+                    .skip_while(|state| {
+                        !matches!(
+                            state.kind,
+                            LR1Kind::Token(Token::Word(Word {
+                                keyword: Keyword::RETURNING,
+                                ..
+                            }))
+                        )
+                    })
+                    .nth(1)
+                    .and_then(|state| match &state.kind {
+                        LR1Kind::ExpressionList(columns) => Some(columns.clone()),
+                        _ => None,
+                    })
+                    .unwrap_or(vec![]);
+
+                CommonTableQuery::DataModifying(keyword.clone(), returning)
+            }
+
+            _ => None?,
+        };
+
+        let common_table_expression = CommonTableExpression { name, query };
+
+        ParseCF::Reduced((
+            (self.stack.len() - (open_index - 2)) as u32,
+            LR1Kind::CommonTableExpression(Arc::new(common_table_expression)),
+        ))
+    }
+
+    fn reduce_with_clause_2(&self, _tok: &Token) -> ParseCF {
+        let (second, first) = self.get_2()?;
+
+        match (&second.kind, &first.kind) {
+            (
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::WITH,
+                    ..
+                })),
+                LR1Kind::CommonTableExpression(cte),
+            ) => ParseCF::Reduced((2, LR1Kind::WithClause(vec![cte.clone()]))),
+
+            _ => ParseCF::NotApplicable,
+        }
+    }
+
+    fn reduce_with_clause_3(&self, _tok: &Token) -> ParseCF {
+        let (third, second, first) = self.get_3()?;
+
+        match (&third.kind, &second.kind, &first.kind) {
+            (
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::WITH,
+                    ..
+                })),
+                LR1Kind::Token(Token::Word(Word {
+                    keyword: Keyword::RECURSIVE,
+                    ..
+                })),
+                LR1Kind::CommonTableExpression(cte),
+            ) => ParseCF::Reduced((3, LR1Kind::WithClause(vec![cte.clone()]))),
+
+            (
+                LR1Kind::WithClause(with),
+                LR1Kind::Token(Token::Comma),
+                LR1Kind::CommonTableExpression(cte),
+            ) => {
+                let mut new_with = with.clone();
+                new_with.push(cte.clone());
+                ParseCF::Reduced((3, LR1Kind::WithClause(new_with)))
+            }
+
+            _ => ParseCF::NotApplicable,
+        }
+    }
+
+    fn reduce_with_clause(&self, tok: &Token) -> ParseCF {
+        self.reduce_common_table_expression(tok)?;
+        self.reduce_with_clause_3(tok)?;
+        self.reduce_with_clause_2(tok)?;
+
+        ParseCF::NotApplicable
+    }
+
     fn get_1(&self) -> Option<&LR1State> {
         if self.stack.len() < 1 {
             return None;
@@ -765,16 +2185,22 @@ impl<'a> ParserResult<'a> {
             });
 
             let as_str = token.to_string();
-            let has_newline = as_str.contains('\n');
-
-            if has_newline {
-                let split_strings = as_str.split('\n');
-                row += (split_strings.count() - 1) as u32;
 
-                let last_line = as_str.split('\n').last().unwrap();
-                column = last_line.len() as u32;
-            } else {
-                column += as_str.len() as u32;
+            // Treat `\r\n` and a lone `\r` as single line breaks, so that files with Windows
+            // line endings line up with the positions the editor reports.
+            let mut chars = as_str.chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\r' | '\n' => {
+                        if c == '\r' && chars.peek() == Some(&'\n') {
+                            chars.next();
+                        }
+
+                        row += 1;
+                        column = 0;
+                    }
+                    c => column += c.len_utf8() as u32,
+                }
             }
         }
 
@@ -785,6 +2211,31 @@ impl<'a> ParserResult<'a> {
         }
     }
 
+    /// The top level states that begin within `range`, e.g. the states of a single statement.
+    pub fn states_within(&self, range: &Range) -> Vec<Arc<LR1State>> {
+        self.states
+            .iter()
+            .filter(|state| {
+                self.token_locations
+                    .get(state.start as usize)
+                    .is_some_and(|location| &range.start <= location && location < &range.end)
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn range_of(&self, state: &LR1State) -> Range {
+        let location_of = |index: u32| {
+            self.token_locations
+                .get(index as usize)
+                .or(self.token_locations.last())
+                .cloned()
+                .unwrap_or_default()
+        };
+
+        Range::new(location_of(state.start), location_of(state.end))
+    }
+
     pub fn inspect(&self, cursor_position: &Position) -> Option<Vec<Arc<LR1State>>> {
         let index = self
             .token_locations
@@ -821,6 +2272,108 @@ impl<'a> ParserResult<'a> {
     }
 }
 
+// Postgres folds unquoted identifiers to lower case, while quoted ones are taken exactly as written
+pub fn identifier_name(word: &Word) -> String {
+    match word.quote_style {
+        Some(_) => word.value.clone(),
+        None => word.value.to_lowercase(),
+    }
+}
+
+// Keywords that can be followed by a parenthesized list or subquery without being a function
+fn keyword_may_name_function(keyword: &Keyword) -> bool {
+    !matches!(
+        keyword,
+        Keyword::AS
+            | Keyword::IN
+            | Keyword::EXISTS
+            | Keyword::USING
+            | Keyword::VALUES
+            | Keyword::ON
+            | Keyword::AND
+            | Keyword::OR
+            | Keyword::NOT
+            | Keyword::SELECT
+            | Keyword::FROM
+            | Keyword::WHERE
+            | Keyword::JOIN
+            | Keyword::INTO
+            | Keyword::OVER
+            | Keyword::FILTER
+            | Keyword::WITHIN
+            | Keyword::RETURNING
+            | Keyword::SET
+            | Keyword::BY
+            | Keyword::THEN
+            | Keyword::WHEN
+            | Keyword::ELSE
+            | Keyword::CASE
+            | Keyword::DISTINCT
+            | Keyword::HAVING
+            | Keyword::IS
+            | Keyword::LIKE
+            | Keyword::ILIKE
+            | Keyword::BETWEEN
+            | Keyword::TABLE
+            | Keyword::CONFLICT
+            | Keyword::LATERAL
+            | Keyword::WITH
+            | Keyword::RECURSIVE
+    )
+}
+
+// The type names postgres spells with more than one word
+const MULTI_WORD_TYPES: [&str; 7] = [
+    "double precision",
+    "character varying",
+    "bit varying",
+    "time with time zone",
+    "time without time zone",
+    "timestamp with time zone",
+    "timestamp without time zone",
+];
+
+fn continues_type_name(name: &str, word: &Word) -> bool {
+    let name = format!("{} {}", name, identifier_name(word));
+
+    MULTI_WORD_TYPES
+        .iter()
+        .any(|multi_word| *multi_word == name || multi_word.starts_with(&format!("{} ", name)))
+}
+
+fn token_is_keyword(tok: &Token, keyword: Keyword) -> bool {
+    matches!(tok, Token::Word(word) if word.keyword == keyword)
+}
+
+fn using_columns(list: &Vec<Arc<ColumnExpression>>) -> Option<Vec<String>> {
+    list.iter()
+        .map(|column| match column.as_ref() {
+            ColumnExpression::Unnamed(expr) => match expr.as_ref() {
+                Expression::Identifier(name) => Some(name.clone()),
+                _ => None,
+            },
+            ColumnExpression::Named(..) => None,
+        })
+        .collect()
+}
+
+fn token_is_join_keyword(tok: &Token) -> bool {
+    match tok {
+        Token::Word(Word {
+            keyword:
+                Keyword::JOIN
+                | Keyword::INNER
+                | Keyword::LEFT
+                | Keyword::RIGHT
+                | Keyword::FULL
+                | Keyword::CROSS
+                | Keyword::NATURAL,
+            ..
+        }) => true,
+        _ => false,
+    }
+}
+
 fn token_is_select_clause_boundary(tok: &Token) -> bool {
     match tok {
         Token::Word(Word {
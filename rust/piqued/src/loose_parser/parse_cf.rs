@@ -47,6 +47,67 @@ pub enum Expression {
     ScopedWildcardLiteral(String),
     BinopExpression(BinopExpression),
     UnopExpression(UnopExpression),
+    FunctionCall(FunctionCall),
+    BetweenExpression(BetweenExpression),
+    IsExpression(IsExpression),
+    Cast(CastExpression),
+}
+
+impl Expression {
+    // The table this names, if it could be one: a bare name or one qualified by its schema
+    pub fn table_name(&self) -> Option<String> {
+        match self {
+            Expression::Identifier(name) => Some(name.clone()),
+            Expression::BinopExpression(binop) if binop.operator.token == Token::Period => {
+                match (binop.left.as_ref(), binop.right.as_ref()) {
+                    (Expression::Identifier(schema), Expression::Identifier(table)) => {
+                        Some(format!("{}.{}", schema, table))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+// `expression [NOT] BETWEEN low AND high`
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct BetweenExpression {
+    pub expression: Arc<Expression>,
+    pub low: Arc<Expression>,
+    pub high: Arc<Expression>,
+    pub negated: bool,
+}
+
+// What an `IS` test checks its expression against
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum IsPredicate {
+    Null,
+    True,
+    False,
+    Unknown,
+}
+
+// `expression IS [NOT] NULL`, and likewise for `TRUE`, `FALSE` and `UNKNOWN`
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct IsExpression {
+    pub expression: Arc<Expression>,
+    pub predicate: IsPredicate,
+    pub negated: bool,
+}
+
+// `expression::type`
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CastExpression {
+    pub expression: Arc<Expression>,
+    pub type_name: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct FunctionCall {
+    pub name: String,
+    pub args: Vec<Arc<Expression>>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
@@ -58,6 +119,10 @@ pub enum ColumnExpression {
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum TableLike {
     Table(String),
+    // A derived table, i.e. `FROM (SELECT ...) AS alias`
+    Subquery(Arc<SelectQuery>),
+    // The table on the left, joined with the one in the `JoinExpression`
+    Join(Arc<FromExpression>, Arc<JoinExpression>),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
@@ -66,10 +131,103 @@ pub struct FromExpression {
     pub alias: Option<String>,
 }
 
+impl FromExpression {
+    // The name the rest of the query knows the relation by: its alias, or else the table's own
+    // name without its schema
+    pub fn scope_name(&self) -> Option<&str> {
+        match (&self.alias, self.table.as_ref()) {
+            (Some(alias), _) => Some(alias),
+            (None, TableLike::Table(table)) => table.rsplit('.').next(),
+            (None, _) => None,
+        }
+    }
+
+    // The tables (and derived tables) brought into scope, with any joins flattened out
+    pub fn relations(self: &Arc<Self>) -> Vec<Arc<FromExpression>> {
+        match self.table.as_ref() {
+            TableLike::Join(left, join) => {
+                let mut relations = left.relations();
+                relations.extend(join.table.relations());
+                relations
+            }
+            _ => vec![self.clone()],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum JoinKind {
+    Inner,
+    Left,
+    Right,
+    Full,
+    Cross,
+    Natural,
+}
+
+// `JOIN table [ON condition | USING (columns)]`
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct JoinExpression {
+    pub kind: JoinKind,
+    pub table: Arc<FromExpression>,
+    pub condition: Option<Arc<Expression>>,
+    pub using: Vec<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum CommonTableQuery {
+    Select(Arc<SelectQuery>),
+    // An `INSERT`, `UPDATE`, or `DELETE`, along with its `RETURNING` list (if present)
+    DataModifying(Token, Vec<Arc<ColumnExpression>>),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CommonTableExpression {
+    pub name: String,
+    pub query: CommonTableQuery,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct SelectQuery {
+    pub with: Vec<Arc<CommonTableExpression>>,
     pub columns: Vec<Arc<ColumnExpression>>,
     pub from: Option<Vec<Arc<FromExpression>>>,
+    pub group_by: Option<Vec<Arc<ColumnExpression>>>,
+    pub order_by: Option<Vec<Arc<ColumnExpression>>>,
+}
+
+// `INSERT INTO table [AS alias] [(columns)] ... [RETURNING ...]`. The rows being inserted aren't
+// modeled beyond where each of their values sits, only the names the statement brings into scope.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct InsertStatement {
+    pub table: String,
+    pub alias: Option<String>,
+    pub columns: Vec<String>,
+    // The token range of each value in each `VALUES` row, in column order
+    pub values: Vec<Vec<(u32, u32)>>,
+    pub returning: Option<Vec<Arc<ColumnExpression>>>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum SetOperator {
+    Union,
+    Except,
+    Intersect,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct SetOperation {
+    pub operator: SetOperator,
+    pub all: bool,
+}
+
+// Queries combined with `UNION`, `EXCEPT`, or `INTERSECT`. The result takes its columns from the
+// first branch, but each branch keeps its own scope.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CompoundQuery {
+    pub branches: Vec<Arc<SelectQuery>>,
+    // The operation joining each branch to the one before it
+    pub operations: Vec<SetOperation>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
@@ -79,14 +237,27 @@ pub enum LR1Kind {
     Operator(Operator),
     ExpressionList(Vec<Arc<ColumnExpression>>),
     Expression(Arc<Expression>),
+    // The target of a `::` cast, e.g. `timestamp with time zone` or `text[]`
+    TypeName(String),
     TableLike(Arc<TableLike>),
     FromExpression(Arc<FromExpression>),
     FromExpressionList(Vec<Arc<FromExpression>>),
+    JoinKind(JoinKind),
+    JoinExpression(Arc<JoinExpression>),
 
+    // `DISTINCT ON ( ... )`, holding the expressions that rows are made distinct on
+    DistinctClause(Vec<Arc<ColumnExpression>>),
     SelectStmt(Vec<Arc<ColumnExpression>>),
     FromStmt(Vec<Arc<FromExpression>>),
+    GroupByClause(Vec<Arc<ColumnExpression>>),
+    OrderByClause(Vec<Arc<ColumnExpression>>),
+
+    CommonTableExpression(Arc<CommonTableExpression>),
+    WithClause(Vec<Arc<CommonTableExpression>>),
 
     SelectQuery(Arc<SelectQuery>),
+    CompoundQuery(Arc<CompoundQuery>),
+    InsertStmt(Arc<InsertStatement>),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
@@ -103,6 +274,8 @@ pub enum ParseCF {
     Shifted(LR1Kind),
     Reduced((u32, LR1Kind)),
     ReduceWhitespace,
+    // A `;`, which closes off everything on the stack as a statement of its own
+    EndStatement,
 }
 
 impl FromResidual<ParseCF> for ParseCF {
@@ -136,6 +309,7 @@ impl Try for ParseCF {
             ParseCF::Shifted(shifted) => ControlFlow::Break(ParseCF::Shifted(shifted)),
             ParseCF::Reduced(reduced) => ControlFlow::Break(ParseCF::Reduced(reduced)),
             ParseCF::ReduceWhitespace => ControlFlow::Break(ParseCF::ReduceWhitespace),
+            ParseCF::EndStatement => ControlFlow::Break(ParseCF::EndStatement),
         }
     }
 
@@ -0,0 +1,202 @@
+use std::sync::Arc;
+
+use pg_query::{protobuf::Token as PgToken, NodeEnum};
+use sqlparser::tokenizer::Token;
+use tower_lsp::lsp_types::{Location, Position, Range, Url};
+
+use crate::{
+    loose_parser::{
+        parse::ParserContext,
+        parse_cf::{Binop, BinopExpression, Expression, LR1Kind, LR1State},
+    },
+    parser::parser::{self, position_at},
+    query::query::collect_loose_relations,
+    utils::result::{PiquedError, Result},
+    workspace::workspace::Workspace,
+};
+
+use super::lsp::Backend;
+
+struct SchemaTable {
+    name: String,
+    range: Range,
+    columns: Vec<(String, Range)>,
+}
+
+impl Backend {
+    pub async fn get_definition(
+        &self,
+        workspace: &Workspace,
+        file_contents: &str,
+        position: &Position,
+    ) -> Result<Option<Location>> {
+        // Tables only live in the database, so there's nowhere to jump to without a schema file
+        let schema_file = match &workspace.config.lsp.schema_file {
+            Some(schema_file) => workspace.root_dir.join(schema_file),
+            None => return Ok(None),
+        };
+
+        let mut context = ParserContext::new(file_contents);
+        let partial_parsed = context.parse();
+
+        let stack = match partial_parsed.inspect(position) {
+            Some(stack) => stack,
+            None => return Ok(None),
+        };
+
+        let (qualifier, name) = match find_reference(&stack) {
+            Some(reference) => reference,
+            None => return Ok(None),
+        };
+
+        let schema_contents = tokio::fs::read_to_string(&schema_file).await?;
+        let tables = load_schema_tables(&schema_contents)?;
+        let uri = Url::from_file_path(&schema_file).map_err(|_| {
+            PiquedError::OtherError(format!("Invalid schema file {:?}", schema_file))
+        })?;
+
+        // Pick up the tables named by the enclosing query, falling back to the rest of the
+        // statement when it's too incomplete to have been reduced
+        let mut relations = collect_loose_relations(&stack);
+        if relations.is_empty() {
+            let parsed = parser::load_file(file_contents)?;
+            let statement = parsed
+                .statements
+                .iter()
+                .find(|stmt| stmt.range.start <= *position && stmt.range.end >= *position);
+
+            if let Some(statement) = statement {
+                relations =
+                    collect_loose_relations(&partial_parsed.states_within(&statement.range));
+            }
+        }
+
+        let table_for = |name: &String| {
+            let table = relations
+                .iter()
+                .find(|(alias, _)| alias == name)
+                .map_or(name, |(_, table)| table);
+
+            tables
+                .iter()
+                .find(|schema_table| &schema_table.name == table)
+        };
+
+        if qualifier.is_none() {
+            if let Some(table) = table_for(name) {
+                return Ok(Some(Location::new(uri, table.range)));
+            }
+        }
+
+        let column_range = relations
+            .iter()
+            .filter(|(alias, table)| match qualifier {
+                Some(qualifier) => alias == qualifier || table == qualifier,
+                None => true,
+            })
+            .filter_map(|(_, table)| {
+                tables
+                    .iter()
+                    .find(|schema_table| &schema_table.name == table)
+            })
+            .flat_map(|table| table.columns.iter())
+            .find(|(column, _)| column == name)
+            .map(|(_, range)| *range);
+
+        Ok(column_range.map(|range| Location::new(uri, range)))
+    }
+}
+
+/// Finds the identifier under the cursor, along with the relation it's qualified by (if any).
+fn find_reference(stack: &Vec<Arc<LR1State>>) -> Option<(Option<&String>, &String)> {
+    let expression = match &stack.first()?.kind {
+        LR1Kind::Token(Token::Word(word)) => return Some((None, &word.value)),
+        LR1Kind::Expression(expression) => expression,
+        _ => return None,
+    };
+
+    let Expression::Identifier(name) = expression.as_ref() else {
+        return None;
+    };
+
+    // For `alias.column`, the column is only looked up within `alias`
+    let qualifier = stack.iter().skip(1).find_map(|state| match &state.kind {
+        LR1Kind::Expression(parent) => match parent.as_ref() {
+            Expression::BinopExpression(BinopExpression {
+                left,
+                right,
+                operator:
+                    Binop {
+                        token: Token::Period,
+                        ..
+                    },
+            }) if Arc::ptr_eq(right, expression) => match left.as_ref() {
+                Expression::Identifier(qualifier) => Some(qualifier),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    });
+
+    Some((qualifier, name))
+}
+
+fn load_schema_tables(contents: &str) -> Result<Vec<SchemaTable>> {
+    let parsed = pg_query::parse(contents)?;
+    let tokens = pg_query::scan(contents)?
+        .tokens
+        .into_iter()
+        .filter(|token| !matches!(token.token(), PgToken::CComment | PgToken::SqlComment))
+        .collect::<Vec<_>>();
+
+    // `location` is where the name starts, including any schema it's qualified by, so the name
+    // itself is the last of the dotted tokens from there. Quoted names keep their quotes.
+    let range_of = |location: i32| {
+        let mut following = tokens.iter().skip_while(|token| token.start < location);
+        let mut name = following.next()?;
+        while let (Some(dot), Some(next)) = (following.next(), following.next()) {
+            if dot.token() != PgToken::Ascii46 {
+                break;
+            }
+            name = next;
+        }
+
+        Some(Range::new(
+            position_at(contents, name.start as u32),
+            position_at(contents, name.end as u32),
+        ))
+    };
+
+    let tables = parsed
+        .protobuf
+        .stmts
+        .iter()
+        .filter_map(|stmt| match stmt.stmt.as_ref()?.node.as_ref()? {
+            NodeEnum::CreateStmt(create) => Some(create),
+            _ => None,
+        })
+        .filter_map(|create| {
+            let relation = create.relation.as_ref()?;
+
+            let columns = create
+                .table_elts
+                .iter()
+                .filter_map(|element| match element.node.as_ref()? {
+                    NodeEnum::ColumnDef(column) => {
+                        Some((column.colname.clone(), range_of(column.location)?))
+                    }
+                    _ => None,
+                })
+                .collect();
+
+            Some(SchemaTable {
+                name: relation.relname.clone(),
+                range: range_of(relation.location)?,
+                columns,
+            })
+        })
+        .collect();
+
+    Ok(tables)
+}
@@ -0,0 +1,162 @@
+use pg_query::protobuf::{KeywordKind, ParseResult, RawStmt, ScanToken, Token};
+use tower_lsp::lsp_types::{Range, TextEdit};
+
+use crate::{
+    parser::parser::{self, position_at, ParsedFile, RelocatedStmt},
+    utils::result::Result,
+};
+
+// Keywords that start a clause on a line of its own, when they aren't nested in parentheses
+const CLAUSE_KEYWORDS: [&str; 22] = [
+    "SELECT",
+    "FROM",
+    "WHERE",
+    "GROUP",
+    "HAVING",
+    "WINDOW",
+    "ORDER",
+    "LIMIT",
+    "OFFSET",
+    "VALUES",
+    "SET",
+    "RETURNING",
+    "UNION",
+    "INTERSECT",
+    "EXCEPT",
+    "JOIN",
+    "LEFT",
+    "RIGHT",
+    "INNER",
+    "FULL",
+    "CROSS",
+    "NATURAL",
+];
+
+// Keywords after which a clause keyword carries on what came before, e.g. the `JOIN` in
+// `LEFT JOIN` or the `FROM` in `DELETE FROM` and `IS DISTINCT FROM`
+const CONTINUING_KEYWORDS: [&str; 10] = [
+    "LEFT", "RIGHT", "INNER", "FULL", "CROSS", "NATURAL", "OUTER", "DELETE", "DISTINCT", "WITHIN",
+];
+
+/// Reprints every statement in the file from its parse tree, one clause to a line. The comments
+/// heading each statement are left exactly as they are, as is any statement that can't safely be
+/// reprinted: one that doesn't parse, uses templates, or has comments within it.
+pub fn format_file(file_contents: &str) -> Result<Vec<TextEdit>> {
+    let parsed = parser::load_file(file_contents)?;
+
+    Ok(parsed
+        .statements
+        .iter()
+        .filter_map(|stmt| format_statement(file_contents, &parsed, stmt))
+        .collect())
+}
+
+fn format_statement(
+    file_contents: &str,
+    parsed: &ParsedFile,
+    stmt: &RelocatedStmt,
+) -> Option<TextEdit> {
+    let prepared =
+        parser::get_prepared_statement(stmt, &parsed.tokens, file_contents, || String::new())
+            .ok()?;
+    if !prepared.details.templates.is_empty() {
+        return None;
+    }
+
+    let stmt_end = (stmt.index_start + stmt.index_len) as i32;
+    let tokens = parsed
+        .tokens
+        .iter()
+        .filter(|token| token.start >= stmt.index_start as i32 && token.end <= stmt_end)
+        .skip_while(|token| is_comment(token))
+        .filter(|token| token.token() != Token::Ascii59)
+        .collect::<Vec<_>>();
+
+    if tokens.iter().any(|token| is_comment(token)) {
+        return None;
+    }
+
+    let (start, end) = (tokens.first()?.start as usize, tokens.last()?.end as usize);
+    let formatted = layout(&deparse(stmt.stmt.as_ref().ok()?)?)?;
+
+    // Reprinting should never change what the statement does
+    let original = &file_contents[start..end];
+    if pg_query::fingerprint(original).ok()?.hex != pg_query::fingerprint(&formatted).ok()?.hex {
+        return None;
+    }
+
+    if formatted == original {
+        return None;
+    }
+
+    Some(TextEdit::new(
+        Range::new(
+            position_at(file_contents, start as u32),
+            position_at(file_contents, end as u32),
+        ),
+        formatted,
+    ))
+}
+
+fn deparse(stmt: &RawStmt) -> Option<String> {
+    let result = ParseResult {
+        stmts: vec![RawStmt {
+            stmt: stmt.stmt.clone(),
+            stmt_len: 0,
+            stmt_location: 0,
+        }],
+        version: 160001,
+    };
+
+    result.deparse().ok()
+}
+
+// Breaks the deparsed statement, which is all on one line, before each of its clauses
+fn layout(deparsed: &str) -> Option<String> {
+    let tokens = pg_query::scan(deparsed).ok()?.tokens;
+
+    let mut result = String::new();
+    let mut last = 0;
+    let mut depth = 0;
+    let mut previous: Option<String> = None;
+
+    for token in &tokens {
+        let text = &deparsed[token.start as usize..token.end as usize];
+        let keyword = (token.keyword_kind() != KeywordKind::NoKeyword).then(|| text.to_uppercase());
+
+        let breaks = depth == 0
+            && last > 0
+            && keyword
+                .as_deref()
+                .map_or(false, |keyword| CLAUSE_KEYWORDS.contains(&keyword))
+            && !previous
+                .as_deref()
+                .map_or(false, |previous| CONTINUING_KEYWORDS.contains(&previous));
+
+        let gap = &deparsed[last..token.start as usize];
+        if breaks {
+            result.push_str(gap.trim_end_matches(' '));
+            result.push('\n');
+        } else {
+            result.push_str(gap);
+        }
+        result.push_str(text);
+
+        match token.token() {
+            Token::Ascii40 => depth += 1,
+            Token::Ascii41 => depth -= 1,
+            _ => (),
+        }
+
+        last = token.end as usize;
+        previous = keyword;
+    }
+
+    result.push_str(&deparsed[last..]);
+
+    Some(result)
+}
+
+fn is_comment(token: &ScanToken) -> bool {
+    matches!(token.token(), Token::CComment | Token::SqlComment)
+}
@@ -1,29 +1,39 @@
 use std::sync::Arc;
 
+use serde_json::Value;
 use tokio::sync::{MappedMutexGuard, Mutex, MutexGuard};
 use tokio_postgres::config;
 use tower_lsp::jsonrpc;
 use tower_lsp::lsp_types::{
-    DidChangeTextDocumentParams, DidOpenTextDocumentParams, Hover, HoverParams,
-    HoverProviderCapability, InitializeParams, InitializeResult, InitializedParams, MessageType,
-    ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+    DidChangeTextDocumentParams, DidChangeWatchedFilesParams,
+    DidChangeWatchedFilesRegistrationOptions, DidOpenTextDocumentParams, DocumentFormattingParams,
+    ExecuteCommandOptions, ExecuteCommandParams, FileChangeType, FileSystemWatcher,
+    GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverParams, HoverProviderCapability,
+    InitializeParams, InitializeResult, InitializedParams, MessageType, OneOf, Registration,
+    ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url,
 };
 use tower_lsp::{Client, LanguageServer};
 
 use crate::config::config::Config;
-use crate::workspace::workspace::Workspace;
+use crate::lsp::formatting::format_file;
+use crate::query::query::SchemaListener;
+use crate::workspace::workspace::{error_message, GenerationMode, Workspace};
+
+// Regenerates the code for the workspace containing the file URI it's given, or for every
+// workspace if there isn't one
+pub const GENERATE_COMMAND: &str = "piqued.generate";
 
 #[derive(Debug)]
 pub struct Backend {
     pub client: Client,
-    workspaces: Mutex<Vec<Workspace>>,
+    workspaces: Arc<Mutex<Vec<Workspace>>>,
 }
 
 impl Backend {
     pub fn new(client: Client) -> Self {
         Backend {
             client,
-            workspaces: Mutex::new(Vec::new()),
+            workspaces: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
@@ -54,19 +64,68 @@ impl Backend {
         }
     }
 
-    pub async fn run_diagnostics(&self, workspace: &Workspace, uri: Url) {
-        let diagnostics = workspace.get_diagnostics(uri.as_str()).await;
+    pub async fn run_diagnostics(&self, workspace: &mut Workspace, uri: Url) {
+        publish_diagnostics(&self.client, workspace, uri).await;
+    }
+}
 
-        match diagnostics {
-            Ok(diagnostics) => {
-                self.client
-                    .publish_diagnostics(uri, diagnostics, None)
-                    .await;
-            }
-            Err(e) => {
-                self.client
-                    .log_message(MessageType::ERROR, e.to_string())
-                    .await;
+async fn publish_diagnostics(client: &Client, workspace: &mut Workspace, uri: Url) {
+    let diagnostics = workspace.get_diagnostics(uri.as_str()).await;
+
+    match diagnostics {
+        Ok(diagnostics) => {
+            client.publish_diagnostics(uri, diagnostics, None).await;
+        }
+        Err(e) => {
+            client.log_message(MessageType::ERROR, e.to_string()).await;
+        }
+    }
+}
+
+// Reloads the workspace's schema whenever a notification comes in on `lsp.schemaChannel`, e.g. from
+// an event trigger run by a migration, and rechecks every file against it
+async fn listen_for_schema_changes(
+    client: Client,
+    workspaces: Arc<Mutex<Vec<Workspace>>>,
+    index: usize,
+) {
+    let config = workspaces.lock().await[index].config.clone();
+    if config.lsp.schema_channel.is_empty() {
+        return;
+    }
+
+    let mut listener = match SchemaListener::connect(&config, &config.lsp.schema_channel).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            client
+                .log_message(
+                    MessageType::WARNING,
+                    format!("Unable to listen for schema changes: {}", error_message(&e)),
+                )
+                .await;
+            return;
+        }
+    };
+
+    while listener.recv().await.is_some() {
+        let mut workspaces = workspaces.lock().await;
+        let workspace = &mut workspaces[index];
+
+        if let Err(e) = workspace.refresh_schema().await {
+            client
+                .log_message(
+                    MessageType::ERROR,
+                    format!("Unable to reload the schema: {}", error_message(&e)),
+                )
+                .await;
+        }
+        for warning in workspace.query.iter().flat_map(|query| &query.warnings) {
+            client.log_message(MessageType::WARNING, warning).await;
+        }
+
+        for uri in workspace.file_uris() {
+            if let Ok(uri) = Url::parse(&uri) {
+                publish_diagnostics(&client, workspace, uri).await;
             }
         }
     }
@@ -108,6 +167,9 @@ impl LanguageServer for Backend {
 
                 let config = config.unwrap();
                 let workspace = Workspace::new(Arc::new(config), root_dir).await;
+                for warning in workspace.query.iter().flat_map(|query| &query.warnings) {
+                    self.client.log_message(MessageType::WARNING, warning).await;
+                }
                 workspaces.push(workspace);
             }
         }
@@ -117,6 +179,12 @@ impl LanguageServer for Backend {
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
+                definition_provider: Some(OneOf::Left(true)),
+                document_formatting_provider: Some(OneOf::Left(true)),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: vec![GENERATE_COMMAND.to_string()],
+                    ..Default::default()
+                }),
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
                     TextDocumentSyncKind::FULL,
                 )),
@@ -130,6 +198,46 @@ impl LanguageServer for Backend {
         self.client
             .log_message(MessageType::INFO, "Server Initialized!")
             .await;
+
+        for index in 0..self.workspaces.lock().await.len() {
+            tokio::spawn(listen_for_schema_changes(
+                self.client.clone(),
+                self.workspaces.clone(),
+                index,
+            ));
+        }
+
+        // Queries can change on disk without going through the editor, e.g. on a `git checkout`
+        let mut extensions = vec![];
+        for workspace in self.workspaces.lock().await.iter() {
+            for extension in &workspace.config.workspace.query_extensions {
+                let extension = extension.trim_start_matches('.').to_string();
+                if !extensions.contains(&extension) {
+                    extensions.push(extension);
+                }
+            }
+        }
+
+        let watchers = DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![FileSystemWatcher {
+                glob_pattern: format!("**/*.{{{}}}", extensions.join(",")).into(),
+                kind: None,
+            }],
+        };
+        let registration = Registration {
+            id: "piqued-watched-files".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(watchers).ok(),
+        };
+
+        if let Err(e) = self.client.register_capability(vec![registration]).await {
+            self.client
+                .log_message(
+                    MessageType::WARNING,
+                    format!("Unable to watch query files: {:#?}", e),
+                )
+                .await;
+        }
     }
 
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
@@ -148,7 +256,7 @@ impl LanguageServer for Backend {
 
         workspace.patch_file(uri.to_string(), params.text_document.text.clone());
 
-        self.run_diagnostics(&workspace, params.text_document.uri)
+        self.run_diagnostics(&mut workspace, params.text_document.uri)
             .await;
     }
 
@@ -165,10 +273,121 @@ impl LanguageServer for Backend {
             params.content_changes[0].text.clone(),
         );
 
-        self.run_diagnostics(&workspace, params.text_document.uri)
+        self.run_diagnostics(&mut workspace, params.text_document.uri)
             .await;
     }
 
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        for change in params.changes {
+            let Ok(path) = change.uri.to_file_path() else {
+                continue;
+            };
+            let mut workspace = match self.workspace_for_file(&change.uri).await {
+                Some(workspace) => workspace,
+                None => continue,
+            };
+
+            if !workspace.is_compile_target(&path).await {
+                continue;
+            }
+
+            if change.typ == FileChangeType::DELETED {
+                workspace.remove_file(change.uri.as_str());
+                self.client
+                    .publish_diagnostics(change.uri, vec![], None)
+                    .await;
+                continue;
+            }
+
+            let contents = match tokio::fs::read_to_string(&path).await {
+                Ok(contents) => contents,
+                Err(e) => {
+                    self.client
+                        .log_message(MessageType::ERROR, format!("{:#?}", e))
+                        .await;
+                    continue;
+                }
+            };
+
+            workspace.patch_file(change.uri.to_string(), contents);
+            self.run_diagnostics(&mut workspace, change.uri).await;
+        }
+    }
+
+    async fn execute_command(
+        &self,
+        params: ExecuteCommandParams,
+    ) -> jsonrpc::Result<Option<Value>> {
+        if params.command != GENERATE_COMMAND {
+            return Err(jsonrpc::Error::invalid_params(format!(
+                "Unknown command {}",
+                params.command
+            )));
+        }
+
+        let uri = match params.arguments.first() {
+            Some(Value::String(uri)) => match Url::parse(uri) {
+                Ok(uri) => Some(uri),
+                Err(e) => return Err(jsonrpc::Error::invalid_params(e.to_string())),
+            },
+            Some(_) => {
+                return Err(jsonrpc::Error::invalid_params(
+                    "Expected the URI of a file in the workspace",
+                ))
+            }
+            None => None,
+        };
+
+        let results = match uri {
+            Some(uri) => match self.workspace_for_file(&uri).await {
+                Some(workspace) => vec![workspace.gen_code(GenerationMode::Full).await],
+                None => {
+                    self.client
+                        .show_message(MessageType::ERROR, format!("No workspace contains {}", uri))
+                        .await;
+                    return Ok(None);
+                }
+            },
+            None => {
+                let workspaces = self.workspaces.lock().await;
+
+                let mut results = vec![];
+                for workspace in workspaces.iter() {
+                    results.push(workspace.gen_code(GenerationMode::Full).await);
+                }
+                results
+            }
+        };
+
+        let warnings = results
+            .iter()
+            .flat_map(|result| result.iter().flat_map(|report| report.warnings.clone()))
+            .collect::<Vec<String>>();
+        for warning in warnings {
+            self.client
+                .show_message(MessageType::WARNING, warning)
+                .await;
+        }
+
+        match results.into_iter().find_map(|result| result.err()) {
+            Some(err) => {
+                self.client
+                    .show_message(
+                        MessageType::ERROR,
+                        format!("Unable to generate code: {}", error_message(&err)),
+                    )
+                    .await;
+            }
+            None => {
+                self.client
+                    .show_message(MessageType::INFO, "Generated code")
+                    .await;
+            }
+        }
+
+        Ok(None)
+    }
+
     async fn shutdown(&self) -> jsonrpc::Result<()> {
         Ok(())
     }
@@ -210,4 +429,79 @@ impl LanguageServer for Backend {
             Ok(hov) => Ok(Some(hov)),
         }
     }
+
+    async fn goto_definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> jsonrpc::Result<Option<GotoDefinitionResponse>> {
+        let position = params.text_document_position_params.position;
+        let file_name = params
+            .text_document_position_params
+            .text_document
+            .uri
+            .to_string();
+
+        let maybe_workspace = self
+            .workspace_for_file(&params.text_document_position_params.text_document.uri)
+            .await;
+
+        let workspace = match maybe_workspace {
+            Some(workspace) => workspace,
+            None => return Ok(None),
+        };
+
+        let file_data = match workspace.get_file(&file_name) {
+            Some(data) => data,
+            None => {
+                self.client
+                    .log_message(MessageType::ERROR, "File not found")
+                    .await;
+                return Ok(None);
+            }
+        };
+
+        match self.get_definition(&workspace, file_data, &position).await {
+            Err(e) => {
+                self.client
+                    .log_message(MessageType::ERROR, format!("{:#?}", e))
+                    .await;
+                Ok(None)
+            }
+            Ok(location) => Ok(location.map(GotoDefinitionResponse::Scalar)),
+        }
+    }
+
+    async fn formatting(
+        &self,
+        params: DocumentFormattingParams,
+    ) -> jsonrpc::Result<Option<Vec<TextEdit>>> {
+        let file_name = params.text_document.uri.to_string();
+
+        let maybe_workspace = self.workspace_for_file(&params.text_document.uri).await;
+
+        let workspace = match maybe_workspace {
+            Some(workspace) => workspace,
+            None => return Ok(None),
+        };
+
+        let file_data = match workspace.get_file(&file_name) {
+            Some(data) => data,
+            None => {
+                self.client
+                    .log_message(MessageType::ERROR, "File not found")
+                    .await;
+                return Ok(None);
+            }
+        };
+
+        match format_file(file_data) {
+            Err(e) => {
+                self.client
+                    .log_message(MessageType::ERROR, format!("{:#?}", e))
+                    .await;
+                Ok(None)
+            }
+            Ok(edits) => Ok(Some(edits)),
+        }
+    }
 }
@@ -1,13 +1,46 @@
-use crate::{code_builder::codegen_helper::CodegenHelper, query::query::Column};
+use crate::{
+    code_builder::codegen_helper::CodegenHelper,
+    query::query::{Column, ForeignKey},
+};
 
 pub fn format_table_like(builder: &mut CodegenHelper, table_like: &Vec<Column>) {
     for column in table_like.iter() {
-        let line = if !column.nullable {
-            format!("{} {} NOT NULL", column.name, column.type_name)
+        let mut line = if !column.nullable {
+            format!("{} {} NOT NULL", column.name, column.column_type)
         } else {
-            format!("{} {}", column.name, column.type_name)
+            format!("{} {}", column.name, column.column_type)
         };
 
+        if column.generated {
+            line.push_str(" GENERATED");
+        }
+
+        if let Some(references) = &column.references {
+            line.push_str(&format!(
+                " REFERENCES {} ({})",
+                references.table, references.column
+            ));
+        }
+
         builder.write_line(Some(&line));
     }
 }
+
+pub fn format_column(
+    name: &str,
+    typ: &str,
+    nullable: bool,
+    references: Option<&ForeignKey>,
+) -> String {
+    let mut line = if nullable {
+        format!("{} {} NULL", name, typ)
+    } else {
+        format!("{} {}", name, typ)
+    };
+
+    if let Some(references) = references {
+        line.push_str(&format!("\n→ {}.{}", references.table, references.column));
+    }
+
+    line
+}
@@ -1,22 +1,29 @@
-use std::iter::zip;
+use std::{iter::zip, sync::Arc};
 
 use crate::{
     code_builder::codegen_helper::CodegenHelper,
     loose_parser::{
         parse::ParserContext,
-        parse_cf::{Expression, LR1Kind},
+        parse_cf::{
+            Binop, BinopExpression, ColumnExpression, CommonTableExpression, CommonTableQuery,
+            Expression, FromExpression, JoinExpression, LR1Kind, LR1State, SelectQuery, TableLike,
+        },
     },
     parser::parser,
-    query::query::Query,
+    query::query::{collect_loose_relations, Column, ColumnType, CustomType, Query},
     utils::result::{PiquedError, Result},
     workspace::workspace::Workspace,
 };
 
+use sqlparser::tokenizer::Token;
 use tower_lsp::lsp_types::{
     Hover, HoverContents, LanguageString, MarkedString, MessageType, Position,
 };
 
-use super::{lsp::Backend, lsp_fmt::format_table_like};
+use super::{
+    lsp::Backend,
+    lsp_fmt::{format_column, format_table_like},
+};
 
 impl Backend {
     pub async fn get_hover_data(
@@ -44,12 +51,130 @@ impl Backend {
             .log_message(MessageType::INFO, format!("Stack: {:#?}", stack))
             .await;
 
-        for stack_el in stack.unwrap_or(vec![]).iter() {
+        let stack = stack.unwrap_or(vec![]);
+        let ctes = enclosing_ctes(&stack);
+
+        // The target of a cast is a type, not a column
+        if let Some(type_name) = find_cast_type(&stack) {
+            return type_hover(query_obj, type_name).await;
+        }
+
+        // A CTE shadows any table of the same name
+        if let Some(cte) = find_cte_reference(&stack, &ctes) {
+            let columns = table_like_columns(query_obj, &ctes, &TableLike::Table(cte.name.clone()));
+            return Ok(relation_hover("(cte)", &cte.name, &columns));
+        }
+
+        // `relation.*` expands to every column of the relation it's scoped to
+        if let Some((name, from)) = find_scoped_wildcard(&stack) {
+            let columns = table_like_columns(query_obj, &ctes, &from.table);
+            return Ok(relation_hover(
+                "(columns)",
+                &format!("{}.*", name),
+                &columns,
+            ));
+        }
+
+        // `USING` columns come from both sides of the join
+        if let Some((name, left, join)) = find_using_column(&stack) {
+            let lines = [left, join.table.clone()]
+                .iter()
+                .flat_map(|side| side.relations())
+                .filter_map(|from| {
+                    let column = table_like_columns(query_obj, &ctes, &from.table)
+                        .into_iter()
+                        .find(|column| &column.name == name)?;
+                    Some(format_column(
+                        &format!("{}.{}", relation_label(&from), column.name),
+                        &column.column_type.to_string(),
+                        column.nullable,
+                        column.references.as_ref(),
+                    ))
+                })
+                .collect::<Vec<_>>();
+
+            if !lines.is_empty() {
+                return Ok(Hover {
+                    contents: HoverContents::Array(vec![
+                        MarkedString::LanguageString(LanguageString {
+                            language: "pgsql".to_string(),
+                            value: "(column)".to_string(),
+                        }),
+                        MarkedString::LanguageString(LanguageString {
+                            language: "pgsql".to_string(),
+                            value: lines.join("\n"),
+                        }),
+                    ]),
+                    range: None,
+                });
+            }
+        }
+
+        // `ON` conditions can refer to the joined table and everything to its left
+        if let Some((from, column)) = find_join_condition_column(query_obj, &ctes, &stack) {
+            return Ok(Hover {
+                contents: HoverContents::Array(vec![
+                    MarkedString::LanguageString(LanguageString {
+                        language: "pgsql".to_string(),
+                        value: "(column)".to_string(),
+                    }),
+                    MarkedString::LanguageString(LanguageString {
+                        language: "pgsql".to_string(),
+                        value: format_column(
+                            &format!("{}.{}", relation_label(&from), column.name),
+                            &column.column_type.to_string(),
+                            column.nullable,
+                            column.references.as_ref(),
+                        ),
+                    }),
+                ]),
+                range: None,
+            });
+        }
+
+        // Columns named by an `INSERT`, or filled by one of its values, belong to its target table
+        if let Some(column) = find_insert_value_column(query_obj, &stack)
+            .or_else(|| find_insert_column(query_obj, &stack))
+        {
+            return Ok(Hover {
+                contents: HoverContents::Array(vec![
+                    MarkedString::LanguageString(LanguageString {
+                        language: "pgsql".to_string(),
+                        value: "(column)".to_string(),
+                    }),
+                    MarkedString::LanguageString(LanguageString {
+                        language: "pgsql".to_string(),
+                        value: format_column(
+                            &column.name,
+                            &column.column_type.to_string(),
+                            column.nullable,
+                            column.references.as_ref(),
+                        ),
+                    }),
+                ]),
+                range: None,
+            });
+        }
+
+        // Either half of `schema.table` stands for the table as a whole
+        let qualified = stack.get(1).filter(|state| match &state.kind {
+            LR1Kind::Expression(expr) => {
+                matches!(expr.as_ref(), Expression::BinopExpression(_))
+                    && expr.table_name().is_some()
+            }
+            _ => false,
+        });
+        for stack_el in qualified.into_iter().chain(stack.iter()) {
             if let Some(hov) = self.get_hover_data_for_kind(&query_obj, &stack_el.kind) {
                 return Ok(hov);
             }
         }
 
+        if let Some((alias, select_query)) = find_subquery_alias(&stack) {
+            let columns = table_like_columns(query_obj, &ctes, &TableLike::Subquery(select_query));
+            return Ok(relation_hover("(subquery)", alias, &columns));
+        }
+
         let statement = parsed
             .statements
             .iter()
@@ -63,6 +188,60 @@ impl Backend {
         }
 
         let (i, statement) = statement.unwrap();
+
+        // Operators are described by the type they produce, as far as it can be told from their
+        // operands. `WHERE` clauses aren't reduced into their query, so fall back to every table
+        // in the statement for those.
+        if let Some(expression) = find_hovered_operator(&stack) {
+            let relations =
+                collect_loose_relations(&partial_parsed.states_within(&statement.range));
+            let typ = expression_type(query_obj, &stack, &relations, expression)
+                .unwrap_or_else(|| "unknown".to_string());
+
+            return Ok(Hover {
+                contents: HoverContents::Array(vec![
+                    MarkedString::LanguageString(LanguageString {
+                        language: "pgsql".to_string(),
+                        value: "(expression)".to_string(),
+                    }),
+                    MarkedString::LanguageString(LanguageString {
+                        language: "pgsql".to_string(),
+                        value: typ,
+                    }),
+                ]),
+                range: None,
+            });
+        }
+
+        // Arguments to a function are columns of the tables being selected from, not results
+        if let Some(column_name) = find_function_argument(&stack) {
+            // `WHERE` clauses aren't reduced into their query, so fall back to the whole statement
+            let typ = find_table_column_type(query_obj, &stack, column_name).or_else(|| {
+                collect_loose_relations(&partial_parsed.states_within(&statement.range))
+                    .iter()
+                    .filter_map(|(_, table)| query_obj.table(table))
+                    .flatten()
+                    .find(|column| &column.name == column_name)
+                    .map(|column| column.column_type.to_string())
+            });
+
+            if let Some(typ) = typ {
+                return Ok(Hover {
+                    contents: HoverContents::Array(vec![
+                        MarkedString::LanguageString(LanguageString {
+                            language: "pgsql".to_string(),
+                            value: "(column)".to_string(),
+                        }),
+                        MarkedString::LanguageString(LanguageString {
+                            language: "pgsql".to_string(),
+                            value: format!("{} {}", column_name, typ),
+                        }),
+                    ]),
+                    range: None,
+                });
+            }
+        }
+
         let prepared_statement =
             parser::get_prepared_statement(&statement, &parsed.tokens, &file_contents, || {
                 format!("query_{i}", i = i)
@@ -81,6 +260,71 @@ impl Backend {
             )
             .await;
 
+        // `ORDER BY`/`GROUP BY` terms usually refer to a result column by name, but may also name
+        // a column of one of the tables being selected from
+        if let Some(column_name) = find_grouping_column_name(&stack) {
+            let typ = match probed_type
+                .column_names
+                .iter()
+                .position(|name| name == column_name)
+            {
+                Some(index) => probed_type
+                    .column_types
+                    .get(index)
+                    .map(|typ| typ.to_string()),
+                None => find_table_column_type(query_obj, &stack, column_name),
+            };
+
+            if let Some(typ) = typ {
+                return Ok(Hover {
+                    contents: HoverContents::Array(vec![
+                        MarkedString::LanguageString(LanguageString {
+                            language: "pgsql".to_string(),
+                            value: "(column)".to_string(),
+                        }),
+                        MarkedString::LanguageString(LanguageString {
+                            language: "pgsql".to_string(),
+                            value: format!("{} {}", column_name, typ),
+                        }),
+                    ]),
+                    range: None,
+                });
+            }
+        }
+
+        // If we're hovering over one of the result columns, the probed type is more useful than
+        // the type of the query as a whole.
+        if let Some(column_index) = find_result_column_index(query_obj, &stack) {
+            let name = probed_type.column_names.get(column_index);
+            let typ = probed_type.column_types.get(column_index);
+            let nullable = probed_type.column_nullable.get(column_index).copied();
+
+            if let (Some(name), Some(typ)) = (name, typ) {
+                // A column selected straight from a table keeps its foreign key
+                let references = find_hovered_table_column(query_obj, &stack)
+                    .and_then(|column| column.references);
+
+                return Ok(Hover {
+                    contents: HoverContents::Array(vec![
+                        MarkedString::LanguageString(LanguageString {
+                            language: "pgsql".to_string(),
+                            value: "(column)".to_string(),
+                        }),
+                        MarkedString::LanguageString(LanguageString {
+                            language: "pgsql".to_string(),
+                            value: format_column(
+                                name,
+                                &typ.to_string(),
+                                nullable.unwrap_or(false),
+                                references.as_ref(),
+                            ),
+                        }),
+                    ]),
+                    range: None,
+                });
+            }
+        }
+
         let mut arg_string_vec: Vec<String> = vec![];
         for (i, arg) in probed_type.args.iter().enumerate() {
             match &prepared_statement.details.params {
@@ -92,8 +336,14 @@ impl Backend {
         }
 
         let mut col_string_vec = vec![];
-        for (name, typ) in zip(&probed_type.column_names, &probed_type.column_types) {
-            col_string_vec.push(format!("    {} {}", name, typ));
+        for (i, (name, typ)) in
+            zip(&probed_type.column_names, &probed_type.column_types).enumerate()
+        {
+            let nullable = probed_type.column_nullable.get(i).copied().unwrap_or(false);
+            col_string_vec.push(format!(
+                "    {}",
+                format_column(name, &typ.to_string(), nullable, None)
+            ));
         }
 
         let header = format!("(query) {}", prepared_statement.details.name);
@@ -120,38 +370,774 @@ impl Backend {
     }
 
     fn get_hover_data_for_kind(&self, query: &Query, kind: &LR1Kind) -> Option<Hover> {
-        let mut builder = CodegenHelper::new(&"  ", "\n");
-
         match kind {
             LR1Kind::Expression(exp) => {
-                if let Expression::Identifier(table_name) = exp.as_ref() {
-                    let table_data = query.tables.get(table_name)?;
-
-                    builder.write_line(Some(&format!("{} (", table_name)));
-                    builder.with_indent(|mut builder| {
-                        format_table_like(&mut builder, table_data);
-                    });
-                    builder.write_line(Some(&")"));
-
-                    Some(Hover {
-                        contents: HoverContents::Array(vec![
-                            MarkedString::LanguageString(LanguageString {
-                                language: "pgsql".to_string(),
-                                value: "(table)".to_string(),
-                            }),
-                            MarkedString::LanguageString(LanguageString {
-                                language: "pgsql".to_string(),
-                                value: builder.serialize(),
-                            }),
-                        ]),
-                        range: None,
-                    })
-                } else {
-                    None
+                let table_name = exp.table_name()?;
+                let table_data = query.table(&table_name)?;
+
+                Some(relation_hover("(table)", &table_name, table_data))
+            }
+
+            _ => None,
+        }
+    }
+}
+
+fn relation_hover(label: &str, name: &str, columns: &Vec<Column>) -> Hover {
+    let mut builder = CodegenHelper::new("  ", "\n");
+
+    // An empty pair of parentheses reads like something went wrong
+    if columns.is_empty() {
+        builder.write_line(Some(&format!("{} (no columns)", name)));
+    } else {
+        builder.write_line(Some(&format!("{} (", name)));
+        builder.with_indent(|mut builder| {
+            format_table_like(&mut builder, columns);
+        });
+        builder.write_line(Some(&")"));
+    }
+
+    Hover {
+        contents: HoverContents::Array(vec![
+            MarkedString::LanguageString(LanguageString {
+                language: "pgsql".to_string(),
+                value: label.to_string(),
+            }),
+            MarkedString::LanguageString(LanguageString {
+                language: "pgsql".to_string(),
+                value: builder.serialize(),
+            }),
+        ]),
+        range: None,
+    }
+}
+
+async fn type_hover(query: &Query, type_name: &str) -> Result<Hover> {
+    // Casting to an array of a custom type is described by its element type
+    let element_name = type_name.trim_end_matches("[]");
+
+    let value = match query
+        .custom_types_by_name
+        .get(element_name)
+        .map(|t| t.as_ref())
+    {
+        Some(CustomType::Composite(composite)) => {
+            return Ok(relation_hover("(type)", type_name, &composite.fields));
+        }
+        Some(CustomType::Enum(enum_type)) => {
+            let values = enum_type
+                .values
+                .iter()
+                .map(|value| format!("'{}'", value.replace('\'', "''")))
+                .collect::<Vec<_>>();
+            format!("{} AS ENUM ({})", type_name, values.join(", "))
+        }
+        None => query
+            .resolve_type_name(type_name)
+            .await?
+            .ok_or_else(|| PiquedError::OtherError(format!("Unknown type {}", type_name)))?,
+    };
+
+    Ok(Hover {
+        contents: HoverContents::Array(vec![
+            MarkedString::LanguageString(LanguageString {
+                language: "pgsql".to_string(),
+                value: "(type)".to_string(),
+            }),
+            MarkedString::LanguageString(LanguageString {
+                language: "pgsql".to_string(),
+                value,
+            }),
+        ]),
+        range: None,
+    })
+}
+
+// The outermost type name is the whole of it, e.g. `text[]` rather than `text`
+fn find_cast_type(stack: &Vec<Arc<LR1State>>) -> Option<&String> {
+    stack.iter().rev().find_map(|state| match &state.kind {
+        LR1Kind::TypeName(type_name) => Some(type_name),
+        _ => None,
+    })
+}
+
+/// Finds the position of the hovered column within the outermost `SELECT` list, which lines up
+/// with the columns reported when probing the statement. Wildcards before it take up one position
+/// for each column they expand to.
+fn find_result_column_index(query: &Query, stack: &Vec<Arc<LR1State>>) -> Option<usize> {
+    let column = stack.iter().find_map(|state| match &state.kind {
+        LR1Kind::ColumnExpression(column) => Some(column),
+        _ => None,
+    })?;
+
+    let select_query = stack.iter().rev().find_map(|state| match &state.kind {
+        LR1Kind::SelectQuery(select_query) => Some(select_query),
+        _ => None,
+    })?;
+
+    let ctes = enclosing_ctes(stack);
+    let relations = select_query
+        .from
+        .iter()
+        .flatten()
+        .flat_map(|from| from.relations())
+        .collect::<Vec<_>>();
+
+    let mut index = 0;
+    for candidate in select_query.columns.iter() {
+        if Arc::ptr_eq(candidate, column) {
+            return Some(index);
+        }
+
+        index += match candidate.as_ref() {
+            ColumnExpression::Unnamed(expr) => match expr.as_ref() {
+                Expression::WildcardLiteral => relations
+                    .iter()
+                    .map(|from| table_like_columns(query, &ctes, &from.table).len())
+                    .sum(),
+                Expression::ScopedWildcardLiteral(name) => {
+                    let from = find_scoped_relation(&relations, name)?;
+                    table_like_columns(query, &ctes, &from.table).len()
                 }
+                _ => 1,
+            },
+            ColumnExpression::Named(..) => 1,
+        };
+    }
+
+    None
+}
+
+fn find_grouping_column_name(stack: &Vec<Arc<LR1State>>) -> Option<&String> {
+    let column = stack.iter().find_map(|state| match &state.kind {
+        LR1Kind::ColumnExpression(column) => Some(column),
+        _ => None,
+    })?;
+
+    let in_grouping_clause = stack.iter().any(|state| match &state.kind {
+        LR1Kind::GroupByClause(list) | LR1Kind::OrderByClause(list) => {
+            list.iter().any(|candidate| Arc::ptr_eq(candidate, column))
+        }
+        _ => false,
+    });
+
+    if !in_grouping_clause {
+        return None;
+    }
+
+    match column.as_ref() {
+        ColumnExpression::Unnamed(expr) => match expr.as_ref() {
+            Expression::Identifier(name) => Some(name),
+            _ => None,
+        },
+        ColumnExpression::Named(..) => None,
+    }
+}
+
+fn find_function_argument(stack: &Vec<Arc<LR1State>>) -> Option<&String> {
+    let expression = match &stack.first()?.kind {
+        LR1Kind::Expression(expression) => expression,
+        _ => return None,
+    };
+
+    let Expression::Identifier(name) = expression.as_ref() else {
+        return None;
+    };
+
+    // The argument may be the bare column, or the `relation.column` it's the right half of
+    let is_argument = |arg: &Arc<Expression>| match arg.as_ref() {
+        Expression::BinopExpression(BinopExpression {
+            right,
+            operator: Binop {
+                token: Token::Period,
+                ..
+            },
+            ..
+        }) => Arc::ptr_eq(right, expression),
+        _ => Arc::ptr_eq(arg, expression),
+    };
+
+    stack
+        .iter()
+        .skip(1)
+        .any(|state| match &state.kind {
+            LR1Kind::Expression(parent) => match parent.as_ref() {
+                Expression::FunctionCall(call) => call.args.iter().any(is_argument),
+                _ => false,
+            },
+            _ => false,
+        })
+        .then_some(name)
+}
+
+// The operator itself has to be hovered, its operands are hovered as columns as usual
+fn find_hovered_operator(stack: &Vec<Arc<LR1State>>) -> Option<&Expression> {
+    if !matches!(stack.first()?.kind, LR1Kind::Operator(_)) {
+        return None;
+    }
+
+    match &stack.get(1)?.kind {
+        LR1Kind::Expression(expression) => match expression.as_ref() {
+            Expression::BinopExpression(binop) if binop.operator.token != Token::Period => {
+                Some(expression)
             }
+            Expression::UnopExpression(_) | Expression::IsExpression(_) => Some(expression),
+            _ => None,
+        },
+        _ => None,
+    }
+}
 
+/// Works out the type of an expression from the columns it refers to and the literals in it,
+/// without asking the database. Anything that can't be resolved is `None`.
+fn expression_type(
+    query: &Query,
+    stack: &Vec<Arc<LR1State>>,
+    loose_relations: &Vec<(String, String)>,
+    expression: &Expression,
+) -> Option<String> {
+    let loose_column = |relation: Option<&String>, column_name: &String| {
+        loose_relations
+            .iter()
+            .filter(|(alias, _)| relation.is_none() || relation == Some(alias))
+            .filter_map(|(_, table)| query.table(table))
+            .flatten()
+            .find(|column| &column.name == column_name)
+            .map(|column| column.column_type.to_string())
+    };
+
+    match expression {
+        Expression::Identifier(name) => {
+            find_table_column_type(query, stack, name).or_else(|| loose_column(None, name))
+        }
+        Expression::BinopExpression(BinopExpression {
+            left,
+            right,
+            operator: Binop {
+                token: Token::Period,
+                ..
+            },
+        }) => match (left.as_ref(), right.as_ref()) {
+            (Expression::Identifier(relation), Expression::Identifier(column_name)) => {
+                find_scoped_column_type(query, stack, relation, column_name)
+                    .or_else(|| loose_column(Some(relation), column_name))
+            }
             _ => None,
+        },
+        Expression::BinopExpression(binop) => binop.operator.result_type(
+            expression_type(query, stack, loose_relations, &binop.left).as_deref(),
+            expression_type(query, stack, loose_relations, &binop.right).as_deref(),
+        ),
+        Expression::UnopExpression(unop) => unop.operator.result_type(
+            expression_type(query, stack, loose_relations, &unop.expression).as_deref(),
+        ),
+        Expression::NumberLiteral(number) => Some(
+            if number.parse::<i32>().is_ok() {
+                "int4"
+            } else if number.parse::<i64>().is_ok() {
+                "int8"
+            } else {
+                "numeric"
+            }
+            .to_string(),
+        ),
+        Expression::StringLiteral(_) => Some("text".to_string()),
+        Expression::IsExpression(_) => Some("bool".to_string()),
+        Expression::Cast(cast) => Some(cast.type_name.clone()),
+        _ => None,
+    }
+}
+
+fn find_scoped_column_type(
+    query: &Query,
+    stack: &Vec<Arc<LR1State>>,
+    relation: &String,
+    column_name: &String,
+) -> Option<String> {
+    let select_query = stack.iter().rev().find_map(|state| match &state.kind {
+        LR1Kind::SelectQuery(select_query) => Some(select_query),
+        _ => None,
+    })?;
+
+    let relations = select_query
+        .from
+        .iter()
+        .flatten()
+        .flat_map(|from| from.relations())
+        .collect::<Vec<_>>();
+    let from = find_scoped_relation(&relations, relation)?;
+
+    table_like_columns(query, &enclosing_ctes(stack), &from.table)
+        .into_iter()
+        .find(|column| &column.name == column_name)
+        .map(|column| column.column_type.to_string())
+}
+
+fn find_hovered_table_column(query: &Query, stack: &Vec<Arc<LR1State>>) -> Option<Column> {
+    match &stack.first()?.kind {
+        LR1Kind::Expression(expression) => match expression.as_ref() {
+            Expression::Identifier(name) => find_table_column(query, stack, name),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn find_table_column_type(
+    query: &Query,
+    stack: &Vec<Arc<LR1State>>,
+    column_name: &String,
+) -> Option<String> {
+    find_table_column(query, stack, column_name).map(|column| column.column_type.to_string())
+}
+
+fn find_table_column(
+    query: &Query,
+    stack: &Vec<Arc<LR1State>>,
+    column_name: &String,
+) -> Option<Column> {
+    let select_query = stack.iter().rev().find_map(|state| match &state.kind {
+        LR1Kind::SelectQuery(select_query) => Some(select_query),
+        _ => None,
+    })?;
+
+    let ctes = enclosing_ctes(stack);
+
+    select_query
+        .from
+        .iter()
+        .flatten()
+        .flat_map(|from| table_like_columns(query, &ctes, &from.table))
+        .find(|column| &column.name == column_name)
+}
+
+/// Finds the column of the target table named in an `INSERT`'s column list or `RETURNING` clause.
+/// Anything within a `SELECT` providing the rows is scoped to that instead.
+fn find_insert_column(query: &Query, stack: &Vec<Arc<LR1State>>) -> Option<Column> {
+    let name = match &stack.first()?.kind {
+        LR1Kind::Expression(expr) => match expr.as_ref() {
+            Expression::Identifier(name) => name,
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let insert = stack
+        .iter()
+        .take_while(|state| {
+            !matches!(
+                state.kind,
+                LR1Kind::SelectQuery(_) | LR1Kind::CompoundQuery(_)
+            )
+        })
+        .find_map(|state| match &state.kind {
+            LR1Kind::InsertStmt(insert) => Some(insert),
+            _ => None,
+        })?;
+
+    query
+        .table(&insert.table)?
+        .iter()
+        .find(|column| &column.name == name)
+        .cloned()
+}
+
+/// Finds the column of the target table that the `VALUES` entry under the cursor is inserted into,
+/// pairing each row with the column list, or the table's own columns when there isn't one.
+fn find_insert_value_column(query: &Query, stack: &Vec<Arc<LR1State>>) -> Option<Column> {
+    let position = stack.first()?.start;
+    let insert = stack
+        .iter()
+        .take_while(|state| {
+            !matches!(
+                state.kind,
+                LR1Kind::SelectQuery(_) | LR1Kind::CompoundQuery(_)
+            )
+        })
+        .find_map(|state| match &state.kind {
+            LR1Kind::InsertStmt(insert) => Some(insert),
+            _ => None,
+        })?;
+
+    let index = insert.values.iter().find_map(|row| {
+        row.iter()
+            .position(|(start, end)| (*start..*end).contains(&position))
+    })?;
+
+    let columns = query.table(&insert.table)?;
+    match insert.columns.get(index) {
+        Some(name) => columns.iter().find(|column| &column.name == name).cloned(),
+        None if insert.columns.is_empty() => columns.get(index).cloned(),
+        None => None,
+    }
+}
+
+/// Finds the `USING` column under the cursor, along with the two sides of its join.
+fn find_using_column(
+    stack: &Vec<Arc<LR1State>>,
+) -> Option<(&String, Arc<FromExpression>, Arc<JoinExpression>)> {
+    let name = match &stack.first()?.kind {
+        LR1Kind::Expression(expr) => match expr.as_ref() {
+            Expression::Identifier(name) => name,
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let join = stack.iter().find_map(|state| match &state.kind {
+        LR1Kind::JoinExpression(join) if join.using.contains(name) => Some(join),
+        _ => None,
+    })?;
+
+    Some((name, find_enclosing_join_left(stack, join)?, join.clone()))
+}
+
+/// Finds the column under the cursor in a join's `ON` condition, along with the relation it belongs
+/// to. The condition can refer to the joined table, and to anything joined before it.
+fn find_join_condition_column(
+    query: &Query,
+    ctes: &[Arc<CommonTableExpression>],
+    stack: &Vec<Arc<LR1State>>,
+) -> Option<(Arc<FromExpression>, Column)> {
+    let name = match &stack.first()?.kind {
+        LR1Kind::Expression(expr) => match expr.as_ref() {
+            Expression::Identifier(name) => name,
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    // Only the right hand side of `relation.column` is a column
+    let relation = match stack.get(1).map(|state| &state.kind) {
+        Some(LR1Kind::Expression(expr)) => match expr.as_ref() {
+            Expression::BinopExpression(BinopExpression {
+                left,
+                right,
+                operator:
+                    Binop {
+                        token: Token::Period,
+                        ..
+                    },
+            }) => match (left.as_ref(), right.as_ref()) {
+                (Expression::Identifier(relation), Expression::Identifier(column))
+                    if column == name && stack[0].start > stack[1].start =>
+                {
+                    Some(relation)
+                }
+                _ => return None,
+            },
+            _ => None,
+        },
+        _ => None,
+    };
+
+    // The condition is the last of the join's states, so it's the one just beneath the join
+    let index = stack
+        .iter()
+        .take_while(|state| !matches!(state.kind, LR1Kind::SelectQuery(_)))
+        .position(|state| matches!(state.kind, LR1Kind::JoinExpression(_)))?;
+    let join = match (&stack[index].kind, &stack.get(index.checked_sub(1)?)?.kind) {
+        (LR1Kind::JoinExpression(join), LR1Kind::ExpressionList(_)) if join.condition.is_some() => {
+            join
         }
+        _ => return None,
+    };
+
+    let mut relations = find_enclosing_join_left(stack, join)?.relations();
+    relations.extend(join.table.relations());
+
+    let column_of = |from: &Arc<FromExpression>| {
+        table_like_columns(query, ctes, &from.table)
+            .into_iter()
+            .find(|column| &column.name == name)
+    };
+
+    match relation {
+        Some(relation) => {
+            let from = find_scoped_relation(&relations, relation)?;
+            let column = column_of(&from)?;
+            Some((from, column))
+        }
+        None => relations
+            .iter()
+            .find_map(|from| Some((from.clone(), column_of(from)?))),
+    }
+}
+
+// The left side of a join is only known to whatever the join was attached to
+fn find_enclosing_join_left(
+    stack: &Vec<Arc<LR1State>>,
+    join: &Arc<JoinExpression>,
+) -> Option<Arc<FromExpression>> {
+    stack
+        .iter()
+        .filter_map(|state| match &state.kind {
+            LR1Kind::SelectQuery(select_query) => select_query.from.as_ref(),
+            LR1Kind::FromStmt(list) | LR1Kind::FromExpressionList(list) => Some(list),
+            _ => None,
+        })
+        .flatten()
+        .find_map(|from| find_join_left(from, join))
+}
+
+fn find_join_left(
+    from: &Arc<FromExpression>,
+    join: &Arc<JoinExpression>,
+) -> Option<Arc<FromExpression>> {
+    match from.table.as_ref() {
+        TableLike::Join(left, candidate) if Arc::ptr_eq(candidate, join) => Some(left.clone()),
+        TableLike::Join(left, candidate) => {
+            find_join_left(left, join).or_else(|| find_join_left(&candidate.table, join))
+        }
+        _ => None,
     }
 }
+
+/// The CTEs visible from the hovered position, outermost first, so that later entries shadow
+/// earlier ones.
+fn enclosing_ctes(stack: &Vec<Arc<LR1State>>) -> Vec<Arc<CommonTableExpression>> {
+    stack
+        .iter()
+        .rev()
+        .filter_map(|state| match &state.kind {
+            LR1Kind::SelectQuery(select_query) => Some(&select_query.with),
+            // A `WITH` clause is parsed as part of the first branch, but is visible from all of them
+            LR1Kind::CompoundQuery(compound_query) => compound_query
+                .branches
+                .first()
+                .map(|select_query| &select_query.with),
+            // The main query may not have been reduced yet
+            LR1Kind::WithClause(ctes) => Some(ctes),
+            _ => None,
+        })
+        .flatten()
+        .cloned()
+        .collect()
+}
+
+fn find_cte_reference(
+    stack: &Vec<Arc<LR1State>>,
+    ctes: &Vec<Arc<CommonTableExpression>>,
+) -> Option<Arc<CommonTableExpression>> {
+    let name = match &stack.first()?.kind {
+        LR1Kind::Expression(expr) => match expr.as_ref() {
+            Expression::Identifier(name) => name,
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    ctes.iter().rev().find(|cte| &cte.name == name).cloned()
+}
+
+/// Finds the derived table that the hovered identifier names, i.e. the alias of a `FROM (SELECT
+/// ...) AS alias` entry in any of the enclosing queries.
+fn find_subquery_alias<'a>(
+    stack: &'a Vec<Arc<LR1State>>,
+) -> Option<(&'a String, Arc<SelectQuery>)> {
+    // The alias itself is left as a bare word within the `FROM` clause
+    let name = match stack.first().map(|state| &state.kind) {
+        Some(LR1Kind::Token(Token::Word(word))) => &word.value,
+        _ => stack.iter().find_map(|state| match &state.kind {
+            LR1Kind::Expression(expr) => match expr.as_ref() {
+                Expression::Identifier(name) => Some(name),
+                _ => None,
+            },
+            _ => None,
+        })?,
+    };
+
+    stack
+        .iter()
+        .filter_map(|state| match &state.kind {
+            LR1Kind::SelectQuery(select_query) => select_query.from.as_ref(),
+            LR1Kind::FromStmt(from_stmt) => Some(from_stmt),
+            _ => None,
+        })
+        .flatten()
+        .chain(stack.iter().filter_map(|state| match &state.kind {
+            LR1Kind::FromExpression(from) => Some(from),
+            _ => None,
+        }))
+        .flat_map(|from| from.relations())
+        .find_map(|from| match (from.table.as_ref(), &from.alias) {
+            (TableLike::Subquery(select_query), Some(alias)) if alias == name => {
+                Some((name, select_query.clone()))
+            }
+            _ => None,
+        })
+}
+
+/// Finds the `FROM` entry that a hovered `relation.*` refers to. An aliased table can only be
+/// referred to by its alias.
+fn find_scoped_wildcard(stack: &Vec<Arc<LR1State>>) -> Option<(&String, Arc<FromExpression>)> {
+    // Either half of the wildcard may be hovered, so look up to the column it makes up
+    let name = stack
+        .iter()
+        .take_while(|state| !matches!(state.kind, LR1Kind::ColumnExpression(_)))
+        .find_map(|state| match &state.kind {
+            LR1Kind::Expression(expr) => match expr.as_ref() {
+                Expression::ScopedWildcardLiteral(name) => Some(name),
+                _ => None,
+            },
+            _ => None,
+        })?;
+
+    let relations = stack
+        .iter()
+        .filter_map(|state| match &state.kind {
+            LR1Kind::SelectQuery(select_query) => select_query.from.as_ref(),
+            LR1Kind::FromStmt(from_stmt) => Some(from_stmt),
+            _ => None,
+        })
+        .flatten()
+        .flat_map(|from| from.relations())
+        .collect::<Vec<_>>();
+
+    Some((name, find_scoped_relation(&relations, name)?))
+}
+
+// How a relation is named when describing one of its columns
+fn relation_label(from: &FromExpression) -> String {
+    match (&from.alias, from.table.as_ref()) {
+        (Some(alias), _) => alias.clone(),
+        (None, TableLike::Table(table)) => table.clone(),
+        (None, _) => "(subquery)".to_string(),
+    }
+}
+
+/// Finds the relation that `name` refers to, which is its alias if it has one.
+fn find_scoped_relation(
+    relations: &[Arc<FromExpression>],
+    name: &String,
+) -> Option<Arc<FromExpression>> {
+    relations
+        .iter()
+        .find(|from| from.scope_name() == Some(name.as_str()))
+        .cloned()
+}
+
+/// The columns exposed by an entry in a `FROM` clause. For derived tables and CTEs these come from
+/// the inner select list, with plain column references resolved against its own `FROM` clause.
+fn table_like_columns(
+    query: &Query,
+    ctes: &[Arc<CommonTableExpression>],
+    table_like: &TableLike,
+) -> Vec<Column> {
+    let select_query = match table_like {
+        TableLike::Table(table) => match ctes.iter().rposition(|cte| &cte.name == table) {
+            // A CTE can only see the ones declared before it
+            Some(index) => match &ctes[index].query {
+                CommonTableQuery::Select(select_query) => {
+                    return table_like_columns(
+                        query,
+                        &ctes[..index],
+                        &TableLike::Subquery(select_query.clone()),
+                    )
+                }
+                CommonTableQuery::DataModifying(..) => return vec![],
+            },
+            None => return query.table(table).cloned().unwrap_or_default(),
+        },
+        TableLike::Subquery(select_query) => select_query,
+        TableLike::Join(left, join) => {
+            let mut columns = table_like_columns(query, ctes, &left.table);
+            columns.extend(table_like_columns(query, ctes, &join.table.table));
+            return columns;
+        }
+    };
+
+    let ctes = [ctes, select_query.with.as_slice()].concat();
+
+    let relations: Vec<(Option<String>, Vec<Column>)> = select_query
+        .from
+        .iter()
+        .flatten()
+        .flat_map(|from| from.relations())
+        .map(|from| {
+            let name = from.scope_name().map(|name| name.to_string());
+
+            (name, table_like_columns(query, &ctes, &from.table))
+        })
+        .collect();
+
+    let unknown_column = |name: &str| Column {
+        name: name.to_string(),
+        column_type: ColumnType::Named("unknown".to_string()),
+        type_oid: 0,
+        nullable: true,
+        generated: false,
+        references: None,
+    };
+
+    let resolve = |expr: &Expression| -> Option<Column> {
+        let (relation_name, column_name) = match expr {
+            Expression::Identifier(column) => (None, column),
+            Expression::BinopExpression(BinopExpression {
+                left,
+                right,
+                operator:
+                    Binop {
+                        token: Token::Period,
+                        ..
+                    },
+            }) => match (left.as_ref(), right.as_ref()) {
+                (Expression::Identifier(relation), Expression::Identifier(column)) => {
+                    (Some(relation), column)
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        relations
+            .iter()
+            .filter(|(name, _)| relation_name.is_none() || name.as_ref() == relation_name)
+            .flat_map(|(_, columns)| columns.iter())
+            .find(|column| &column.name == column_name)
+            .cloned()
+    };
+
+    let mut columns = vec![];
+    for column in select_query.columns.iter() {
+        match column.as_ref() {
+            ColumnExpression::Named(name, expr) => {
+                let column = match resolve(expr) {
+                    Some(column) => Column {
+                        name: name.clone(),
+                        ..column
+                    },
+                    None => unknown_column(name),
+                };
+
+                columns.push(column);
+            }
+            ColumnExpression::Unnamed(expr) => match expr.as_ref() {
+                Expression::WildcardLiteral => {
+                    for (_, relation_columns) in relations.iter() {
+                        columns.extend(relation_columns.iter().cloned());
+                    }
+                }
+                Expression::ScopedWildcardLiteral(relation_name) => {
+                    for (_, relation_columns) in relations
+                        .iter()
+                        .filter(|(name, _)| name.as_ref() == Some(relation_name))
+                    {
+                        columns.extend(relation_columns.iter().cloned());
+                    }
+                }
+                expr => match (resolve(expr), expr) {
+                    (Some(column), _) => columns.push(column),
+                    // Postgres names the result of a bare function call after the function
+                    (None, Expression::FunctionCall(call)) => {
+                        columns.push(unknown_column(&call.name))
+                    }
+                    // Postgres' name for a column it can't name from the expression
+                    (None, _) => columns.push(unknown_column("?column?")),
+                },
+            },
+        }
+    }
+
+    columns
+}
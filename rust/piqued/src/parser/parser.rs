@@ -5,25 +5,133 @@ use pg_query::{
 };
 use tower_lsp::lsp_types::{Position, Range};
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StatementKind {
+    Select,
+    Insert,
+    Update,
+    Delete,
+    Prepare,
+    Utility,
+    // The statement couldn't be parsed
+    Unknown,
+}
+
+impl StatementKind {
+    pub fn from_stmt(stmt: &Result<RawStmt>) -> Self {
+        let Ok(RawStmt {
+            stmt: Some(node), ..
+        }) = stmt
+        else {
+            return StatementKind::Unknown;
+        };
+
+        // Statements with a `WITH` clause are classified by their final statement, since that's
+        // what determines the shape of the result
+        match node.node {
+            Some(NodeEnum::SelectStmt(_)) => StatementKind::Select,
+            Some(NodeEnum::InsertStmt(_)) => StatementKind::Insert,
+            Some(NodeEnum::UpdateStmt(_)) => StatementKind::Update,
+            Some(NodeEnum::DeleteStmt(_)) => StatementKind::Delete,
+            Some(NodeEnum::PrepareStmt(_)) => StatementKind::Prepare,
+            Some(_) => StatementKind::Utility,
+            None => StatementKind::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct RelocatedStmt {
     pub stmt: Result<RawStmt>,
+    pub kind: StatementKind,
 
     pub range: Range,
     pub index_start: u32,
     pub index_len: u32,
 }
 
+#[derive(Debug)]
 pub struct ParsedFile {
     pub statements: Vec<RelocatedStmt>,
     pub tokens: Vec<ScanToken>,
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub enum TemplateKind {
+    // `@xtemplate name example`: the placeholder is replaced with the example SQL
+    Example(String),
+    // `@xarray name element_type`: the placeholder becomes an array parameter
+    Array(String),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Template {
+    pub name: String,
+    pub kind: TemplateKind,
+}
+
+impl Template {
+    pub fn new(name: &str, kind: TemplateKind) -> Self {
+        Template {
+            name: name.to_string(),
+            kind,
+        }
+    }
+
+    /// The example SQL substituted for an `@xtemplate` placeholder.
+    ///
+    /// ```
+    /// use piqued::parser::parser::{Template, TemplateKind};
+    ///
+    /// let template = Template::new("ids", TemplateKind::Example("(1, 2)".to_string()));
+    /// assert_eq!(template.example(), Some("(1, 2)"));
+    ///
+    /// let template = Template::new("ids", TemplateKind::Array("int4".to_string()));
+    /// assert_eq!(template.example(), None);
+    /// ```
+    pub fn example(&self) -> Option<&str> {
+        match &self.kind {
+            TemplateKind::Example(example) => Some(example),
+            TemplateKind::Array(_) => None,
+        }
+    }
+}
+
+// `@cache ttl=60`: results are memoized by the generated runtime for `ttl` seconds
+#[derive(Debug, PartialEq, Clone)]
+pub struct CacheHint {
+    pub ttl: u32,
+}
+
+impl Default for CacheHint {
+    fn default() -> Self {
+        CacheHint { ttl: 60 }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct ParsedDetails {
     pub comment: String,
     pub name: String,
     pub params: Option<Vec<String>>,
+    // `@params name:type`: the type each parameter is annotated with, in the same order as `params`
+    pub param_types: Vec<Option<String>>,
+    pub templates: Vec<Template>,
+    pub cache: Option<CacheHint>,
+    // `@role name`: the role the statement is probed as, e.g. to see it through row-level security
+    pub role: Option<String>,
+    // `@index name`: the index the statement's plan is expected to use
+    pub index: Option<String>,
+}
+
+impl ParsedDetails {
+    /// The names of the statement's templates, in the order they were declared.
+    pub fn template_names(&self) -> Vec<&str> {
+        self.templates
+            .iter()
+            .map(|template| template.name.as_str())
+            .collect()
+    }
 }
 
 pub struct ParsedPreparedQuery {
@@ -33,68 +141,67 @@ pub struct ParsedPreparedQuery {
     pub contents: String,
 }
 
+impl ParsedPreparedQuery {
+    /// The number of parameters the statement takes, i.e. the highest `$n` it refers to.
+    pub fn param_count(&self) -> usize {
+        let referenced = pg_query::scan(&self.contents)
+            .map(|scanned| scanned.tokens)
+            .unwrap_or_default()
+            .iter()
+            .filter(|token| token.token() == Token::Param)
+            .filter_map(|token| {
+                self.contents[token.start as usize + 1..token.end as usize]
+                    .parse::<usize>()
+                    .ok()
+            })
+            .max()
+            .unwrap_or(0);
+
+        referenced.max(self.variables.len())
+    }
+}
+
 pub fn parse_single_query<'a>(query: &str) -> Result<RawStmt> {
     Ok(pg_query::parse(query)?.protobuf.stmts[0].clone())
 }
 
 pub fn load_file(contents: &str) -> Result<ParsedFile> {
     let queries = pg_query::split_with_scanner(&contents)?;
-    let index_by_line: Vec<u32> = (contents.to_string() + "\n")
-        .split("\n")
-        .scan(0, |acc, line| {
-            let result = acc.clone();
-            *acc += line.len() + 1;
-            Some(result as u32)
-        })
-        .collect();
-
-    let get_position = |index: u32| {
-        let line = index_by_line
-            .iter()
-            .enumerate()
-            .find(|(_, &x)| x > index)
-            .map(|(i, _)| i - 1)
-            .unwrap_or(0);
+    let lines = line_spans(contents);
 
-        let column = index - index_by_line[line];
-
-        Position::new(line as u32, column as u32)
-    };
+    let get_position = |index: u32| position_in(contents, &lines, index);
 
     let get_range =
         |start: u32, len: u32| Range::new(get_position(start), get_position(start + len));
 
     let parsed_statements: Vec<Result<RawStmt>> = queries
         .iter()
-        .map(|query| parse_single_query(query))
+        .map(|query| {
+            let (query, _) = apply_templates(query)?;
+            parse_single_query(&query)
+        })
         .collect();
 
     let relocated_statements: Vec<RelocatedStmt> = parsed_statements
         .iter()
         .zip(queries.iter())
-        .scan(0, |state, (stmt, query)| {
-            let mut whitespace = 0;
-            for c in query.chars() {
-                if c.is_whitespace() {
-                    whitespace += 1;
-                } else {
-                    break;
-                }
-            }
-
-            let location = *state;
-            let len = query.len() as u32 + 1; // Add one for the semicolon
-            *state += &len;
+        .map(|(stmt, query)| {
+            // The scanner hands back slices of `contents`, so their offsets are exact. Summing up
+            // lengths instead drifts whenever a separator isn't exactly one semicolon (e.g. `;;`).
+            let location = query.as_ptr() as usize - contents.as_ptr() as usize;
+            let whitespace = query.len() - query.trim_start().len();
+            let semicolon = contents[location + query.len()..].starts_with(';') as usize;
 
-            let index_start = location + whitespace as u32;
-            let index_len = len - whitespace as u32;
+            let index_start = (location + whitespace) as u32;
+            let index_len = (query.len() - whitespace + semicolon) as u32;
 
-            Some(RelocatedStmt {
+            RelocatedStmt {
                 stmt: stmt.clone(),
+                kind: StatementKind::from_stmt(stmt),
                 range: get_range(index_start, index_len),
                 index_start,
                 index_len,
-            })
+            }
         })
         .collect();
 
@@ -106,12 +213,163 @@ pub fn load_file(contents: &str) -> Result<ParsedFile> {
     });
 }
 
+/// Converts a byte offset within `contents` into the position an editor would report for it.
+pub fn position_at(contents: &str, index: u32) -> Position {
+    position_in(contents, &line_spans(contents), index)
+}
+
+fn position_in(contents: &str, lines: &Vec<(u32, u32)>, index: u32) -> Position {
+    let line = lines
+        .iter()
+        .rposition(|(start, _)| *start <= index)
+        .unwrap_or(0);
+
+    let (start, end) = lines[line];
+    // Clamp to the end of the line's content, so that offsets landing inside of a `\r\n`
+    // terminator don't end up a character past where the editor thinks the line ends.
+    let index = index.min(end);
+
+    // Editors count columns in UTF-16 code units rather than bytes
+    let column = contents
+        .get(start as usize..index as usize)
+        .map_or(index - start, |prefix| prefix.encode_utf16().count() as u32);
+
+    Position::new(line as u32, column)
+}
+
+/// Returns the `(start, end)` byte offsets of each line's content, excluding its terminator.
+/// `\n`, `\r\n`, and a lone `\r` are all treated as line terminators, per the LSP spec.
+fn line_spans(contents: &str) -> Vec<(u32, u32)> {
+    let bytes = contents.as_bytes();
+    let mut spans = vec![];
+
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\r' if bytes.get(i + 1) == Some(&b'\n') => {
+                spans.push((start as u32, i as u32));
+                i += 2;
+                start = i;
+            }
+            b'\r' | b'\n' => {
+                spans.push((start as u32, i as u32));
+                i += 1;
+                start = i;
+            }
+            _ => i += 1,
+        }
+    }
+
+    spans.push((start as u32, bytes.len() as u32));
+    spans
+}
+
+/// Rewrites the `:placeholder`s declared by a statement's leading comments into plain SQL.
+/// Returns the rewritten statement, along with the parameter number given to each array template.
+fn apply_templates(query: &str) -> Result<(String, Vec<(String, u32)>)> {
+    let tokens = pg_query::scan(query)?.tokens;
+
+    let comments = tokens
+        .iter()
+        .take_while(|token| matches!(token.token(), Token::CComment | Token::SqlComment))
+        .map(|token| query[token.start as usize..token.end as usize].to_string())
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let templates = parse_comment(&comments, || String::new()).templates;
+    if templates.is_empty() {
+        return Ok((query.to_string(), vec![]));
+    }
+
+    let mut next_param = tokens
+        .iter()
+        .filter(|token| token.token() == Token::Param)
+        .filter_map(|token| {
+            query[token.start as usize + 1..token.end as usize]
+                .parse::<u32>()
+                .ok()
+        })
+        .max()
+        .unwrap_or(0);
+    let mut array_params: Vec<(String, u32)> = vec![];
+
+    let mut result = String::new();
+    let mut last = 0;
+
+    for (i, token) in tokens.iter().enumerate() {
+        if token.token() != Token::Ascii58 {
+            continue;
+        }
+
+        // Keywords are fine as placeholder names, so match on the text rather than the token kind
+        let Some(ident) = tokens.get(i + 1).filter(|ident| ident.start == token.end) else {
+            continue;
+        };
+
+        let name = &query[ident.start as usize..ident.end as usize];
+        let Some(template) = templates.iter().find(|template| template.name == name) else {
+            continue;
+        };
+
+        let (start, replacement) = match &template.kind {
+            TemplateKind::Example(example) => (token.start, example.clone()),
+
+            TemplateKind::Array(element_type) => {
+                let param = match array_params
+                    .iter()
+                    .find(|(param_name, _)| param_name == name)
+                {
+                    Some((_, param)) => *param,
+                    None => {
+                        next_param += 1;
+                        array_params.push((name.to_string(), next_param));
+                        next_param
+                    }
+                };
+
+                let array = format!("${}::{}[]", param, element_type);
+
+                // `x IN :xs` isn't valid for an array, so it becomes `x = ANY($n::type[])`
+                let prev = i.checked_sub(1).and_then(|j| tokens.get(j));
+                let prev_prev = i.checked_sub(2).and_then(|j| tokens.get(j));
+
+                match (prev_prev, prev) {
+                    (Some(not), Some(in_))
+                        if in_.token() == Token::InP
+                            && matches!(not.token(), Token::Not | Token::NotLa) =>
+                    {
+                        (not.start, format!("<> ALL({})", array))
+                    }
+                    (_, Some(in_)) if in_.token() == Token::InP => {
+                        (in_.start, format!("= ANY({})", array))
+                    }
+                    _ => (token.start, array),
+                }
+            }
+        };
+
+        result.push_str(&query[last..start as usize]);
+        result.push_str(&replacement);
+        last = ident.end as usize;
+    }
+
+    result.push_str(&query[last..]);
+
+    Ok((result, array_params))
+}
+
 fn parse_comment<F>(string: &String, default_name: F) -> ParsedDetails
 where
     F: FnOnce() -> String,
 {
     let mut name: Option<String> = None;
     let mut params: Option<Vec<String>> = None;
+    let mut param_types: Vec<Option<String>> = vec![];
+    let mut templates: Vec<Template> = vec![];
+    let mut cache: Option<CacheHint> = None;
+    let mut role: Option<String> = None;
+    let mut index: Option<String> = None;
     let mut comment_lines: Vec<String> = vec![];
 
     for line in string.lines() {
@@ -121,6 +379,7 @@ where
             .trim_end_matches("*/")
             .trim_start_matches(vec![' ', '\t'].as_slice())
             .trim_start_matches("-- ")
+            .trim_start_matches("--")
             .trim_start_matches("* ")
             .trim_end();
 
@@ -133,7 +392,75 @@ where
             let mut param_iter = trimmed_comment.split(" ").into_iter();
 
             param_iter.next();
-            params = Some(param_iter.map(|val| val.trim().to_string()).collect());
+
+            // Each parameter is either a bare name, or annotated with its type as `name:type`
+            let (names, types) = param_iter
+                .map(|val| match val.trim().split_once(':') {
+                    Some((name, type_)) if !name.is_empty() && !type_.is_empty() => (
+                        name.to_string(),
+                        Some(type_.trim_end_matches(',').to_string()),
+                    ),
+                    _ => (val.trim().to_string(), None),
+                })
+                .unzip();
+
+            params = Some(names);
+            param_types = types;
+        } else if trimmed_comment.starts_with("@xtemplate") {
+            // Examples are often SQL with spaces in them, so everything after the name is the
+            // example. Wrapping it in quotes is also allowed.
+            let rest = trimmed_comment.trim_start_matches("@xtemplate").trim();
+
+            if let Some((name, example)) = rest.split_once(char::is_whitespace) {
+                let example = example.trim();
+                let example = example
+                    .strip_prefix('"')
+                    .and_then(|example| example.strip_suffix('"'))
+                    .unwrap_or(example);
+
+                push_template(
+                    &mut templates,
+                    Template::new(name, TemplateKind::Example(example.to_string())),
+                );
+            } else {
+                comment_lines.push(trimmed_comment.to_string());
+            }
+        } else if trimmed_comment.starts_with("@xarray") {
+            let mut parts = trimmed_comment.split_whitespace().skip(1);
+
+            match (parts.next(), parts.next()) {
+                (Some(name), Some(element_type)) => push_template(
+                    &mut templates,
+                    Template::new(
+                        name,
+                        TemplateKind::Array(element_type.trim_end_matches("[]").to_string()),
+                    ),
+                ),
+                _ => comment_lines.push(trimmed_comment.to_string()),
+            }
+        } else if trimmed_comment.starts_with("@cache") {
+            match parse_cache_hint(trimmed_comment) {
+                Some(hint) => cache = Some(hint),
+                None => comment_lines.push(trimmed_comment.to_string()),
+            }
+        } else if trimmed_comment.starts_with("@role") {
+            match trimmed_comment
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .as_slice()
+            {
+                ["@role", name] => role = Some(name.to_string()),
+                _ => comment_lines.push(trimmed_comment.to_string()),
+            }
+        } else if trimmed_comment.starts_with("@index") {
+            match trimmed_comment
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .as_slice()
+            {
+                ["@index", name] => index = Some(name.to_string()),
+                _ => comment_lines.push(trimmed_comment.to_string()),
+            }
         } else {
             comment_lines.push(trimmed_comment.to_string());
         }
@@ -143,9 +470,62 @@ where
         comment: comment_lines.join("\n"),
         name: name.unwrap_or_else(default_name),
         params,
+        param_types,
+        templates,
+        cache,
+        role,
+        index,
     };
 }
 
+// A placeholder can be used any number of times, but declaring it again just replaces it
+fn push_template(templates: &mut Vec<Template>, template: Template) {
+    match templates
+        .iter_mut()
+        .find(|existing| existing.name == template.name)
+    {
+        Some(existing) => *existing = template,
+        None => templates.push(template),
+    }
+}
+
+fn parse_cache_hint(line: &str) -> Option<CacheHint> {
+    let mut hint = CacheHint::default();
+
+    for option in line.split_whitespace().skip(1) {
+        match option.split_once('=') {
+            Some(("ttl", ttl)) => hint.ttl = ttl.parse().ok()?,
+            _ => return None,
+        }
+    }
+
+    Some(hint)
+}
+
+/// The name each statement is generated under: its `@name`, or else `query_N` after its position in
+/// the file, along with whether it's the latter. Statements that can't be prepared don't generate
+/// anything, so they're left out.
+pub fn statement_names<'a>(
+    parsed: &'a ParsedFile,
+    content: &str,
+) -> Vec<(String, bool, &'a RelocatedStmt)> {
+    parsed
+        .statements
+        .iter()
+        .enumerate()
+        .filter_map(|(i, stmt)| {
+            let mut fallback = false;
+            let prepared = get_prepared_statement(stmt, &parsed.tokens, content, || {
+                fallback = true;
+                format!("query_{i}")
+            })
+            .ok()?;
+
+            Some((prepared.details.name, fallback, stmt))
+        })
+        .collect()
+}
+
 pub fn get_prepared_statement<F>(
     obj: &RelocatedStmt,
     tokens: &Vec<ScanToken>,
@@ -156,7 +536,9 @@ where
     F: FnOnce() -> String,
 {
     let start = obj.index_start;
-    let stmt = obj.stmt.clone()?;
+
+    let end = ((obj.index_start + obj.index_len) as usize).min(content.len());
+    let (templated, array_params) = apply_templates(&content[start as usize..end])?;
 
     let mut comments: Vec<String> = vec![];
 
@@ -173,6 +555,24 @@ where
     }
 
     let comments = comments.join("\n");
+
+    // `pg_query` only knows a single version of the grammar, so a statement it rejects may still be
+    // fine by the server. Those are sent along as written, and left for the server to judge.
+    let stmt = match obj.stmt.clone() {
+        Ok(stmt) => stmt,
+        Err(_) => {
+            let mut details = parse_comment(&comments, default_name);
+            name_array_params(&mut details, &array_params);
+
+            return Ok(ParsedPreparedQuery {
+                contents: templated.trim_end().trim_end_matches(';').to_string(),
+                query: RawStmt::default(),
+                variables: vec![],
+                details,
+            });
+        }
+    };
+
     if let Some(box_stmt) = stmt.stmt {
         return match *box_stmt {
             protobuf::Node {
@@ -184,7 +584,8 @@ where
                     stmt_len: 0,
                 };
 
-                let details = parse_comment(&comments, || prep_stmt.name.clone());
+                let mut details = parse_comment(&comments, || prep_stmt.name.clone());
+                name_array_params(&mut details, &array_params);
 
                 Ok(ParsedPreparedQuery {
                     contents: deparse_statement(&statement),
@@ -201,7 +602,8 @@ where
                     stmt_len: 0,
                 };
 
-                let details = parse_comment(&comments, default_name);
+                let mut details = parse_comment(&comments, default_name);
+                name_array_params(&mut details, &array_params);
 
                 Ok(ParsedPreparedQuery {
                     contents: deparse_statement(&statement),
@@ -216,6 +618,24 @@ where
     }
 }
 
+// Array templates introduce their own parameters, so they get to name them
+fn name_array_params(details: &mut ParsedDetails, array_params: &Vec<(String, u32)>) {
+    if array_params.is_empty() {
+        return;
+    }
+
+    let params = details.params.get_or_insert_with(Vec::new);
+    for (name, param) in array_params {
+        let index = *param as usize - 1;
+
+        while params.len() <= index {
+            params.push(format!("${}", params.len()));
+        }
+
+        params[index] = name.clone();
+    }
+}
+
 fn deparse_statement(stmt: &RawStmt) -> String {
     let as_prepared_statement = ParseResult {
         stmts: vec![RawStmt {
@@ -1,68 +1,467 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    future::poll_fn,
+    io,
+    path::PathBuf,
+    sync::{Arc, OnceLock, RwLock},
+};
 
 use pg_query::{
-    protobuf::{ParseResult, RawStmt},
+    protobuf::{AExprKind, ColumnRef, JoinType, ParseResult, RawStmt},
     Node, NodeEnum,
 };
-use tokio::spawn;
+use serde_derive::{Deserialize, Serialize};
+use sqlparser::{
+    keywords::Keyword,
+    tokenizer::{Token, Word},
+};
+use tokio::{fs, spawn, sync::mpsc};
 use tokio_postgres::{
     connect,
     types::{Kind, Type},
-    Client, NoTls,
+    AsyncMessage, Client, NoTls, SimpleQueryMessage,
 };
 
 use crate::{
     config::config::Config,
+    loose_parser::{
+        parse::{identifier_name, ParserContext},
+        parse_cf::{
+            Binop, BinopExpression, ColumnExpression, Expression, FromExpression, LR1Kind,
+            LR1State, Operator, TableLike, Unop,
+        },
+    },
     parser::parser::{node_to_string, ParsedPreparedQuery},
-    utils::result::Result,
+    utils::result::{PiquedError, Result},
 };
 
 #[derive(Debug)]
 pub struct Query {
     pub client: Client,
-    pub tables: HashMap<String, Vec<Column>>,
-    pub custom_types_by_oid: HashMap<u32, Arc<CustomType>>,
-    pub custom_types_by_name: HashMap<String, Arc<CustomType>>,
+    pub tables: Arc<HashMap<String, Vec<Column>>>,
+    // The tables in `pg_catalog`, which is always on the search path. They're only there to look
+    // names up in, and never generated.
+    pub catalog_tables: Arc<HashMap<String, Vec<Column>>>,
+    // The tables in `public`, when that isn't the configured schema, for names qualified with it.
+    // Like the catalog's, they're never generated.
+    pub public_tables: Arc<HashMap<String, Vec<Column>>>,
+    pub custom_types_by_oid: Arc<HashMap<u32, Arc<CustomType>>>,
+    pub custom_types_by_name: Arc<HashMap<String, Arc<CustomType>>>,
     pub config: Arc<Config>,
+    // Problems loading the schema that didn't stop it from loading, left for the caller to report
+    pub warnings: Vec<String>,
+}
+
+// A connection that's done nothing but `LISTEN` on a channel, handing back each notification's
+// payload
+#[derive(Debug)]
+pub struct SchemaListener {
+    // Keeps the connection open
+    _client: Client,
+    notifications: mpsc::UnboundedReceiver<String>,
+}
+
+impl SchemaListener {
+    pub async fn connect(config: &Config, channel: &str) -> Result<SchemaListener> {
+        let (client, mut connection) = connect(&config.postgres.uri, NoTls).await?;
+        let (sender, notifications) = mpsc::unbounded_channel();
+
+        // Notifications only come out of the connection when it's polled for them directly
+        spawn(async move {
+            while let Some(message) = poll_fn(|cx| connection.poll_message(cx)).await {
+                match message {
+                    Ok(AsyncMessage::Notification(notification)) => {
+                        if sender.send(notification.payload().to_string()).is_err() {
+                            break;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        eprintln!("connection error: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+
+        let channel = channel.replace('"', "\"\"");
+        client
+            .batch_execute(&format!("LISTEN \"{}\"", channel))
+            .await?;
+
+        Ok(SchemaListener {
+            _client: client,
+            notifications,
+        })
+    }
+
+    // Waits for the next notification, folding in any others that have already arrived so that a
+    // migration with many statements only counts once. `None` once the connection is gone.
+    pub async fn recv(&mut self) -> Option<String> {
+        let payload = self.notifications.recv().await?;
+        while self.notifications.try_recv().is_ok() {}
+
+        Some(payload)
+    }
 }
 
+// Everything we load about a schema, so that workspaces pointed at the same database don't each
+// have to load it themselves
 #[derive(Debug)]
+struct SchemaCacheEntry {
+    tables: Arc<HashMap<String, Vec<Column>>>,
+    catalog_tables: Arc<HashMap<String, Vec<Column>>>,
+    public_tables: Arc<HashMap<String, Vec<Column>>>,
+    custom_types_by_oid: Arc<HashMap<u32, Arc<CustomType>>>,
+    custom_types_by_name: Arc<HashMap<String, Arc<CustomType>>>,
+}
+
+fn schema_cache_path(config: &Config) -> Option<PathBuf> {
+    let file = config.postgres.schema_cache.as_ref()?;
+    Some(config.workspace.root.as_ref()?.join(file))
+}
+
+// What's written to `postgres.schemaCache`. It's only trusted while the catalogs still have the
+// same fingerprint.
+#[derive(Debug, Serialize, Deserialize)]
+struct SchemaCacheFile<T> {
+    schema: String,
+    fingerprint: String,
+    tables: HashMap<String, Vec<Column>>,
+    custom_types: Vec<T>,
+}
+
+type SchemaCacheKey = (String, String);
+
+static SCHEMA_CACHE: OnceLock<RwLock<HashMap<SchemaCacheKey, SchemaCacheEntry>>> = OnceLock::new();
+
+fn schema_cache() -> &'static RwLock<HashMap<SchemaCacheKey, SchemaCacheEntry>> {
+    SCHEMA_CACHE.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+fn schema_cache_key(config: &Config) -> SchemaCacheKey {
+    (config.postgres.uri.clone(), config.postgres.schema.clone())
+}
+
+// The type of a column or parameter, with arrays broken out so that generators can work with the
+// element type directly (rather than postgres' `_int4`-style names)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ColumnType {
+    Named(String),
+    ArrayOf(Box<ColumnType>),
+}
+
+impl ColumnType {
+    pub fn from_type(type_: &Type) -> ColumnType {
+        match type_.kind() {
+            // Postgres doesn't distinguish arrays by dimension, so `int4[][]` is a single level too
+            Kind::Array(element) => ColumnType::ArrayOf(Box::new(ColumnType::from_type(element))),
+            _ => ColumnType::Named(type_.name().to_string()),
+        }
+    }
+
+    // `element_name` is the element type of an array (i.e. `typcategory = 'A'`) type
+    pub fn from_catalog(type_name: String, element_name: Option<String>) -> ColumnType {
+        match element_name {
+            Some(element_name) => ColumnType::ArrayOf(Box::new(ColumnType::Named(element_name))),
+            None => ColumnType::Named(type_name),
+        }
+    }
+}
+
+// Mostly for building types by hand, so `text[]` is an array of `text`
+impl From<&str> for ColumnType {
+    fn from(name: &str) -> Self {
+        match name.strip_suffix("[]") {
+            Some(element) => ColumnType::ArrayOf(Box::new(ColumnType::from(element))),
+            None => ColumnType::Named(name.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ColumnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnType::Named(name) => write!(f, "{}", name),
+            ColumnType::ArrayOf(element) => write!(f, "{}[]", element),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Column {
     pub name: String,
-    pub type_name: String,
+    pub column_type: ColumnType,
     pub type_oid: u32,
     pub nullable: bool,
+    pub generated: bool,
+    // Only single-column foreign keys are tracked
+    pub references: Option<ForeignKey>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForeignKey {
+    pub table: String,
+    pub column: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CompositeType {
     pub oid: u32,
     pub name: String,
     pub fields: Vec<Column>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct EnumType {
     pub oid: u32,
     pub name: String,
     pub values: Vec<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum CustomType {
     Composite(CompositeType),
     Enum(EnumType),
 }
 
-#[derive(Debug)]
+impl CustomType {
+    pub fn oid(&self) -> u32 {
+        match self {
+            CustomType::Composite(t) => t.oid,
+            CustomType::Enum(t) => t.oid,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            CustomType::Composite(t) => &t.name,
+            CustomType::Enum(t) => &t.name,
+        }
+    }
+}
+
+// Everything loaded about the configured schema, in a stable order, as printed by `piqued schema`
+#[derive(Debug, Serialize)]
+pub struct SchemaDump<'a> {
+    pub schema: &'a str,
+    pub tables: BTreeMap<&'a str, &'a Vec<Column>>,
+    pub composite_types: Vec<&'a CompositeType>,
+    pub enums: Vec<&'a EnumType>,
+}
+
+#[derive(Debug, Default)]
 pub struct ProbeResponse {
-    pub args: Vec<String>,
-    pub column_types: Vec<String>,
+    pub args: Vec<ColumnType>,
+    pub column_types: Vec<ColumnType>,
     pub column_names: Vec<String>,
+    pub column_nullable: Vec<bool>,
+}
+
+impl ProbeResponse {
+    /// The number of distinct parameters. Postgres reports each `$n` once however many times the
+    /// statement uses it, so this is also the number of values it has to be given.
+    pub fn param_count(&self) -> usize {
+        self.args.len()
+    }
+}
+
+// Lets a response be put together without probing a database, e.g. to test a generator
+impl ProbeResponse {
+    pub fn arg(mut self, type_: impl Into<ColumnType>) -> Self {
+        self.args.push(type_.into());
+        self
+    }
+
+    pub fn column(mut self, name: &str, type_: impl Into<ColumnType>, nullable: bool) -> Self {
+        self.column_names.push(name.to_string());
+        self.column_types.push(type_.into());
+        self.column_nullable.push(nullable);
+        self
+    }
+}
+
+// A relation in the `FROM` clause, along with whether an outer join can null it out
+struct FromRelation {
+    name: String,
+    table: Option<String>,
+    nullable: bool,
 }
 
 impl Query {
     pub async fn new(config: Arc<Config>) -> Result<Query> {
+        let mut query = Query::connect(config.clone()).await?;
+
+        query.load_schema(&config).await?;
+
+        Ok(query)
+    }
+
+    // Like `new`, but reuses the schema loaded by any other `Query` for the same database and
+    // schema. Each query still gets its own connection.
+    pub async fn new_shared(config: Arc<Config>) -> Result<Query> {
+        let key = schema_cache_key(&config);
+        let mut query = Query::connect(config.clone()).await?;
+
+        if let Some(entry) = schema_cache().read().unwrap().get(&key) {
+            query.tables = entry.tables.clone();
+            query.catalog_tables = entry.catalog_tables.clone();
+            query.public_tables = entry.public_tables.clone();
+            query.custom_types_by_oid = entry.custom_types_by_oid.clone();
+            query.custom_types_by_name = entry.custom_types_by_name.clone();
+
+            return Ok(query);
+        }
+
+        query.load_schema(&config).await?;
+
+        schema_cache().write().unwrap().insert(
+            key,
+            SchemaCacheEntry {
+                tables: query.tables.clone(),
+                catalog_tables: query.catalog_tables.clone(),
+                public_tables: query.public_tables.clone(),
+                custom_types_by_oid: query.custom_types_by_oid.clone(),
+                custom_types_by_name: query.custom_types_by_name.clone(),
+            },
+        );
+
+        Ok(query)
+    }
+
+    pub fn invalidate_shared_schema(config: &Config) {
+        schema_cache()
+            .write()
+            .unwrap()
+            .remove(&schema_cache_key(config));
+    }
+
+    // Forgets the schema saved to `postgres.schemaCache`, so that the next load goes to the database
+    pub async fn invalidate_schema_cache_file(config: &Config) -> Result<()> {
+        let Some(path) = schema_cache_path(config) else {
+            return Ok(());
+        };
+
+        match fs::remove_file(path).await {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    // Goes back to the database for the schema, e.g. after a migration, and shares the result
+    // with every other `Query` for it
+    pub async fn refresh_schema(&mut self) -> Result<()> {
+        let config = self.config.clone();
+
+        Query::invalidate_schema_cache_file(&config).await?;
+        self.warnings.clear();
+        self.load_schema(&config).await?;
+
+        schema_cache().write().unwrap().insert(
+            schema_cache_key(&config),
+            SchemaCacheEntry {
+                tables: self.tables.clone(),
+                catalog_tables: self.catalog_tables.clone(),
+                public_tables: self.public_tables.clone(),
+                custom_types_by_oid: self.custom_types_by_oid.clone(),
+                custom_types_by_name: self.custom_types_by_name.clone(),
+            },
+        );
+
+        Ok(())
+    }
+
+    async fn load_schema(&mut self, config: &Config) -> Result<()> {
+        // Catalog tables only change with the server's version, so they're quick to load and left
+        // out of `postgres.schemaCache`
+        self.catalog_tables = Arc::new(self.fetch_tables("pg_catalog").await?);
+        self.public_tables = Arc::new(match config.postgres.schema.as_str() {
+            "public" => HashMap::new(),
+            _ => self.fetch_tables("public").await?,
+        });
+
+        let Some(path) = schema_cache_path(config) else {
+            self.load_table_schema(config).await?;
+            return self.load_custom_types(config).await;
+        };
+
+        let fingerprint = self.schema_fingerprint().await?;
+
+        // Anything wrong with the file just means going to the database instead
+        let cached = fs::read(&path)
+            .await
+            .ok()
+            .and_then(|contents| {
+                serde_json::from_slice::<SchemaCacheFile<CustomType>>(&contents).ok()
+            })
+            .filter(|cached| {
+                cached.schema == config.postgres.schema && cached.fingerprint == fingerprint
+            });
+
+        if let Some(cached) = cached {
+            self.tables = Arc::new(cached.tables);
+            self.set_custom_types(cached.custom_types.into_iter().map(Arc::new).collect());
+
+            return Ok(());
+        }
+
+        self.load_table_schema(config).await?;
+        self.load_custom_types(config).await?;
+
+        let mut custom_types = self
+            .custom_types_by_oid
+            .values()
+            .map(|custom_type| custom_type.as_ref())
+            .collect::<Vec<_>>();
+        custom_types.sort_by_key(|custom_type| custom_type.oid());
+
+        let contents = serde_json::to_vec(&SchemaCacheFile {
+            schema: config.postgres.schema.clone(),
+            fingerprint,
+            tables: self.tables.as_ref().clone(),
+            custom_types,
+        })
+        .map_err(|e| PiquedError::OtherError(format!("Unable to serialize schema: {e}")))?;
+
+        // The file only saves time on the next run, so there's no reason to give up on the schema
+        // we just loaded when it can't be written
+        if let Err(e) = fs::write(&path, contents).await {
+            self.warnings.push(format!(
+                "Unable to write the schema cache to {}: {}",
+                path.display(),
+                e
+            ));
+        }
+
+        Ok(())
+    }
+
+    // DDL rewrites rows in the catalogs, which changes either how many there are or the newest
+    // transaction to have touched them
+    async fn schema_fingerprint(&self) -> Result<String> {
+        let row = self
+            .client
+            .query_one(
+                "
+                SELECT concat_ws(
+                    ':',
+                    current_database(),
+                    (SELECT count(*) || '/' || max(xmin::text::bigint) FROM pg_class),
+                    (SELECT count(*) || '/' || max(xmin::text::bigint) FROM pg_attribute),
+                    (SELECT count(*) || '/' || max(xmin::text::bigint) FROM pg_type),
+                    (SELECT count(*) || '/' || max(xmin::text::bigint) FROM pg_enum),
+                    (SELECT count(*) || '/' || max(xmin::text::bigint) FROM pg_constraint)
+                )
+            ",
+                &[],
+            )
+            .await?;
+
+        Ok(row.get(0))
+    }
+
+    async fn connect(config: Arc<Config>) -> Result<Query> {
         let (client, connection) = connect(&config.postgres.uri, NoTls).await?;
 
         spawn(async move {
@@ -71,21 +470,34 @@ impl Query {
             }
         });
 
-        let mut query = Query {
-            client,
-            tables: HashMap::new(),
-            custom_types_by_oid: HashMap::new(),
-            custom_types_by_name: HashMap::new(),
-            config: config.clone(),
-        };
-
-        query.load_table_schema(&config).await?;
-        query.load_custom_types(&config).await?;
+        // Unqualified names should resolve against the schema we introspect before anything the
+        // role happens to default to. The rest of the path stays, since that's usually where
+        // extensions' types and functions live.
+        client
+            .query_one(
+                "SELECT set_config('search_path', quote_ident($1) || ', ' || current_setting('search_path'), false)",
+                &[&config.postgres.schema],
+            )
+            .await?;
 
-        Ok(query)
+        Ok(Query {
+            client,
+            tables: Arc::new(HashMap::new()),
+            catalog_tables: Arc::new(HashMap::new()),
+            public_tables: Arc::new(HashMap::new()),
+            custom_types_by_oid: Arc::new(HashMap::new()),
+            custom_types_by_name: Arc::new(HashMap::new()),
+            config,
+            warnings: vec![],
+        })
     }
 
-    pub async fn probe_type(&self, stmt: &ParsedPreparedQuery) -> Result<ProbeResponse> {
+    fn deparse_statement(&self, stmt: &ParsedPreparedQuery) -> String {
+        // Statements that `pg_query` couldn't parse are already as written
+        if stmt.query.stmt.is_none() {
+            return stmt.contents.clone();
+        }
+
         let as_prepared_statement: ParseResult = ParseResult {
             stmts: vec![RawStmt {
                 stmt: stmt.query.stmt.clone(),
@@ -96,69 +508,635 @@ impl Query {
             version: 160001,
         };
 
-        let prepared_statement = as_prepared_statement.deparse().unwrap();
-        let argtypes: Vec<Type> = stmt
-            .variables
-            .iter()
-            .filter_map(|node| self.parse_arg(node.clone()))
-            .collect();
+        as_prepared_statement.deparse().unwrap()
+    }
 
-        let results = self
-            .client
-            .prepare_typed(&prepared_statement, argtypes.as_slice())
-            .await?;
+    pub async fn probe_type(&self, stmt: &ParsedPreparedQuery) -> Result<ProbeResponse> {
+        let prepared_statement = self.deparse_statement(stmt);
+        let mut argtypes = self.infer_arg_types(stmt);
+        for (i, annotated) in self
+            .annotated_arg_types(stmt)
+            .await?
+            .into_iter()
+            .enumerate()
+        {
+            let Some(annotated) = annotated else {
+                continue;
+            };
+
+            if argtypes.len() <= i {
+                argtypes.resize(i + 1, Type::UNKNOWN);
+            }
+            argtypes[i] = annotated;
+        }
+        self.check_custom_arg_types(&argtypes).await?;
+
+        // The role only lasts as long as the transaction, so it can't leak into other probes
+        let results = match &stmt.details.role {
+            Some(role) => {
+                self.client.batch_execute("BEGIN").await?;
+
+                let set_role = format!("SET LOCAL ROLE \"{}\"", role.replace('"', "\"\""));
+                let results = match self.client.batch_execute(&set_role).await {
+                    Ok(_) => {
+                        self.client
+                            .prepare_typed(&prepared_statement, argtypes.as_slice())
+                            .await
+                    }
+                    Err(e) => Err(e),
+                };
+
+                self.client.batch_execute("ROLLBACK").await?;
+                results?
+            }
+            None => {
+                self.client
+                    .prepare_typed(&prepared_statement, argtypes.as_slice())
+                    .await?
+            }
+        };
 
         let args = results
             .params()
             .into_iter()
-            .map(|typ| type_to_string(typ).to_string())
-            .collect::<Vec<String>>();
+            .map(ColumnType::from_type)
+            .collect::<Vec<ColumnType>>();
         let column_types = results
             .columns()
             .into_iter()
-            .map(|col| type_to_string(col.type_()).to_string())
-            .collect::<Vec<String>>();
+            .map(|col| ColumnType::from_type(col.type_()))
+            .collect::<Vec<ColumnType>>();
         let column_names = results
             .columns()
             .into_iter()
             .map(|col| col.name().to_string())
             .collect::<Vec<String>>();
+        let column_nullable = self.infer_nullable_columns(stmt, column_names.len());
 
         return Ok(ProbeResponse {
             args,
             column_types,
             column_names,
+            column_nullable,
         });
     }
 
+    // The types given by the statement's `@params` annotations, by position. Postgres resolves the
+    // names, so any spelling it accepts (`integer`, `int4`, `varchar(10)`, ...) will do.
+    async fn annotated_arg_types(&self, stmt: &ParsedPreparedQuery) -> Result<Vec<Option<Type>>> {
+        let mut types = vec![];
+        for annotation in &stmt.details.param_types {
+            let Some(name) = annotation else {
+                types.push(None);
+                continue;
+            };
+
+            let row = self
+                .client
+                .query_one("SELECT to_regtype($1)::oid", &[name])
+                .await?;
+            let Some(oid) = row.get::<_, Option<u32>>(0) else {
+                return Err(PiquedError::OtherError(format!(
+                    "Unknown type \"{}\" in @params",
+                    name
+                )));
+            };
+
+            // Only the OID is sent along with the statement
+            let type_ = Type::from_oid(oid)
+                .unwrap_or_else(|| Type::new(name.clone(), oid, Kind::Simple, String::new()));
+            types.push(Some(type_));
+        }
+
+        Ok(types)
+    }
+
+    /// The parameters whose `@params` annotation disagrees with the type Postgres infers for them
+    /// by itself, along with that type. Parameters it can't infer a type for are left out.
+    pub async fn param_type_mismatches(
+        &self,
+        stmt: &ParsedPreparedQuery,
+    ) -> Result<Vec<(usize, ColumnType)>> {
+        let annotated = self.annotated_arg_types(stmt).await?;
+        if annotated.iter().all(Option::is_none) {
+            return Ok(vec![]);
+        }
+
+        let prepared_statement = self.deparse_statement(stmt);
+        let argtypes = self.infer_arg_types(stmt);
+        let results = self
+            .client
+            .prepare_typed(&prepared_statement, argtypes.as_slice())
+            .await?;
+
+        Ok(annotated
+            .iter()
+            .zip(results.params())
+            .enumerate()
+            .filter_map(|(i, (annotated, inferred))| {
+                let annotated = annotated.as_ref()?;
+                let mismatched = inferred != &Type::UNKNOWN && annotated.oid() != inferred.oid();
+
+                mismatched.then(|| (i, ColumnType::from_type(inferred)))
+            })
+            .collect())
+    }
+
+    // Custom types are passed by the OID we loaded them with. If the type has since been dropped,
+    // Postgres only fails with an unhelpful "cache lookup failed", so check for it up front.
+    async fn check_custom_arg_types(&self, argtypes: &Vec<Type>) -> Result<()> {
+        let custom_types = argtypes
+            .iter()
+            .map(|type_| match type_.kind() {
+                Kind::Array(element) => element,
+                _ => type_,
+            })
+            .filter_map(|type_| self.custom_types_by_oid.get(&type_.oid()))
+            .collect::<Vec<_>>();
+
+        if custom_types.is_empty() {
+            return Ok(());
+        }
+
+        let oids = custom_types
+            .iter()
+            .map(|custom_type| custom_type.oid())
+            .collect::<Vec<u32>>();
+        let existing = self
+            .client
+            .query("SELECT oid FROM pg_type WHERE oid = ANY($1)", &[&oids])
+            .await?
+            .into_iter()
+            .map(|row| row.get::<_, u32>(0))
+            .collect::<Vec<u32>>();
+
+        match custom_types
+            .iter()
+            .find(|custom_type| !existing.contains(&custom_type.oid()))
+        {
+            Some(missing) => Err(PiquedError::OtherError(format!(
+                "Type \"{}\" no longer exists. If it was dropped or recreated, reload the schema.",
+                missing.name()
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    fn infer_arg_types(&self, stmt: &ParsedPreparedQuery) -> Vec<Type> {
+        if !stmt.variables.is_empty() {
+            return stmt
+                .variables
+                .iter()
+                .filter_map(|node| self.parse_arg(node.clone()))
+                .collect();
+        }
+
+        // Without explicit types, Postgres is left to infer each parameter from its context, which
+        // can land on `unknown`. Borrow the column type for parameters compared against a column.
+        let mut context = ParserContext::new(&stmt.contents);
+        let parsed = context.parse();
+        let relations = collect_loose_relations(&parsed.states);
+        let states = &flatten_statements(&parsed.states);
+
+        let mut inferred: HashMap<usize, Type> = HashMap::new();
+
+        for (i, window) in states.windows(3).enumerate() {
+            let next = states.get(i + 3).map(|state| &state.kind);
+            if !is_comparison(&window[1].kind) || next.map_or(false, continues_expression) {
+                continue;
+            }
+
+            let (column, param) = match (&window[0].kind, &window[2].kind) {
+                (column, LR1Kind::Token(Token::Placeholder(param))) => (column, param),
+                (LR1Kind::Token(Token::Placeholder(param)), column) => (column, param),
+                _ => continue,
+            };
+
+            let Some(index) = param
+                .strip_prefix('$')
+                .and_then(|index| index.parse::<usize>().ok())
+                .filter(|index| *index > 0)
+            else {
+                continue;
+            };
+
+            if let Some(column) = self.resolve_loose_column(column, &relations) {
+                inferred
+                    .entry(index - 1)
+                    .or_insert_with(|| self.column_type(column));
+            }
+        }
+
+        let count = inferred.keys().max().map_or(0, |max| max + 1);
+
+        // `unknown` leaves the parameter for Postgres to infer, just as if we hadn't passed a type
+        (0..count)
+            .map(|i| inferred.remove(&i).unwrap_or(Type::UNKNOWN))
+            .collect()
+    }
+
+    fn resolve_loose_column(
+        &self,
+        kind: &LR1Kind,
+        relations: &Vec<(String, String)>,
+    ) -> Option<&Column> {
+        let (relation_name, column_name) = match kind {
+            LR1Kind::Expression(expr) => match expr.as_ref() {
+                Expression::Identifier(column) => (None, column),
+                Expression::BinopExpression(BinopExpression {
+                    left,
+                    right,
+                    operator:
+                        Binop {
+                            token: Token::Period,
+                            ..
+                        },
+                }) => match (left.as_ref(), right.as_ref()) {
+                    (Expression::Identifier(relation), Expression::Identifier(column)) => {
+                        (Some(relation), column)
+                    }
+                    _ => return None,
+                },
+                _ => return None,
+            },
+            LR1Kind::Token(Token::Word(word)) if word.quote_style.is_none() => (None, &word.value),
+            _ => return None,
+        };
+
+        relations
+            .iter()
+            .filter(|(alias, table)| match relation_name {
+                Some(relation_name) => alias == relation_name || table == relation_name,
+                None => true,
+            })
+            .filter_map(|(_, table)| self.table(table))
+            .flat_map(|columns| columns.iter())
+            .find(|column| &column.name == column_name)
+    }
+
+    fn column_type(&self, column: &Column) -> Type {
+        Type::from_oid(column.type_oid).unwrap_or_else(|| {
+            Type::new(
+                column.column_type.to_string(),
+                column.type_oid,
+                Kind::Simple,
+                self.config.postgres.schema.clone(),
+            )
+        })
+    }
+
+    // Postgres doesn't report nullability for result columns, so we approximate it from the join
+    // tree. Anything we can't account for is assumed to be non-null.
+    fn infer_nullable_columns(&self, stmt: &ParsedPreparedQuery, column_count: usize) -> Vec<bool> {
+        let fallback = vec![false; column_count];
+
+        let select = match stmt.query.stmt.as_ref().and_then(|node| node.node.as_ref()) {
+            Some(NodeEnum::SelectStmt(select)) => select,
+            _ => return fallback,
+        };
+
+        let mut relations = vec![];
+        for from in &select.from_clause {
+            collect_from_relations(from, false, &mut relations);
+        }
+
+        let mut nullable = vec![];
+        for target in &select.target_list {
+            let res_target = match &target.node {
+                Some(NodeEnum::ResTarget(res_target)) => res_target,
+                _ => return fallback,
+            };
+
+            let Some(val) = res_target.val.as_ref() else {
+                nullable.push(false);
+                continue;
+            };
+
+            let column_ref = match val.node.as_ref() {
+                Some(NodeEnum::ColumnRef(column_ref)) => column_ref,
+                _ => {
+                    nullable.push(self.expression_nullable(val, &relations));
+                    continue;
+                }
+            };
+
+            match column_ref_fields(column_ref).as_slice() {
+                [None] => {
+                    for relation in &relations {
+                        let Some(columns) = self.relation_columns(relation) else {
+                            return fallback;
+                        };
+                        nullable.extend(columns.iter().map(|_| relation.nullable));
+                    }
+                }
+                [Some(relation_name), None] => {
+                    let relation = relations.iter().find(|rel| &rel.name == relation_name);
+                    let Some(relation) = relation else {
+                        return fallback;
+                    };
+                    let Some(columns) = self.relation_columns(relation) else {
+                        return fallback;
+                    };
+                    nullable.extend(columns.iter().map(|_| relation.nullable));
+                }
+                _ => nullable.push(self.expression_nullable(val, &relations)),
+            }
+        }
+
+        if nullable.len() != column_count {
+            return fallback;
+        }
+
+        nullable
+    }
+
+    // Whether a single (i.e. not `*`) result expression can be null. Besides columns, this knows
+    // about the built-ins that are commonly used to handle nulls.
+    fn expression_nullable(&self, node: &Node, relations: &Vec<FromRelation>) -> bool {
+        let all_nullable = |args: &Vec<Node>| {
+            !args.is_empty()
+                && args
+                    .iter()
+                    .all(|arg| self.expression_nullable(arg, relations))
+        };
+
+        match node.node.as_ref() {
+            Some(NodeEnum::ColumnRef(column_ref)) => match column_ref_fields(column_ref).as_slice()
+            {
+                [Some(column_name)] => {
+                    let owner = relations.iter().find(|relation| {
+                        self.relation_columns(relation)
+                            .map(|columns| columns.iter().any(|col| &col.name == column_name))
+                            .unwrap_or(false)
+                    });
+                    owner.map(|relation| relation.nullable).unwrap_or(false)
+                }
+                [.., Some(relation_name), Some(_)] => {
+                    let relation = relations.iter().find(|rel| &rel.name == relation_name);
+                    relation.map(|relation| relation.nullable).unwrap_or(false)
+                }
+                _ => false,
+            },
+            Some(NodeEnum::AConst(constant)) => constant.isnull,
+            Some(NodeEnum::TypeCast(cast)) => cast
+                .arg
+                .as_ref()
+                .map_or(false, |arg| self.expression_nullable(arg, relations)),
+            // `GREATEST` and `LEAST` skip over nulls just like `COALESCE`, so they're only null
+            // when every argument is
+            Some(NodeEnum::CoalesceExpr(coalesce)) => all_nullable(&coalesce.args),
+            Some(NodeEnum::MinMaxExpr(min_max)) => all_nullable(&min_max.args),
+            // Null whenever the arguments are equal
+            Some(NodeEnum::AExpr(expr)) if expr.kind() == AExprKind::AexprNullif => true,
+            _ => false,
+        }
+    }
+
+    fn relation_columns(&self, relation: &FromRelation) -> Option<&Vec<Column>> {
+        self.table(relation.table.as_ref()?)
+    }
+
+    pub fn dump_schema(&self) -> SchemaDump<'_> {
+        let mut custom_types = self.custom_types_by_name.values().collect::<Vec<_>>();
+        custom_types.sort_by(|a, b| a.name().cmp(b.name()));
+
+        SchemaDump {
+            schema: &self.config.postgres.schema,
+            tables: self
+                .tables
+                .iter()
+                .map(|(name, columns)| (name.as_str(), columns))
+                .collect(),
+            composite_types: custom_types
+                .iter()
+                .copied()
+                .filter_map(|custom_type| match custom_type.as_ref() {
+                    CustomType::Composite(composite) => Some(composite),
+                    CustomType::Enum(_) => None,
+                })
+                .collect(),
+            enums: custom_types
+                .iter()
+                .copied()
+                .filter_map(|custom_type| match custom_type.as_ref() {
+                    CustomType::Enum(enum_type) => Some(enum_type),
+                    CustomType::Composite(_) => None,
+                })
+                .collect(),
+        }
+    }
+
+    // Looks a table up the way postgres resolves its name. An unqualified name finds `pg_catalog`
+    // first, while a qualified one can be in the configured schema, `public` or `pg_catalog`.
+    pub fn table(&self, name: &str) -> Option<&Vec<Column>> {
+        match name.split_once('.') {
+            None => self
+                .catalog_tables
+                .get(name)
+                .or_else(|| self.tables.get(name)),
+            Some((schema, table)) if schema == self.config.postgres.schema => {
+                self.tables.get(table)
+            }
+            Some(("public", table)) => self.public_tables.get(table),
+            Some(("pg_catalog", table)) => self.catalog_tables.get(table),
+            Some(_) => None,
+        }
+    }
+
+    // The name postgres knows a type by, e.g. `integer` for `int4`, or `None` if there's no such
+    // type
+    pub async fn resolve_type_name(&self, name: &str) -> Result<Option<String>> {
+        let row = self
+            .client
+            .query_one("SELECT to_regtype($1)::text", &[&name])
+            .await?;
+
+        Ok(row.get(0))
+    }
+
+    pub async fn explain(&self, stmt: &ParsedPreparedQuery, analyze: bool) -> Result<String> {
+        let probed = self.probe_type(stmt).await?;
+        let prepared_statement = self.deparse_statement(stmt);
+
+        // We don't have real values for the parameters, so we plan (or run) the statement with
+        // every parameter bound to null
+        let arg_list = if probed.args.is_empty() {
+            "".to_string()
+        } else {
+            format!(
+                "({})",
+                probed
+                    .args
+                    .iter()
+                    .map(|arg| arg.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )
+        };
+        let null_list = if probed.args.is_empty() {
+            "".to_string()
+        } else {
+            format!("({})", vec!["NULL"; probed.args.len()].join(", "))
+        };
+        let options = if analyze {
+            "FORMAT JSON, ANALYZE"
+        } else {
+            "FORMAT JSON"
+        };
+
+        self.client
+            .batch_execute(&format!(
+                "PREPARE piqued_explain{} AS {}",
+                arg_list, prepared_statement
+            ))
+            .await?;
+
+        // Analyzing runs the statement for real, so whatever it changes is rolled back afterwards
+        if analyze {
+            self.client.batch_execute("BEGIN").await?;
+        }
+
+        // The plan comes back as `json`, so use the simple query protocol to get it as text
+        let plan = self
+            .client
+            .simple_query(&format!(
+                "EXPLAIN ({}) EXECUTE piqued_explain{}",
+                options, null_list
+            ))
+            .await;
+
+        if analyze {
+            self.client.batch_execute("ROLLBACK").await?;
+        }
+
+        self.client
+            .batch_execute("DEALLOCATE piqued_explain")
+            .await?;
+
+        let plan = plan?
+            .into_iter()
+            .filter_map(|message| match message {
+                SimpleQueryMessage::Row(row) => row.get(0).map(|line| line.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        Ok(plan)
+    }
+
+    // The indexes the statement's generic plan scans. Unlike `explain`, this doesn't depend on the
+    // parameters, which would otherwise all be null and plan to nothing at all
+    pub async fn plan_indexes(&self, stmt: &ParsedPreparedQuery) -> Result<Vec<String>> {
+        self.client
+            .batch_execute("SET plan_cache_mode = force_generic_plan")
+            .await?;
+        let plan = self.explain(stmt, false).await;
+        self.client.batch_execute("RESET plan_cache_mode").await?;
+
+        let plan: serde_json::Value = serde_json::from_str(&plan?)
+            .map_err(|e| PiquedError::OtherError(format!("Unable to read the plan: {}", e)))?;
+
+        let mut indexes = vec![];
+        collect_index_names(&plan, &mut indexes);
+
+        Ok(indexes)
+    }
+
     async fn load_table_schema(&mut self, config: &Config) -> Result<()> {
+        self.tables = Arc::new(self.fetch_tables(&config.postgres.schema).await?);
+        Ok(())
+    }
+
+    async fn fetch_tables(&self, schema: &str) -> Result<HashMap<String, Vec<Column>>> {
         let columns = self
             .client
             .query(
                 "
                 SELECT
-                    table_name,
-                    column_name,
-                    data_type,
-                    coalesce(pg_type.oid, -1) as type_oid,
-                    is_nullable,
-                    ordinal_position
+                    columns.table_name,
+                    columns.column_name,
+                    pg_type.typname as type_name,
+                    pg_type.oid as type_oid,
+                    columns.is_nullable,
+                    columns.is_generated = 'ALWAYS' as generated,
+                    element_type.typname as element_type_name
                 FROM information_schema.columns
-                    LEFT JOIN pg_type ON pg_type.typname = data_type
+                INNER JOIN pg_type
+                    ON pg_type.oid = (quote_ident(udt_schema) || '.' || quote_ident(udt_name))::regtype::oid
+                LEFT JOIN pg_type element_type
+                    ON pg_type.typcategory = 'A' AND element_type.oid = pg_type.typelem
+                WHERE columns.table_schema = $1
+                ORDER BY columns.table_name, columns.ordinal_position
+            ",
+                &[&schema],
+            )
+            .await?;
+
+        let foreign_keys = self
+            .client
+            .query(
+                "
+                SELECT
+                    class.relname as table_name,
+                    attribute.attname as column_name,
+                    ref_class.relname as ref_table_name,
+                    ref_attribute.attname as ref_column_name
+                FROM pg_constraint
+                INNER JOIN pg_class class
+                    ON class.oid = pg_constraint.conrelid
+                INNER JOIN pg_namespace
+                    ON pg_namespace.oid = class.relnamespace
+                INNER JOIN pg_attribute attribute
+                    ON attribute.attrelid = pg_constraint.conrelid
+                    AND attribute.attnum = pg_constraint.conkey[1]
+                INNER JOIN pg_class ref_class
+                    ON ref_class.oid = pg_constraint.confrelid
+                INNER JOIN pg_attribute ref_attribute
+                    ON ref_attribute.attrelid = pg_constraint.confrelid
+                    AND ref_attribute.attnum = pg_constraint.confkey[1]
+                WHERE pg_constraint.contype = 'f'
+                    AND cardinality(pg_constraint.conkey) = 1
+                    AND pg_namespace.nspname = $1
+            ",
+                &[&schema],
+            )
+            .await?;
+
+        let mut foreign_keys: HashMap<(String, String), ForeignKey> = foreign_keys
+            .into_iter()
+            .map(|row| {
+                let foreign_key = ForeignKey {
+                    table: row.get(2),
+                    column: row.get(3),
+                };
+
+                ((row.get(0), row.get(1)), foreign_key)
+            })
+            .collect();
+
+        // Tables without any columns don't show up in `information_schema.columns` at all
+        let table_names = self
+            .client
+            .query(
+                "
+                SELECT table_name
+                FROM information_schema.tables
                 WHERE table_schema = $1
-                ORDER BY table_name, ordinal_position
             ",
-                &[&config.postgres.schema.as_str()],
+                &[&schema],
             )
             .await?;
 
-        let tables: HashMap<String, Vec<Column>> =
+        let mut tables: HashMap<String, Vec<Column>> =
             columns.into_iter().fold(HashMap::new(), |mut acc, row| {
-                let table_name = row.get(0);
-                let column_name = row.get(1);
+                let table_name: String = row.get(0);
+                let column_name: String = row.get(1);
                 let type_name = row.get(2);
                 let type_oid = row.get(3);
                 let is_nullable_str = row.get(4);
+                let generated = row.get(5);
+                let element_type_name = row.get(6);
 
                 let nullable = match is_nullable_str {
                     "YES" => true,
@@ -166,11 +1144,15 @@ impl Query {
                     _ => false,
                 };
 
+                let references = foreign_keys.remove(&(table_name.clone(), column_name.clone()));
+
                 let column = Column {
                     name: column_name,
-                    type_name,
+                    column_type: ColumnType::from_catalog(type_name, element_type_name),
                     type_oid,
                     nullable,
+                    generated,
+                    references,
                 };
 
                 acc.entry(table_name).or_insert_with(Vec::new).push(column);
@@ -178,8 +1160,11 @@ impl Query {
                 acc
             });
 
-        self.tables = tables;
-        Ok(())
+        for row in table_names {
+            tables.entry(row.get(0)).or_insert_with(Vec::new);
+        }
+
+        Ok(tables)
     }
 
     async fn load_custom_types(&mut self, config: &Config) -> Result<()> {
@@ -191,7 +1176,9 @@ impl Query {
                     pg_attribute.attname as col_name,
                     pg_attribute.atttypid as col_type_oid,
                     col_type.typname as col_type_name,
-                    not pg_attribute.attnotnull as col_nullable
+                    not pg_attribute.attnotnull as col_nullable,
+                    pg_attribute.attgenerated <> '' as col_generated,
+                    col_element_type.typname as col_element_type_name
                 FROM pg_type
                 INNER JOIN pg_namespace
                     ON pg_type.typnamespace = pg_namespace.oid
@@ -199,6 +1186,8 @@ impl Query {
                     ON pg_type.typrelid = pg_attribute.attrelid
                 INNER JOIN pg_type col_type
                     ON pg_attribute.atttypid = col_type.oid
+                LEFT JOIN pg_type col_element_type
+                    ON col_type.typcategory = 'A' AND col_element_type.oid = col_type.typelem
                 WHERE pg_namespace.nspname = $1
                     AND pg_type.typcategory = 'C'
                     AND pg_attribute.attnum > 0
@@ -219,12 +1208,16 @@ impl Query {
                 let col_type_oid = row.get(3);
                 let col_type_name = row.get(4);
                 let col_nullable = row.get(5);
+                let col_generated = row.get(6);
+                let col_element_type_name = row.get(7);
 
                 let column = Column {
                     name: col_name,
-                    type_name: col_type_name,
+                    column_type: ColumnType::from_catalog(col_type_name, col_element_type_name),
                     type_oid: col_type_oid,
                     nullable: col_nullable,
+                    generated: col_generated,
+                    references: None,
                 };
 
                 let composite_type = acc.entry(type_oid).or_insert_with(|| CompositeType {
@@ -241,14 +1234,6 @@ impl Query {
             .map(|(oid, composite_type)| (oid, Arc::new(CustomType::Composite(composite_type))))
             .collect::<HashMap<_, _>>();
 
-        let composite_types_by_name = composite_types_by_oid
-            .iter()
-            .map(|(_, composite_type)| match composite_type.as_ref() {
-                CustomType::Composite(t) => (t.name.clone(), Arc::clone(composite_type)),
-                _ => panic!("Expected composite type"),
-            })
-            .collect::<HashMap<_, _>>();
-
         let enum_types_query = self
             .client
             .query(
@@ -293,28 +1278,31 @@ impl Query {
             .map(|(oid, composite_type)| (oid, Arc::new(CustomType::Enum(composite_type))))
             .collect::<HashMap<_, _>>();
 
-        let enum_types_by_name = enum_types_by_oid
-            .iter()
-            .map(|(_, composite_type)| match composite_type.as_ref() {
-                CustomType::Enum(t) => (t.name.clone(), Arc::clone(composite_type)),
-                _ => panic!("Expected composite type"),
-            })
-            .collect::<HashMap<_, _>>();
-
-        let mut custom_types_by_oid = HashMap::new();
-        custom_types_by_oid.extend(composite_types_by_oid);
-        custom_types_by_oid.extend(enum_types_by_oid);
-
-        let mut custom_types_by_name = HashMap::new();
-        custom_types_by_name.extend(composite_types_by_name);
-        custom_types_by_name.extend(enum_types_by_name);
-
-        self.custom_types_by_oid = custom_types_by_oid;
-        self.custom_types_by_name = custom_types_by_name;
+        self.set_custom_types(
+            composite_types_by_oid
+                .into_values()
+                .chain(enum_types_by_oid.into_values())
+                .collect(),
+        );
 
         Ok(())
     }
 
+    fn set_custom_types(&mut self, custom_types: Vec<Arc<CustomType>>) {
+        self.custom_types_by_name = Arc::new(
+            custom_types
+                .iter()
+                .map(|custom_type| (custom_type.name().to_string(), custom_type.clone()))
+                .collect(),
+        );
+        self.custom_types_by_oid = Arc::new(
+            custom_types
+                .into_iter()
+                .map(|custom_type| (custom_type.oid(), custom_type))
+                .collect(),
+        );
+    }
+
     pub fn parse_arg(&self, node: Node) -> Option<Type> {
         let typ = node.node?;
 
@@ -322,59 +1310,328 @@ impl Query {
             NodeEnum::TypeName(tn) => {
                 let last_name = tn.names.last()?;
                 let name = node_to_string(last_name.clone())?;
+                let element = self.parse_arg_element(&name, tn.type_oid)?;
 
-                if let Some(custom_type) = self.custom_types_by_name.get(&name) {
-                    let type_ = match custom_type.as_ref() {
-                        CustomType::Composite(t) => Some(Type::new(
-                            t.name.clone(),
-                            t.oid,
-                            Kind::Simple,
-                            "public".to_string(),
-                        )),
-                        CustomType::Enum(t) => Some(Type::new(
-                            t.name.clone(),
-                            t.oid,
-                            Kind::Simple,
-                            "public".to_string(),
-                        )),
-                    };
+                if tn.array_bounds.is_empty() {
+                    return Some(element);
+                }
 
-                    return type_;
-                };
+                // We only know the array OIDs of the builtin types, so otherwise leave it to postgres
+                // to infer the parameter's type
+                let array_type = builtin_array_type(&element).unwrap_or_else(|| {
+                    Type::new(
+                        format!("_{}", element.name()),
+                        0,
+                        Kind::Array(element.clone()),
+                        element.schema().to_string(),
+                    )
+                });
 
-                match name.as_str() {
-                    "int4" => Some(Type::INT4),
-                    "int8" => Some(Type::INT8),
-                    "text" => Some(Type::TEXT),
-                    "bool" => Some(Type::BOOL),
-                    "float4" => Some(Type::FLOAT4),
-                    "float8" => Some(Type::FLOAT8),
-                    "numeric" => Some(Type::NUMERIC),
-                    "date" => Some(Type::DATE),
-                    "time" => Some(Type::TIME),
-                    "timestamp" => Some(Type::TIMESTAMP),
-                    "timestamptz" => Some(Type::TIMESTAMPTZ),
-                    "interval" => Some(Type::INTERVAL),
-                    "uuid" => Some(Type::UUID),
-                    "json" => Some(Type::JSON),
-                    "jsonb" => Some(Type::JSONB),
-                    "bytea" => Some(Type::BYTEA),
-                    "varchar" => Some(Type::VARCHAR),
-                    "char" => Some(Type::CHAR),
-
-                    n => Some(Type::new(
-                        n.to_string(),
-                        tn.type_oid,
-                        Kind::Simple,
-                        "pg_catalog".to_string(),
-                    )),
-                }
+                Some(array_type)
             }
             _ => None,
         }
     }
+
+    fn parse_arg_element(&self, name: &String, type_oid: u32) -> Option<Type> {
+        if let Some(custom_type) = self.custom_types_by_name.get(name) {
+            let type_ = match custom_type.as_ref() {
+                CustomType::Composite(t) => Some(Type::new(
+                    t.name.clone(),
+                    t.oid,
+                    Kind::Simple,
+                    "public".to_string(),
+                )),
+                CustomType::Enum(t) => Some(Type::new(
+                    t.name.clone(),
+                    t.oid,
+                    Kind::Simple,
+                    "public".to_string(),
+                )),
+            };
+
+            return type_;
+        };
+
+        match name.as_str() {
+            "int4" => Some(Type::INT4),
+            "int8" => Some(Type::INT8),
+            "text" => Some(Type::TEXT),
+            "bool" => Some(Type::BOOL),
+            "float4" => Some(Type::FLOAT4),
+            "float8" => Some(Type::FLOAT8),
+            "numeric" => Some(Type::NUMERIC),
+            "date" => Some(Type::DATE),
+            "time" => Some(Type::TIME),
+            "timestamp" => Some(Type::TIMESTAMP),
+            "timestamptz" => Some(Type::TIMESTAMPTZ),
+            "interval" => Some(Type::INTERVAL),
+            "uuid" => Some(Type::UUID),
+            "json" => Some(Type::JSON),
+            "jsonb" => Some(Type::JSONB),
+            "bytea" => Some(Type::BYTEA),
+            "varchar" => Some(Type::VARCHAR),
+            "char" => Some(Type::CHAR),
+
+            n => Some(Type::new(
+                n.to_string(),
+                type_oid,
+                Kind::Simple,
+                "pg_catalog".to_string(),
+            )),
+        }
+    }
+}
+
+fn builtin_array_type(element: &Type) -> Option<Type> {
+    [
+        (Type::INT4, Type::INT4_ARRAY),
+        (Type::INT8, Type::INT8_ARRAY),
+        (Type::TEXT, Type::TEXT_ARRAY),
+        (Type::BOOL, Type::BOOL_ARRAY),
+        (Type::FLOAT4, Type::FLOAT4_ARRAY),
+        (Type::FLOAT8, Type::FLOAT8_ARRAY),
+        (Type::NUMERIC, Type::NUMERIC_ARRAY),
+        (Type::DATE, Type::DATE_ARRAY),
+        (Type::TIME, Type::TIME_ARRAY),
+        (Type::TIMESTAMP, Type::TIMESTAMP_ARRAY),
+        (Type::TIMESTAMPTZ, Type::TIMESTAMPTZ_ARRAY),
+        (Type::INTERVAL, Type::INTERVAL_ARRAY),
+        (Type::UUID, Type::UUID_ARRAY),
+        (Type::JSON, Type::JSON_ARRAY),
+        (Type::JSONB, Type::JSONB_ARRAY),
+        (Type::BYTEA, Type::BYTEA_ARRAY),
+        (Type::VARCHAR, Type::VARCHAR_ARRAY),
+        (Type::CHAR, Type::CHAR_ARRAY),
+    ]
+    .into_iter()
+    .find(|(candidate, _)| candidate == element)
+    .map(|(_, array)| array)
+}
+
+// Every `Index Name` in an `EXPLAIN (FORMAT JSON)` plan, which covers index, index only and bitmap
+// index scans alike
+fn collect_index_names(plan: &serde_json::Value, indexes: &mut Vec<String>) {
+    match plan {
+        serde_json::Value::Object(node) => {
+            for (key, value) in node {
+                match (key.as_str(), value) {
+                    ("Index Name", serde_json::Value::String(name)) => indexes.push(name.clone()),
+                    _ => collect_index_names(value, indexes),
+                }
+            }
+        }
+        serde_json::Value::Array(nodes) => {
+            for node in nodes {
+                collect_index_names(node, indexes);
+            }
+        }
+        _ => (),
+    }
 }
 
 pub fn type_to_string<'a>(type_: &'a Type) -> &'a str {
     type_.name()
 }
+
+// The names in a column reference, where `None` is a `*`
+fn column_ref_fields(column_ref: &ColumnRef) -> Vec<Option<&str>> {
+    column_ref
+        .fields
+        .iter()
+        .map(|field| match &field.node {
+            Some(NodeEnum::String(str)) => Some(str.sval.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn collect_from_relations(node: &Node, nullable: bool, relations: &mut Vec<FromRelation>) {
+    match &node.node {
+        Some(NodeEnum::RangeVar(range_var)) => relations.push(FromRelation {
+            name: match &range_var.alias {
+                Some(alias) => alias.aliasname.clone(),
+                None => range_var.relname.clone(),
+            },
+            table: Some(range_var.relname.clone()),
+            nullable,
+        }),
+        Some(NodeEnum::RangeSubselect(subselect)) => relations.push(FromRelation {
+            name: subselect
+                .alias
+                .as_ref()
+                .map(|alias| alias.aliasname.clone())
+                .unwrap_or_default(),
+            table: None,
+            nullable,
+        }),
+        Some(NodeEnum::JoinExpr(join)) => {
+            let (left_nullable, right_nullable) = match join.jointype() {
+                JoinType::JoinLeft => (false, true),
+                JoinType::JoinRight => (true, false),
+                JoinType::JoinFull => (true, true),
+                _ => (false, false),
+            };
+
+            if let Some(larg) = &join.larg {
+                collect_from_relations(larg, nullable || left_nullable, relations);
+            }
+            if let Some(rarg) = &join.rarg {
+                collect_from_relations(rarg, nullable || right_nullable, relations);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Finds the tables a statement reads from or writes to, as `(alias, table)` pairs
+pub fn collect_loose_relations(states: &Vec<Arc<LR1State>>) -> Vec<(String, String)> {
+    let mut relations = vec![];
+
+    for (i, state) in states.iter().enumerate() {
+        match &state.kind {
+            LR1Kind::SelectQuery(select_query) => {
+                relations.extend(
+                    select_query
+                        .from
+                        .iter()
+                        .flatten()
+                        .flat_map(|from| from.relations())
+                        .filter_map(|from| from_expression_relation(&from)),
+                );
+            }
+            LR1Kind::CompoundQuery(compound_query) => {
+                relations.extend(
+                    compound_query
+                        .branches
+                        .iter()
+                        .flat_map(|select_query| select_query.from.iter().flatten())
+                        .flat_map(|from| from.relations())
+                        .filter_map(|from| from_expression_relation(&from)),
+                );
+            }
+            LR1Kind::InsertStmt(insert) => {
+                let alias = insert.alias.clone().unwrap_or_else(|| insert.table.clone());
+                relations.push((alias, insert.table.clone()));
+
+                // The rows may come from a query with tables of its own
+                let rows = state
+                    .children
+                    .iter()
+                    .filter(|child| {
+                        !matches!(child.kind, LR1Kind::InsertStmt(_) | LR1Kind::Token(_))
+                    })
+                    .cloned()
+                    .collect();
+                relations.extend(collect_loose_relations(&rows));
+            }
+            LR1Kind::FromStmt(from_stmt) | LR1Kind::FromExpressionList(from_stmt) => {
+                relations.extend(
+                    from_stmt
+                        .iter()
+                        .flat_map(|from| from.relations())
+                        .filter_map(|from| from_expression_relation(&from)),
+                );
+            }
+            LR1Kind::Token(Token::Word(Word {
+                keyword: Keyword::FROM | Keyword::JOIN | Keyword::UPDATE | Keyword::INTO,
+                ..
+            })) => {
+                let table = match states.get(i + 1).map(|state| &state.kind) {
+                    Some(LR1Kind::Token(Token::Word(word))) => identifier_name(word),
+                    Some(LR1Kind::Expression(expr)) => match expr.as_ref() {
+                        Expression::Identifier(table) => table.clone(),
+                        _ => continue,
+                    },
+                    // A lone table (optionally aliased) may have been reduced as a column list
+                    Some(LR1Kind::ExpressionList(list)) => match list.as_slice() {
+                        [column] => match column.as_ref() {
+                            ColumnExpression::Unnamed(expr) => match expr.as_ref() {
+                                Expression::Identifier(table) => {
+                                    relations.push((table.clone(), table.clone()));
+                                    continue;
+                                }
+                                _ => continue,
+                            },
+                            ColumnExpression::Named(alias, expr) => match expr.as_ref() {
+                                Expression::Identifier(table) => {
+                                    relations.push((alias.clone(), table.clone()));
+                                    continue;
+                                }
+                                _ => continue,
+                            },
+                        },
+                        _ => continue,
+                    },
+                    _ => continue,
+                };
+
+                let alias = match states.get(i + 2).map(|state| &state.kind) {
+                    Some(LR1Kind::Token(Token::Word(word)))
+                        if word.keyword == Keyword::NoKeyword =>
+                    {
+                        identifier_name(word)
+                    }
+                    Some(LR1Kind::Expression(expr)) => match expr.as_ref() {
+                        Expression::Identifier(alias) => alias.clone(),
+                        _ => table.clone(),
+                    },
+                    _ => table.clone(),
+                };
+
+                relations.push((alias, table));
+            }
+            _ => (),
+        }
+    }
+
+    relations
+}
+
+// Set operations and `INSERT`s take in the clauses around them, which are otherwise left at the
+// top level
+fn flatten_statements(states: &Vec<Arc<LR1State>>) -> Vec<Arc<LR1State>> {
+    states
+        .iter()
+        .flat_map(|state| match &state.kind {
+            LR1Kind::CompoundQuery(_) | LR1Kind::InsertStmt(_) => {
+                flatten_statements(&state.children)
+            }
+            _ => vec![state.clone()],
+        })
+        .collect()
+}
+
+// Derived tables don't have a table to look columns up in, so they're left out
+fn from_expression_relation(from: &FromExpression) -> Option<(String, String)> {
+    let TableLike::Table(table) = from.table.as_ref() else {
+        return None;
+    };
+    Some((from.scope_name()?.to_string(), table.clone()))
+}
+
+fn is_comparison(kind: &LR1Kind) -> bool {
+    let token = match kind {
+        LR1Kind::Operator(Operator::Binop(Binop { token, .. })) => token,
+        LR1Kind::Token(token) => token,
+        _ => return false,
+    };
+
+    matches!(
+        token,
+        Token::Eq | Token::Neq | Token::Lt | Token::Gt | Token::LtEq | Token::GtEq
+    )
+}
+
+// Whether the state following a parameter binds tighter than the comparison, making the parameter
+// only part of a larger expression
+fn continues_expression(kind: &LR1Kind) -> bool {
+    let precedence = match kind {
+        LR1Kind::Operator(Operator::Binop(Binop { precedence, .. }))
+        | LR1Kind::Operator(Operator::Unop(Unop { precedence, .. })) => Some(*precedence),
+        LR1Kind::Token(token) => Operator::precedence_from_token(token),
+        _ => None,
+    };
+
+    precedence.map_or(false, |precedence| precedence < 10)
+}
@@ -1,11 +1,19 @@
 use core::fmt;
+use std::{error, io, sync::Arc};
 
-#[derive(Debug, Eq, PartialEq, Clone)]
+use tokio_postgres::error::ErrorPosition;
+
+#[derive(Debug, Clone)]
 pub enum PiquedError {
     ParseErrorAt(String),
-    PostgresError(String),
+    // Along with the (1-based) character position in the query Postgres was sent, if it gave one
+    PostgresError(String, Option<u32>),
     OtherError(String),
     SerdeParseError(toml::de::Error),
+    // These two are kept whole so that they can be chained as the `source()`
+    IoError(Arc<io::Error>),
+    // Failing to reach or talk to Postgres, rather than Postgres rejecting a statement
+    ConnectionError(Arc<tokio_postgres::Error>),
 }
 
 impl fmt::Display for PiquedError {
@@ -14,6 +22,17 @@ impl fmt::Display for PiquedError {
     }
 }
 
+impl error::Error for PiquedError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            PiquedError::SerdeParseError(err) => Some(err),
+            PiquedError::IoError(err) => Some(err.as_ref()),
+            PiquedError::ConnectionError(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
 impl From<pg_query::Error> for PiquedError {
     fn from(err: pg_query::Error) -> Self {
         match err {
@@ -37,17 +56,24 @@ impl From<pg_query::Error> for PiquedError {
     }
 }
 
-impl From<std::io::Error> for PiquedError {
-    fn from(err: std::io::Error) -> Self {
-        PiquedError::OtherError(format!("{:#?}", err))
+impl From<io::Error> for PiquedError {
+    fn from(err: io::Error) -> Self {
+        PiquedError::IoError(Arc::new(err))
     }
 }
 
 impl From<tokio_postgres::Error> for PiquedError {
     fn from(err: tokio_postgres::Error) -> Self {
         match err.as_db_error() {
-            None => PiquedError::OtherError(format!("{:#?}", err)),
-            Some(db_err) => PiquedError::PostgresError(db_err.message().to_string()),
+            None => PiquedError::ConnectionError(Arc::new(err)),
+            Some(db_err) => {
+                let position = match db_err.position() {
+                    Some(ErrorPosition::Original(position)) => Some(*position),
+                    _ => None,
+                };
+
+                PiquedError::PostgresError(db_err.message().to_string(), position)
+            }
         }
     }
 }
@@ -1,18 +1,76 @@
 use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
-use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+use pg_query::protobuf::{ScanToken, Token};
+use serde_derive::Serialize;
+use sqlparser::tokenizer::Token as SqlToken;
+use tokio::fs;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Range};
 
 use crate::{
-    codegen::{codegen::CodeGenerationContext, ts::schema::TSGenerator},
-    config::config::Config,
-    parser::parser::{self, ParsedFile, RelocatedStmt},
-    query::query::Query,
+    codegen::{
+        codegen::{CodeGenerationContext, CodeGenerator},
+        python::schema::PythonGenerator,
+        rust::schema::RustGenerator,
+        ts::schema::TSGenerator,
+        utils::to_camel_case,
+    },
+    config::config::{Config, Language, Severity},
+    loose_parser::{
+        parse::{ParserContext, ParserResult},
+        parse_cf::{ColumnExpression, Expression, JoinKind, LR1Kind, LR1State, TableLike},
+    },
+    parser::parser::{self, ParsedFile, ParsedPreparedQuery, RelocatedStmt},
+    query::query::{Column, ColumnType, ProbeResponse, Query},
     utils::result::{PiquedError, Result},
 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenerationMode {
+    Full,
+    // Only the system types and table file, skipping every query file
+    SchemaOnly,
+}
+
+// What a run of the generators did, short of failing outright
+#[derive(Debug, Clone, Default)]
+pub struct GenerationReport {
+    // The files whose contents changed (or would have, when only checking)
+    pub changed_files: Vec<PathBuf>,
+    // Problems that didn't stop generation, such as duplicate names when they're allowed
+    pub warnings: Vec<String>,
+}
+
+// What we last learned about a file, so that an edit only has to reprobe the statements it touched
+#[derive(Debug, Default)]
+struct FileCache {
+    parsed: Option<Arc<ParsedFile>>,
+    // Keyed by the statement's byte range in the current contents
+    probes: HashMap<(u32, u32), Result<Arc<ProbeResponse>>>,
+    // The `@params` annotations Postgres disagrees with, keyed the same way
+    param_mismatches: HashMap<(u32, u32), Vec<(usize, ColumnType)>>,
+}
+
+#[derive(Debug)]
+pub struct TypecheckFailure {
+    pub path: PathBuf,
+    // 1-based, as editors and compilers print it
+    pub line: u32,
+    pub error: PiquedError,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuerySummary {
+    pub name: String,
+    pub path: PathBuf,
+    // 1-based, as editors and compilers print it
+    pub line: u32,
+    pub params: usize,
+}
+
 #[derive(Debug)]
 pub struct Workspace {
     files: HashMap<String, String>,
+    file_caches: HashMap<String, FileCache>,
     pub root_dir: PathBuf,
     pub config: Arc<Config>,
     pub query: Result<Query>,
@@ -20,12 +78,13 @@ pub struct Workspace {
 
 impl Workspace {
     pub async fn new(config: Arc<Config>, root_dir: PathBuf) -> Self {
-        let query = Query::new(config.clone()).await;
+        let query = Query::new_shared(config.clone()).await;
 
         Workspace {
             root_dir,
             config: config.clone(),
             files: HashMap::<String, String>::new(),
+            file_caches: HashMap::new(),
             query,
         }
     }
@@ -39,7 +98,28 @@ impl Workspace {
         self.files.get(path)
     }
 
+    // The URIs of every file the workspace has been given
+    pub fn file_uris(&self) -> Vec<String> {
+        self.files.keys().cloned().collect()
+    }
+
     pub fn patch_file(&mut self, path: String, contents: String) {
+        let previous = self.files.get(&path);
+        let cache = self.file_caches.entry(path.clone()).or_default();
+
+        cache.parsed = None;
+        match previous {
+            Some(previous) => {
+                cache.probes = shift_statements(&cache.probes, previous, &contents);
+                cache.param_mismatches =
+                    shift_statements(&cache.param_mismatches, previous, &contents);
+            }
+            None => {
+                cache.probes.clear();
+                cache.param_mismatches.clear();
+            }
+        }
+
         match self.files.entry(path) {
             std::collections::hash_map::Entry::Occupied(mut o) => {
                 o.insert(contents);
@@ -50,9 +130,33 @@ impl Workspace {
         };
     }
 
+    pub fn remove_file(&mut self, path: &str) {
+        self.files.remove(path);
+        self.file_caches.remove(path);
+    }
+
     pub async fn reload_config(&mut self, config: Arc<Config>) {
+        // The config may have changed because the schema did, so don't trust what's cached
+        Query::invalidate_shared_schema(&self.config);
+        Query::invalidate_shared_schema(&config);
+
         self.config = config.clone();
-        self.query = Query::new(config).await;
+        self.query = Query::new_shared(config).await;
+        self.file_caches.clear();
+    }
+
+    // Picks up changes to the database's schema, reprobing every statement against it
+    pub async fn refresh_schema(&mut self) -> Result<()> {
+        self.file_caches.clear();
+
+        match &mut self.query {
+            Ok(query) => query.refresh_schema().await,
+            Err(_) => {
+                Query::invalidate_shared_schema(&self.config);
+                self.query = Query::new_shared(self.config.clone()).await;
+                self.query.as_ref().map(|_| ()).map_err(|e| e.clone())
+            }
+        }
     }
 
     pub async fn diagnostics_for_statment(
@@ -60,7 +164,7 @@ impl Workspace {
         file_contents: &str,
         parsed: &ParsedFile,
         stmt: &RelocatedStmt,
-    ) -> Result<()> {
+    ) -> Result<ProbeResponse> {
         let query = match &self.query {
             Err(e) => return Err(e.clone()),
             Ok(q) => q,
@@ -71,51 +175,299 @@ impl Workspace {
                 "query".to_string()
             })?;
 
-        let _ = query.probe_type(&prepared_statement).await?;
+        query.probe_type(&prepared_statement).await
+    }
+
+    // Warns about each `@index` hint that the statement's plan doesn't live up to
+    async fn index_hint_diagnostics(
+        &self,
+        file_contents: &str,
+        parsed: &ParsedFile,
+    ) -> Vec<Diagnostic> {
+        let Ok(query) = &self.query else {
+            return vec![];
+        };
+
+        let mut diagnostics = vec![];
+        for stmt in &parsed.statements {
+            let Ok(prepared_statement) =
+                parser::get_prepared_statement(&stmt, &parsed.tokens, &file_contents, || {
+                    "query".to_string()
+                })
+            else {
+                continue;
+            };
+            let Some(index) = &prepared_statement.details.index else {
+                continue;
+            };
+
+            // A statement that can't be planned already has an error of its own
+            let Ok(indexes) = query.plan_indexes(&prepared_statement).await else {
+                continue;
+            };
 
-        Ok(())
+            if !indexes.contains(index) {
+                diagnostics.push(Diagnostic::new(
+                    stmt.range,
+                    Some(DiagnosticSeverity::WARNING),
+                    None,
+                    None,
+                    format!("The query plan doesn't use the expected index {}", index),
+                    None,
+                    None,
+                ));
+            }
+        }
+
+        diagnostics
     }
 
-    pub async fn get_diagnostics(&self, path: &str) -> Result<Vec<Diagnostic>> {
+    // The parameters whose `@params` annotation isn't the type Postgres would have picked
+    async fn param_type_mismatches(
+        &self,
+        file_contents: &str,
+        parsed: &ParsedFile,
+        stmt: &RelocatedStmt,
+    ) -> Vec<(usize, ColumnType)> {
+        let Ok(query) = &self.query else {
+            return vec![];
+        };
+        let Ok(prepared_statement) =
+            parser::get_prepared_statement(stmt, &parsed.tokens, file_contents, || {
+                "query".to_string()
+            })
+        else {
+            return vec![];
+        };
+
+        query
+            .param_type_mismatches(&prepared_statement)
+            .await
+            .unwrap_or_default()
+    }
+
+    // Warns about each `@params` annotation that isn't the type Postgres would have picked
+    fn param_type_diagnostics(
+        &self,
+        file_contents: &str,
+        parsed: &ParsedFile,
+        mismatches: &HashMap<(u32, u32), Vec<(usize, ColumnType)>>,
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        for stmt in &parsed.statements {
+            let key = (stmt.index_start, stmt.index_start + stmt.index_len);
+            let Some(mismatches) = mismatches.get(&key).filter(|m| !m.is_empty()) else {
+                continue;
+            };
+            let Ok(prepared_statement) =
+                parser::get_prepared_statement(stmt, &parsed.tokens, file_contents, || {
+                    "query".to_string()
+                })
+            else {
+                continue;
+            };
+
+            let details = &prepared_statement.details;
+            for (i, inferred) in mismatches {
+                let name = details
+                    .params
+                    .as_ref()
+                    .and_then(|params| params.get(*i))
+                    .cloned()
+                    .unwrap_or_else(|| format!("${}", i + 1));
+                let annotated = details.param_types[*i].as_deref().unwrap_or_default();
+
+                diagnostics.push(Diagnostic::new(
+                    stmt.range,
+                    Some(DiagnosticSeverity::WARNING),
+                    None,
+                    None,
+                    format!(
+                        "Parameter {} is annotated as {}, but Postgres infers {}",
+                        name, annotated, inferred
+                    ),
+                    None,
+                    None,
+                ));
+            }
+        }
+
+        diagnostics
+    }
+
+    pub async fn get_diagnostics(&mut self, path: &str) -> Result<Vec<Diagnostic>> {
         let file_contents = match self.files.get(path) {
             Some(data) => data,
             None => return Err(PiquedError::OtherError("File not found".to_string())),
         };
 
-        let parsed = parser::load_file(file_contents)?;
+        let mut cache = self.file_caches.remove(path).unwrap_or_default();
+        let parsed = match &cache.parsed {
+            Some(parsed) => parsed.clone(),
+            None => Arc::new(parser::load_file(file_contents)?),
+        };
 
-        let mut diagnostics: Vec<Diagnostic> = Vec::new();
-        for stmt in &parsed.statements {
-            match self
-                .diagnostics_for_statment(file_contents, &parsed, stmt)
-                .await
-            {
-                Ok(_) => {}
-                Err(err) => {
-                    let msg = match err {
-                        PiquedError::ParseErrorAt(e) => format!("Error parsing query at \"{e}\""),
-                        PiquedError::PostgresError(e) => e,
-                        PiquedError::OtherError(e) => format!("Error: {e}"),
-                        PiquedError::SerdeParseError(e) => format!("Error: {e}"),
+        let mut diagnostics = match &self.query {
+            Ok(_) => {
+                let mut probes = HashMap::new();
+                let mut param_mismatches = HashMap::new();
+                for stmt in &parsed.statements {
+                    let key = (stmt.index_start, stmt.index_start + stmt.index_len);
+                    let probe = match cache.probes.remove(&key) {
+                        Some(probe) => probe,
+                        None => self
+                            .diagnostics_for_statment(file_contents, &parsed, stmt)
+                            .await
+                            .map(Arc::new),
+                    };
+                    // A statement that can't be prepared already has an error of its own
+                    let mismatches = match cache.param_mismatches.remove(&key) {
+                        Some(mismatches) => mismatches,
+                        None if probe.is_ok() => {
+                            self.param_type_mismatches(file_contents, &parsed, stmt)
+                                .await
+                        }
+                        None => vec![],
                     };
 
-                    diagnostics.push(Diagnostic::new(
-                        stmt.range.clone(),
-                        Some(DiagnosticSeverity::ERROR),
-                        None,
-                        None,
-                        msg,
-                        None,
-                        None,
-                    ))
+                    probes.insert(key, probe);
+                    param_mismatches.insert(key, mismatches);
+                }
+
+                cache.parsed = Some(parsed.clone());
+                cache.probes = probes;
+                cache.param_mismatches = param_mismatches;
+                self.file_caches.insert(path.to_string(), cache);
+
+                let cache = &self.file_caches[path];
+
+                let mut diagnostics: Vec<Diagnostic> = Vec::new();
+                for stmt in &parsed.statements {
+                    let key = (stmt.index_start, stmt.index_start + stmt.index_len);
+                    match cache.probes[&key].clone() {
+                        Ok(_) => {}
+                        Err(err) => {
+                            let range = match &err {
+                                PiquedError::PostgresError(_, Some(position)) => {
+                                    error_range(file_contents, &parsed, stmt, *position)
+                                }
+                                _ => None,
+                            };
+
+                            diagnostics.push(Diagnostic::new(
+                                range.unwrap_or(stmt.range),
+                                Some(DiagnosticSeverity::ERROR),
+                                None,
+                                None,
+                                error_message(&err),
+                                None,
+                                None,
+                            ))
+                        }
+                    }
+                }
+
+                if self.config.lsp.check_index_hints {
+                    diagnostics.extend(self.index_hint_diagnostics(file_contents, &parsed).await);
                 }
+                diagnostics.extend(self.param_type_diagnostics(
+                    file_contents,
+                    &parsed,
+                    &cache.param_mismatches,
+                ));
+
+                diagnostics
             }
+            // Without a database nothing can be type checked, but the statements can still be parsed
+            Err(err) => {
+                let mut diagnostics = vec![Diagnostic::new(
+                    Range::default(),
+                    Some(DiagnosticSeverity::INFORMATION),
+                    None,
+                    None,
+                    format!(
+                        "Type checking is disabled because the database is unreachable. {}",
+                        error_message(err)
+                    ),
+                    None,
+                    None,
+                )];
+                diagnostics.extend(parse_error_diagnostics(file_contents, &parsed));
+
+                cache.parsed = Some(parsed.clone());
+                self.file_caches.insert(path.to_string(), cache);
+
+                diagnostics
+            }
+        };
+
+        let mut context = ParserContext::new(file_contents);
+        let loose_parsed = context.parse();
+
+        diagnostics.extend(duplicate_name_diagnostics(
+            &self.config,
+            file_contents,
+            &parsed,
+        ));
+        diagnostics.extend(cartesian_join_diagnostics(&loose_parsed));
+        if let Ok(query) = &self.query {
+            diagnostics.extend(unknown_column_diagnostics(query, &parsed, &loose_parsed));
         }
+        if self.config.lsp.warn_select_star {
+            diagnostics.extend(select_star_diagnostics(&loose_parsed));
+        }
+
+        let min_severity = self.config.lsp.min_severity;
+        diagnostics.retain(|diagnostic| severity_of(diagnostic) <= min_severity);
 
         Ok(diagnostics)
     }
 
-    pub async fn gen_code(&self) -> Result<()> {
+    pub async fn gen_code(&self, mode: GenerationMode) -> Result<GenerationReport> {
+        self.run_generators(mode, false).await
+    }
+
+    // Reports the generated files that are out of date as changed, without writing any of them
+    pub async fn check_code(&self, mode: GenerationMode) -> Result<GenerationReport> {
+        self.run_generators(mode, true).await
+    }
+
+    async fn run_generators(
+        &self,
+        mode: GenerationMode,
+        check_only: bool,
+    ) -> Result<GenerationReport> {
+        let query = match &self.query {
+            Err(e) => return Err(e.clone()),
+            Ok(q) => q,
+        };
+
+        let mut codegen = CodeGenerationContext::new(self.config.clone(), query);
+        if check_only {
+            codegen = codegen.check_only();
+        }
+
+        let generator: Box<dyn CodeGenerator> = match self.config.emit.language {
+            Language::Typescript => Box::new(TSGenerator::new()),
+            Language::Rust => Box::new(RustGenerator::new()),
+            Language::Python => Box::new(PythonGenerator::new()),
+        };
+
+        codegen.generate_system_types(generator.as_ref()).await?;
+        codegen.generate_table_file(generator.as_ref()).await?;
+
+        if mode == GenerationMode::Full {
+            codegen.generate_queries(generator.as_ref()).await?;
+        }
+
+        Ok(GenerationReport {
+            changed_files: codegen.changed_files(),
+            warnings: codegen.warnings(),
+        })
+    }
+
+    // Probes every statement in the workspace without generating any code
+    pub async fn typecheck(&self) -> Result<Vec<TypecheckFailure>> {
         let query = match &self.query {
             Err(e) => return Err(e.clone()),
             Ok(q) => q,
@@ -123,19 +475,624 @@ impl Workspace {
 
         let codegen = CodeGenerationContext::new(self.config.clone(), query);
 
-        let ts_generator = TSGenerator::new();
+        let mut failures = vec![];
+        for query_file in codegen.locate_query_files().await {
+            let contents = fs::read_to_string(&query_file).await?;
+            let parsed = match parser::load_file(&contents) {
+                Ok(parsed) => parsed,
+                Err(error) => {
+                    failures.push(TypecheckFailure {
+                        path: query_file,
+                        line: 1,
+                        error,
+                    });
+                    continue;
+                }
+            };
+
+            for stmt in &parsed.statements {
+                let Err(error) = self
+                    .diagnostics_for_statment(&contents, &parsed, stmt)
+                    .await
+                else {
+                    continue;
+                };
 
-        codegen.generate_system_types(&ts_generator).await;
-        codegen.generate_table_file(&ts_generator).await;
-        codegen.generate_queries(&ts_generator).await;
+                let range = match &error {
+                    PiquedError::PostgresError(_, Some(position)) => {
+                        error_range(&contents, &parsed, stmt, *position)
+                    }
+                    _ => None,
+                };
 
-        Ok(())
+                failures.push(TypecheckFailure {
+                    path: query_file.clone(),
+                    line: range.unwrap_or(stmt.range).start.line + 1,
+                    error,
+                });
+            }
+        }
+
+        Ok(failures)
     }
 
-    pub async fn is_compile_target(&self, path: &PathBuf) -> bool {
-        let ext = path.extension().unwrap();
+    // Every query in the workspace, in the order they appear. Doesn't need a database connection.
+    pub async fn list_queries(&self) -> Result<Vec<QuerySummary>> {
+        let codegen =
+            CodeGenerationContext::from_schema(self.config.clone(), HashMap::new(), HashMap::new());
+
+        let mut query_files = codegen.locate_query_files().await;
+        query_files.sort();
+
+        let mut summaries = vec![];
+        for query_file in query_files {
+            let contents = fs::read_to_string(&query_file).await?;
+            let parsed = parser::load_file(&contents)?;
+
+            for (i, stmt) in parsed.statements.iter().enumerate() {
+                let Ok(prepared_statement) =
+                    parser::get_prepared_statement(&stmt, &parsed.tokens, &contents, || {
+                        format!("query_{i}")
+                    })
+                else {
+                    continue;
+                };
+
+                summaries.push(QuerySummary {
+                    name: prepared_statement.details.name.clone(),
+                    path: query_file.clone(),
+                    line: stmt.range.start.line + 1,
+                    params: prepared_statement.param_count(),
+                });
+            }
+        }
+
+        Ok(summaries)
+    }
+
+    pub async fn find_query(&self, name: &str) -> Result<ParsedPreparedQuery> {
+        let query = match &self.query {
+            Err(e) => return Err(e.clone()),
+            Ok(q) => q,
+        };
+
+        let codegen = CodeGenerationContext::new(self.config.clone(), query);
 
+        for query_file in codegen.locate_query_files().await {
+            let contents = fs::read_to_string(&query_file).await?;
+            let parsed = parser::load_file(&contents)?;
+
+            for (i, stmt) in parsed.statements.iter().enumerate() {
+                let Ok(prepared_statement) =
+                    parser::get_prepared_statement(&stmt, &parsed.tokens, &contents, || {
+                        format!("query_{i}")
+                    })
+                else {
+                    continue;
+                };
+
+                // Accept either the name as written, or the name of the generated binding
+                let query_name = &prepared_statement.details.name;
+                if query_name == name || to_camel_case(query_name, false) == name {
+                    return Ok(prepared_statement);
+                }
+            }
+        }
+
+        Err(PiquedError::OtherError(format!(
+            "No query named \"{name}\" found"
+        )))
+    }
+
+    pub async fn is_compile_target(&self, path: &PathBuf) -> bool {
         path.starts_with(&self.root_dir)
-            && (ext == "sql" || ext == "psql" || ext == "pgsql" || ext == "pg")
+            && self.config.workspace.is_query_file(path)
+            && !self.config.workspace.is_excluded(path)
+    }
+}
+
+pub fn error_message(err: &PiquedError) -> String {
+    match err {
+        PiquedError::ParseErrorAt(e) => format!("Error parsing query at \"{e}\""),
+        PiquedError::PostgresError(e, _) => e.clone(),
+        PiquedError::OtherError(e) => format!("Error: {e}"),
+        PiquedError::SerdeParseError(e) => format!("Error: {e}"),
+        PiquedError::IoError(e) => format!("Error: {e}"),
+        PiquedError::ConnectionError(e) => format!("Error: {e}"),
+    }
+}
+
+// The errors `pg_query` found in each statement. These only name the token they stopped at, so the
+// statement is cut off after each token with that text until the parse fails on that token, rather
+// than running out of input.
+fn parse_error_diagnostics(file_contents: &str, parsed: &ParsedFile) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for stmt in &parsed.statements {
+        let err = match &stmt.stmt {
+            Ok(_) => continue,
+            Err(err) => err,
+        };
+
+        let range = match err {
+            PiquedError::ParseErrorAt(near) => {
+                let stmt_end = stmt.index_start + stmt.index_len;
+                parsed
+                    .tokens
+                    .iter()
+                    .filter(|token| {
+                        token.start as u32 >= stmt.index_start && (token.end as u32) <= stmt_end
+                    })
+                    .filter(|token| {
+                        &file_contents[token.start as usize..token.end as usize] == near
+                    })
+                    .find(|token| {
+                        let prefix = &file_contents[stmt.index_start as usize..token.end as usize];
+                        matches!(
+                            pg_query::parse(prefix).map_err(PiquedError::from),
+                            Err(PiquedError::ParseErrorAt(_))
+                        )
+                    })
+                    .map(|token| {
+                        Range::new(
+                            parser::position_at(file_contents, token.start as u32),
+                            parser::position_at(file_contents, token.end as u32),
+                        )
+                    })
+            }
+            _ => None,
+        };
+
+        diagnostics.push(Diagnostic::new(
+            range.unwrap_or(stmt.range),
+            Some(DiagnosticSeverity::ERROR),
+            None,
+            None,
+            error_message(err),
+            None,
+            None,
+        ));
+    }
+
+    diagnostics
+}
+
+// Postgres reports error positions within the deparsed query we sent it, rather than the statement
+// as written. So the token at that position is looked up again in the statement, by its text and
+// how many times it's appeared so far.
+fn error_range(
+    file_contents: &str,
+    parsed: &ParsedFile,
+    stmt: &RelocatedStmt,
+    position: u32,
+) -> Option<Range> {
+    let prepared =
+        parser::get_prepared_statement(stmt, &parsed.tokens, file_contents, || "query".to_string())
+            .ok()?;
+    let deparsed = &prepared.contents;
+
+    let offset = deparsed
+        .char_indices()
+        .nth(position.checked_sub(1)? as usize)?
+        .0 as i32;
+
+    let normalize = |text: &str| text.trim_matches('"').to_lowercase();
+    let is_code = |token: &ScanToken| !matches!(token.token(), Token::CComment | Token::SqlComment);
+
+    let deparsed_tokens = pg_query::scan(deparsed).ok()?.tokens;
+    let target = deparsed_tokens.iter().find(|token| token.start == offset)?;
+    let text = normalize(&deparsed[target.start as usize..target.end as usize]);
+    let occurrence = deparsed_tokens
+        .iter()
+        .take_while(|token| token.start < offset)
+        .filter(|token| normalize(&deparsed[token.start as usize..token.end as usize]) == text)
+        .count();
+
+    let stmt_end = stmt.index_start + stmt.index_len;
+    let token = parsed
+        .tokens
+        .iter()
+        .filter(|token| token.start as u32 >= stmt.index_start && (token.end as u32) <= stmt_end)
+        .filter(|token| is_code(token))
+        .filter(|token| normalize(&file_contents[token.start as usize..token.end as usize]) == text)
+        .nth(occurrence)?;
+
+    Some(Range::new(
+        parser::position_at(file_contents, token.start as u32),
+        parser::position_at(file_contents, token.end as u32),
+    ))
+}
+
+// Two statements generated under the same name won't compile, so the later one is flagged. That's
+// only a warning if duplicates are allowed, as code generation then goes ahead anyway.
+fn duplicate_name_diagnostics(
+    config: &Config,
+    file_contents: &str,
+    parsed: &ParsedFile,
+) -> Vec<Diagnostic> {
+    let severity = if config.emit.allow_duplicate_names {
+        DiagnosticSeverity::WARNING
+    } else {
+        DiagnosticSeverity::ERROR
+    };
+
+    let names = parser::statement_names(parsed, file_contents);
+
+    let mut diagnostics = vec![];
+    for (i, (name, _, stmt)) in names.iter().enumerate() {
+        let Some((first_name, _, first_stmt)) = names[..i].iter().find(|(first_name, _, _)| {
+            to_camel_case(first_name, false) == to_camel_case(name, false)
+        }) else {
+            continue;
+        };
+
+        let message = if first_name == name {
+            format!(
+                "Query name \"{}\" on line {} is already used on line {}",
+                name,
+                stmt.range.start.line + 1,
+                first_stmt.range.start.line + 1
+            )
+        } else {
+            format!(
+                "Query name \"{}\" on line {} collides with \"{}\" on line {}",
+                name,
+                stmt.range.start.line + 1,
+                first_name,
+                first_stmt.range.start.line + 1
+            )
+        };
+
+        diagnostics.push(Diagnostic::new(
+            stmt.range,
+            Some(severity),
+            None,
+            None,
+            message,
+            None,
+            None,
+        ));
+    }
+
+    diagnostics
+}
+
+// A join without `ON` or `USING` pairs every row with every other. That's almost always a mistake
+// unless it's spelled `CROSS JOIN` (or is `NATURAL`, which brings its own condition).
+fn cartesian_join_diagnostics(parsed: &ParserResult) -> Vec<Diagnostic> {
+    fn visit(
+        parsed: &ParserResult,
+        states: &Vec<Arc<LR1State>>,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        for state in states {
+            if let LR1Kind::JoinExpression(join) = &state.kind {
+                let has_condition = join.condition.is_some() || !join.using.is_empty();
+                let implicit = matches!(join.kind, JoinKind::Cross | JoinKind::Natural);
+
+                if !has_condition && !implicit {
+                    diagnostics.push(Diagnostic::new(
+                        parsed.range_of(state),
+                        Some(DiagnosticSeverity::WARNING),
+                        None,
+                        None,
+                        "Join has no ON or USING condition, so it produces a cartesian product. Use CROSS JOIN if this is intended".to_string(),
+                        None,
+                        None,
+                    ));
+                }
+            }
+
+            visit(parsed, &state.children, diagnostics);
+        }
+    }
+
+    let mut diagnostics = vec![];
+    visit(parsed, &parsed.states, &mut diagnostics);
+
+    diagnostics
+}
+
+// Only a `*` in a select list counts, not the one in e.g. `count(*)`, nor a scoped `t.*`
+fn select_star_diagnostics(parsed: &ParserResult) -> Vec<Diagnostic> {
+    fn find_column<'a>(
+        states: &'a Vec<Arc<LR1State>>,
+        column: &Arc<ColumnExpression>,
+    ) -> Option<&'a LR1State> {
+        states.iter().find_map(|state| match &state.kind {
+            LR1Kind::ColumnExpression(candidate) if Arc::ptr_eq(candidate, column) => {
+                Some(&**state)
+            }
+            _ => find_column(&state.children, column),
+        })
+    }
+
+    fn visit(
+        parsed: &ParserResult,
+        states: &Vec<Arc<LR1State>>,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        for state in states {
+            if let LR1Kind::SelectStmt(columns) = &state.kind {
+                for column in columns {
+                    let ColumnExpression::Unnamed(expr) = column.as_ref() else {
+                        continue;
+                    };
+                    if !matches!(expr.as_ref(), Expression::WildcardLiteral) {
+                        continue;
+                    }
+
+                    if let Some(column_state) = find_column(&state.children, column) {
+                        diagnostics.push(Diagnostic::new(
+                            parsed.range_of(column_state),
+                            Some(DiagnosticSeverity::WARNING),
+                            None,
+                            None,
+                            "SELECT * changes the generated type whenever the schema does. Consider listing the columns instead".to_string(),
+                            None,
+                            None,
+                        ));
+                    }
+                }
+            }
+
+            visit(parsed, &state.children, diagnostics);
+        }
+    }
+
+    let mut diagnostics = vec![];
+    visit(parsed, &parsed.states, &mut diagnostics);
+
+    diagnostics
+}
+
+// Columns every table has, without them showing up among its attributes
+const SYSTEM_COLUMNS: [&str; 6] = ["tableoid", "xmin", "cmin", "xmax", "cmax", "ctid"];
+
+// Flags the columns in a select list that none of the tables in scope have, without waiting on the
+// database. A relation that isn't a plain table (a CTE, a derived table, a function) could have any
+// column, so queries selecting from one are left alone, as are statements that don't parse yet.
+fn unknown_column_diagnostics(
+    query: &Query,
+    file: &ParsedFile,
+    parsed: &ParserResult,
+) -> Vec<Diagnostic> {
+    fn find_expression<'a>(
+        states: &'a Vec<Arc<LR1State>>,
+        expression: &Arc<Expression>,
+    ) -> Option<&'a LR1State> {
+        states.iter().find_map(|state| match &state.kind {
+            LR1Kind::Expression(candidate) if Arc::ptr_eq(candidate, expression) => Some(&**state),
+            _ => find_expression(&state.children, expression),
+        })
+    }
+
+    // The message for a column reference that doesn't resolve, if it doesn't
+    fn check(scopes: &Vec<(String, &Vec<Column>)>, expression: &Expression) -> Option<String> {
+        let has_column = |columns: &Vec<Column>, name: &String| {
+            SYSTEM_COLUMNS.contains(&name.as_str())
+                || columns.iter().any(|column| &column.name == name)
+        };
+
+        match expression {
+            // A bare table name selects its whole row
+            Expression::Identifier(name) => (!scopes
+                .iter()
+                .any(|(scope, columns)| scope == name || has_column(columns, name)))
+            .then(|| format!("Column \"{}\" doesn't exist on any table in scope", name)),
+
+            Expression::BinopExpression(binop) => match (binop.left.as_ref(), binop.right.as_ref())
+            {
+                (Expression::Identifier(scope), Expression::Identifier(name)) => {
+                    let (_, columns) = scopes
+                        .iter()
+                        .rev()
+                        .find(|(candidate, _)| candidate == scope)?;
+
+                    (!has_column(columns, name))
+                        .then(|| format!("Column \"{}\" doesn't exist on \"{}\"", name, scope))
+                }
+                _ => None,
+            },
+
+            _ => None,
+        }
+    }
+
+    fn check_expression(
+        parsed: &ParserResult,
+        query_state: &LR1State,
+        scopes: &Vec<(String, &Vec<Column>)>,
+        expression: &Arc<Expression>,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let children = match expression.as_ref() {
+            Expression::Identifier(_) => vec![],
+            Expression::BinopExpression(binop) if binop.operator.token == SqlToken::Period => {
+                vec![]
+            }
+            Expression::BinopExpression(binop) => vec![binop.left.clone(), binop.right.clone()],
+            Expression::UnopExpression(unop) => vec![unop.expression.clone()],
+            Expression::FunctionCall(call) => call.args.clone(),
+            Expression::BetweenExpression(between) => vec![
+                between.expression.clone(),
+                between.low.clone(),
+                between.high.clone(),
+            ],
+            Expression::IsExpression(is) => vec![is.expression.clone()],
+            Expression::Cast(cast) => vec![cast.expression.clone()],
+            _ => return,
+        };
+
+        if children.is_empty() {
+            let Some(message) = check(scopes, expression) else {
+                return;
+            };
+            let Some(state) = find_expression(&query_state.children, expression) else {
+                return;
+            };
+
+            diagnostics.push(Diagnostic::new(
+                parsed.range_of(state),
+                Some(DiagnosticSeverity::WARNING),
+                None,
+                None,
+                message,
+                None,
+                None,
+            ));
+        }
+
+        for child in &children {
+            check_expression(parsed, query_state, scopes, child, diagnostics);
+        }
+    }
+
+    // `scopes` holds the tables of the enclosing queries, which a subquery can refer to as well. It's
+    // `None` when one of those couldn't be resolved.
+    fn visit<'a>(
+        query: &'a Query,
+        parsed: &ParserResult,
+        states: &Vec<Arc<LR1State>>,
+        scopes: Option<&Vec<(String, &'a Vec<Column>)>>,
+        ctes: &Vec<String>,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        for state in states {
+            let LR1Kind::SelectQuery(select_query) = &state.kind else {
+                visit(query, parsed, &state.children, scopes, ctes, diagnostics);
+                continue;
+            };
+
+            let mut ctes = ctes.clone();
+            ctes.extend(select_query.with.iter().map(|cte| cte.name.clone()));
+
+            let mut inner_scopes = scopes.cloned();
+            for from in select_query
+                .from
+                .iter()
+                .flatten()
+                .flat_map(|from| from.relations())
+            {
+                let columns = match (from.table.as_ref(), from.scope_name()) {
+                    (TableLike::Table(table), Some(scope)) if !ctes.contains(table) => query
+                        .table(table)
+                        .map(|columns| (scope.to_string(), columns)),
+                    _ => None,
+                };
+
+                match (&mut inner_scopes, columns) {
+                    (Some(inner_scopes), Some(columns)) => inner_scopes.push(columns),
+                    _ => inner_scopes = None,
+                }
+            }
+
+            if let (Some(inner_scopes), Some(_)) = (&inner_scopes, &select_query.from) {
+                for column in &select_query.columns {
+                    let (ColumnExpression::Unnamed(expression)
+                    | ColumnExpression::Named(_, expression)) = column.as_ref();
+
+                    check_expression(parsed, state, inner_scopes, expression, diagnostics);
+                }
+            }
+
+            visit(
+                query,
+                parsed,
+                &state.children,
+                inner_scopes.as_ref(),
+                &ctes,
+                diagnostics,
+            );
+        }
     }
+
+    let mut diagnostics = vec![];
+    let mut previous_stmt = None;
+    for state in &parsed.states {
+        let start = parsed.range_of(state).start;
+        let Some(stmt_index) = file
+            .statements
+            .iter()
+            .position(|stmt| stmt.range.start <= start && start < stmt.range.end)
+        else {
+            continue;
+        };
+
+        let first_in_stmt = previous_stmt != Some(stmt_index);
+        previous_stmt = Some(stmt_index);
+
+        if file.statements[stmt_index].stmt.is_err() {
+            continue;
+        }
+
+        // Only a query that starts its statement is known to be at the top level. Whatever the
+        // loose parser couldn't fit into one, e.g. a `WHERE` clause or a subquery it didn't manage
+        // to reduce, may refer to the tables of a query we can't see.
+        let scopes = match &state.kind {
+            LR1Kind::SelectQuery(_) | LR1Kind::CompoundQuery(_) if first_in_stmt => Some(vec![]),
+            _ => None,
+        };
+
+        visit(
+            query,
+            parsed,
+            &vec![state.clone()],
+            scopes.as_ref(),
+            &vec![],
+            &mut diagnostics,
+        );
+    }
+
+    diagnostics
+}
+
+fn severity_of(diagnostic: &Diagnostic) -> Severity {
+    match diagnostic.severity {
+        Some(DiagnosticSeverity::ERROR) => Severity::Error,
+        Some(DiagnosticSeverity::WARNING) => Severity::Warning,
+        Some(DiagnosticSeverity::INFORMATION) => Severity::Information,
+        _ => Severity::Hint,
+    }
+}
+
+// Carries what's cached about each statement over an edit. Only statements entirely before or
+// after the changed span are kept, with the latter moved along by however much the edit grew or
+// shrank the file.
+fn shift_statements<T: Clone>(
+    cached: &HashMap<(u32, u32), T>,
+    previous: &str,
+    contents: &str,
+) -> HashMap<(u32, u32), T> {
+    let prefix = previous
+        .bytes()
+        .zip(contents.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let max_suffix = previous.len().min(contents.len()) - prefix;
+    let suffix = previous
+        .bytes()
+        .rev()
+        .zip(contents.bytes().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let changed_end = previous.len() - suffix;
+    let delta = contents.len() as i64 - previous.len() as i64;
+
+    cached
+        .iter()
+        .filter_map(|(&(start, end), value)| {
+            if end as usize <= prefix {
+                Some(((start, end), value.clone()))
+            } else if start as usize >= changed_end {
+                let start = (start as i64 + delta) as u32;
+                let end = (end as i64 + delta) as u32;
+                Some(((start, end), value.clone()))
+            } else {
+                None
+            }
+        })
+        .collect()
 }
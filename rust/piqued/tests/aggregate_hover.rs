@@ -0,0 +1,77 @@
+mod common;
+
+use std::{env, fs, process};
+
+use tower_lsp::{
+    lsp_types::{HoverParams, Url},
+    LanguageServer,
+};
+
+const AGGREGATE_QUERIES: &str = include_str!("fixtures/aggregate-queries.sql");
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn aggregates_show_their_result_type() {
+    let config = common::test_config(&env::temp_dir()).await;
+    let client = common::connect(&config).await;
+
+    let schema = format!("piqued_aggregate_hover_{}", process::id());
+    client
+        .batch_execute(&format!(
+            "
+            CREATE SCHEMA {schema};
+            CREATE TABLE {schema}.payment (
+                id int4 PRIMARY KEY,
+                payer_id int4 NOT NULL,
+                amount int4 NOT NULL
+            );
+            "
+        ))
+        .await
+        .unwrap();
+
+    let root = env::temp_dir().join(format!("piqued_aggregate_hover_{}", process::id()));
+    fs::create_dir_all(&root).unwrap();
+    fs::write(
+        root.join("piqued.toml"),
+        format!(
+            "[postgres]\nuri = \"{}\"\nschema = \"{}\"\n\n[lsp]\nschemaChannel = \"\"\n",
+            config.postgres.uri, schema
+        ),
+    )
+    .unwrap();
+
+    let service = common::language_server(&root).await;
+    let backend = service.inner();
+
+    let file_uri = Url::from_file_path(root.join("queries.sql")).unwrap();
+    common::open_document(backend, &file_uri, AGGREGATE_QUERIES).await;
+
+    let mut hovers = vec![];
+    for needle in ["sum(", "count("] {
+        let hover = backend
+            .hover(HoverParams {
+                text_document_position_params: common::document_position(
+                    &file_uri,
+                    common::position_of(AGGREGATE_QUERIES, needle, 1),
+                ),
+                work_done_progress_params: Default::default(),
+            })
+            .await
+            .unwrap();
+        hovers.push(format!("{:?}", hover));
+    }
+
+    client
+        .batch_execute(&format!("DROP SCHEMA {schema} CASCADE;"))
+        .await
+        .unwrap();
+    fs::remove_dir_all(&root).unwrap();
+
+    // Summing `int4`s gives an `int8`, which the table's columns couldn't have said
+    let expected = ["total int8", "count int8"];
+    for (hover, expected) in hovers.iter().zip(expected) {
+        assert!(hover.contains("(column)"), "{}", hover);
+        assert!(hover.contains(expected), "{} in {}", expected, hover);
+    }
+}
@@ -0,0 +1,19 @@
+mod common;
+
+use common::describe_columns;
+
+#[test]
+fn between_consumes_its_and() {
+    let contents = include_str!("../../../ts/test-workspace/src/data/between-queries.sql");
+    assert_eq!(
+        describe_columns(contents),
+        vec![
+            "id",
+            "legal_name",
+            "((id BETWEEN 1 AND 10) AND (legal_name <> ''))",
+            "id",
+            "(id NOT BETWEEN 10 AND (10 * 2)) AS outlier",
+            "((length(legal_name) BETWEEN 1 AND 100) OR (id = 0))",
+        ]
+    );
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use std::{env, process, sync::Arc};
+
+use piqued::{
+    parser::parser::{self, CacheHint},
+    workspace::workspace::GenerationMode,
+};
+
+const CACHED_QUERIES: &str = include_str!("../../../ts/test-workspace/src/data/cached-queries.sql");
+
+#[test]
+fn cache_hint_is_parsed() {
+    let parsed = parser::load_file(CACHED_QUERIES).unwrap();
+
+    let hints = parsed
+        .statements
+        .iter()
+        .map(|stmt| {
+            parser::get_prepared_statement(stmt, &parsed.tokens, CACHED_QUERIES, || {
+                "unnamed".to_string()
+            })
+            .unwrap()
+            .details
+        })
+        .map(|details| (details.name, details.cache, details.comment))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        hints,
+        vec![
+            (
+                "cached_company_count".to_string(),
+                Some(CacheHint { ttl: 30 }),
+                "Counted often, but it's fine if it lags behind a little.".to_string()
+            ),
+            (
+                "company_by_id".to_string(),
+                Some(CacheHint { ttl: 60 }),
+                "".to_string()
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn cached_query_is_marked_for_memoization() {
+    let root = env::temp_dir().join(format!("piqued-cache-{}", process::id()));
+    tokio::fs::create_dir_all(&root).await.unwrap();
+    tokio::fs::write(
+        root.join("cached.sql"),
+        "-- @name cached_one\n-- @cache ttl=5\nSELECT 1 AS one;\n\n-- @name uncached_one\nSELECT 1 AS one;\n",
+    )
+    .await
+    .unwrap();
+
+    let mut config = common::test_config(&root).await;
+    config.emit.type_file = "./types".to_string();
+    let config = Arc::new(config);
+
+    let workspace = common::connected_workspace(config.clone(), root.clone()).await;
+
+    workspace.gen_code(GenerationMode::Full).await.unwrap();
+    let generated = tokio::fs::read_to_string(root.join("cached.ts"))
+        .await
+        .unwrap();
+    tokio::fs::remove_dir_all(&root).await.unwrap();
+
+    assert_eq!(generated.matches("    cache: { ttl: 5 },\n").count(), 1);
+    let cached = generated.find("name: \"cached_one\"").unwrap();
+    let uncached = generated.find("name: \"uncached_one\"").unwrap();
+    assert!(cached < generated.find("cache:").unwrap());
+    assert!(generated.find("cache:").unwrap() < uncached);
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use std::{env, sync::Arc};
+
+use piqued::{
+    loose_parser::{
+        parse::ParserContext,
+        parse_cf::{ColumnExpression, Expression, LR1Kind, LR1State},
+    },
+    query::query::Query,
+};
+
+const CAST_QUERIES: &str = include_str!("fixtures/cast-queries.sql");
+
+fn type_names(states: &Vec<Arc<LR1State>>, names: &mut Vec<String>) {
+    for state in states {
+        match &state.kind {
+            LR1Kind::TypeName(name) => names.push(name.clone()),
+            _ => type_names(&state.children, names),
+        }
+    }
+}
+
+#[test]
+fn casts_are_reduced() {
+    let mut context = ParserContext::new(CAST_QUERIES);
+    let parsed = context.parse();
+
+    let casts = parsed
+        .states
+        .iter()
+        .flat_map(|state| match &state.kind {
+            LR1Kind::SelectQuery(select_query) => select_query.columns.clone(),
+            _ => vec![],
+        })
+        .filter_map(|column| match column.as_ref() {
+            ColumnExpression::Unnamed(expr) => match expr.as_ref() {
+                Expression::Cast(cast) => Some(cast.type_name.clone()),
+                _ => None,
+            },
+            ColumnExpression::Named(..) => None,
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        casts,
+        vec![
+            "text",
+            "numeric",
+            "timestamp with time zone",
+            "varchar[]",
+            "user_status"
+        ]
+    );
+}
+
+#[test]
+fn casted_parameters_name_a_type() {
+    let mut context = ParserContext::new(CAST_QUERIES);
+    let parsed = context.parse();
+
+    let mut names = vec![];
+    type_names(&parsed.states, &mut names);
+
+    assert_eq!(names.last().map(|name| name.as_str()), Some("date"));
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn type_names_resolve() {
+    let config = common::test_config(&env::temp_dir()).await;
+
+    let query = Query::new(Arc::new(config)).await.unwrap();
+
+    let resolved = ["int", "varchar[]", "timestamp with time zone", "not_a_type"];
+    let mut names = vec![];
+    for name in resolved {
+        names.push(query.resolve_type_name(name).await.unwrap());
+    }
+
+    assert_eq!(
+        names,
+        vec![
+            Some("integer".to_string()),
+            Some("character varying[]".to_string()),
+            Some("timestamp with time zone".to_string()),
+            None,
+        ]
+    );
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use std::{env, sync::Arc};
+
+use piqued::{parser::parser, query::query::Query};
+
+const CATALOG_QUERIES: &str =
+    include_str!("../../../ts/test-workspace/src/data/catalog-queries.sql");
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn catalog_tables_are_looked_up_but_not_generated() {
+    let config = common::test_config(&env::temp_dir()).await;
+
+    let query = Query::new(Arc::new(config)).await.unwrap();
+
+    let parsed = parser::load_file(CATALOG_QUERIES).unwrap();
+    let prepared = parser::get_prepared_statement(
+        &parsed.statements[0],
+        &parsed.tokens,
+        CATALOG_QUERIES,
+        || "query".to_string(),
+    )
+    .unwrap();
+    let probed = query.probe_type(&prepared).await.unwrap();
+
+    assert!(!query.tables.contains_key("pg_class"));
+    assert!(query
+        .table("pg_class")
+        .unwrap()
+        .iter()
+        .any(|column| column.name == "relname"));
+
+    // Both columns are `NOT NULL` in the catalog, but the outer join can still null out `nspname`
+    assert_eq!(probed.column_names, vec!["relname", "nspname"]);
+    assert_eq!(probed.column_nullable, vec![false, true]);
+}
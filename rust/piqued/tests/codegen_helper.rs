@@ -0,0 +1,65 @@
+use piqued::code_builder::codegen_helper::CodegenHelper;
+
+#[test]
+fn nested_indentation() {
+    let mut c = CodegenHelper::new("    ", "\n");
+
+    c.write_line(Some("a {"));
+    c.with_indent(|c| {
+        c.write_line(Some("b {"));
+        c.with_indent(|c| c.write_line(Some("c;")));
+        c.write_line(Some("}"));
+    });
+    c.write_line(Some("}"));
+
+    assert_eq!(c.serialize(), "a {\n    b {\n        c;\n    }\n}\n");
+}
+
+#[test]
+fn indent_and_terminator_are_configurable() {
+    let mut c = CodegenHelper::new("\t", "\r\n");
+
+    c.write_line(Some("a"));
+    c.with_indent(|c| c.write_line(Some("b")));
+
+    assert_eq!(c.serialize(), "a\r\n\tb\r\n");
+}
+
+#[test]
+fn blank_lines_are_not_indented() {
+    let mut c = CodegenHelper::new("  ", "\n");
+
+    c.with_indent(|c| {
+        c.write_line(Some("a"));
+        c.write_line(None);
+        c.write_line(Some(""));
+        c.write_line(Some("b"));
+    });
+
+    assert_eq!(c.serialize(), "  a\n\n\n  b\n");
+}
+
+#[test]
+fn no_trailing_newline_unless_written() {
+    let mut c = CodegenHelper::new("  ", "\n");
+
+    c.write_token("export");
+    c.write_token("type");
+    c.write("t");
+    c.write_symbol(";");
+
+    assert_eq!(c.serialize(), "export type t;");
+}
+
+#[test]
+fn symbols_are_not_separated_from_tokens() {
+    let mut c = CodegenHelper::new("  ", "\n");
+
+    c.write_token("foo");
+    c.with_parens(|c| {
+        c.iter_and_join(["a", "b"], ", ", |c, item| c.write_token(item));
+    });
+    c.write_line(None);
+
+    assert_eq!(c.serialize(), "foo(a, b)\n");
+}
@@ -0,0 +1,275 @@
+// Shared between the integration tests, each of which only uses some of it.
+//
+// Tests that need a database are `#[ignore]`d, so that they're reported as skipped rather than
+// quietly passing. Run them with `cargo test -- --ignored` against the database in
+// `PIQUED_TEST_URI` (or the default config's).
+#![allow(dead_code)]
+
+use std::{
+    collections::HashMap,
+    env,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use futures::StreamExt;
+use piqued::{
+    codegen::codegen::{CodeGenerationContext, QueryContext},
+    config::config::Config,
+    loose_parser::{
+        parse::ParserContext,
+        parse_cf::{ColumnExpression, Expression, IsPredicate, LR1Kind},
+    },
+    lsp::lsp::Backend,
+    parser::parser,
+    query::query::{Column, ColumnType, CompositeType, CustomType, EnumType, ProbeResponse},
+    workspace::workspace::Workspace,
+};
+use sqlparser::tokenizer::Token;
+use tokio_postgres::{Client, NoTls};
+use tower_lsp::{
+    lsp_types::{
+        DidOpenTextDocumentParams, InitializeParams, InitializedParams, Position,
+        TextDocumentIdentifier, TextDocumentItem, TextDocumentPositionParams, Url, WorkspaceFolder,
+    },
+    LanguageServer, LspService,
+};
+
+// The config in `root` (or the default one), pointed at the test database
+pub async fn test_config(root: &Path) -> Config {
+    let mut config = Config::load(&None, &root.to_path_buf()).await.unwrap();
+    if let Ok(uri) = env::var("PIQUED_TEST_URI") {
+        config.postgres.uri = uri;
+    }
+
+    config
+}
+
+// A connection of the test's own, for setting up and tearing down what it needs
+pub async fn connect(config: &Config) -> Client {
+    let (client, connection) = tokio_postgres::connect(&config.postgres.uri, NoTls)
+        .await
+        .unwrap_or_else(|e| panic!("Unable to connect to {}: {}", config.postgres.uri, e));
+    tokio::spawn(connection);
+
+    client
+}
+
+pub async fn connected_workspace(config: Arc<Config>, root: PathBuf) -> Workspace {
+    let workspace = Workspace::new(config.clone(), root).await;
+    if let Err(e) = &workspace.query {
+        panic!("Unable to connect to {}: {}", config.postgres.uri, e);
+    }
+
+    workspace
+}
+
+// A language server for the workspace at `root`, initialized the way an editor would
+pub async fn language_server(root: &Path) -> LspService<Backend> {
+    let (service, socket) = LspService::new(|client| Backend::new(client));
+    // Drain server-to-client messages so `showMessage` doesn't block
+    tokio::spawn(socket.for_each(|_| async {}));
+
+    #[allow(deprecated)]
+    service
+        .inner()
+        .initialize(InitializeParams {
+            workspace_folders: Some(vec![WorkspaceFolder {
+                uri: Url::from_directory_path(root).unwrap(),
+                name: "piqued".to_string(),
+            }]),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    service.inner().initialized(InitializedParams {}).await;
+
+    service
+}
+
+pub async fn open_document(backend: &Backend, uri: &Url, text: &str) {
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "sql".to_string(),
+                version: 1,
+                text: text.to_string(),
+            },
+        })
+        .await;
+}
+
+pub fn document_position(uri: &Url, position: Position) -> TextDocumentPositionParams {
+    TextDocumentPositionParams {
+        text_document: TextDocumentIdentifier { uri: uri.clone() },
+        position,
+    }
+}
+
+// Where `offset` characters into the first occurrence of `needle` in `contents` is
+pub fn position_of(contents: &str, needle: &str, offset: usize) -> Position {
+    let index = contents.find(needle).unwrap() + offset;
+    let before = &contents[..index];
+    let line = before.matches('\n').count();
+    let character = index - before.rfind('\n').map_or(0, |newline| newline + 1);
+
+    Position::new(line as u32, character as u32)
+}
+
+// A small, fixed schema for the generator snapshots, so that the generated code doesn't depend on
+// a live database. Each generator picks the company columns and status values that exercise it.
+pub fn column(name: &str, column_type: ColumnType, nullable: bool) -> Column {
+    Column {
+        name: name.to_string(),
+        column_type,
+        type_oid: 0,
+        nullable,
+        generated: false,
+        references: None,
+    }
+}
+
+pub fn named(name: &str) -> ColumnType {
+    ColumnType::Named(name.to_string())
+}
+
+pub fn array_of(name: &str) -> ColumnType {
+    ColumnType::ArrayOf(Box::new(named(name)))
+}
+
+// A `company` table (and composite type) of `company_columns`, and a `company_status` enum
+pub fn schema_context(
+    config: Config,
+    company_columns: Vec<Column>,
+    status_values: &[&str],
+) -> CodeGenerationContext<'static> {
+    let mut config = config;
+    config.workspace.root = Some(PathBuf::from("/workspace"));
+
+    let company = CustomType::Composite(CompositeType {
+        oid: 1,
+        name: "company".to_string(),
+        fields: company_columns.clone(),
+    });
+    let status = CustomType::Enum(EnumType {
+        oid: 2,
+        name: "company_status".to_string(),
+        values: status_values
+            .iter()
+            .map(|value| value.to_string())
+            .collect(),
+    });
+
+    CodeGenerationContext::from_schema(
+        Arc::new(config),
+        HashMap::from([("company".to_string(), company_columns)]),
+        HashMap::from([
+            ("company".to_string(), Arc::new(company)),
+            ("company_status".to_string(), Arc::new(status)),
+        ]),
+    )
+}
+
+pub fn query_context(sql: &str, probe: ProbeResponse) -> QueryContext {
+    let parsed = parser::load_file(sql).unwrap();
+    QueryContext::from_stmt(&parsed.statements[0], &parsed.tokens, sql, probe).unwrap()
+}
+
+// Spells out an expression with every operator parenthesized, so that the shape of the tree shows
+fn describe(expr: &Expression) -> String {
+    match expr {
+        Expression::Identifier(name) => name.clone(),
+        Expression::NumberLiteral(value) => value.clone(),
+        Expression::StringLiteral(value) => format!("'{}'", value),
+        Expression::WildcardLiteral => "*".to_string(),
+        Expression::BinopExpression(binop) if binop.operator.token == Token::Period => {
+            format!("{}.{}", describe(&binop.left), describe(&binop.right))
+        }
+        Expression::BinopExpression(binop) => format!(
+            "({} {} {})",
+            describe(&binop.left),
+            binop.operator.token,
+            describe(&binop.right)
+        ),
+        Expression::UnopExpression(unop) => {
+            format!("({} {})", unop.operator.token, describe(&unop.expression))
+        }
+        Expression::BetweenExpression(between) => format!(
+            "({} {}BETWEEN {} AND {})",
+            describe(&between.expression),
+            if between.negated { "NOT " } else { "" },
+            describe(&between.low),
+            describe(&between.high)
+        ),
+        Expression::IsExpression(is) => format!(
+            "({} IS {}{})",
+            describe(&is.expression),
+            if is.negated { "NOT " } else { "" },
+            match is.predicate {
+                IsPredicate::Null => "NULL",
+                IsPredicate::True => "TRUE",
+                IsPredicate::False => "FALSE",
+                IsPredicate::Unknown => "UNKNOWN",
+            }
+        ),
+        Expression::FunctionCall(call) => format!(
+            "{}({})",
+            call.name,
+            call.args
+                .iter()
+                .map(|arg| describe(arg))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        other => format!("{:?}", other),
+    }
+}
+
+pub fn describe_column(column: &ColumnExpression) -> String {
+    match column {
+        ColumnExpression::Unnamed(expr) => describe(expr),
+        ColumnExpression::Named(name, expr) => format!("{} AS {}", describe(expr), name),
+    }
+}
+
+// Every column (or expression list entry) in `contents`, described in order
+pub fn describe_columns(contents: &str) -> Vec<String> {
+    let mut context = ParserContext::new(contents);
+    let parsed = context.parse();
+
+    parsed
+        .states
+        .iter()
+        .flat_map(|state| match &state.kind {
+            LR1Kind::SelectQuery(select_query) => select_query
+                .columns
+                .iter()
+                .map(|column| describe_column(column))
+                .collect::<Vec<_>>(),
+            LR1Kind::ExpressionList(list) => {
+                list.iter().map(|column| describe_column(column)).collect()
+            }
+            _ => vec![],
+        })
+        .collect()
+}
+
+// Describes the single column selected by `contents`
+pub fn describe_select(contents: &str) -> String {
+    let mut context = ParserContext::new(contents);
+    let parsed = context.parse();
+
+    let columns = match parsed.states.as_slice() {
+        [state] => match &state.kind {
+            LR1Kind::SelectQuery(select_query) => select_query.columns.clone(),
+            other => panic!("Expected a select query, got {:?}", other),
+        },
+        states => panic!("Expected a single state, got {:#?}", states),
+    };
+
+    match columns.as_slice() {
+        [column] => describe_column(column),
+        _ => panic!("Expected a single column, got {:?}", columns),
+    }
+}
@@ -0,0 +1,31 @@
+use piqued::config::config::Config;
+use serde_json::Value;
+
+// The schema of the setting at `section.key`, following the section's reference into the
+// definitions
+fn setting<'a>(schema: &'a Value, section: &str, key: &str) -> &'a Value {
+    let reference = schema["properties"][section]["$ref"]
+        .as_str()
+        .unwrap_or_else(|| panic!("No section {} in {}", section, schema));
+    let definition = reference.trim_start_matches("#/definitions/");
+
+    &schema["definitions"][definition]["properties"][key]
+}
+
+#[test]
+fn schema_describes_the_toml_keys() {
+    let schema: Value = serde_json::from_str(&Config::json_schema()).unwrap();
+
+    // Keys are spelled the way they are in `piqued.toml`, not the way the struct fields are
+    let uri = setting(&schema, "postgres", "uri");
+    assert_eq!(uri["type"], "string");
+    assert_eq!(
+        uri["default"],
+        "postgresql://postgres:@localhost:5432/postgres"
+    );
+
+    let type_file = setting(&schema, "emit", "typeFile");
+    assert_eq!(type_file["type"], "string");
+    assert_eq!(type_file["default"], "./postgres");
+    assert!(setting(&schema, "emit", "type_file").is_null());
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use std::{env, process, sync::Arc};
+
+use piqued::{
+    parser::parser,
+    query::query::{ProbeResponse, Query},
+};
+
+async fn probe(query: &Query, sql: &str) -> Result<ProbeResponse, String> {
+    let parsed = parser::load_file(sql).unwrap();
+    let prepared =
+        parser::get_prepared_statement(&parsed.statements[0], &parsed.tokens, sql, || {
+            "query".to_string()
+        })
+        .unwrap();
+
+    query.probe_type(&prepared).await.map_err(|e| e.to_string())
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn dropped_custom_type() {
+    let mut config = common::test_config(&env::temp_dir()).await;
+    let client = common::connect(&config).await;
+
+    let schema = format!("piqued_custom_types_{}", process::id());
+    client
+        .batch_execute(&format!(
+            "CREATE SCHEMA {schema}; CREATE TYPE {schema}.mood AS ENUM ('happy', 'sad');"
+        ))
+        .await
+        .unwrap();
+
+    config.postgres.schema = schema.clone();
+    let mut query = Query::new(Arc::new(config)).await.unwrap();
+
+    let sql = "PREPARE by_mood (mood) AS SELECT $1::text AS mood;";
+    let before = probe(&query, sql).await;
+
+    // Recreating the type gives it a new OID, so the one we loaded no longer exists
+    client
+        .batch_execute(&format!(
+            "DROP TYPE {schema}.mood; CREATE TYPE {schema}.mood AS ENUM ('happy', 'sad');"
+        ))
+        .await
+        .unwrap();
+    let dropped = probe(&query, sql).await;
+
+    query.refresh_schema().await.unwrap();
+    let refreshed = probe(&query, sql).await;
+
+    client
+        .batch_execute(&format!("DROP SCHEMA {schema} CASCADE;"))
+        .await
+        .unwrap();
+
+    assert!(before.is_ok());
+    assert!(dropped
+        .unwrap_err()
+        .contains("Type \\\"mood\\\" no longer exists"));
+    assert!(refreshed.is_ok());
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use tower_lsp::{
+    lsp_types::{GotoDefinitionParams, GotoDefinitionResponse, Location, Position, Range, Url},
+    LanguageServer, LspService,
+};
+
+use piqued::lsp::lsp::Backend;
+
+const SCHEMA: &str = "CREATE TABLE app.users (
+    id int4 PRIMARY KEY,
+    \"Display Name\" text
+);
+
+CREATE TABLE \"Company\" (id int4 PRIMARY KEY, owner_id int4);
+";
+
+const QUERIES: &str =
+    "SELECT u.id, \"Display Name\" FROM users u JOIN \"Company\" c ON c.owner_id = u.id;\n";
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// A workspace with the schema above (if `schema_file`), and a database that isn't there
+async fn workspace(schema_file: bool) -> (PathBuf, LspService<Backend>) {
+    let root = env::temp_dir().join(format!(
+        "piqued_definition_{}_{}",
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    ));
+    fs::create_dir_all(&root).unwrap();
+
+    let mut toml = "[postgres]\nuri = \"postgresql://postgres@127.0.0.1:1/postgres\"\n\n[lsp]\nschemaChannel = \"\"\n".to_string();
+    if schema_file {
+        fs::write(root.join("schema.sql"), SCHEMA).unwrap();
+        toml.push_str("schemaFile = \"schema.sql\"\n");
+    }
+    fs::write(root.join("piqued.toml"), toml).unwrap();
+
+    let service = common::language_server(&root).await;
+    let file_uri = Url::from_file_path(root.join("queries.sql")).unwrap();
+    common::open_document(service.inner(), &file_uri, QUERIES).await;
+
+    (root, service)
+}
+
+async fn definition(root: &Path, backend: &Backend, needle: &str, offset: usize) -> Option<Range> {
+    let file_uri = Url::from_file_path(root.join("queries.sql")).unwrap();
+    let response = backend
+        .goto_definition(GotoDefinitionParams {
+            text_document_position_params: common::document_position(
+                &file_uri,
+                common::position_of(QUERIES, needle, offset),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .await
+        .unwrap();
+
+    match response? {
+        GotoDefinitionResponse::Scalar(Location { uri, range }) => {
+            let schema_file = root.join("schema.sql").canonicalize().unwrap();
+            assert_eq!(
+                uri.to_file_path().unwrap().canonicalize().unwrap(),
+                schema_file
+            );
+            Some(range)
+        }
+        other => panic!("Expected a single location, got {:?}", other),
+    }
+}
+
+fn range(line: u32, start: u32, end: u32) -> Option<Range> {
+    Some(Range::new(
+        Position::new(line, start),
+        Position::new(line, end),
+    ))
+}
+
+#[tokio::test]
+async fn tables_go_to_their_name_without_the_schema() {
+    let (root, service) = workspace(true).await;
+    let backend = service.inner();
+
+    let users = definition(&root, backend, "users", 1).await;
+    let company = definition(&root, backend, "\"Company\" c", 2).await;
+
+    fs::remove_dir_all(&root).unwrap();
+
+    // Only the `users` of `app.users`, and `"Company"` quotes and all
+    assert_eq!(users, range(0, 17, 22));
+    assert_eq!(company, range(5, 13, 22));
+}
+
+#[tokio::test]
+async fn columns_go_to_their_definition() {
+    let (root, service) = workspace(true).await;
+    let backend = service.inner();
+
+    let display_name = definition(&root, backend, "\"Display Name\"", 2).await;
+    let owner_id = definition(&root, backend, "owner_id", 1).await;
+
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(display_name, range(2, 4, 18));
+    assert_eq!(owner_id, range(5, 45, 53));
+}
+
+#[tokio::test]
+async fn aliases_resolve_to_their_table() {
+    let (root, service) = workspace(true).await;
+    let backend = service.inner();
+
+    // The aliases themselves, and a column qualified by one
+    let alias = definition(&root, backend, "u.id", 0).await;
+    let column = definition(&root, backend, "u.id", 2).await;
+    let other_alias = definition(&root, backend, "c.owner_id", 0).await;
+
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(alias, range(0, 17, 22));
+    assert_eq!(column, range(1, 4, 6));
+    assert_eq!(other_alias, range(5, 13, 22));
+}
+
+#[tokio::test]
+async fn nothing_to_go_to_without_a_schema_file() {
+    let (root, service) = workspace(false).await;
+    let backend = service.inner();
+
+    let users = definition(&root, backend, "users", 1).await;
+    let column = definition(&root, backend, "u.id", 2).await;
+
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(users, None);
+    assert_eq!(column, None);
+}
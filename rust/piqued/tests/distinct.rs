@@ -0,0 +1,63 @@
+use piqued::loose_parser::{
+    parse::ParserContext,
+    parse_cf::{ColumnExpression, Expression, LR1Kind, SelectQuery, TableLike},
+};
+
+fn parse_select(contents: &str) -> SelectQuery {
+    let mut context = ParserContext::new(contents);
+    let parsed = context.parse();
+
+    assert_eq!(parsed.states.len(), 1, "{:#?}", parsed.states);
+    match &parsed.states[0].kind {
+        LR1Kind::SelectQuery(select_query) => select_query.as_ref().clone(),
+        other => panic!("Expected a select query, got {:?}", other),
+    }
+}
+
+fn column_names(select_query: &SelectQuery) -> Vec<String> {
+    select_query
+        .columns
+        .iter()
+        .map(|column| match column.as_ref() {
+            ColumnExpression::Unnamed(expr) => match expr.as_ref() {
+                Expression::Identifier(name) => name.clone(),
+                other => format!("{:?}", other),
+            },
+            ColumnExpression::Named(name, _) => name.clone(),
+        })
+        .collect()
+}
+
+fn table_names(select_query: &SelectQuery) -> Vec<String> {
+    select_query
+        .from
+        .iter()
+        .flatten()
+        .map(|from| match from.table.as_ref() {
+            TableLike::Table(name) => name.clone(),
+            other => format!("{:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn distinct_on_keys_are_not_columns() {
+    let select_query = parse_select("SELECT DISTINCT ON (a) a, b FROM t");
+
+    assert_eq!(column_names(&select_query), vec!["a", "b"]);
+    assert_eq!(table_names(&select_query), vec!["t"]);
+}
+
+#[test]
+fn select_modifiers_are_skipped() {
+    for contents in [
+        "SELECT DISTINCT a, b FROM t",
+        "SELECT ALL a, b FROM t",
+        "SELECT DISTINCT ON (a, lower(b)) a, b FROM t ORDER BY a",
+    ] {
+        let select_query = parse_select(contents);
+
+        assert_eq!(column_names(&select_query), vec!["a", "b"], "{}", contents);
+        assert_eq!(table_names(&select_query), vec!["t"], "{}", contents);
+    }
+}
@@ -0,0 +1,247 @@
+use std::{
+    collections::HashMap,
+    env,
+    path::PathBuf,
+    process,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use piqued::{
+    codegen::{codegen::CodeGenerationContext, ts::schema::TSGenerator},
+    config::config::Config,
+    error::error::Error,
+    workspace::workspace::Workspace,
+};
+use tower_lsp::lsp_types::{DiagnosticSeverity, Position, Range};
+
+const DUPLICATE_NAME_QUERIES: &str = include_str!("fixtures/duplicate-name-queries.sql");
+
+// Names are checked without the database, so this points at one that isn't there
+async fn duplicate_name_diagnostics(
+    allow_duplicate_names: bool,
+) -> Vec<(DiagnosticSeverity, Range, String)> {
+    let mut config = Config::load(&None, &env::temp_dir()).await.unwrap();
+    config.postgres.uri = "postgresql://postgres@localhost:1/postgres".to_string();
+    config.emit.allow_duplicate_names = allow_duplicate_names;
+
+    let path = "file:///duplicate-name-queries.sql";
+    let mut workspace = Workspace::new(Arc::new(config), env::temp_dir()).await;
+    workspace.patch_file(path.to_string(), DUPLICATE_NAME_QUERIES.to_string());
+
+    workspace
+        .get_diagnostics(path)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|diagnostic| diagnostic.message.starts_with("Query name"))
+        .map(|diagnostic| {
+            (
+                diagnostic.severity.unwrap(),
+                diagnostic.range,
+                diagnostic.message,
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn later_declarations_of_a_name_are_errors() {
+    let diagnostics = duplicate_name_diagnostics(false).await;
+    let summary: Vec<_> = diagnostics
+        .iter()
+        .map(|(severity, range, message)| (*severity, range.start, message.as_str()))
+        .collect();
+
+    assert_eq!(
+        summary,
+        vec![
+            (
+                DiagnosticSeverity::ERROR,
+                Position::new(3, 0),
+                "Query name \"getCompany\" on line 4 collides with \"get_company\" on line 1"
+            ),
+            (
+                DiagnosticSeverity::ERROR,
+                Position::new(8, 0),
+                "Query name \"get_company\" on line 9 is already used on line 1"
+            ),
+            (
+                DiagnosticSeverity::ERROR,
+                Position::new(11, 0),
+                "Query name \"query_2\" on line 12 is already used on line 7"
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn allowed_duplicates_are_warnings() {
+    let severities: Vec<_> = duplicate_name_diagnostics(true)
+        .await
+        .into_iter()
+        .map(|(severity, _, _)| severity)
+        .collect();
+
+    assert_eq!(severities, vec![DiagnosticSeverity::WARNING; 3]);
+}
+
+// Names are compared before the database is needed, so these only get as far as the check
+async fn generate_queries(files: &[(&str, &str)], registry: bool) -> (PathBuf, Result<(), Error>) {
+    let root = env::temp_dir().join(format!(
+        "piqued-duplicates-{}-{}",
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    ));
+    tokio::fs::create_dir_all(&root).await.unwrap();
+    for (name, contents) in files {
+        tokio::fs::write(root.join(name), contents).await.unwrap();
+    }
+
+    let mut config = Config::load(&None, &root).await.unwrap();
+    config.workspace.root = Some(root.clone());
+    config.emit.type_file = "./types".to_string();
+    if registry {
+        config.emit.registry = Some("./registry".to_string());
+    }
+
+    let ctx = CodeGenerationContext::from_schema(Arc::new(config), HashMap::new(), HashMap::new());
+    let result = ctx.generate_queries(&TSGenerator::new()).await;
+    tokio::fs::remove_dir_all(&root).await.unwrap();
+
+    (root, result.map_err(Error::from))
+}
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+const NO_CONNECTION: &str = "Generating queries requires a database connection";
+
+#[tokio::test]
+async fn duplicates_across_files_stop_generation() {
+    let (root, result) = generate_queries(
+        &[
+            ("a.sql", "-- @name get_user\nSELECT 1 AS one;\n"),
+            (
+                "b.sql",
+                "-- @name other\nSELECT 2 AS two;\n\n-- @name get_user\nSELECT 3 AS three;\n",
+            ),
+        ],
+        false,
+    )
+    .await;
+
+    let expected = format!(
+        "Query name \"get_user\" at {}:4 is already used at {}:1",
+        root.join("b.sql").to_string_lossy(),
+        root.join("a.sql").to_string_lossy()
+    );
+    match result {
+        Err(Error::Other { message }) => assert_eq!(message, expected),
+        other => panic!("Expected a duplicate name error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn unnamed_queries_only_collide_across_files_in_the_registry() {
+    let files = [
+        ("a.sql", "SELECT 1 AS one;\n"),
+        ("b.sql", "SELECT 2 AS two;\n"),
+    ];
+
+    match generate_queries(&files, false).await.1 {
+        Err(Error::Other { message }) => assert_eq!(message, NO_CONNECTION),
+        other => panic!("Expected to get past the name check, got {:?}", other),
+    }
+
+    let (root, result) = generate_queries(&files, true).await;
+    let expected = format!(
+        "Query name \"query_0\" at {}:1 is already used at {}:1",
+        root.join("b.sql").to_string_lossy(),
+        root.join("a.sql").to_string_lossy()
+    );
+    match result {
+        Err(Error::Other { message }) => assert_eq!(message, expected),
+        other => panic!("Expected a duplicate name error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn unnamed_queries_still_collide_with_names_in_the_same_file() {
+    let (root, result) = generate_queries(
+        &[
+            ("a.sql", "-- @name query_1\nSELECT 1 AS one;\n"),
+            (
+                "b.sql",
+                "SELECT 1 AS one;\n\nSELECT 2 AS two;\n\n-- @name query_1\nSELECT 3;\n",
+            ),
+        ],
+        false,
+    )
+    .await;
+
+    // The fallback on line 3 is the nearer declaration
+    let b = root.join("b.sql").to_string_lossy().to_string();
+    let expected = format!(
+        "Query name \"query_1\" at {}:5 is already used at {}:3",
+        b, b
+    );
+    match result {
+        Err(Error::Other { message }) => assert_eq!(message, expected),
+        other => panic!("Expected a duplicate name error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn allowed_duplicates_are_left_for_the_caller_to_report() {
+    let root = env::temp_dir().join(format!(
+        "piqued-duplicates-{}-{}",
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    ));
+    tokio::fs::create_dir_all(&root).await.unwrap();
+    tokio::fs::write(root.join("a.sql"), "-- @name get_user\nSELECT 1 AS one;\n")
+        .await
+        .unwrap();
+    tokio::fs::write(root.join("b.sql"), "-- @name get_user\nSELECT 2 AS two;\n")
+        .await
+        .unwrap();
+
+    let mut config = Config::load(&None, &root).await.unwrap();
+    config.workspace.root = Some(root.clone());
+    config.emit.allow_duplicate_names = true;
+
+    let ctx = CodeGenerationContext::from_schema(Arc::new(config), HashMap::new(), HashMap::new());
+    let result = ctx.generate_queries(&TSGenerator::new()).await;
+    tokio::fs::remove_dir_all(&root).await.unwrap();
+
+    match result.map_err(Error::from) {
+        Err(Error::Other { message }) => assert_eq!(message, NO_CONNECTION),
+        other => panic!("Expected to get past the name check, got {:?}", other),
+    }
+    assert_eq!(
+        ctx.warnings(),
+        vec![format!(
+            "Query name \"get_user\" at {}:1 is already used at {}:1",
+            root.join("b.sql").to_string_lossy(),
+            root.join("a.sql").to_string_lossy()
+        )]
+    );
+}
+
+#[tokio::test]
+async fn sample_workspace_names_are_unique() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../ts/test-workspace");
+    let config = Config::load(&None, &root).await.unwrap();
+
+    let ctx = CodeGenerationContext::from_schema(Arc::new(config), HashMap::new(), HashMap::new());
+    match ctx
+        .generate_queries(&TSGenerator::new())
+        .await
+        .map_err(Error::from)
+    {
+        Err(Error::Other { message }) => assert_eq!(message, NO_CONNECTION),
+        other => panic!("Expected to get past the name check, got {:?}", other),
+    }
+}
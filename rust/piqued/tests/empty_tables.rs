@@ -0,0 +1,86 @@
+mod common;
+
+use std::{env, fs, process, sync::Arc};
+
+use piqued::{config::config::Config, workspace::workspace::GenerationMode};
+use tower_lsp::{
+    lsp_types::{HoverParams, Url},
+    LanguageServer,
+};
+
+const QUERIES: &str = "SELECT count(*) AS total FROM placeholder;\n";
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn tables_without_columns_are_shown_and_skipped() {
+    let config = common::test_config(&env::temp_dir()).await;
+    let client = common::connect(&config).await;
+
+    let schema = format!("piqued_empty_tables_{}", process::id());
+    client
+        .batch_execute(&format!(
+            "
+            CREATE SCHEMA {schema};
+            CREATE TABLE {schema}.placeholder ();
+            CREATE TABLE {schema}.company (id int4 PRIMARY KEY);
+            "
+        ))
+        .await
+        .unwrap();
+
+    let root = env::temp_dir().join(format!("piqued_empty_tables_{}", process::id()));
+    fs::create_dir_all(&root).unwrap();
+    fs::write(
+        root.join("piqued.toml"),
+        format!(
+            "[postgres]\nuri = \"{}\"\nschema = \"{}\"\n\n[emit]\ntypeFile = \"./types\"\ntableFile = \"./tables\"\n\n[lsp]\nschemaChannel = \"\"\n",
+            config.postgres.uri, schema
+        ),
+    )
+    .unwrap();
+
+    let service = common::language_server(&root).await;
+    let backend = service.inner();
+
+    let file_uri = Url::from_file_path(root.join("queries.sql")).unwrap();
+    common::open_document(backend, &file_uri, QUERIES).await;
+    let hover = backend
+        .hover(HoverParams {
+            text_document_position_params: common::document_position(
+                &file_uri,
+                common::position_of(QUERIES, "placeholder", 2),
+            ),
+            work_done_progress_params: Default::default(),
+        })
+        .await
+        .unwrap();
+
+    // The same workspace, as the command line would see it
+    let config = Config::load(&Some(root.join("piqued.toml")), &root)
+        .await
+        .unwrap();
+    let workspace = common::connected_workspace(Arc::new(config), root.clone()).await;
+    workspace
+        .gen_code(GenerationMode::SchemaOnly)
+        .await
+        .unwrap();
+    let tables = fs::read_to_string(root.join("tables.ts")).unwrap();
+
+    client
+        .batch_execute(&format!("DROP SCHEMA {schema} CASCADE;"))
+        .await
+        .unwrap();
+    fs::remove_dir_all(&root).unwrap();
+
+    let hover = format!("{:?}", hover);
+    assert!(hover.contains("\"(table)\""), "{}", hover);
+    assert!(
+        hover.contains("\"placeholder (no columns)\\n\""),
+        "{}",
+        hover
+    );
+
+    // There's nothing to build a row of, so the table file leaves it out
+    assert!(tables.contains("company"), "{}", tables);
+    assert!(!tables.contains("placeholder"), "{}", tables);
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use std::{env, error, io, sync::Arc};
+
+use piqued::{config::config::Config, error::error::Error, parser::parser, query::query::Query};
+use tokio_postgres::error::Error as PostgresError;
+
+fn prepare(sql: &str) -> Result<parser::ParsedPreparedQuery, Error> {
+    let parsed = parser::load_file(sql)?;
+    let stmt = parser::get_prepared_statement(&parsed.statements[0], &parsed.tokens, sql, || {
+        "query".to_string()
+    })?;
+
+    Ok(stmt)
+}
+
+#[test]
+fn parse_errors_name_where_they_stopped() {
+    let parsed = parser::load_file("SELECT id FROM users WHERE = 1;").unwrap();
+    match parsed.statements[0].stmt.clone().map_err(Error::from).err() {
+        Some(Error::Parse { near }) => assert_eq!(near, "="),
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+}
+
+#[test]
+fn config_errors_chain_to_their_source() {
+    let err: Error = Config::from_str("[emit]\nlanguage = 3\n", &env::temp_dir())
+        .unwrap_err()
+        .into();
+
+    assert!(matches!(err, Error::Config { .. }));
+    assert!(err.to_string().starts_with("invalid configuration: "));
+    assert!(error::Error::source(&err)
+        .unwrap()
+        .to_string()
+        .contains("language"));
+}
+
+#[test]
+fn io_errors_chain_to_their_source() {
+    let err: Error = Config::from_str("", &env::temp_dir().join("piqued-missing-root"))
+        .unwrap_err()
+        .into();
+
+    assert!(matches!(err, Error::Io { .. }), "{:?}", err);
+    let source = error::Error::source(&err).unwrap();
+    assert_eq!(
+        source.downcast_ref::<io::Error>().unwrap().kind(),
+        io::ErrorKind::NotFound
+    );
+}
+
+#[tokio::test]
+async fn connection_errors_chain_to_their_source() {
+    let mut config = Config::default();
+    // Nothing listens on port 1
+    config.postgres.uri = "postgresql://postgres@127.0.0.1:1/postgres".to_string();
+
+    let err: Error = Query::new(Arc::new(config)).await.unwrap_err().into();
+
+    assert!(matches!(err, Error::Connection { .. }), "{:?}", err);
+    assert!(error::Error::source(&err)
+        .unwrap()
+        .downcast_ref::<PostgresError>()
+        .is_some());
+}
+
+#[test]
+fn other_errors_keep_their_message() {
+    let err: Error = Config::from_str("[workspace]\nexclude = [\"[\"]\n", &env::temp_dir())
+        .unwrap_err()
+        .into();
+
+    match err {
+        Error::Other { message } => assert!(message.starts_with("Invalid pattern \"[\"")),
+        other => panic!("expected another error, got {:?}", other),
+    }
+}
+
+// Piqued's own errors go straight into anything built on `std::error::Error`, sources and all
+#[test]
+fn internal_errors_convert_with_question_mark() {
+    fn load() -> Result<Config, Box<dyn error::Error + Send + Sync>> {
+        Ok(Config::from_str(
+            "",
+            &env::temp_dir().join("piqued-missing-root"),
+        )?)
+    }
+
+    let err = load().unwrap_err();
+    assert!(err.source().unwrap().downcast_ref::<io::Error>().is_some());
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn postgres_errors_carry_their_position() {
+    let config = common::test_config(&env::temp_dir()).await;
+
+    let query = Query::new(Arc::new(config)).await.unwrap();
+
+    let stmt = prepare("SELECT piqued_missing_column FROM pg_class;").unwrap();
+    match query.probe_type(&stmt).await.map_err(Error::from) {
+        Err(Error::Postgres { message, position }) => {
+            assert!(message.contains("piqued_missing_column"));
+            assert!(position.is_some());
+        }
+        other => panic!("expected a postgres error, got {:?}", other.err()),
+    }
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use std::{env, fs, path::PathBuf, process::Command};
+
+// A workspace of its own for each test, with a `piqued.toml` pointed at `uri`
+fn workspace(name: &str, uri: &str, queries: &str) -> PathBuf {
+    let root = env::temp_dir().join(format!("piqued-exit-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&root).unwrap();
+    fs::write(
+        root.join("piqued.toml"),
+        format!("[postgres]\nuri = \"{}\"\n", uri),
+    )
+    .unwrap();
+    fs::write(root.join("queries.sql"), queries).unwrap();
+
+    root
+}
+
+fn piqued(root: &PathBuf, args: &[&str]) -> i32 {
+    Command::new(env!("CARGO_BIN_EXE_piqued"))
+        .arg("--quiet")
+        .args(args)
+        .current_dir(root)
+        .output()
+        .unwrap()
+        .status
+        .code()
+        .unwrap()
+}
+
+#[test]
+fn generation_errors_exit_with_1() {
+    // Nothing listens on port 1, so nothing can be generated or checked
+    let root = workspace(
+        "unreachable",
+        "postgresql://postgres@localhost:1/postgres",
+        "-- @name one\nSELECT 1 AS one;\n",
+    );
+
+    assert_eq!(piqued(&root, &[]), 1);
+    assert_eq!(piqued(&root, &["--check"]), 1);
+    assert_eq!(piqued(&root, &["--no-emit"]), 1);
+    assert_eq!(piqued(&root, &["explain", "--name", "one"]), 1);
+    assert_eq!(piqued(&root, &["schema"]), 1);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn config_errors_exit_with_1() {
+    let root = workspace(
+        "config",
+        "postgresql://postgres@localhost:1/postgres",
+        "-- @name one\nSELECT 1 AS one;\n",
+    );
+
+    assert_eq!(piqued(&root, &["--config", "missing.toml", "list"]), 1);
+
+    fs::write(root.join("piqued.toml"), "[postgres\n").unwrap();
+    assert_eq!(piqued(&root, &["list"]), 1);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn drift_exits_with_2_until_regenerated() {
+    let uri = common::test_config(&env::temp_dir()).await.postgres.uri;
+
+    let root = workspace("drift", &uri, "-- @name one\nSELECT 1 AS one;\n");
+
+    assert_eq!(piqued(&root, &["--check"]), 2);
+    assert_eq!(piqued(&root, &[]), 0);
+    assert_eq!(piqued(&root, &["--check"]), 0);
+
+    // Subcommands fail the same way
+    assert_eq!(piqued(&root, &["explain", "--name", "one"]), 0);
+    assert_eq!(piqued(&root, &["explain", "--name", "two"]), 1);
+
+    fs::write(root.join("queries.sql"), "-- @name one\nSELECT 1 AS uno;\n").unwrap();
+    assert_eq!(piqued(&root, &["--check"]), 2);
+
+    // A query that doesn't typecheck is an error, not drift
+    fs::write(
+        root.join("queries.sql"),
+        "-- @name one\nSELECT piqued_missing_column FROM pg_class;\n",
+    )
+    .unwrap();
+    assert_eq!(piqued(&root, &["--no-emit"]), 1);
+
+    // Nor is anything generated for it
+    let generated = fs::read_to_string(root.join("queries.ts")).unwrap();
+    assert_eq!(piqued(&root, &[]), 1);
+    assert_eq!(
+        fs::read_to_string(root.join("queries.ts")).unwrap(),
+        generated
+    );
+
+    fs::remove_dir_all(&root).unwrap();
+}
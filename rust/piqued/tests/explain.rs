@@ -0,0 +1,44 @@
+mod common;
+
+use std::{env, process, sync::Arc};
+
+use piqued::{parser::parser, query::query::Query};
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn analyzing_rolls_back_changes() {
+    let config = common::test_config(&env::temp_dir()).await;
+    let client = common::connect(&config).await;
+
+    let table = format!("piqued_explain_{}", process::id());
+    client
+        .batch_execute(&format!(
+            "CREATE TABLE {table} (id int4 NOT NULL); INSERT INTO {table} VALUES (1), (2);"
+        ))
+        .await
+        .unwrap();
+
+    let query = Query::new(Arc::new(config)).await.unwrap();
+    let sql = format!("DELETE FROM {table};");
+    let parsed = parser::load_file(&sql).unwrap();
+    let prepared =
+        parser::get_prepared_statement(&parsed.statements[0], &parsed.tokens, &sql, || {
+            "query".to_string()
+        })
+        .unwrap();
+
+    let plan = query.explain(&prepared, true).await;
+    let remaining = client
+        .query_one(&format!("SELECT count(*) FROM {table}"), &[])
+        .await
+        .unwrap()
+        .get::<_, i64>(0);
+
+    client
+        .batch_execute(&format!("DROP TABLE {table}"))
+        .await
+        .unwrap();
+
+    assert!(plan.unwrap().contains("\"Actual Rows\""));
+    assert_eq!(remaining, 2);
+}
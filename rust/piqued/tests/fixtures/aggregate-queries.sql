@@ -0,0 +1,2 @@
+-- @name payment_totals
+SELECT payer_id, sum(amount) AS total, count(*) FROM payment GROUP BY payer_id;
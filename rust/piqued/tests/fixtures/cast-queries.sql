@@ -0,0 +1,11 @@
+-- @name casted_columns
+SELECT
+    id::text,
+    amount::numeric(10, 2),
+    created_at::timestamp with time zone,
+    tags::varchar[],
+    status::user_status
+FROM users;
+
+-- @name casted_param
+SELECT id FROM users WHERE created_at > $1::date;
@@ -0,0 +1,13 @@
+-- @name get_company
+SELECT 1 AS one;
+
+-- @name getCompany
+SELECT 2 AS two;
+
+SELECT 3 AS three;
+
+-- @name get_company
+SELECT 4 AS four;
+
+-- @name query_2
+SELECT 5 AS five;
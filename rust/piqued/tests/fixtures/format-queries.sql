@@ -0,0 +1,21 @@
+-- @name active_companies
+-- Every company that's still trading
+-- @params status
+select c.id, c.legal_name, count(e.id) as employees from company c left join employee e on e.company_id = c.id where c.status = $1 and e.id is distinct from null group by c.id order by c.legal_name limit 10;
+
+-- @name rename_company
+update company set legal_name = $2 where id = $1 returning id;
+
+-- @name first_letters
+select left(legal_name, 1) from company union all select right(legal_name, 1) from company;
+
+-- @name half_written
+select id from company where;
+
+-- @name commented
+select id -- the primary key
+from company;
+
+-- @name by_ids
+-- @xarray ids int4
+SELECT id FROM company WHERE id IN (:ids);
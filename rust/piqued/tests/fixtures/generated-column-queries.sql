@@ -0,0 +1,3 @@
+-- @name invoice_totals
+-- `total` is a stored generated column
+SELECT id, subtotal, total FROM invoice WHERE id = $1;
@@ -0,0 +1,3 @@
+SELECT id, name FROM users;
+
+SELECT id FROM users WHERE id = = 1;
@@ -0,0 +1,6 @@
+-- @name first
+SELECT id FROM account;
+-- @name second
+SELECT total FROM invoice WHERE total > 1;
+-- @name third
+SELECT id, (SELECT 1 LIMIT 1) FROM receipt;
@@ -0,0 +1,25 @@
+-- @name account_names
+SELECT a.id, display_name, lower(a.display_name) AS lowered, a FROM account a;
+
+-- @name misspelled_column
+SELECT id, dispaly_name FROM account;
+
+-- @name misspelled_scoped_column
+SELECT a.id, i.totl
+FROM account a JOIN invoice i ON i.account_id = a.id;
+
+-- @name misspelled_filtered_column
+SELECT id, upper(nmae) FROM account WHERE id = $1;
+
+-- @name invoice_counts
+SELECT a.id, (SELECT count(*) FROM invoice i WHERE i.account_id = a.id) AS invoices
+FROM account a;
+
+-- @name correlated_column
+SELECT id, (SELECT display_name FROM invoice LIMIT 1) AS name FROM account;
+
+-- @name cte_columns
+WITH totals AS (SELECT account_id AS owner FROM invoice) SELECT owner FROM totals;
+
+-- @name unfinished
+SELECT nope FROM account WHERE
@@ -0,0 +1,5 @@
+-- @name create_orders
+INSERT INTO orders (user_id, amount) VALUES ($1, lower($2)), (1, 2.5);
+
+-- @name create_order
+INSERT INTO orders VALUES ($1, $2, $3, now());
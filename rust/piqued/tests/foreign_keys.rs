@@ -0,0 +1,68 @@
+mod common;
+
+use std::{env, process, sync::Arc};
+
+use piqued::{
+    lsp::lsp_fmt::format_column,
+    query::query::{ForeignKey, Query},
+};
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn foreign_keys_are_loaded_and_shown() {
+    let mut config = common::test_config(&env::temp_dir()).await;
+    let client = common::connect(&config).await;
+
+    let schema = format!("piqued_foreign_keys_{}", process::id());
+    client
+        .batch_execute(&format!(
+            "
+            CREATE SCHEMA {schema};
+            CREATE TABLE {schema}.company (id int4 PRIMARY KEY, legal_name text);
+            CREATE TABLE {schema}.webhook_address (
+                id int4 PRIMARY KEY,
+                owner_company_id int4 NOT NULL REFERENCES {schema}.company (id),
+                callback_address text
+            );
+            "
+        ))
+        .await
+        .unwrap();
+
+    config.postgres.schema = schema.clone();
+    let query = Query::new(Arc::new(config)).await;
+
+    client
+        .batch_execute(&format!("DROP SCHEMA {schema} CASCADE;"))
+        .await
+        .unwrap();
+
+    let query = query.unwrap();
+    let columns = &query.tables["webhook_address"];
+    let owner = columns
+        .iter()
+        .find(|column| column.name == "owner_company_id")
+        .unwrap();
+
+    assert_eq!(
+        owner.references,
+        Some(ForeignKey {
+            table: "company".to_string(),
+            column: "id".to_string(),
+        })
+    );
+    assert!(columns
+        .iter()
+        .filter(|column| column.name != "owner_company_id")
+        .all(|column| column.references.is_none()));
+
+    assert_eq!(
+        format_column(
+            &owner.name,
+            &owner.column_type.to_string(),
+            owner.nullable,
+            owner.references.as_ref()
+        ),
+        "owner_company_id int4\n→ company.id"
+    );
+}
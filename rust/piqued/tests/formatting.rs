@@ -0,0 +1,74 @@
+use piqued::lsp::formatting::format_file;
+use tower_lsp::lsp_types::{Position, TextEdit};
+
+const FORMAT_QUERIES: &str = include_str!("fixtures/format-queries.sql");
+
+fn offset(contents: &str, position: Position) -> usize {
+    let line_start: usize = contents
+        .split_inclusive('\n')
+        .take(position.line as usize)
+        .map(|line| line.len())
+        .sum();
+
+    line_start + position.character as usize
+}
+
+fn apply(contents: &str, edits: &[TextEdit]) -> String {
+    let mut result = contents.to_string();
+    for edit in edits.iter().rev() {
+        let start = offset(contents, edit.range.start);
+        let end = offset(contents, edit.range.end);
+        result.replace_range(start..end, &edit.new_text);
+    }
+
+    result
+}
+
+#[test]
+fn statements_are_reprinted_one_clause_to_a_line() {
+    let edits = format_file(FORMAT_QUERIES).unwrap();
+    let formatted = apply(FORMAT_QUERIES, &edits);
+
+    // Statements that don't parse, have comments within them, or use templates are left alone
+    assert_eq!(
+        formatted,
+        "-- @name active_companies
+-- Every company that's still trading
+-- @params status
+SELECT c.id, c.legal_name, count(e.id) AS employees
+FROM company c
+LEFT JOIN employee e ON e.company_id = c.id
+WHERE c.status = $1 AND e.id IS DISTINCT FROM NULL
+GROUP BY c.id
+ORDER BY c.legal_name
+LIMIT 10;
+
+-- @name rename_company
+UPDATE company
+SET legal_name = $2
+WHERE id = $1
+RETURNING id;
+
+-- @name first_letters
+SELECT \"left\"(legal_name, 1)
+FROM company
+UNION ALL
+SELECT \"right\"(legal_name, 1)
+FROM company;
+
+-- @name half_written
+select id from company where;
+
+-- @name commented
+select id -- the primary key
+from company;
+
+-- @name by_ids
+-- @xarray ids int4
+SELECT id FROM company WHERE id IN (:ids);
+"
+    );
+
+    // Formatting what's already formatted changes nothing
+    assert!(format_file(&formatted).unwrap().is_empty());
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use common::describe_column;
+use piqued::loose_parser::{parse::ParserContext, parse_cf::LR1Kind};
+
+#[test]
+fn function_calls_reduce_with_arguments() {
+    let contents = include_str!("../../../ts/test-workspace/src/data/function-call-queries.sql");
+    let mut context = ParserContext::new(contents);
+    let parsed = context.parse();
+
+    let columns = parsed
+        .states
+        .iter()
+        .filter_map(|state| match &state.kind {
+            LR1Kind::SelectQuery(select_query) => Some(select_query),
+            _ => None,
+        })
+        .flat_map(|select_query| select_query.columns.iter())
+        .map(|column| describe_column(column))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        columns,
+        vec![
+            "count(*)",
+            "max(length(c.legal_name)) AS longest",
+            "coalesce(callback_address, 'none') AS callback",
+            "now()",
+        ]
+    );
+}
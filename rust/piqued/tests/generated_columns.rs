@@ -0,0 +1,100 @@
+mod common;
+
+use std::{env, process, sync::Arc};
+
+use piqued::{
+    codegen::{
+        codegen::{CodeGenerationContext, CodeGenerator},
+        ts::schema::TSGenerator,
+    },
+    parser::parser,
+    query::query::{ColumnType, Query},
+};
+
+const GENERATED_COLUMN_QUERIES: &str = include_str!("fixtures/generated-column-queries.sql");
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn generated_columns_are_read_but_not_written() {
+    let mut config = common::test_config(&env::temp_dir()).await;
+    let client = common::connect(&config).await;
+
+    let schema = format!("piqued_generated_{}", process::id());
+    client
+        .batch_execute(&format!(
+            "
+            CREATE SCHEMA {schema};
+            CREATE TABLE {schema}.invoice (
+                id int4 PRIMARY KEY,
+                subtotal numeric NOT NULL,
+                tax numeric NOT NULL,
+                total numeric NOT NULL GENERATED ALWAYS AS (subtotal + tax) STORED
+            );
+            "
+        ))
+        .await
+        .unwrap();
+
+    config.postgres.schema = schema.clone();
+    config.workspace.root = Some(env::temp_dir());
+    let config = Arc::new(config);
+    let query = Query::new(config.clone()).await;
+
+    let parsed = parser::load_file(GENERATED_COLUMN_QUERIES).unwrap();
+    let prepared = parser::get_prepared_statement(
+        &parsed.statements[0],
+        &parsed.tokens,
+        GENERATED_COLUMN_QUERIES,
+        || "query".to_string(),
+    )
+    .unwrap();
+    let probed = match &query {
+        Ok(query) => query.probe_type(&prepared).await,
+        Err(e) => Err(e.clone()),
+    };
+
+    client
+        .batch_execute(&format!("DROP SCHEMA {schema} CASCADE;"))
+        .await
+        .unwrap();
+
+    let query = query.unwrap();
+    let generated = query.tables["invoice"]
+        .iter()
+        .map(|column| (column.name.as_str(), column.generated))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        generated,
+        vec![
+            ("id", false),
+            ("subtotal", false),
+            ("tax", false),
+            ("total", true)
+        ]
+    );
+
+    // Selecting it is like selecting any other column
+    let probed = probed.unwrap();
+    assert_eq!(probed.column_names, vec!["id", "subtotal", "total"]);
+    assert_eq!(
+        probed.column_types,
+        vec![
+            ColumnType::from("int4"),
+            ColumnType::from("numeric"),
+            ColumnType::from("numeric")
+        ]
+    );
+    assert_eq!(probed.column_nullable, vec![false, false, false]);
+
+    // But it's left out of the row type that's written
+    let ctx = CodeGenerationContext::new(config, &query);
+    let invoice = TSGenerator::new()
+        .serialize_type(&ctx, &ctx.custom_types_by_name["invoice"])
+        .generated_code;
+    assert!(invoice.contains("\"total\": number;"), "{}", invoice);
+    assert!(
+        invoice.contains("export type writable = Omit<t, \"total\">;"),
+        "{}",
+        invoice
+    );
+}
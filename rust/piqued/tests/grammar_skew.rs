@@ -0,0 +1,23 @@
+use piqued::parser::parser::{self, StatementKind};
+
+#[test]
+fn unparseable_statements_are_sent_as_written() {
+    let contents = include_str!("../../../ts/test-workspace/src/data/grammar-skew-queries.sql");
+    let parsed = parser::load_file(contents).unwrap();
+
+    let stmt = &parsed.statements[0];
+    assert!(stmt.stmt.is_err());
+    assert_eq!(stmt.kind, StatementKind::Unknown);
+
+    let prepared =
+        parser::get_prepared_statement(stmt, &parsed.tokens, contents, || "query".to_string())
+            .unwrap();
+
+    assert_eq!(prepared.details.name, "upsert_company_returning");
+    assert!(prepared.query.stmt.is_none());
+    assert!(prepared.contents.contains("MERGE INTO company c"));
+    assert!(prepared
+        .contents
+        .ends_with("RETURNING merge_action(), c.id"));
+    assert_eq!(prepared.param_count(), 2);
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use std::{env, sync::Arc};
+
+use piqued::{parser::parser, query::query::Query};
+use tower_lsp::lsp_types::{DiagnosticSeverity, Position};
+
+// Planning depends on the data as much as the query, so these lean on the catalog's own indexes
+// rather than on whatever tables the test database happens to have
+const INDEX_QUERIES: &str = include_str!("../../../ts/test-workspace/src/data/index-queries.sql");
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn plans_report_their_indexes() {
+    let config = common::test_config(&env::temp_dir()).await;
+    let query = Query::new(Arc::new(config)).await.unwrap();
+
+    let parsed = parser::load_file(INDEX_QUERIES).unwrap();
+    let mut indexes = vec![];
+    for stmt in &parsed.statements {
+        let prepared = parser::get_prepared_statement(stmt, &parsed.tokens, INDEX_QUERIES, || {
+            "query".to_string()
+        })
+        .unwrap();
+
+        assert_eq!(
+            prepared.details.index.as_deref(),
+            Some("pg_class_oid_index")
+        );
+        indexes.push(query.plan_indexes(&prepared).await.unwrap());
+    }
+
+    assert_eq!(
+        indexes,
+        vec![
+            vec!["pg_class_oid_index".to_string()],
+            vec!["pg_class_relname_nsp_index".to_string()],
+        ]
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn unused_index_hints_are_warned_about_when_enabled() {
+    let mut config = common::test_config(&env::temp_dir()).await;
+    config.lsp.check_index_hints = true;
+
+    let path = "file:///index-queries.sql";
+    let mut workspace = common::connected_workspace(Arc::new(config), env::temp_dir()).await;
+    workspace.patch_file(path.to_string(), INDEX_QUERIES.to_string());
+
+    let diagnostics = workspace.get_diagnostics(path).await.unwrap();
+    let summary: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic.severity.unwrap(),
+                diagnostic.range.start,
+                diagnostic.message.as_str(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        summary,
+        vec![(
+            DiagnosticSeverity::WARNING,
+            Position::new(4, 0),
+            "The query plan doesn't use the expected index pg_class_oid_index"
+        )]
+    );
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use common::describe_column;
+use piqued::loose_parser::{
+    parse::ParserContext,
+    parse_cf::{InsertStatement, LR1Kind},
+};
+use tower_lsp::lsp_types::Position;
+
+const INSERT_QUERIES: &str = include_str!("../../../ts/test-workspace/src/data/insert-queries.sql");
+
+#[test]
+fn inserts_keep_their_target() {
+    let mut context = ParserContext::new(INSERT_QUERIES);
+    let parsed = context.parse();
+
+    let inserts = parsed
+        .states
+        .iter()
+        .filter_map(|state| match &state.kind {
+            LR1Kind::InsertStmt(insert) => Some(insert.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let summary = inserts
+        .iter()
+        .map(|insert| {
+            let InsertStatement {
+                table,
+                alias,
+                columns,
+                returning,
+                ..
+            } = insert.as_ref();
+
+            (
+                table.as_str(),
+                alias.as_deref(),
+                columns.clone(),
+                returning
+                    .iter()
+                    .flatten()
+                    .map(|column| describe_column(column))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        summary,
+        vec![
+            (
+                "company",
+                Some("c"),
+                vec!["id".to_string(), "legal_name".to_string()],
+                vec!["c.id".to_string(), "legal_name".to_string()],
+            ),
+            ("company", None, vec![], vec!["*".to_string()]),
+        ]
+    );
+}
+
+#[test]
+fn insert_is_in_scope_for_columns_and_returning() {
+    let mut context = ParserContext::new(INSERT_QUERIES);
+    let parsed = context.parse();
+
+    let in_insert = |line, character| {
+        parsed
+            .inspect(&Position::new(line, character))
+            .unwrap()
+            .iter()
+            .any(|state| matches!(state.kind, LR1Kind::InsertStmt(_)))
+    };
+
+    // `legal_name` in the column list
+    assert!(in_insert(2, 33));
+    // `legal_name` in `RETURNING`
+    assert!(in_insert(3, 18));
+}
+
+#[test]
+fn values_pair_with_insert_columns() {
+    const VALUES_QUERIES: &str = include_str!("fixtures/values-queries.sql");
+
+    let mut context = ParserContext::new(VALUES_QUERIES);
+    let parsed = context.parse();
+
+    let value_index = |line, character| {
+        let stack = parsed.inspect(&Position::new(line, character)).unwrap();
+        let position = stack.first().unwrap().start;
+
+        stack.iter().find_map(|state| match &state.kind {
+            LR1Kind::InsertStmt(insert) => insert.values.iter().find_map(|row| {
+                row.iter()
+                    .position(|(start, end)| (*start..*end).contains(&position))
+            }),
+            _ => None,
+        })
+    };
+
+    // `$1` and the `$2` within `lower($2)`
+    assert_eq!(value_index(1, 45), Some(0));
+    assert_eq!(value_index(1, 55), Some(1));
+    // The second row lines up the same way
+    assert_eq!(value_index(1, 65), Some(1));
+    // Without a column list, `$3` is the table's third column
+    assert_eq!(value_index(4, 35), Some(2));
+    // The column list itself isn't a value
+    assert_eq!(value_index(1, 21), None);
+}
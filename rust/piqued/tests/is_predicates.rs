@@ -0,0 +1,23 @@
+mod common;
+
+use common::describe_columns;
+
+#[test]
+fn is_predicates_are_postfix() {
+    let contents = include_str!("../../../ts/test-workspace/src/data/is-predicate-queries.sql");
+    assert_eq!(
+        describe_columns(contents),
+        vec![
+            "id",
+            "(legal_name IS NULL) AS unnamed",
+            "((legal_name IS NOT NULL) AND (id > 0))",
+            "((id > 10) IS TRUE)",
+            "((id > 10) IS NOT TRUE)",
+            "((id > 10) IS FALSE)",
+            "((id > 10) IS NOT FALSE)",
+            "(((id < 10) IS UNKNOWN) OR ((id < 10) IS NOT UNKNOWN))",
+            "id",
+            "(NOT ((id + 1) IS NULL))",
+        ]
+    );
+}
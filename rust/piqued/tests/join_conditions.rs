@@ -0,0 +1,95 @@
+mod common;
+
+use std::{env, fs, process};
+
+use futures::StreamExt;
+use tower_lsp::{
+    lsp_types::{HoverParams, Url},
+    LanguageServer,
+};
+
+const JOIN_CONDITION_QUERIES: &str =
+    include_str!("../../../ts/test-workspace/src/data/join-condition-queries.sql");
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn join_conditions_see_every_relation_to_their_left() {
+    let config = common::test_config(&env::temp_dir()).await;
+    let client = common::connect(&config).await;
+
+    let schema = format!("piqued_join_conditions_{}", process::id());
+    client
+        .batch_execute(&format!(
+            "
+            CREATE SCHEMA {schema};
+            CREATE TABLE {schema}.company (id int4 PRIMARY KEY, legal_name text NOT NULL);
+            CREATE TABLE {schema}.webhook_address (
+                id int4 PRIMARY KEY,
+                owner_company_id int4 NOT NULL,
+                callback_address text
+            );
+            "
+        ))
+        .await
+        .unwrap();
+
+    let root = env::temp_dir().join(format!("piqued_join_conditions_{}", process::id()));
+    fs::create_dir_all(&root).unwrap();
+    fs::write(
+        root.join("piqued.toml"),
+        format!(
+            "[postgres]\nuri = \"{}\"\nschema = \"{}\"\n\n[lsp]\nschemaChannel = \"\"\n",
+            config.postgres.uri, schema
+        ),
+    )
+    .unwrap();
+
+    let service = common::language_server(&root).await;
+    let backend = service.inner();
+
+    let file_uri = Url::from_file_path(root.join("queries.sql")).unwrap();
+    common::open_document(backend, &file_uri, JOIN_CONDITION_QUERIES).await;
+
+    let mut hovers = vec![];
+    for (needle, offset) in [
+        // The first join sees its own table and the one it's joined to
+        ("w.owner_company_id", 2),
+        ("c.id\nJOIN", 2),
+        // The second sees both of the tables before it too
+        ("s.owner_company_id", 2),
+        ("c.id AND", 2),
+        ("w.id AND", 2),
+        ("legal_name <>", 0),
+    ] {
+        let hover = backend
+            .hover(HoverParams {
+                text_document_position_params: common::document_position(
+                    &file_uri,
+                    common::position_of(JOIN_CONDITION_QUERIES, needle, offset),
+                ),
+                work_done_progress_params: Default::default(),
+            })
+            .await
+            .unwrap();
+        hovers.push(format!("{:?}", hover));
+    }
+
+    client
+        .batch_execute(&format!("DROP SCHEMA {schema} CASCADE;"))
+        .await
+        .unwrap();
+    fs::remove_dir_all(&root).unwrap();
+
+    let expected = [
+        "w.owner_company_id int4",
+        "c.id int4",
+        "s.owner_company_id int4",
+        "c.id int4",
+        "w.id int4",
+        "c.legal_name text",
+    ];
+    for (hover, expected) in hovers.iter().zip(expected) {
+        assert!(hover.contains("(column)"), "{}", hover);
+        assert!(hover.contains(expected), "{} in {}", expected, hover);
+    }
+}
@@ -0,0 +1,61 @@
+use piqued::loose_parser::{
+    parse::ParserContext,
+    parse_cf::{JoinExpression, JoinKind, LR1Kind, LR1State},
+};
+use std::sync::Arc;
+
+fn collect_joins(states: &Vec<Arc<LR1State>>, joins: &mut Vec<Arc<JoinExpression>>) {
+    for state in states {
+        if let LR1Kind::JoinExpression(join) = &state.kind {
+            joins.push(join.clone());
+        }
+
+        collect_joins(&state.children, joins);
+    }
+}
+
+#[test]
+fn conditionless_and_using_joins() {
+    let contents = include_str!("../../../ts/test-workspace/src/data/cartesian-join-queries.sql");
+    let mut context = ParserContext::new(contents);
+    let parsed = context.parse();
+
+    let mut joins = vec![];
+    collect_joins(&parsed.states, &mut joins);
+
+    let summary = joins
+        .iter()
+        .map(|join| {
+            (
+                join.kind.clone(),
+                join.condition.is_some(),
+                join.using.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        summary,
+        vec![
+            (JoinKind::Inner, false, vec![]),
+            (JoinKind::Inner, false, vec!["id".to_string()]),
+        ]
+    );
+}
+
+#[test]
+fn using_join_reduces_with_columns() {
+    let contents = include_str!("../../../ts/test-workspace/src/data/join-queries.sql");
+    let mut context = ParserContext::new(contents);
+    let parsed = context.parse();
+
+    let mut joins = vec![];
+    collect_joins(&parsed.states, &mut joins);
+
+    let using = joins
+        .iter()
+        .map(|join| join.using.clone())
+        .collect::<Vec<_>>();
+
+    assert_eq!(using, vec![vec![], vec!["company_id".to_string()]]);
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use std::{env, process, sync::Arc};
+
+use piqued::{parser::parser, query::query::Query};
+use tokio_postgres::config::Host;
+
+// The test database's URL, spelled as `host=... port=... dbname=...` instead
+fn key_value_dsn(uri: &str, options: &str) -> String {
+    let config: tokio_postgres::Config = uri.parse().unwrap();
+
+    let mut pairs = vec![];
+    for host in config.get_hosts() {
+        match host {
+            Host::Tcp(host) => pairs.push(format!("host={}", host)),
+            Host::Unix(path) => pairs.push(format!("host={}", path.display())),
+        }
+    }
+    for port in config.get_ports() {
+        pairs.push(format!("port={}", port));
+    }
+    if let Some(user) = config.get_user() {
+        pairs.push(format!("user={}", user));
+    }
+    if let Some(password) = config.get_password() {
+        pairs.push(format!("password={}", String::from_utf8_lossy(password)));
+    }
+    if let Some(dbname) = config.get_dbname() {
+        pairs.push(format!("dbname={}", dbname));
+    }
+    pairs.push(format!("options='{}'", options));
+
+    pairs.join(" ")
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn key_value_connection_strings_pass_options_along() {
+    let mut config = common::test_config(&env::temp_dir()).await;
+    let client = common::connect(&config).await;
+
+    // Only reachable through the `search_path` given in the connection string's options
+    let schema = format!("piqued_dsn_{}", process::id());
+    client
+        .batch_execute(&format!(
+            "
+            CREATE SCHEMA {schema};
+            CREATE FUNCTION {schema}.dsn_label() RETURNS text AS 'SELECT ''dsn''' LANGUAGE sql;
+            "
+        ))
+        .await
+        .unwrap();
+
+    config.postgres.uri = key_value_dsn(
+        &config.postgres.uri,
+        &format!("-c search_path={},public", schema),
+    );
+    let query = Query::new(Arc::new(config)).await;
+
+    let sql = "-- @name dsn_label\nSELECT dsn_label() AS label;\n";
+    let parsed = parser::load_file(sql).unwrap();
+    let prepared =
+        parser::get_prepared_statement(&parsed.statements[0], &parsed.tokens, sql, || {
+            "query".to_string()
+        })
+        .unwrap();
+    let probed = match &query {
+        Ok(query) => query.probe_type(&prepared).await,
+        Err(e) => Err(e.clone()),
+    };
+
+    client
+        .batch_execute(&format!("DROP SCHEMA {schema} CASCADE;"))
+        .await
+        .unwrap();
+
+    query.unwrap();
+    assert_eq!(probed.unwrap().column_names, vec!["label".to_string()]);
+}
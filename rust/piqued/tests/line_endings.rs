@@ -0,0 +1,110 @@
+mod common;
+
+use std::{env, fs, process};
+
+use piqued::parser::parser;
+use tower_lsp::{
+    lsp_types::{HoverParams, Position, Range, Url},
+    LanguageServer,
+};
+
+// Saved with `\r\n` line endings, which would shift every column after the first line if the `\r`s
+// were counted as part of the line
+const CRLF_QUERIES: &str = include_str!("../../../ts/test-workspace/src/data/crlf-queries.sql");
+
+fn statement_ranges(contents: &str) -> Vec<Range> {
+    parser::load_file(contents)
+        .unwrap()
+        .statements
+        .iter()
+        .map(|stmt| stmt.range)
+        .collect()
+}
+
+// `\r\n` and a lone `\r` both end a line, just like `\n`
+#[test]
+fn statement_ranges_ignore_the_kind_of_line_ending() {
+    let lf = CRLF_QUERIES.replace("\r\n", "\n");
+    let cr = CRLF_QUERIES.replace("\r\n", "\r");
+
+    let expected = vec![Range::new(Position::new(0, 0), Position::new(6, 44))];
+    assert_eq!(statement_ranges(&lf), expected);
+    assert_eq!(statement_ranges(CRLF_QUERIES), expected);
+    assert_eq!(statement_ranges(&cr), expected);
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn hover_lines_up_with_crlf_line_endings() {
+    assert!(CRLF_QUERIES.contains("\r\n"));
+
+    let config = common::test_config(&env::temp_dir()).await;
+    let client = common::connect(&config).await;
+
+    let schema = format!("piqued_line_endings_{}", process::id());
+    client
+        .batch_execute(&format!(
+            "
+            CREATE SCHEMA {schema};
+            CREATE TABLE {schema}.company (id int4 PRIMARY KEY, legal_name text NOT NULL);
+            CREATE TABLE {schema}.webhook_address (
+                id int4 PRIMARY KEY,
+                owner_company_id int4 NOT NULL,
+                callback_address text
+            );
+            "
+        ))
+        .await
+        .unwrap();
+
+    let root = env::temp_dir().join(format!("piqued_line_endings_{}", process::id()));
+    fs::create_dir_all(&root).unwrap();
+    fs::write(
+        root.join("piqued.toml"),
+        format!(
+            "[postgres]\nuri = \"{}\"\nschema = \"{}\"\n\n[lsp]\nschemaChannel = \"\"\n",
+            config.postgres.uri, schema
+        ),
+    )
+    .unwrap();
+
+    let service = common::language_server(&root).await;
+    let backend = service.inner();
+
+    let file_uri = Url::from_file_path(root.join("queries.sql")).unwrap();
+    common::open_document(backend, &file_uri, CRLF_QUERIES).await;
+
+    let mut hovers = vec![];
+    for (needle, offset) in [
+        ("w.callback_address", 2),
+        ("c.legal_name", 2),
+        // Right at the end of the last line, where any drift has added up the most
+        ("w.owner_company_id;", 17),
+    ] {
+        let position = common::position_of(CRLF_QUERIES, needle, offset);
+        let hover = backend
+            .hover(HoverParams {
+                text_document_position_params: common::document_position(&file_uri, position),
+                work_done_progress_params: Default::default(),
+            })
+            .await
+            .unwrap();
+        hovers.push(format!("{:?}", hover));
+    }
+
+    client
+        .batch_execute(&format!("DROP SCHEMA {schema} CASCADE;"))
+        .await
+        .unwrap();
+    fs::remove_dir_all(&root).unwrap();
+
+    let expected = [
+        "\"callback_address text\"",
+        "\"legal_name text\"",
+        "\"w.owner_company_id int4\"",
+    ];
+    for (hover, expected) in hovers.iter().zip(expected) {
+        assert!(hover.contains("(column)"), "{}", hover);
+        assert!(hover.contains(expected), "{} in {}", expected, hover);
+    }
+}
@@ -0,0 +1,74 @@
+use std::{env, fs, path::PathBuf, process, process::Command, sync::Arc};
+
+use piqued::{config::config::Config, workspace::workspace::Workspace};
+
+#[tokio::test]
+async fn lists_sample_queries() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../ts/test-workspace/src/data");
+    let config = Config::load(&None, &root).await.unwrap();
+    let workspace_root = config.workspace.root.clone().unwrap();
+
+    // Listing only reads files, so there's no need for the database to be up
+    let workspace = Workspace::new(Arc::new(config), workspace_root).await;
+    let queries = workspace.list_queries().await.unwrap();
+
+    let summary = |name: &str| {
+        queries
+            .iter()
+            .find(|query| query.name == name)
+            .map(|query| {
+                (
+                    query
+                        .path
+                        .file_name()
+                        .unwrap()
+                        .to_str()
+                        .unwrap()
+                        .to_string(),
+                    query.line,
+                    query.params,
+                )
+            })
+    };
+
+    assert_eq!(
+        summary("company_by_optional_id"),
+        Some(("param-queries.sql".to_string(), 1, 1))
+    );
+    assert_eq!(
+        summary("reflect_2"),
+        Some(("simple-queries.sql".to_string(), 4, 2))
+    );
+    assert_eq!(
+        summary("webhook_callbacks"),
+        Some(("function-call-queries.sql".to_string(), 7, 0))
+    );
+}
+
+#[test]
+fn json_output_is_only_json() {
+    let root = env::temp_dir().join(format!("piqued-list-json-{}", process::id()));
+    fs::create_dir_all(&root).unwrap();
+    // Nothing listens on port 1, which listing doesn't need anyway
+    fs::write(
+        root.join("piqued.toml"),
+        "[postgres]\nuri = \"postgresql://postgres@localhost:1/postgres\"\n",
+    )
+    .unwrap();
+    fs::write(root.join("queries.sql"), "-- @name one\nSELECT 1 AS one;\n").unwrap();
+
+    // Even with everything else the command line has to say turned on
+    let output = Command::new(env!("CARGO_BIN_EXE_piqued"))
+        .args(["--verbose", "list", "--format", "json"])
+        .current_dir(&root)
+        .output()
+        .unwrap();
+
+    fs::remove_dir_all(&root).unwrap();
+
+    let queries: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap_or_else(|e| {
+        panic!("{}: {}", e, String::from_utf8_lossy(&output.stdout));
+    });
+    assert_eq!(queries[0]["name"], "one");
+    assert!(!output.stderr.is_empty());
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::describe_select;
+
+#[test]
+fn chained_operators_associate_left() {
+    assert_eq!(describe_select("SELECT a AND b AND c"), "((a AND b) AND c)");
+    assert_eq!(describe_select("SELECT a OR b OR c"), "((a OR b) OR c)");
+}
+
+#[test]
+fn and_binds_tighter_than_or() {
+    assert_eq!(describe_select("SELECT a OR b AND c"), "(a OR (b AND c))");
+    assert_eq!(describe_select("SELECT a AND b OR c"), "((a AND b) OR c)");
+    assert_eq!(
+        describe_select("SELECT a OR b OR c AND d AND e"),
+        "((a OR b) OR ((c AND d) AND e))"
+    );
+}
+
+// `NOT a AND b` and `NOT a = b` themselves are in operator_precedence.rs
+#[test]
+fn not_binds_tighter_than_logical_operators() {
+    assert_eq!(describe_select("SELECT a AND NOT b"), "(a AND (NOT b))");
+    assert_eq!(describe_select("SELECT NOT a OR b"), "((NOT a) OR b)");
+    assert_eq!(
+        describe_select("SELECT NOT NOT a AND b"),
+        "((NOT (NOT a)) AND b)"
+    );
+    assert_eq!(
+        describe_select("SELECT a OR NOT b AND c"),
+        "(a OR ((NOT b) AND c))"
+    );
+}
+
+#[test]
+fn comparisons_bind_tighter_than_logical_operators() {
+    assert_eq!(
+        describe_select("SELECT a AND b = c AND d"),
+        "((a AND (b = c)) AND d)"
+    );
+    assert_eq!(
+        describe_select("SELECT a = 1 AND b = 2 OR c = 3"),
+        "(((a = 1) AND (b = 2)) OR (c = 3))"
+    );
+    assert_eq!(
+        describe_select("SELECT a AND b + c * d OR e"),
+        "((a AND (b + (c * d))) OR e)"
+    );
+}
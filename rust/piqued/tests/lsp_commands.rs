@@ -0,0 +1,55 @@
+mod common;
+
+use std::{env, fs, process};
+
+use piqued::lsp::lsp::GENERATE_COMMAND;
+use serde_json::Value;
+use tower_lsp::{
+    lsp_types::{ExecuteCommandParams, Url},
+    LanguageServer,
+};
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn generate_command() {
+    let uri = common::test_config(&env::temp_dir()).await.postgres.uri;
+
+    let root = env::temp_dir().join(format!("piqued_lsp_commands_{}", process::id()));
+    fs::create_dir_all(&root).unwrap();
+    fs::write(
+        root.join("piqued.toml"),
+        format!("[postgres]\nuri = \"{}\"\n", uri),
+    )
+    .unwrap();
+    fs::write(
+        root.join("queries.sql"),
+        "-- @name answer\nSELECT 42 AS answer;\n",
+    )
+    .unwrap();
+
+    let service = common::language_server(&root).await;
+    let backend = service.inner();
+
+    let unknown = backend
+        .execute_command(ExecuteCommandParams {
+            command: "piqued.unknown".to_string(),
+            ..Default::default()
+        })
+        .await;
+
+    let file_uri = Url::from_file_path(root.join("queries.sql")).unwrap();
+    let generated = backend
+        .execute_command(ExecuteCommandParams {
+            command: GENERATE_COMMAND.to_string(),
+            arguments: vec![Value::String(file_uri.to_string())],
+            ..Default::default()
+        })
+        .await;
+
+    let query_file = fs::read_to_string(root.join("queries.ts"));
+    fs::remove_dir_all(&root).unwrap();
+
+    assert!(unknown.is_err());
+    assert!(generated.is_ok());
+    assert!(query_file.unwrap().contains("export const Answer"));
+}
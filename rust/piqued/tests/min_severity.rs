@@ -0,0 +1,66 @@
+use std::{env, sync::Arc};
+
+use piqued::{
+    config::config::{Config, Severity},
+    workspace::workspace::Workspace,
+};
+use tower_lsp::lsp_types::DiagnosticSeverity;
+
+// One of each: a `SELECT *` warning, a parse error, and (since nothing listens on port 1) the note
+// that type checking is off
+const QUERIES: &str = "SELECT * FROM company;\nSELECT id FROM WHERE;\n";
+
+async fn severities(min_severity: Severity) -> Vec<DiagnosticSeverity> {
+    let mut config = Config::default();
+    config.postgres.uri = "postgresql://postgres@127.0.0.1:1/postgres".to_string();
+    config.lsp.warn_select_star = true;
+    config.lsp.min_severity = min_severity;
+
+    let path = "file:///min-severity.sql";
+    let mut workspace = Workspace::new(Arc::new(config), env::temp_dir()).await;
+    workspace.patch_file(path.to_string(), QUERIES.to_string());
+
+    let mut severities = workspace
+        .get_diagnostics(path)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|diagnostic| diagnostic.severity.unwrap())
+        .collect::<Vec<_>>();
+    severities.sort();
+    severities.dedup();
+
+    severities
+}
+
+#[tokio::test]
+async fn everything_is_reported_by_default() {
+    assert_eq!(
+        severities(Severity::default()).await,
+        vec![
+            DiagnosticSeverity::ERROR,
+            DiagnosticSeverity::WARNING,
+            DiagnosticSeverity::INFORMATION,
+        ]
+    );
+}
+
+#[tokio::test]
+async fn less_severe_diagnostics_are_dropped() {
+    assert_eq!(
+        severities(Severity::Information).await,
+        vec![
+            DiagnosticSeverity::ERROR,
+            DiagnosticSeverity::WARNING,
+            DiagnosticSeverity::INFORMATION,
+        ]
+    );
+    assert_eq!(
+        severities(Severity::Warning).await,
+        vec![DiagnosticSeverity::ERROR, DiagnosticSeverity::WARNING]
+    );
+    assert_eq!(
+        severities(Severity::Error).await,
+        vec![DiagnosticSeverity::ERROR]
+    );
+}
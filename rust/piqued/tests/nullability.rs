@@ -0,0 +1,69 @@
+mod common;
+
+use std::{env, process, sync::Arc};
+
+use piqued::{parser::parser, query::query::Query};
+
+const NULL_HANDLING_QUERIES: &str =
+    include_str!("../../../ts/test-workspace/src/data/null-handling-queries.sql");
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn null_handling_functions() {
+    let mut config = common::test_config(&env::temp_dir()).await;
+    let client = common::connect(&config).await;
+
+    let schema = format!("piqued_nullability_{}", process::id());
+    client
+        .batch_execute(&format!(
+            "
+            CREATE SCHEMA {schema};
+            CREATE TABLE {schema}.company (id int4 PRIMARY KEY, legal_name text NOT NULL);
+            CREATE TABLE {schema}.webhook_address (
+                id int4 PRIMARY KEY,
+                owner_company_id int4,
+                callback_address text NOT NULL
+            );
+            "
+        ))
+        .await
+        .unwrap();
+
+    config.postgres.schema = schema.clone();
+    let query = Query::new(Arc::new(config)).await.unwrap();
+
+    let parsed = parser::load_file(NULL_HANDLING_QUERIES).unwrap();
+    let prepared = parser::get_prepared_statement(
+        &parsed.statements[0],
+        &parsed.tokens,
+        NULL_HANDLING_QUERIES,
+        || "query".to_string(),
+    )
+    .unwrap();
+    let probed = query.probe_type(&prepared).await;
+
+    client
+        .batch_execute(&format!("DROP SCHEMA {schema} CASCADE;"))
+        .await
+        .unwrap();
+
+    let probed = probed.unwrap();
+    let columns = probed
+        .column_names
+        .iter()
+        .map(|name| name.as_str())
+        .zip(probed.column_nullable.iter().copied())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        columns,
+        vec![
+            ("id", false),
+            ("legal_name", true),
+            ("owner_name", false),
+            ("callback_address", true),
+            ("owner_id", false),
+            ("nullable_owner_id", true),
+        ]
+    );
+}
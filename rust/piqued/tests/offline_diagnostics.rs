@@ -0,0 +1,38 @@
+use std::{env, sync::Arc};
+
+use piqued::{config::config::Config, workspace::workspace::Workspace};
+use tower_lsp::lsp_types::{DiagnosticSeverity, Position, Range};
+
+const OFFLINE_QUERIES: &str = include_str!("fixtures/offline-queries.sql");
+
+#[tokio::test]
+async fn unreachable_database_still_reports_parse_errors() {
+    let mut config = Config::load(&None, &env::temp_dir()).await.unwrap();
+    // Nothing listens on port 1, so the connection is refused straight away
+    config.postgres.uri = "postgresql://postgres@localhost:1/postgres".to_string();
+
+    let path = "file:///offline-queries.sql";
+    let mut workspace = Workspace::new(Arc::new(config), env::temp_dir()).await;
+    workspace.patch_file(path.to_string(), OFFLINE_QUERIES.to_string());
+
+    let diagnostics = workspace.get_diagnostics(path).await.unwrap();
+    let summary: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.severity.unwrap(), diagnostic.range))
+        .collect();
+
+    assert_eq!(
+        summary,
+        vec![
+            (DiagnosticSeverity::INFORMATION, Range::default()),
+            (
+                DiagnosticSeverity::ERROR,
+                Range::new(Position::new(2, 32), Position::new(2, 33))
+            ),
+        ]
+    );
+    assert!(diagnostics[0]
+        .message
+        .starts_with("Type checking is disabled"));
+    assert_eq!(diagnostics[1].message, "Error parsing query at \"=\"");
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::{describe_columns, describe_select};
+
+#[test]
+fn multiplication_binds_tighter_than_addition() {
+    assert_eq!(describe_select("SELECT a + b * c"), "(a + (b * c))");
+    assert_eq!(describe_select("SELECT a * b + c"), "((a * b) + c)");
+    assert_eq!(describe_select("SELECT a - b * -c"), "(a - (b * (- c)))");
+}
+
+#[test]
+fn arithmetic_associates_left() {
+    assert_eq!(describe_select("SELECT a - b - c"), "((a - b) - c)");
+    assert_eq!(describe_select("SELECT a / b * c"), "((a / b) * c)");
+    assert_eq!(describe_select("SELECT a || b || c"), "((a || b) || c)");
+}
+
+// Postgres groups `^` from the left too, so `2 ^ 3 ^ 2` is 64 rather than 512
+#[test]
+fn exponentiation_associates_left_and_binds_tightest() {
+    assert_eq!(describe_select("SELECT a ^ b ^ c"), "((a ^ b) ^ c)");
+    assert_eq!(describe_select("SELECT a * b ^ c"), "(a * (b ^ c))");
+}
+
+#[test]
+fn unary_minus_binds_tighter_than_binary_operators() {
+    assert_eq!(describe_select("SELECT -a - b"), "((- a) - b)");
+    assert_eq!(describe_select("SELECT -a * b"), "((- a) * b)");
+    assert_eq!(describe_select("SELECT -a ^ b"), "((- a) ^ b)");
+    assert_eq!(describe_select("SELECT a - -b"), "(a - (- b))");
+}
+
+#[test]
+fn arithmetic_binds_tighter_than_comparisons() {
+    assert_eq!(describe_select("SELECT a < b + c"), "(a < (b + c))");
+    assert_eq!(
+        describe_select("SELECT a + b = c * d"),
+        "((a + b) = (c * d))"
+    );
+}
+
+#[test]
+fn not_binds_tighter_than_and() {
+    assert_eq!(describe_select("SELECT NOT a AND b"), "((NOT a) AND b)");
+    assert_eq!(describe_select("SELECT NOT a = b"), "(NOT (a = b))");
+}
+
+// Whether a minus is unary depends on what's before it: nothing, a comma or another operator
+// leaves it unary, while a finished expression (parenthesized or not) makes it binary
+#[test]
+fn minus_is_unary_only_where_an_operand_is_expected() {
+    assert_eq!(describe_select("SELECT -1"), "(- 1)");
+    assert_eq!(describe_select("SELECT a - -1"), "(a - (- 1))");
+    assert_eq!(describe_columns("SELECT a, -1"), vec!["a", "(- 1)"]);
+    assert_eq!(describe_select("SELECT f(-1, a - 1)"), "f((- 1), (a - 1))");
+}
+
+#[test]
+fn minus_after_parentheses_is_binary() {
+    assert_eq!(describe_select("SELECT (a) - 1"), "(a - 1)");
+    assert_eq!(describe_select("SELECT (a + b) - -1"), "((a + b) - (- 1))");
+    assert_eq!(describe_select("SELECT f(a) - 1"), "(f(a) - 1)");
+}
@@ -0,0 +1,165 @@
+use piqued::loose_parser::{
+    parse::ParserContext,
+    parse_cf::{ColumnExpression, Expression, LR1Kind},
+};
+
+// Parses `SELECT <column>` and hands back its expression
+fn select_expression(column: &str) -> Expression {
+    let mut context = ParserContext::new(&format!("SELECT {}", column));
+    let parsed = context.parse();
+
+    let select_query = match parsed.states.as_slice() {
+        [state] => match &state.kind {
+            LR1Kind::SelectQuery(select_query) => select_query.clone(),
+            other => panic!("Expected a select query, got {:?}", other),
+        },
+        states => panic!("Expected a single state, got {:#?}", states),
+    };
+
+    match select_query.columns[0].as_ref() {
+        ColumnExpression::Unnamed(expr) => expr.as_ref().clone(),
+        ColumnExpression::Named(_, expr) => expr.as_ref().clone(),
+    }
+}
+
+// The type of `a <operator> b`, given the types of `a` and `b`
+fn binop_type(operator: &str, left: Option<&str>, right: Option<&str>) -> Option<String> {
+    match select_expression(&format!("a {} b", operator)) {
+        Expression::BinopExpression(binop) => binop.operator.result_type(left, right),
+        other => panic!("Expected a binary operator, got {:?}", other),
+    }
+}
+
+fn unop_type(operator: &str, operand: Option<&str>) -> Option<String> {
+    match select_expression(&format!("{} a", operator)) {
+        Expression::UnopExpression(unop) => unop.operator.result_type(operand),
+        other => panic!("Expected a unary operator, got {:?}", other),
+    }
+}
+
+#[test]
+fn arithmetic_promotes_numeric_types() {
+    assert_eq!(
+        binop_type("+", Some("int4"), Some("int4")).as_deref(),
+        Some("int4")
+    );
+    assert_eq!(
+        binop_type("*", Some("numeric"), Some("int4")).as_deref(),
+        Some("numeric")
+    );
+    assert_eq!(
+        binop_type("-", Some("int8"), Some("int2")).as_deref(),
+        Some("int8")
+    );
+    assert_eq!(
+        binop_type("/", Some("float8"), Some("numeric")).as_deref(),
+        Some("float8")
+    );
+    assert_eq!(
+        binop_type("%", Some("integer"), Some("bigint")).as_deref(),
+        Some("int8")
+    );
+    assert_eq!(
+        binop_type("^", Some("int4"), Some("int4")).as_deref(),
+        Some("float8")
+    );
+    assert_eq!(
+        binop_type("^", Some("numeric"), Some("int4")).as_deref(),
+        Some("numeric")
+    );
+}
+
+#[test]
+fn date_arithmetic() {
+    assert_eq!(
+        binop_type("-", Some("date"), Some("date")).as_deref(),
+        Some("int4")
+    );
+    assert_eq!(
+        binop_type("+", Some("date"), Some("int4")).as_deref(),
+        Some("date")
+    );
+    assert_eq!(
+        binop_type("+", Some("date"), Some("interval")).as_deref(),
+        Some("timestamp")
+    );
+    assert_eq!(
+        binop_type("-", Some("timestamptz"), Some("timestamptz")).as_deref(),
+        Some("interval")
+    );
+    assert_eq!(
+        binop_type("+", Some("interval"), Some("timestamptz")).as_deref(),
+        Some("timestamptz")
+    );
+    assert_eq!(
+        binop_type("*", Some("interval"), Some("int4")).as_deref(),
+        Some("interval")
+    );
+}
+
+#[test]
+fn concatenation_is_text_unless_arrays_or_jsonb() {
+    assert_eq!(
+        binop_type("||", Some("text"), Some("int4")).as_deref(),
+        Some("text")
+    );
+    assert_eq!(
+        binop_type("||", Some("varchar"), Some("text")).as_deref(),
+        Some("text")
+    );
+    assert_eq!(
+        binop_type("||", Some("text[]"), Some("text")).as_deref(),
+        Some("text[]")
+    );
+    assert_eq!(
+        binop_type("||", Some("jsonb"), Some("jsonb")).as_deref(),
+        Some("jsonb")
+    );
+}
+
+#[test]
+fn json_access() {
+    assert_eq!(
+        binop_type("->", Some("jsonb"), Some("text")).as_deref(),
+        Some("jsonb")
+    );
+    assert_eq!(
+        binop_type("->>", Some("json"), Some("int4")).as_deref(),
+        Some("text")
+    );
+    assert_eq!(binop_type("->", Some("int4"), Some("int4")), None);
+}
+
+#[test]
+fn comparisons_are_boolean_whatever_their_operands() {
+    for operator in [
+        "=", "<>", "<", ">=", "LIKE", "ILIKE", "~", "@>", "AND", "OR", "IS",
+    ] {
+        assert_eq!(
+            binop_type(operator, None, None).as_deref(),
+            Some("bool"),
+            "{}",
+            operator
+        );
+    }
+}
+
+#[test]
+fn unresolved_operands_are_unknown() {
+    assert_eq!(binop_type("+", None, Some("int4")), None);
+    assert_eq!(binop_type("||", Some("text"), None), None);
+    assert_eq!(binop_type("+", Some("text"), Some("int4")), None);
+}
+
+#[test]
+fn unary_operators() {
+    assert_eq!(unop_type("-", Some("numeric")).as_deref(), Some("numeric"));
+    assert_eq!(unop_type("-", Some("bigint")).as_deref(), Some("int8"));
+    assert_eq!(
+        unop_type("-", Some("interval")).as_deref(),
+        Some("interval")
+    );
+    assert_eq!(unop_type("-", Some("text")), None);
+    assert_eq!(unop_type("NOT", None).as_deref(), Some("bool"));
+    assert_eq!(unop_type("|/", Some("int4")).as_deref(), Some("float8"));
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use std::{collections::HashMap, env, sync::Arc};
+
+use piqued::{
+    codegen::{
+        codegen::{CodeGenerationContext, CodeGenerator, QueryContext},
+        python::schema::PythonGenerator,
+        rust::schema::RustGenerator,
+        ts::schema::TSGenerator,
+    },
+    config::config::Language,
+    parser::parser,
+    query::query::Query,
+};
+
+// These lean on the catalog, so that they don't depend on whatever tables the test database has
+const PARAM_REUSE_QUERIES: &str =
+    include_str!("../../../ts/test-workspace/src/data/param-reuse-queries.sql");
+
+// Each query's parameter count, along with the code generated for it
+async fn generate(language: Language) -> Vec<(usize, String)> {
+    let mut config = common::test_config(&env::temp_dir()).await;
+    config.emit.language = language;
+    let config = Arc::new(config);
+
+    let query = Query::new(config.clone()).await.unwrap();
+    let ctx = CodeGenerationContext::from_schema(config, HashMap::new(), HashMap::new());
+
+    let parsed = parser::load_file(PARAM_REUSE_QUERIES).unwrap();
+    let mut generated = vec![];
+    for stmt in &parsed.statements {
+        let prepared =
+            parser::get_prepared_statement(stmt, &parsed.tokens, PARAM_REUSE_QUERIES, || {
+                "query".to_string()
+            })
+            .unwrap();
+        let probe = query.probe_type(&prepared).await.unwrap();
+        let param_count = probe.param_count();
+        assert_eq!(param_count, prepared.param_count());
+
+        let query_context =
+            QueryContext::from_stmt(stmt, &parsed.tokens, PARAM_REUSE_QUERIES, probe).unwrap();
+        let code = match language {
+            Language::Typescript => TSGenerator::new().serialize_query(&ctx, &query_context),
+            Language::Rust => RustGenerator::new().serialize_query(&ctx, &query_context),
+            Language::Python => PythonGenerator::new().serialize_query(&ctx, &query_context),
+        };
+
+        generated.push((param_count, code.generated_code));
+    }
+
+    generated
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn reused_placeholders_are_one_parameter() {
+    let generated = generate(Language::Typescript).await;
+
+    let (param_count, code) = &generated[0];
+    assert_eq!(*param_count, 1);
+    assert!(code.contains("params: { \"arg1\": number }"), "{}", code);
+    assert!(code.contains(", [params.arg1]);"), "{}", code);
+
+    let (param_count, code) = &generated[1];
+    assert_eq!(*param_count, 2);
+    assert!(
+        code.contains("params: { \"minPages\": number, \"kind\": string }"),
+        "{}",
+        code
+    );
+    assert!(
+        code.contains(", [params.minPages, params.kind]);"),
+        "{}",
+        code
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn reused_placeholders_are_bound_once() {
+    let generated = generate(Language::Rust).await;
+
+    let (_, code) = &generated[0];
+    assert!(
+        code.contains("pub fn pages_at_least(arg1: i32)"),
+        "{}",
+        code
+    );
+    assert_eq!(code.matches(".bind(").count(), 1, "{}", code);
+
+    let (_, code) = &generated[1];
+    assert_eq!(code.matches(".bind(").count(), 2, "{}", code);
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn reused_placeholders_are_passed_once() {
+    let generated = generate(Language::Python).await;
+
+    let (_, code) = &generated[0];
+    assert!(
+        code.contains("async def pages_at_least(conn: asyncpg.Connection, arg1: int)"),
+        "{}",
+        code
+    );
+    assert!(
+        code.contains("await conn.fetch(PAGES_AT_LEAST_QUERY, arg1)"),
+        "{}",
+        code
+    );
+
+    let (_, code) = &generated[1];
+    assert!(
+        code.contains("await conn.fetch(PAGES_BETWEEN_QUERY, min_pages, kind)"),
+        "{}",
+        code
+    );
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use std::{env, sync::Arc};
+
+use piqued::{
+    error::error::Error,
+    parser::parser::{self, ParsedPreparedQuery},
+    query::query::{ColumnType, Query},
+};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Position};
+
+// These lean on the catalog, so that they don't depend on whatever tables the test database has
+const TYPED_PARAM_QUERIES: &str =
+    include_str!("../../../ts/test-workspace/src/data/typed-param-queries.sql");
+
+fn prepared_statements() -> Vec<ParsedPreparedQuery> {
+    let parsed = parser::load_file(TYPED_PARAM_QUERIES).unwrap();
+
+    parsed
+        .statements
+        .iter()
+        .map(|stmt| {
+            parser::get_prepared_statement(stmt, &parsed.tokens, TYPED_PARAM_QUERIES, || {
+                "query".to_string()
+            })
+            .unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn annotations_are_split_from_names() {
+    let details = prepared_statements()
+        .into_iter()
+        .map(|prepared| (prepared.details.params, prepared.details.param_types))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        details,
+        vec![
+            (
+                Some(vec!["page_size".to_string()]),
+                vec![Some("int8".to_string())]
+            ),
+            (
+                Some(vec!["relname".to_string()]),
+                vec![Some("text".to_string())]
+            ),
+            (
+                Some(vec!["namespace".to_string(), "relname".to_string()]),
+                vec![None, Some("name".to_string())]
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn annotations_override_inferred_types() {
+    let config = common::test_config(&env::temp_dir()).await;
+    let query = Query::new(Arc::new(config)).await.unwrap();
+
+    let mut args = vec![];
+    let mut mismatches = vec![];
+    for prepared in prepared_statements() {
+        args.push(query.probe_type(&prepared).await.unwrap().args);
+        mismatches.push(query.param_type_mismatches(&prepared).await.unwrap());
+    }
+
+    assert_eq!(
+        args,
+        vec![
+            vec![ColumnType::from("int8")],
+            vec![ColumnType::from("text")],
+            vec![ColumnType::from("oid"), ColumnType::from("name")],
+        ]
+    );
+    assert_eq!(
+        mismatches,
+        vec![vec![], vec![(0, ColumnType::from("name"))], vec![]]
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn unknown_annotated_types_are_errors() {
+    let config = common::test_config(&env::temp_dir()).await;
+    let query = Query::new(Arc::new(config)).await.unwrap();
+
+    let contents = "-- @name nonsense\n-- @params value:no_such_type\nSELECT $1 AS value;\n";
+    let parsed = parser::load_file(contents).unwrap();
+    let prepared =
+        parser::get_prepared_statement(&parsed.statements[0], &parsed.tokens, contents, || {
+            "query".to_string()
+        })
+        .unwrap();
+
+    let error = Error::from(query.probe_type(&prepared).await.unwrap_err());
+    assert!(
+        matches!(&error, Error::Other { message } if message == "Unknown type \"no_such_type\" in @params"),
+        "{:?}",
+        error
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn mismatched_annotations_are_warned_about() {
+    let config = common::test_config(&env::temp_dir()).await;
+
+    let path = "file:///typed-param-queries.sql";
+    let mut workspace = common::connected_workspace(Arc::new(config), env::temp_dir()).await;
+    workspace.patch_file(path.to_string(), TYPED_PARAM_QUERIES.to_string());
+
+    let expected = vec![(
+        DiagnosticSeverity::WARNING,
+        Position::new(4, 0),
+        "Parameter relname is annotated as text, but Postgres infers name".to_string(),
+    )];
+    assert_eq!(
+        summarize(workspace.get_diagnostics(path).await.unwrap()),
+        expected
+    );
+
+    // Editing a later statement keeps what was learned about the earlier ones
+    workspace.patch_file(
+        path.to_string(),
+        format!("{}\nSELECT 1 AS one;\n", TYPED_PARAM_QUERIES),
+    );
+    assert_eq!(
+        summarize(workspace.get_diagnostics(path).await.unwrap()),
+        expected
+    );
+}
+
+fn summarize(diagnostics: Vec<Diagnostic>) -> Vec<(DiagnosticSeverity, Position, String)> {
+    diagnostics
+        .into_iter()
+        .map(|diagnostic| {
+            (
+                diagnostic.severity.unwrap(),
+                diagnostic.range.start,
+                diagnostic.message,
+            )
+        })
+        .collect()
+}
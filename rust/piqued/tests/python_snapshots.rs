@@ -0,0 +1,163 @@
+mod common;
+
+use std::path::PathBuf;
+
+use common::{array_of, column, named, query_context};
+use piqued::{
+    codegen::{
+        codegen::{CodeGenerationContext, CodeGenerator, QueryContext},
+        python::schema::PythonGenerator,
+    },
+    config::config::{Config, Language},
+    query::query::{Column, CustomType, EnumType, ProbeResponse},
+};
+
+fn company_columns() -> Vec<Column> {
+    vec![
+        column("id", named("int4"), false),
+        column("legal_name", named("text"), false),
+        column("status", named("company_status"), false),
+        column("tags", array_of("text"), true),
+        column("revenue", named("numeric"), true),
+        column("created_at", named("timestamptz"), false),
+        column("from", named("uuid"), false),
+    ]
+}
+
+fn context(config: Config) -> CodeGenerationContext<'static> {
+    let mut config = config;
+    config.emit.language = Language::Python;
+
+    common::schema_context(
+        config,
+        company_columns(),
+        &["active", "archived", "on hold", "2fa"],
+    )
+}
+
+fn company_by_status() -> QueryContext {
+    query_context(
+        "-- @name companies_by_status\n-- Every company in the given state\n-- @params status\nSELECT id, legal_name, tags, revenue FROM company WHERE status = $1;\n",
+        ProbeResponse::default()
+            .arg("company_status")
+            .column("id", "int4", false)
+            .column("legal_name", "text", false)
+            .column("tags", "text[]", true)
+            .column("revenue", "numeric", true),
+    )
+}
+
+fn rename_company() -> QueryContext {
+    query_context(
+        "-- @name rename_company\n-- Renames a company\n--\n-- Leaves \"\"\" alone\n-- @params id legal_name\nUPDATE company SET legal_name = $2 WHERE id = $1;\n",
+        ProbeResponse::default().arg("int4").arg("text"),
+    )
+}
+
+#[test]
+fn types() {
+    let ctx = context(Config::default());
+    let generator = PythonGenerator::new();
+
+    let mut types = ctx
+        .custom_types_by_name
+        .values()
+        .cloned()
+        .collect::<Vec<_>>();
+    types.sort_by(|a, b| a.name().cmp(b.name()));
+
+    let prefix = generator.serialize_type_prefix(&ctx, &types).unwrap();
+    insta::assert_snapshot!("python_type_prefix", prefix);
+
+    let composite = generator.serialize_type(&ctx, &ctx.custom_types_by_name["company"]);
+    insta::assert_snapshot!("python_composite_type", composite.generated_code);
+
+    let enum_ = generator.serialize_type(&ctx, &ctx.custom_types_by_name["company_status"]);
+    insta::assert_snapshot!("python_enum_type", enum_.generated_code);
+}
+
+#[test]
+fn queries() {
+    let ctx = context(Config::default());
+    let generator = PythonGenerator::new();
+
+    let queries = vec![company_by_status(), rename_company()];
+    let prefix = generator.serialize_query_prefix(&ctx, &queries).unwrap();
+    insta::assert_snapshot!("python_query_prefix", prefix);
+
+    let res = generator.serialize_query(&ctx, &queries[0]);
+    assert_eq!(res.requires_import, vec!["CompanyStatus".to_string()]);
+    insta::assert_snapshot!("python_query", res.generated_code);
+
+    let res = generator.serialize_query(&ctx, &queries[1]);
+    insta::assert_snapshot!("python_query_without_rows", res.generated_code);
+}
+
+#[test]
+fn names() {
+    let ctx = context(Config::default());
+    let generator = PythonGenerator::new();
+
+    // The second parameter has no name of its own
+    let query = query_context(
+        "-- @name getUserById\n-- @params conn\nSELECT $1::int4 AS userId, $2::text AS label;\n",
+        ProbeResponse::default()
+            .arg("int4")
+            .arg("text")
+            .column("userId", "int4", false)
+            .column("label", "text", false),
+    );
+    insta::assert_snapshot!(
+        "python_query_names",
+        generator.serialize_query(&ctx, &query).generated_code
+    );
+
+    let status = CustomType::Enum(EnumType {
+        oid: 3,
+        name: "status".to_string(),
+        values: vec![
+            "active".to_string(),
+            "ACTIVE".to_string(),
+            "on-hold".to_string(),
+            "on_hold".to_string(),
+        ],
+    });
+    insta::assert_snapshot!(
+        "python_enum_member_names",
+        generator.serialize_type(&ctx, &status).generated_code
+    );
+}
+
+#[test]
+fn relative_imports() {
+    let ctx = context(Config::default());
+    let generator = PythonGenerator::new();
+    let identifiers = vec!["Company".to_string(), "CompanyStatus".to_string()];
+
+    let imports = ["./postgres", "../postgres", "../../db/postgres"]
+        .iter()
+        .map(|path| {
+            generator
+                .serialize_import(&ctx, &PathBuf::from(path), &identifiers)
+                .generated_code
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        imports,
+        vec![
+            "from .postgres import Company, CompanyStatus",
+            "from ..postgres import Company, CompanyStatus",
+            "from ...db.postgres import Company, CompanyStatus",
+        ]
+    );
+
+    let mut config = Config::default();
+    config.emit.type_file_module = Some("app.db.postgres".to_string());
+    let ctx = context(config);
+    let import = generator.serialize_import(&ctx, &PathBuf::from("./postgres"), &identifiers);
+    assert_eq!(
+        import.generated_code,
+        "from app.db.postgres import Company, CompanyStatus"
+    );
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use std::{env, process, sync::Arc};
+
+use piqued::{
+    loose_parser::{
+        parse::ParserContext,
+        parse_cf::{LR1Kind, TableLike},
+    },
+    query::query::Query,
+};
+
+const QUALIFIED_QUERIES: &str =
+    include_str!("../../../ts/test-workspace/src/data/qualified-queries.sql");
+
+#[test]
+fn qualified_tables_keep_their_schema() {
+    let mut context = ParserContext::new(QUALIFIED_QUERIES);
+    let parsed = context.parse();
+
+    let from = parsed
+        .states
+        .iter()
+        .find_map(|state| match &state.kind {
+            LR1Kind::SelectQuery(select) => select.from.clone(),
+            _ => None,
+        })
+        .unwrap();
+    let relations = from
+        .iter()
+        .flat_map(|from| from.relations())
+        .map(|from| match from.table.as_ref() {
+            TableLike::Table(table) => (table.clone(), from.scope_name().map(String::from)),
+            other => panic!("expected a table, got {:?}", other),
+        })
+        .collect::<Vec<_>>();
+
+    // Without an alias, a qualified table goes by its bare name
+    assert_eq!(
+        relations,
+        vec![
+            ("public.company".to_string(), Some("company".to_string())),
+            ("public.company".to_string(), Some("c".to_string())),
+        ]
+    );
+
+    let insert = parsed
+        .states
+        .iter()
+        .find_map(|state| match &state.kind {
+            LR1Kind::InsertStmt(insert) => Some(insert.clone()),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(insert.table, "public.company");
+    assert_eq!(insert.columns, vec!["id", "legal_name"]);
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn qualified_names_resolve_in_the_configured_schema_and_public() {
+    let mut config = common::test_config(&env::temp_dir()).await;
+    let client = common::connect(&config).await;
+
+    let schema = format!("piqued_qualified_{}", process::id());
+    let public_table = format!("piqued_qualified_{}", process::id());
+    client
+        .batch_execute(&format!(
+            "CREATE SCHEMA {schema}; CREATE TABLE {schema}.widget (id int4 NOT NULL);
+             CREATE TABLE public.{public_table} (label text);"
+        ))
+        .await
+        .unwrap();
+
+    config.postgres.schema = schema.clone();
+    let query = Query::new(Arc::new(config)).await;
+
+    client
+        .batch_execute(&format!(
+            "DROP SCHEMA {schema} CASCADE; DROP TABLE public.{public_table};"
+        ))
+        .await
+        .unwrap();
+
+    let query = query.unwrap();
+    let columns = |name: &str| {
+        query
+            .table(name)
+            .map(|columns| columns.iter().map(|c| c.name.clone()).collect::<Vec<_>>())
+    };
+
+    assert_eq!(columns("widget"), Some(vec!["id".to_string()]));
+    assert_eq!(columns(&format!("{schema}.widget")), columns("widget"));
+    assert_eq!(columns("public.widget"), None);
+    assert_eq!(columns("elsewhere.widget"), None);
+
+    // `public` isn't the configured schema, so its tables are only found by a qualified name
+    assert_eq!(columns(&public_table), None);
+    assert_eq!(
+        columns(&format!("public.{public_table}")),
+        Some(vec!["label".to_string()])
+    );
+    assert!(!query.tables.contains_key(&public_table));
+
+    assert!(columns("pg_catalog.pg_class").is_some());
+}
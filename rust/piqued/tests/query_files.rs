@@ -0,0 +1,92 @@
+use std::{collections::HashMap, env, path::PathBuf, process, sync::Arc};
+
+use piqued::{codegen::codegen::CodeGenerationContext, config::config::Config};
+
+async fn write_files(root: &PathBuf, files: &[&str]) {
+    for file in files {
+        let path = root.join(file);
+        tokio::fs::create_dir_all(path.parent().unwrap())
+            .await
+            .unwrap();
+        tokio::fs::write(&path, "SELECT 1;\n").await.unwrap();
+    }
+}
+
+// The query files found under a fresh directory holding `files`, relative to it
+async fn locate(name: &str, config: &str, files: &[&str]) -> Vec<String> {
+    let root = env::temp_dir().join(format!("piqued-query-files-{}-{}", name, process::id()));
+    tokio::fs::create_dir_all(&root).await.unwrap();
+    write_files(&root, files).await;
+
+    let config = Arc::new(Config::from_str(config, &root).unwrap());
+    let root = config.workspace.root.clone().unwrap();
+    let codegen = CodeGenerationContext::from_schema(config, HashMap::new(), HashMap::new());
+
+    let mut located = codegen
+        .locate_query_files()
+        .await
+        .iter()
+        .map(|path| {
+            path.strip_prefix(&root)
+                .unwrap()
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect::<Vec<_>>();
+    located.sort();
+
+    tokio::fs::remove_dir_all(&root).await.unwrap();
+    located
+}
+
+const FILES: [&str; 8] = [
+    "a.sql",
+    "b.PSQL",
+    "c.sql.tmpl",
+    "d.txt",
+    "nested/e.pg",
+    "node_modules/pkg/f.sql",
+    "migrations/001.sql",
+    "src/migrations/002.sql",
+];
+
+#[tokio::test]
+async fn default_extensions() {
+    let located = locate("defaults", "", &FILES).await;
+
+    assert_eq!(
+        located,
+        vec![
+            "a.sql",
+            "b.PSQL",
+            "migrations/001.sql",
+            "nested/e.pg",
+            "node_modules/pkg/f.sql",
+            "src/migrations/002.sql",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn configured_extensions() {
+    let config = "[workspace]\nqueryExtensions = [\"sql.tmpl\", \".PG\"]";
+    let located = locate("extensions", config, &FILES).await;
+
+    assert_eq!(located, vec!["c.sql.tmpl", "nested/e.pg"]);
+}
+
+#[tokio::test]
+async fn excluded_directories_are_skipped() {
+    // A bare name matches at any depth, while a path only matches from the root
+    let config = "[workspace]\nexclude = [\"node_modules\", \"/migrations\", \"nested/*.pg\"]";
+    let located = locate("exclude", config, &FILES).await;
+
+    assert_eq!(located, vec!["a.sql", "b.PSQL", "src/migrations/002.sql"]);
+}
+
+#[test]
+fn invalid_exclude_patterns_are_rejected() {
+    let result = Config::from_str("[workspace]\nexclude = [\"[\"]", &env::temp_dir());
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,60 @@
+use piqued::loose_parser::{
+    parse::ParserContext,
+    parse_cf::{ColumnExpression, Expression, FromExpression, LR1Kind, TableLike},
+};
+
+#[test]
+fn bare_identifiers_are_folded_and_quoted_ones_kept() {
+    let contents =
+        include_str!("../../../ts/test-workspace/src/data/quoted-identifier-queries.sql");
+    let mut context = ParserContext::new(contents);
+    let parsed = context.parse();
+
+    let select_query = parsed
+        .states
+        .iter()
+        .find_map(|state| match &state.kind {
+            LR1Kind::SelectQuery(select_query) => Some(select_query.clone()),
+            _ => None,
+        })
+        .unwrap();
+
+    let columns = select_query
+        .columns
+        .iter()
+        .map(|column| match column.as_ref() {
+            ColumnExpression::Unnamed(expr) => (None, expr.clone()),
+            ColumnExpression::Named(name, expr) => (Some(name.clone()), expr.clone()),
+        })
+        .map(|(name, expr)| match expr.as_ref() {
+            Expression::BinopExpression(binop) => match (binop.left.as_ref(), binop.right.as_ref())
+            {
+                (Expression::Identifier(left), Expression::Identifier(right)) => {
+                    (name, format!("{}.{}", left, right))
+                }
+                other => panic!("Unexpected operands {:?}", other),
+            },
+            Expression::Identifier(column) => (name, column.clone()),
+            other => panic!("Unexpected column {:?}", other),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        columns,
+        vec![
+            (None, "c.id".to_string()),
+            (Some("legalName".to_string()), "legal_name".to_string()),
+        ]
+    );
+
+    let from = select_query.from.clone().unwrap();
+    assert_eq!(
+        from.iter()
+            .map(|from| from.as_ref().clone())
+            .collect::<Vec<FromExpression>>(),
+        vec![FromExpression {
+            table: TableLike::Table("company".to_string()).into(),
+            alias: Some("c".to_string()),
+        }]
+    );
+}
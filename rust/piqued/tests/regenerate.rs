@@ -0,0 +1,301 @@
+mod common;
+
+use std::{collections::HashMap, env, path::PathBuf, process, sync::Arc, time::SystemTime};
+
+use common::{column, named};
+use piqued::{
+    codegen::{codegen::CodeGenerationContext, ts::schema::TSGenerator},
+    config::config::Config,
+    query::query::{CompositeType, CustomType},
+    workspace::workspace::GenerationMode,
+};
+
+async fn modified(paths: &[PathBuf]) -> Vec<SystemTime> {
+    let mut times = vec![];
+    for path in paths {
+        let metadata = tokio::fs::metadata(path).await.unwrap();
+        times.push(metadata.modified().unwrap());
+    }
+
+    times
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn regenerating_unchanged_queries_writes_nothing() {
+    let root = env::temp_dir().join(format!("piqued-regenerate-{}", process::id()));
+    tokio::fs::create_dir_all(&root).await.unwrap();
+    tokio::fs::write(
+        root.join("first.sql"),
+        "-- @name first_query\nSELECT 1 AS one;\n",
+    )
+    .await
+    .unwrap();
+    tokio::fs::write(
+        root.join("second.sql"),
+        "-- @name second_query\nSELECT 2 AS two;\n",
+    )
+    .await
+    .unwrap();
+
+    let mut config = common::test_config(&root).await;
+    config.emit.type_file = "./types".to_string();
+    config.emit.table_file = Some("./tables".to_string());
+    config.emit.registry = Some("./registry".to_string());
+    let config = Arc::new(config);
+
+    let workspace = common::connected_workspace(config.clone(), root.clone()).await;
+
+    let generated = [
+        "types.ts",
+        "tables.ts",
+        "registry.ts",
+        "first.ts",
+        "second.ts",
+    ]
+    .iter()
+    .map(|file| root.join(file))
+    .collect::<Vec<_>>();
+
+    workspace.gen_code(GenerationMode::Full).await.unwrap();
+    let before = modified(&generated).await;
+
+    // Make sure a write would show up as a different modification time
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    workspace.gen_code(GenerationMode::Full).await.unwrap();
+    let unchanged = modified(&generated).await;
+
+    tokio::fs::write(
+        root.join("second.sql"),
+        "-- @name second_query\nSELECT 3 AS three;\n",
+    )
+    .await
+    .unwrap();
+    workspace.gen_code(GenerationMode::Full).await.unwrap();
+    let changed = modified(&generated).await;
+
+    tokio::fs::remove_dir_all(&root).await.unwrap();
+
+    assert_eq!(before, unchanged);
+    assert_eq!(before[..4], changed[..4]);
+    assert_ne!(before[4], changed[4]);
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn checking_reports_stale_files_without_writing() {
+    let root = env::temp_dir().join(format!("piqued-check-{}", process::id()));
+    tokio::fs::create_dir_all(&root).await.unwrap();
+    tokio::fs::write(
+        root.join("queries.sql"),
+        "-- @name answer\nSELECT 42 AS answer;\n",
+    )
+    .await
+    .unwrap();
+
+    let mut config = common::test_config(&root).await;
+    config.emit.type_file = "./types".to_string();
+    let config = Arc::new(config);
+
+    let workspace = common::connected_workspace(config.clone(), root.clone()).await;
+
+    let missing = workspace
+        .check_code(GenerationMode::Full)
+        .await
+        .unwrap()
+        .changed_files;
+    let written = tokio::fs::try_exists(root.join("queries.ts"))
+        .await
+        .unwrap();
+
+    workspace.gen_code(GenerationMode::Full).await.unwrap();
+    let fresh = workspace
+        .check_code(GenerationMode::Full)
+        .await
+        .unwrap()
+        .changed_files;
+
+    tokio::fs::write(
+        root.join("queries.sql"),
+        "-- @name answer\nSELECT 'forty-two' AS answer;\n",
+    )
+    .await
+    .unwrap();
+    let stale = workspace
+        .check_code(GenerationMode::Full)
+        .await
+        .unwrap()
+        .changed_files;
+
+    tokio::fs::remove_dir_all(&root).await.unwrap();
+
+    assert_eq!(
+        missing,
+        vec![root.join("types.ts"), root.join("queries.ts")]
+    );
+    assert!(!written);
+    assert_eq!(fresh, Vec::<PathBuf>::new());
+    assert_eq!(stale, vec![root.join("queries.ts")]);
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn large_query_files_are_written_whole() {
+    let root = env::temp_dir().join(format!("piqued-large-{}", process::id()));
+    tokio::fs::create_dir_all(&root).await.unwrap();
+
+    let count = 2000;
+    let queries = (0..count)
+        .map(|i| format!("-- @name query_{i}\nSELECT {i} AS value_{i}, 'row {i}' AS label;\n"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    tokio::fs::write(root.join("queries.sql"), queries)
+        .await
+        .unwrap();
+
+    let mut config = common::test_config(&root).await;
+    config.emit.type_file = "./types".to_string();
+    config.emit.registry = Some("./registry".to_string());
+    let config = Arc::new(config);
+
+    let workspace = common::connected_workspace(config.clone(), root.clone()).await;
+
+    let missing = workspace
+        .check_code(GenerationMode::Full)
+        .await
+        .unwrap()
+        .changed_files;
+    workspace.gen_code(GenerationMode::Full).await.unwrap();
+    let fresh = workspace
+        .check_code(GenerationMode::Full)
+        .await
+        .unwrap()
+        .changed_files;
+
+    let generated = tokio::fs::read_to_string(root.join("queries.ts"))
+        .await
+        .unwrap();
+    let registry = tokio::fs::read_to_string(root.join("registry.ts"))
+        .await
+        .unwrap();
+
+    let mut leftovers = vec![];
+    let mut entries = tokio::fs::read_dir(&root).await.unwrap();
+    while let Some(entry) = entries.next_entry().await.unwrap() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.ends_with(".piqued-tmp") {
+            leftovers.push(name);
+        }
+    }
+
+    tokio::fs::remove_dir_all(&root).await.unwrap();
+
+    assert_eq!(
+        missing,
+        vec![
+            root.join("types.ts"),
+            root.join("queries.ts"),
+            root.join("registry.ts")
+        ]
+    );
+    assert_eq!(fresh, Vec::<PathBuf>::new());
+    assert_eq!(leftovers, Vec::<String>::new());
+    for i in 0..count {
+        assert!(generated.contains(&format!("export namespace Query{i} {{")));
+        assert!(registry.contains(&format!("\"query{i}\": QueriesQueries.Query{i},")));
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn unwritable_files_are_errors() {
+    let root = env::temp_dir().join(format!("piqued-unwritable-{}", process::id()));
+    tokio::fs::create_dir_all(&root).await.unwrap();
+    tokio::fs::write(
+        root.join("queries.sql"),
+        "-- @name answer\nSELECT 42 AS answer;\n",
+    )
+    .await
+    .unwrap();
+
+    let mut config = common::test_config(&root).await;
+    config.emit.type_file = "./missing/types".to_string();
+    let config = Arc::new(config);
+
+    let workspace = common::connected_workspace(config.clone(), root.clone()).await;
+
+    let result = workspace.gen_code(GenerationMode::Full).await;
+    tokio::fs::remove_dir_all(&root).await.unwrap();
+
+    assert!(result.is_err());
+}
+
+// Generates just a table file for a one-column `company` table, without needing the database
+fn table_file_context(root: &PathBuf) -> CodeGenerationContext<'static> {
+    let mut config = Config::default();
+    config.workspace.root = Some(root.clone());
+    config.emit.table_file = Some("./tables".to_string());
+
+    let columns = vec![column("id", named("int4"), false)];
+    let company = CustomType::Composite(CompositeType {
+        oid: 1,
+        name: "company".to_string(),
+        fields: columns.clone(),
+    });
+
+    CodeGenerationContext::from_schema(
+        Arc::new(config),
+        HashMap::from([("company".to_string(), columns)]),
+        HashMap::from([("company".to_string(), Arc::new(company))]),
+    )
+}
+
+async fn table_file_is_stale(root: &PathBuf) -> bool {
+    let ctx = table_file_context(root).check_only();
+    ctx.generate_table_file(&TSGenerator::new()).await.unwrap();
+
+    !ctx.changed_files().is_empty()
+}
+
+#[tokio::test]
+async fn files_are_compared_byte_for_byte() {
+    let root = env::temp_dir().join(format!("piqued-compare-{}", process::id()));
+    tokio::fs::create_dir_all(&root).await.unwrap();
+    let path = root.join("tables.ts");
+
+    let missing = table_file_is_stale(&root).await;
+    let written = tokio::fs::try_exists(&path).await.unwrap();
+
+    table_file_context(&root)
+        .generate_table_file(&TSGenerator::new())
+        .await
+        .unwrap();
+    let generated = tokio::fs::read_to_string(&path).await.unwrap();
+    let fresh = table_file_is_stale(&root).await;
+
+    // The same length, but not the same bytes
+    tokio::fs::write(&path, generated.replace("Company", "Cumpany"))
+        .await
+        .unwrap();
+    let same_length = table_file_is_stale(&root).await;
+
+    // Everything that was generated, and then some
+    tokio::fs::write(&path, format!("{}// extra\n", generated))
+        .await
+        .unwrap();
+    let longer = table_file_is_stale(&root).await;
+
+    tokio::fs::write(&path, &generated[..generated.len() - 1])
+        .await
+        .unwrap();
+    let shorter = table_file_is_stale(&root).await;
+
+    tokio::fs::remove_dir_all(&root).await.unwrap();
+
+    assert!(missing);
+    assert!(!written);
+    assert!(!fresh);
+    assert!(same_length);
+    assert!(longer);
+    assert!(shorter);
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use std::{env, process, sync::Arc};
+
+use piqued::workspace::workspace::GenerationMode;
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn registry_includes_queries_from_every_file() {
+    let root = env::temp_dir().join(format!("piqued-registry-{}", process::id()));
+    tokio::fs::create_dir_all(root.join("nested"))
+        .await
+        .unwrap();
+    tokio::fs::write(
+        root.join("first.sql"),
+        "-- @name first_query\nSELECT 1 AS one;\n",
+    )
+    .await
+    .unwrap();
+    tokio::fs::write(
+        root.join("nested/second.sql"),
+        "-- @name second_query\nSELECT 'two' AS two;\n",
+    )
+    .await
+    .unwrap();
+
+    let mut config = common::test_config(&root).await;
+    config.emit.type_file = "./types".to_string();
+    config.emit.registry = Some("./registry".to_string());
+    let config = Arc::new(config);
+
+    let workspace = common::connected_workspace(config.clone(), root.clone()).await;
+
+    workspace.gen_code(GenerationMode::Full).await.unwrap();
+    let registry = tokio::fs::read_to_string(root.join("registry.ts"))
+        .await
+        .unwrap();
+    tokio::fs::remove_dir_all(&root).await.unwrap();
+
+    assert!(registry.contains("import * as FirstQueries from \"./first\";"));
+    assert!(registry.contains("import * as SecondQueries from \"./nested/second\";"));
+    assert!(registry.contains("\"firstQuery\": FirstQueries.FirstQuery,"));
+    assert!(registry.contains("\"secondQuery\": SecondQueries.SecondQuery,"));
+}
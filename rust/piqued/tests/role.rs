@@ -0,0 +1,85 @@
+mod common;
+
+use std::{env, process, sync::Arc};
+
+use piqued::{
+    parser::parser,
+    query::query::{ProbeResponse, Query},
+};
+
+const ROLE_QUERIES: &str = include_str!("../../../ts/test-workspace/src/data/role-queries.sql");
+
+#[test]
+fn role_is_parsed() {
+    let parsed = parser::load_file(ROLE_QUERIES).unwrap();
+    let details =
+        parser::get_prepared_statement(&parsed.statements[0], &parsed.tokens, ROLE_QUERIES, || {
+            "unnamed".to_string()
+        })
+        .unwrap()
+        .details;
+
+    assert_eq!(details.role, Some("app_user".to_string()));
+    assert_eq!(
+        details.comment,
+        "Probed as the application's role, so that row-level security applies"
+    );
+}
+
+async fn probe(query: &Query, sql: &str) -> Result<ProbeResponse, String> {
+    let parsed = parser::load_file(sql).unwrap();
+    let prepared =
+        parser::get_prepared_statement(&parsed.statements[0], &parsed.tokens, sql, || {
+            "query".to_string()
+        })
+        .unwrap();
+
+    query.probe_type(&prepared).await.map_err(|e| e.to_string())
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn probes_as_role() {
+    let mut config = common::test_config(&env::temp_dir()).await;
+    let client = common::connect(&config).await;
+
+    let schema = format!("piqued_role_{}", process::id());
+    client
+        .batch_execute(&format!(
+            "
+            CREATE SCHEMA {schema};
+            CREATE TABLE {schema}.secret (id int4 NOT NULL);
+            CREATE ROLE {schema} NOLOGIN;
+            "
+        ))
+        .await
+        .unwrap();
+
+    // The role isn't granted the schema, so it can't even look the table up
+    config.postgres.schema = schema.clone();
+    let query = Query::new(Arc::new(config)).await.unwrap();
+
+    let as_owner = probe(&query, &format!("SELECT id FROM {schema}.secret;")).await;
+    let as_role = probe(
+        &query,
+        &format!("-- @role {schema}\nSELECT id FROM {schema}.secret;"),
+    )
+    .await;
+    let current_user = query
+        .client
+        .query_one("SELECT current_user::text", &[])
+        .await
+        .unwrap()
+        .get::<_, String>(0);
+
+    client
+        .batch_execute(&format!(
+            "DROP SCHEMA {schema} CASCADE; DROP ROLE {schema};"
+        ))
+        .await
+        .unwrap();
+
+    assert!(as_owner.is_ok());
+    assert!(as_role.unwrap_err().contains("permission denied"));
+    assert_ne!(current_user, schema);
+}
@@ -0,0 +1,244 @@
+mod common;
+
+use std::{env, fs, path::PathBuf, process, process::Command};
+
+use common::{array_of, column, named, query_context};
+use piqued::{
+    codegen::{
+        codegen::{CodeGenerationContext, CodeGenerator, QueryContext},
+        rust::schema::RustGenerator,
+    },
+    config::config::{Config, Language},
+    query::query::{Column, ProbeResponse},
+};
+
+fn company_columns() -> Vec<Column> {
+    vec![
+        column("id", named("int4"), false),
+        column("legalName", named("text"), false),
+        column("status", named("company_status"), false),
+        column("tags", array_of("text"), true),
+        column("revenue", named("numeric"), true),
+        column("created_at", named("timestamptz"), false),
+        column("type", named("text"), false),
+        column("self", named("uuid"), false),
+    ]
+}
+
+fn context() -> CodeGenerationContext<'static> {
+    let mut config = Config::default();
+    config.emit.language = Language::Rust;
+
+    // Several of these only differ by case or punctuation
+    common::schema_context(
+        config,
+        company_columns(),
+        &["active", "Active", "on-hold", "onhold", "self", "2fa"],
+    )
+}
+
+fn queries() -> Vec<QueryContext> {
+    vec![
+        query_context(
+            "-- @name getCompaniesByStatus\n-- Every company in the given state\n-- @params status\nSELECT id, \"legalName\", tags, revenue, id FROM company WHERE status = $1 AND created_at > $2;\n",
+            ProbeResponse::default()
+                .arg("company_status")
+                .arg("timestamptz")
+                .column("id", "int4", false)
+                .column("legalName", "text", false)
+                .column("tags", "text[]", true)
+                .column("revenue", "numeric", true)
+                .column("id", "int4", false),
+        ),
+        query_context(
+            "-- @name rename_company\n-- @params id type\nUPDATE company SET \"legalName\" = $2 WHERE id = $1;\n",
+            ProbeResponse::default().arg("int4").arg("text"),
+        ),
+    ]
+}
+
+#[test]
+fn types() {
+    let ctx = context();
+    let generator = RustGenerator::new();
+
+    let composite = generator.serialize_type(&ctx, &ctx.custom_types_by_name["company"]);
+    insta::assert_snapshot!("rust_composite_type", composite.generated_code);
+
+    let enum_ = generator.serialize_type(&ctx, &ctx.custom_types_by_name["company_status"]);
+    insta::assert_snapshot!("rust_enum_type", enum_.generated_code);
+}
+
+#[test]
+fn query_functions() {
+    let ctx = context();
+    let generator = RustGenerator::new();
+
+    let queries = queries();
+    let res = generator.serialize_query(&ctx, &queries[0]);
+    assert_eq!(res.requires_import, vec!["CompanyStatus".to_string()]);
+    insta::assert_snapshot!("rust_query", res.generated_code);
+
+    let res = generator.serialize_query(&ctx, &queries[1]);
+    insta::assert_snapshot!("rust_query_without_rows", res.generated_code);
+}
+
+// Just enough of sqlx for the generated code to typecheck against, since the real one isn't around
+const SQLX_MACROS_STUB: &str = r#"
+extern crate proc_macro;
+use proc_macro::TokenStream;
+
+#[proc_macro_derive(Type, attributes(sqlx))]
+pub fn derive_type(_: TokenStream) -> TokenStream {
+    TokenStream::new()
+}
+
+#[proc_macro_derive(FromRow, attributes(sqlx))]
+pub fn derive_from_row(_: TokenStream) -> TokenStream {
+    TokenStream::new()
+}
+"#;
+
+const SQLX_STUB: &str = r#"
+use std::marker::PhantomData;
+
+pub use sqlx_macros::{FromRow, Type};
+
+pub struct Postgres;
+
+pub mod postgres {
+    pub struct PgArguments;
+}
+
+pub mod types {
+    #[derive(Debug, Clone)]
+    pub struct BigDecimal;
+    #[derive(Debug, Clone)]
+    pub struct Uuid;
+    #[derive(Debug, Clone)]
+    pub struct JsonValue;
+
+    pub mod chrono {
+        #[derive(Debug, Clone)]
+        pub struct NaiveDate;
+        #[derive(Debug, Clone)]
+        pub struct NaiveTime;
+        #[derive(Debug, Clone)]
+        pub struct NaiveDateTime;
+        #[derive(Debug, Clone)]
+        pub struct Utc;
+        #[derive(Debug, Clone)]
+        pub struct DateTime<Tz>(pub Tz);
+    }
+}
+
+pub mod query {
+    use super::PhantomData;
+
+    pub struct Query<'q, DB, A>(pub &'q str, pub PhantomData<(DB, A)>);
+    pub struct QueryAs<'q, DB, O, A>(pub &'q str, pub PhantomData<(DB, O, A)>);
+
+    impl<'q, DB, A> Query<'q, DB, A> {
+        pub fn bind<T>(self, _value: T) -> Self {
+            self
+        }
+    }
+
+    impl<'q, DB, O, A> QueryAs<'q, DB, O, A> {
+        pub fn bind<T>(self, _value: T) -> Self {
+            self
+        }
+    }
+}
+
+pub fn query(sql: &'static str) -> query::Query<'static, Postgres, postgres::PgArguments> {
+    query::Query(sql, PhantomData)
+}
+
+pub fn query_as<O>(
+    sql: &'static str,
+) -> query::QueryAs<'static, Postgres, O, postgres::PgArguments> {
+    query::QueryAs(sql, PhantomData)
+}
+"#;
+
+fn rustc(dir: &PathBuf, args: &[&str]) {
+    let output = Command::new(env::var("RUSTC").unwrap_or("rustc".to_string()))
+        .args(["--edition", "2021", "-A", "warnings", "--out-dir"])
+        .arg(dir)
+        .arg("-L")
+        .arg(dir)
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn generated_code_compiles() {
+    let ctx = context();
+    let generator = RustGenerator::new();
+
+    let mut types = ctx
+        .custom_types_by_name
+        .values()
+        .cloned()
+        .collect::<Vec<_>>();
+    types.sort_by(|a, b| a.name().cmp(b.name()));
+
+    let mut types_file = generator.serialize_type_prefix(&ctx, &types).unwrap();
+    for type_ in &types {
+        types_file.push_str(&generator.serialize_type(&ctx, type_).generated_code);
+        types_file.push_str("\n\n");
+    }
+
+    let mut imports = vec![];
+    let mut queries_file = String::new();
+    for query in queries() {
+        let res = generator.serialize_query(&ctx, &query);
+        imports.extend(res.requires_import);
+        queries_file.push_str(&res.generated_code);
+        queries_file.push_str("\n\n");
+    }
+    imports.sort();
+    imports.dedup();
+    let import = generator.serialize_import(&ctx, &PathBuf::from("./types"), &imports);
+
+    let dir = env::temp_dir().join(format!("piqued-rust-compiles-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("sqlx_macros.rs"), SQLX_MACROS_STUB).unwrap();
+    fs::write(dir.join("sqlx.rs"), SQLX_STUB).unwrap();
+    fs::write(dir.join("types.rs"), types_file).unwrap();
+    fs::write(
+        dir.join("queries.rs"),
+        format!("{}\n\n{}", import.generated_code, queries_file),
+    )
+    .unwrap();
+    fs::write(dir.join("lib.rs"), "mod queries;\nmod types;\n").unwrap();
+
+    rustc(&dir, &["--crate-type", "proc-macro", "sqlx_macros.rs"]);
+    rustc(
+        &dir,
+        &["--crate-type", "lib", "--extern", "sqlx_macros", "sqlx.rs"],
+    );
+    rustc(
+        &dir,
+        &[
+            "--crate-type",
+            "lib",
+            "--crate-name",
+            "generated",
+            "--extern",
+            "sqlx",
+            "lib.rs",
+        ],
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
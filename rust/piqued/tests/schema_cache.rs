@@ -0,0 +1,118 @@
+mod common;
+
+use std::{env, fs, process, sync::Arc};
+
+use piqued::{query::query::Query, workspace::workspace::Workspace};
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn workspaces_share_schema() {
+    let root = env::temp_dir();
+    let config = Arc::new(common::test_config(&root).await);
+
+    let first = common::connected_workspace(config.clone(), root.clone()).await;
+    let first_query = first.query.as_ref().unwrap();
+
+    let mut second = Workspace::new(config.clone(), root.clone()).await;
+    let second_query = second.query.as_ref().unwrap();
+
+    assert!(Arc::ptr_eq(&first_query.tables, &second_query.tables));
+    assert!(Arc::ptr_eq(
+        &first_query.custom_types_by_name,
+        &second_query.custom_types_by_name
+    ));
+
+    // Reloading the config should go back to the database rather than the cache
+    second.reload_config(config.clone()).await;
+    let reloaded_query = second.query.as_ref().unwrap();
+
+    assert!(!Arc::ptr_eq(&first_query.tables, &reloaded_query.tables));
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn schema_cache_file_tracks_catalog_changes() {
+    let root = env::temp_dir().join(format!("piqued_schema_cache_{}", process::id()));
+    fs::create_dir_all(&root).unwrap();
+
+    let mut config = common::test_config(&root).await;
+    let client = common::connect(&config).await;
+
+    let schema = format!("piqued_schema_cache_{}", process::id());
+    client
+        .batch_execute(&format!(
+            "CREATE SCHEMA {schema}; CREATE TABLE {schema}.widget (id int4 NOT NULL);"
+        ))
+        .await
+        .unwrap();
+
+    config.postgres.schema = schema.clone();
+    config.postgres.schema_cache = Some("schema-cache.json".to_string());
+    let config = Arc::new(config);
+    let cache_file = root.join("schema-cache.json");
+
+    let first = Query::new(config.clone()).await.unwrap();
+    let written = fs::read_to_string(&cache_file).unwrap();
+
+    // A table that only exists in the file shows that it was read back
+    fs::write(&cache_file, written.replace("\"widget\"", "\"gadget\"")).unwrap();
+    let second = Query::new(config.clone()).await.unwrap();
+
+    client
+        .batch_execute(&format!("CREATE TABLE {schema}.sprocket (id int4);"))
+        .await
+        .unwrap();
+    let third = Query::new(config.clone()).await.unwrap();
+
+    Query::invalidate_schema_cache_file(&config).await.unwrap();
+    let invalidated = cache_file.exists();
+
+    client
+        .batch_execute(&format!("DROP SCHEMA {schema} CASCADE;"))
+        .await
+        .unwrap();
+    fs::remove_dir_all(&root).unwrap();
+
+    assert!(first.tables.contains_key("widget"));
+    assert!(second.tables.contains_key("gadget"));
+    assert!(third.tables.contains_key("widget"));
+    assert!(third.tables.contains_key("sprocket"));
+    assert!(!invalidated);
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn unwritable_schema_cache_file_is_ignored() {
+    let root = env::temp_dir();
+    let mut config = common::test_config(&root).await;
+
+    // The directory it would go in doesn't exist
+    config.postgres.schema_cache = Some(format!(
+        "piqued_missing_{}/schema-cache.json",
+        process::id()
+    ));
+
+    let query = Query::new(Arc::new(config)).await;
+
+    assert!(query.is_ok(), "{:?}", query.err());
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn unwritable_schema_cache_is_a_warning() {
+    let root = env::temp_dir().join(format!("piqued_unwritable_cache_{}", process::id()));
+    fs::create_dir_all(&root).unwrap();
+
+    let mut config = common::test_config(&root).await;
+    config.workspace.root = Some(root.clone());
+    // Nothing creates the directory the file would go in
+    config.postgres.schema_cache = Some("missing/schema-cache.json".to_string());
+
+    let query = Query::new(Arc::new(config)).await;
+
+    fs::remove_dir_all(&root).unwrap();
+
+    let query = query.unwrap();
+    assert_eq!(query.warnings.len(), 1, "{:?}", query.warnings);
+    assert!(query.warnings[0].starts_with("Unable to write the schema cache"));
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use std::{env, process, sync::Arc};
+
+use piqued::query::query::Query;
+use serde_json::json;
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn schema_is_dumped_in_order() {
+    let mut config = common::test_config(&env::temp_dir()).await;
+    let client = common::connect(&config).await;
+
+    let schema = format!("piqued_schema_dump_{}", process::id());
+    client
+        .batch_execute(&format!(
+            "
+            CREATE SCHEMA {schema};
+            CREATE TYPE {schema}.mood AS ENUM ('happy', 'sad');
+            CREATE TYPE {schema}.point2 AS (x float8, y float8);
+            CREATE TABLE {schema}.diary (id int4 PRIMARY KEY, feeling {schema}.mood NOT NULL);
+            CREATE TABLE {schema}.author (id int4 PRIMARY KEY, name text);
+            "
+        ))
+        .await
+        .unwrap();
+
+    config.postgres.schema = schema.clone();
+    let query = Query::new(Arc::new(config)).await;
+
+    client
+        .batch_execute(&format!("DROP SCHEMA {schema} CASCADE;"))
+        .await
+        .unwrap();
+
+    let query = query.unwrap();
+    let dump = serde_json::to_value(query.dump_schema()).unwrap();
+
+    assert_eq!(dump["schema"], json!(schema));
+
+    let tables: Vec<_> = dump["tables"].as_object().unwrap().keys().collect();
+    assert_eq!(tables, vec!["author", "diary"]);
+    assert_eq!(
+        dump["tables"]["author"][1],
+        json!({
+            "name": "name",
+            "column_type": { "Named": "text" },
+            "type_oid": 25,
+            "nullable": true,
+            "generated": false,
+            "references": null,
+        })
+    );
+    assert_eq!(
+        dump["tables"]["diary"][1]["column_type"],
+        json!({ "Named": "mood" })
+    );
+
+    // Tables have a composite type of their own too
+    let composite_types: Vec<_> = dump["composite_types"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|composite| composite["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(composite_types, vec!["author", "diary", "point2"]);
+
+    let enums = dump["enums"].as_array().unwrap();
+    assert_eq!(enums.len(), 1);
+    assert_eq!(enums[0]["name"], json!("mood"));
+    assert_eq!(enums[0]["values"], json!(["happy", "sad"]));
+}
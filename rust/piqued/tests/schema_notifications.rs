@@ -0,0 +1,86 @@
+mod common;
+
+use std::{env, fs, process, time::Duration};
+
+use tower_lsp::{
+    lsp_types::{HoverParams, Position, Url},
+    LanguageServer,
+};
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn notification_reloads_schema() {
+    let config = common::test_config(&env::temp_dir()).await;
+    let client = common::connect(&config).await;
+
+    let table = format!("piqued_notify_{}", process::id());
+    client
+        .batch_execute(&format!("DROP TABLE IF EXISTS {table}"))
+        .await
+        .unwrap();
+
+    let root = env::temp_dir().join(format!("piqued_schema_notifications_{}", process::id()));
+    fs::create_dir_all(&root).unwrap();
+    fs::write(
+        root.join("piqued.toml"),
+        format!(
+            "[postgres]\nuri = \"{}\"\n\n[lsp]\nschemaChannel = \"{}\"\n",
+            config.postgres.uri, table
+        ),
+    )
+    .unwrap();
+
+    let service = common::language_server(&root).await;
+    let backend = service.inner();
+
+    let file_uri = Url::from_file_path(root.join("queries.sql")).unwrap();
+    common::open_document(
+        backend,
+        &file_uri,
+        &format!("SELECT payload FROM {table};\n"),
+    )
+    .await;
+
+    let hover = || {
+        backend.hover(HoverParams {
+            text_document_position_params: common::document_position(
+                &file_uri,
+                Position::new(0, 21),
+            ),
+            work_done_progress_params: Default::default(),
+        })
+    };
+    let before = hover().await.unwrap();
+
+    client
+        .batch_execute(&format!("CREATE TABLE {table} (payload text)"))
+        .await
+        .unwrap();
+
+    // The listener connects in the background, so keep notifying until it's heard
+    let mut after = None;
+    for _ in 0..50 {
+        client
+            .batch_execute(&format!("NOTIFY {table}"))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // Until the schema is reloaded, the table is only known through the query probing it
+        after = hover().await.unwrap();
+        if format!("{:?}", after).contains("(table)") {
+            break;
+        }
+    }
+
+    client
+        .batch_execute(&format!("DROP TABLE {table}"))
+        .await
+        .unwrap();
+    fs::remove_dir_all(&root).unwrap();
+
+    assert!(before.is_none());
+    let after = format!("{:?}", after.unwrap());
+    assert!(after.contains("(table)"));
+    assert!(after.contains("payload text"));
+}
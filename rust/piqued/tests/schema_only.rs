@@ -0,0 +1,41 @@
+mod common;
+
+use std::{env, fs, process, sync::Arc};
+
+use piqued::workspace::workspace::GenerationMode;
+
+const QUERIES: &str = "-- @name one\nSELECT 1 AS one;\n";
+const STALE: &str = "// Left over from an older schema\n";
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn schema_only_leaves_query_files_alone() {
+    let root = env::temp_dir().join(format!("piqued_schema_only_{}", process::id()));
+    fs::create_dir_all(&root).unwrap();
+    fs::write(root.join("queries.sql"), QUERIES).unwrap();
+    fs::write(root.join("queries.ts"), STALE).unwrap();
+
+    let mut config = common::test_config(&root).await;
+    config.emit.type_file = "./types".to_string();
+    config.emit.table_file = Some("./tables".to_string());
+    let workspace = common::connected_workspace(Arc::new(config), root.clone()).await;
+
+    workspace
+        .gen_code(GenerationMode::SchemaOnly)
+        .await
+        .unwrap();
+    let types_written = root.join("types.ts").exists();
+    let tables_written = root.join("tables.ts").exists();
+    let schema_only = fs::read_to_string(root.join("queries.ts")).unwrap();
+
+    // Whereas a full run does regenerate it
+    workspace.gen_code(GenerationMode::Full).await.unwrap();
+    let full = fs::read_to_string(root.join("queries.ts")).unwrap();
+
+    fs::remove_dir_all(&root).unwrap();
+
+    assert!(types_written);
+    assert!(tables_written);
+    assert_eq!(schema_only, STALE);
+    assert_ne!(full, STALE);
+}
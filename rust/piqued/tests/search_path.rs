@@ -0,0 +1,68 @@
+mod common;
+
+use std::{env, process, sync::Arc};
+
+use piqued::{parser::parser, query::query::Query};
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn probes_resolve_against_configured_schema() {
+    let mut config = common::test_config(&env::temp_dir()).await;
+    let client = common::connect(&config).await;
+
+    // Only exists outside of `public`, so it can't resolve by accident
+    let schema = format!("piqued_search_path_{}", process::id());
+    client
+        .batch_execute(&format!(
+            "
+            CREATE SCHEMA {schema};
+            CREATE TABLE {schema}.widget (id int4 NOT NULL, label text);
+            CREATE FUNCTION public.{schema}_label(text) RETURNS text AS 'SELECT upper($1)' LANGUAGE sql;
+            "
+        ))
+        .await
+        .unwrap();
+
+    config.postgres.schema = schema.clone();
+    let query = Query::new(Arc::new(config)).await;
+
+    // Functions from `public`, like those of most extensions, still resolve
+    let sql = format!(
+        "-- @name widget_labels\nSELECT label, {schema}_label(label) AS shouted FROM widget WHERE id = $1;\n"
+    );
+    let parsed = parser::load_file(&sql).unwrap();
+    let prepared =
+        parser::get_prepared_statement(&parsed.statements[0], &parsed.tokens, &sql, || {
+            "query".to_string()
+        })
+        .unwrap();
+
+    let probed = match &query {
+        Ok(query) => query.probe_type(&prepared).await,
+        Err(e) => Err(e.clone()),
+    };
+
+    client
+        .batch_execute(&format!(
+            "DROP SCHEMA {schema} CASCADE; DROP FUNCTION public.{schema}_label(text);"
+        ))
+        .await
+        .unwrap();
+
+    let query = query.unwrap();
+    assert!(query.tables.contains_key("widget"));
+
+    let probed = probed.unwrap();
+    assert_eq!(
+        probed.column_names,
+        vec!["label".to_string(), "shouted".to_string()]
+    );
+    assert_eq!(
+        probed
+            .args
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>(),
+        vec!["int4"]
+    );
+}
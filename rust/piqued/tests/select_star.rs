@@ -0,0 +1,44 @@
+mod common;
+
+use std::{env, sync::Arc};
+
+use piqued::workspace::workspace::Workspace;
+use tower_lsp::lsp_types::{DiagnosticSeverity, Position, Range};
+
+const SELECT_STAR_QUERIES: &str =
+    include_str!("../../../ts/test-workspace/src/data/select-star-queries.sql");
+
+// The warning is the same whether or not the database is around, so this doesn't need one
+async fn select_star_warnings(warn_select_star: bool) -> Vec<Range> {
+    let mut config = common::test_config(&env::temp_dir()).await;
+    config.lsp.warn_select_star = warn_select_star;
+
+    let path = "file:///select-star-queries.sql";
+    let mut workspace = Workspace::new(Arc::new(config), env::temp_dir()).await;
+    workspace.patch_file(path.to_string(), SELECT_STAR_QUERIES.to_string());
+
+    workspace
+        .get_diagnostics(path)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|diagnostic| {
+            diagnostic.severity == Some(DiagnosticSeverity::WARNING)
+                && diagnostic.message.starts_with("SELECT *")
+        })
+        .map(|diagnostic| diagnostic.range)
+        .collect()
+}
+
+#[tokio::test]
+async fn select_star_is_flagged_when_enabled() {
+    assert_eq!(
+        select_star_warnings(true).await,
+        vec![Range::new(Position::new(1, 7), Position::new(1, 8))]
+    );
+}
+
+#[tokio::test]
+async fn select_star_is_allowed_by_default() {
+    assert_eq!(select_star_warnings(false).await, vec![]);
+}
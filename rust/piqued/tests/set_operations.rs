@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use piqued::loose_parser::{
+    parse::ParserContext,
+    parse_cf::{FromExpression, LR1Kind, SetOperation, SetOperator, TableLike},
+};
+use tower_lsp::lsp_types::Position;
+
+const SET_OPERATION_QUERIES: &str =
+    include_str!("../../../ts/test-workspace/src/data/set-operation-queries.sql");
+
+fn tables(from: &Option<Vec<Arc<FromExpression>>>) -> Vec<String> {
+    from.iter()
+        .flatten()
+        .filter_map(|from| match from.table.as_ref() {
+            TableLike::Table(table) => Some(table.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn union_keeps_both_branches() {
+    let mut context = ParserContext::new(SET_OPERATION_QUERIES);
+    let parsed = context.parse();
+
+    let compound_query = parsed
+        .states
+        .iter()
+        .find_map(|state| match &state.kind {
+            LR1Kind::CompoundQuery(compound_query) => Some(compound_query.clone()),
+            _ => None,
+        })
+        .expect("the union should be reduced to a single query");
+
+    let branch_tables = compound_query
+        .branches
+        .iter()
+        .map(|select_query| tables(&select_query.from))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        branch_tables,
+        vec![vec!["company"], vec!["webhook_address"]]
+    );
+    assert_eq!(
+        compound_query.operations,
+        vec![SetOperation {
+            operator: SetOperator::Union,
+            all: true,
+        }]
+    );
+}
+
+#[test]
+fn scope_follows_the_cursor() {
+    let mut context = ParserContext::new(SET_OPERATION_QUERIES);
+    let parsed = context.parse();
+
+    // The outermost query around the cursor decides which tables are in scope, just like hovers
+    let scope = |line, character| {
+        let stack = parsed.inspect(&Position::new(line, character)).unwrap();
+        let select_query = stack
+            .iter()
+            .rev()
+            .find_map(|state| match &state.kind {
+                LR1Kind::SelectQuery(select_query) => Some(select_query.clone()),
+                _ => None,
+            })
+            .unwrap();
+
+        tables(&select_query.from)
+    };
+
+    // `legal_name`
+    assert_eq!(scope(1, 12), vec!["company"]);
+    // `callback_address`
+    assert_eq!(scope(3, 12), vec!["webhook_address"]);
+}
@@ -0,0 +1,66 @@
+use piqued::parser::parser::{self, StatementKind};
+
+fn kinds(contents: &str) -> Vec<StatementKind> {
+    parser::load_file(contents)
+        .unwrap()
+        .statements
+        .iter()
+        .map(|stmt| stmt.kind)
+        .collect()
+}
+
+#[test]
+fn selects() {
+    assert_eq!(
+        kinds("SELECT 1;\nVALUES (1);\nSELECT 1 UNION SELECT 2;"),
+        vec![StatementKind::Select; 3]
+    );
+}
+
+// A `WITH` clause doesn't change the kind, only the statement that follows it does
+#[test]
+fn inserts() {
+    assert_eq!(
+        kinds(
+            "INSERT INTO company (id) VALUES (1);\nWITH c AS (SELECT 1 AS id) INSERT INTO company (id) SELECT id FROM c;"
+        ),
+        vec![StatementKind::Insert; 2]
+    );
+}
+
+#[test]
+fn updates() {
+    assert_eq!(
+        kinds("UPDATE company SET id = 2 WHERE id = 1 RETURNING id;"),
+        vec![StatementKind::Update]
+    );
+}
+
+#[test]
+fn deletes() {
+    assert_eq!(
+        kinds("DELETE FROM company;\nWITH c AS (SELECT 1 AS id) DELETE FROM company USING c;"),
+        vec![StatementKind::Delete; 2]
+    );
+}
+
+#[test]
+fn prepares() {
+    assert_eq!(
+        kinds("PREPARE get_company AS SELECT * FROM company WHERE id = $1;"),
+        vec![StatementKind::Prepare]
+    );
+}
+
+#[test]
+fn utility_statements() {
+    assert_eq!(
+        kinds("CREATE TABLE company (id int4);\nSET search_path = app;\nBEGIN;"),
+        vec![StatementKind::Utility; 3]
+    );
+}
+
+#[test]
+fn unparseable_statements() {
+    assert_eq!(kinds("SELECT FROM WHERE;"), vec![StatementKind::Unknown]);
+}
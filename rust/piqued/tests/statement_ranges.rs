@@ -0,0 +1,81 @@
+use piqued::{
+    loose_parser::{
+        parse::ParserContext,
+        parse_cf::{LR1Kind, TableLike},
+    },
+    parser::parser,
+};
+use tower_lsp::lsp_types::{Position, Range};
+
+#[test]
+fn multiline_statement_range_is_tight() {
+    let contents = include_str!("../../../ts/test-workspace/src/data/multiline-queries.sql");
+    let parsed = parser::load_file(contents).unwrap();
+
+    let ranges = parsed
+        .statements
+        .iter()
+        .map(|stmt| stmt.range)
+        .collect::<Vec<Range>>();
+
+    assert_eq!(
+        ranges,
+        vec![
+            Range::new(Position::new(0, 0), Position::new(6, 14)),
+            Range::new(Position::new(8, 0), Position::new(9, 23)),
+        ]
+    );
+}
+
+const STATEMENT_BOUNDARY_QUERIES: &str = include_str!("fixtures/statement-boundary-queries.sql");
+
+#[test]
+fn loose_states_stop_at_semicolons() {
+    let mut context = ParserContext::new(STATEMENT_BOUNDARY_QUERIES);
+    let parsed = context.parse();
+
+    // Nothing spills past its `;`, into the next statement's comments
+    let ends = parsed
+        .states
+        .iter()
+        .map(|state| parsed.range_of(state).end)
+        .collect::<Vec<_>>();
+
+    assert!(ends.iter().all(|end| end.line % 2 == 1), "{:?}", ends);
+    assert_eq!(ends[0], Position::new(1, 22));
+    assert_eq!(ends.last(), Some(&Position::new(5, 42)));
+}
+
+#[test]
+fn cursor_near_semicolons_finds_its_own_statement() {
+    let mut context = ParserContext::new(STATEMENT_BOUNDARY_QUERIES);
+    let parsed = context.parse();
+
+    // The table named by the innermost `FROM` around the cursor, if there is one
+    let table_at = |line, character| {
+        let stack = parsed.inspect(&Position::new(line, character))?;
+        stack.iter().find_map(|state| match &state.kind {
+            LR1Kind::FromStmt(from) => match from.first()?.table.as_ref() {
+                TableLike::Table(table) => Some(table.clone()),
+                _ => None,
+            },
+            _ => None,
+        })
+    };
+    let inspect = |line, character| parsed.inspect(&Position::new(line, character));
+
+    // The last character before each `;` belongs to its statement
+    assert_eq!(table_at(1, 21).as_deref(), Some("account"));
+    assert_eq!(table_at(5, 41).as_deref(), Some("receipt"));
+    assert!(inspect(3, 40).is_some());
+
+    // The `;` itself, and the comments heading the next statement, belong to none of them
+    for (line, character) in [(1, 22), (2, 5), (3, 41), (4, 5), (5, 42)] {
+        assert!(
+            inspect(line, character).is_none(),
+            "{}:{} is in a statement",
+            line,
+            character
+        );
+    }
+}
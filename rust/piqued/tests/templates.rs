@@ -0,0 +1,24 @@
+use piqued::parser::parser;
+
+const TEMPLATE_QUERIES: &str =
+    include_str!("../../../ts/test-workspace/src/data/template-queries.sql");
+
+#[test]
+fn repeated_template_is_one_parameter() {
+    let parsed = parser::load_file(TEMPLATE_QUERIES).unwrap();
+    let statement = parsed.statements.last().unwrap();
+
+    let prepared =
+        parser::get_prepared_statement(statement, &parsed.tokens, TEMPLATE_QUERIES, || {
+            "unnamed".to_string()
+        })
+        .unwrap();
+
+    assert_eq!(prepared.details.name, "companies_by_either_id");
+    assert_eq!(prepared.details.params, Some(vec!["ids".to_string()]));
+    assert_eq!(prepared.details.template_names(), vec!["ids"]);
+    assert_eq!(
+        prepared.contents,
+        "SELECT company FROM company WHERE id = ANY($1::uuid[]) OR uid <> ALL($1::uuid[])"
+    );
+}
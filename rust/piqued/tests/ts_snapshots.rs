@@ -0,0 +1,281 @@
+mod common;
+
+use std::path::PathBuf;
+
+use common::{array_of, column, named, query_context};
+use piqued::{
+    codegen::{
+        codegen::{CodeGenerationContext, CodeGenerator, QueryContext},
+        ts::schema::TSGenerator,
+    },
+    config::config::{Config, EnumStyle},
+    query::query::{Column, ProbeResponse},
+};
+
+fn company_columns() -> Vec<Column> {
+    vec![
+        column("id", named("int4"), false),
+        column("legal_name", named("text"), false),
+        column("status", named("company_status"), false),
+        column("tags", array_of("text"), true),
+        Column {
+            generated: true,
+            ..column("search_name", named("text"), true)
+        },
+        column("created_at", named("timestamptz"), false),
+    ]
+}
+
+fn context(config: Config) -> CodeGenerationContext<'static> {
+    common::schema_context(
+        config,
+        company_columns(),
+        &["active", "archived", "on hold"],
+    )
+}
+
+fn company_by_status() -> QueryContext {
+    query_context(
+        "-- @name companies_by_status\n-- Every company in the given state\n-- @params status\nSELECT id, legal_name, tags FROM company WHERE status = $1;\n",
+        ProbeResponse::default()
+            .arg("company_status")
+            .column("id", "int4", false)
+            .column("legal_name", "text", false)
+            .column("tags", "text[]", true),
+    )
+}
+
+fn company_row() -> QueryContext {
+    query_context(
+        "-- @name company_row\nSELECT company FROM company WHERE id = $1;\n",
+        ProbeResponse::default()
+            .arg("int4")
+            .column("company", "company", false),
+    )
+}
+
+#[test]
+fn composite_type() {
+    let ctx = context(Config::default());
+    let generator = TSGenerator::new();
+
+    let res = generator.serialize_type(&ctx, &ctx.custom_types_by_name["company"]);
+    insta::assert_snapshot!(res.generated_code);
+}
+
+#[test]
+fn enum_type() {
+    let ctx = context(Config::default());
+    let generator = TSGenerator::new();
+
+    let res = generator.serialize_type(&ctx, &ctx.custom_types_by_name["company_status"]);
+    insta::assert_snapshot!(res.generated_code);
+}
+
+#[test]
+fn enum_type_as_enum() {
+    let mut config = Config::default();
+    config.emit.enum_style = EnumStyle::Enum;
+
+    let ctx = context(config);
+    let generator = TSGenerator::new();
+
+    let res = generator.serialize_type(&ctx, &ctx.custom_types_by_name["company_status"]);
+    insta::assert_snapshot!(res.generated_code);
+}
+
+#[test]
+fn enum_type_as_branded_union() {
+    let mut config = Config::default();
+    config.emit.enum_style = EnumStyle::BrandedUnion;
+
+    let ctx = context(config);
+    let generator = TSGenerator::new();
+
+    let res = generator.serialize_type(&ctx, &ctx.custom_types_by_name["company_status"]);
+    insta::assert_snapshot!(res.generated_code);
+}
+
+#[test]
+fn table() {
+    let ctx = context(Config::default());
+    let generator = TSGenerator::new();
+
+    let res = generator.serialize_table(&ctx, &"company".to_string());
+    insta::assert_snapshot!(res.generated_code);
+}
+
+#[test]
+fn queries() {
+    let ctx = context(Config::default());
+    let generator = TSGenerator::new();
+
+    for (name, query) in [
+        ("companies_by_status", company_by_status()),
+        ("company_row", company_row()),
+    ] {
+        let res = generator.serialize_query(&ctx, &query);
+        insta::assert_snapshot!(name, res.generated_code);
+    }
+}
+
+#[test]
+fn scalarized_query() {
+    let mut config = Config::default();
+    config.emit.scalarize_single_column = true;
+    config.emit.force_nullable_results = true;
+
+    let ctx = context(config);
+    let generator = TSGenerator::new();
+
+    let res = generator.serialize_query(&ctx, &company_row());
+    insta::assert_snapshot!(res.generated_code);
+}
+
+#[test]
+fn readonly_rows() {
+    let mut config = Config::default();
+    config.emit.readonly = true;
+
+    let ctx = context(config);
+    let generator = TSGenerator::new();
+
+    let composite = generator.serialize_type(&ctx, &ctx.custom_types_by_name["company"]);
+    insta::assert_snapshot!("readonly_composite_type", composite.generated_code);
+
+    let query = generator.serialize_query(&ctx, &company_by_status());
+    insta::assert_snapshot!("readonly_query", query.generated_code);
+}
+
+#[test]
+fn exported_param_types() {
+    let mut config = Config::default();
+    config.emit.export_param_types = true;
+
+    let ctx = context(config);
+    let generator = TSGenerator::new();
+
+    let res = generator.serialize_query(&ctx, &company_by_status());
+    insta::assert_snapshot!(res.generated_code);
+}
+
+#[test]
+fn documented_query() {
+    let ctx = context(Config::default());
+    let generator = TSGenerator::new();
+
+    let query = query_context(
+        "-- @name rename_company\n-- Renames a company, matching */ as written\n--\n-- Returns the renamed row\n-- @params id, legal_name\nUPDATE company SET legal_name = $2 WHERE id = $1 RETURNING id;\n",
+        ProbeResponse::default()
+            .arg("int4")
+            .arg("text")
+            .column("id", "int4", false),
+    );
+
+    let res = generator.serialize_query(&ctx, &query);
+    insta::assert_snapshot!(res.generated_code);
+}
+
+#[test]
+fn cached_query() {
+    let ctx = context(Config::default());
+    let generator = TSGenerator::new();
+
+    let query = query_context(
+        "-- @name company_by_id\n-- @cache ttl=30\n-- @params id\nSELECT legal_name FROM company WHERE id = $1;\n",
+        ProbeResponse::default()
+            .arg("int4")
+            .column("legal_name", "text", false),
+    );
+
+    let res = generator.serialize_query(&ctx, &query);
+    insta::assert_snapshot!(res.generated_code);
+
+    // Only files with a cached query need the wrapper
+    let prefix = |queries: &Vec<QueryContext>| generator.serialize_query_prefix(&ctx, queries);
+    assert!(prefix(&vec![query]).unwrap().contains("memoize"));
+    assert!(!prefix(&vec![company_by_status()])
+        .unwrap()
+        .contains("memoize"));
+}
+
+#[test]
+fn runtime_checks() {
+    let mut config = Config::default();
+    config.emit.runtime_checks = true;
+
+    let ctx = context(config);
+    let generator = TSGenerator::new();
+
+    let query = generator.serialize_query(&ctx, &company_by_status());
+    insta::assert_snapshot!("runtime_checked_query", query.generated_code);
+
+    let unparameterized = query_context(
+        "-- @name all_companies\nSELECT id FROM company;\n",
+        ProbeResponse::default().column("id", "int4", false),
+    );
+    let query = generator.serialize_query(&ctx, &unparameterized);
+    insta::assert_snapshot!(
+        "runtime_checked_unparameterized_query",
+        query.generated_code
+    );
+}
+
+#[test]
+fn branded_scalars() {
+    let mut config = Config::default();
+    config.emit.branded_scalars = vec!["uuid".to_string(), "company_status".to_string()];
+
+    let ctx = context(config);
+    let generator = TSGenerator::new();
+
+    // Custom types are emitted as they always are, rather than being branded
+    assert_eq!(ctx.branded_scalars(), vec!["uuid".to_string()]);
+
+    let res = generator
+        .serialize_branded_scalar(&ctx, &"uuid".to_string())
+        .unwrap();
+    insta::assert_snapshot!("branded_scalar", res.generated_code);
+
+    let query = query_context(
+        "-- @name companies_by_token\n-- @params token\nSELECT id, token, previous_tokens FROM company WHERE token = $1;\n",
+        ProbeResponse::default()
+            .arg("uuid")
+            .column("id", "int4", false)
+            .column("token", "uuid", false)
+            .column("previous_tokens", "uuid[]", true),
+    );
+    let res = generator.serialize_query(&ctx, &query);
+    assert!(res.requires_import.contains(&"Uuid".to_string()));
+    insta::assert_snapshot!("branded_scalar_query", res.generated_code);
+}
+
+// Relative imports need an extension under ESM, but module aliases are left to the bundler
+#[test]
+fn type_file_imports() {
+    let generator = TSGenerator::new();
+    let identifiers = vec!["Company".to_string(), "CompanyStatus".to_string()];
+    let import = |module_type: &str, type_file_module: Option<&str>, path: &str| {
+        let mut config = Config::default();
+        config.emit.module_type = module_type.to_string();
+        config.emit.type_file_module = type_file_module.map(str::to_string);
+
+        let ctx = context(config);
+        generator
+            .serialize_import(&ctx, &PathBuf::from(path), &identifiers)
+            .generated_code
+    };
+
+    insta::assert_snapshot!(
+        "type_file_import_esm",
+        import("esm", None, "../types/postgres")
+    );
+    insta::assert_snapshot!(
+        "type_file_import_module_esm",
+        import("esm", Some("@app/db-types"), "@app/db-types")
+    );
+    insta::assert_snapshot!(
+        "type_file_import_module_commonjs",
+        import("CommonJS", Some("@app/db-types"), "@app/db-types")
+    );
+}
@@ -0,0 +1,29 @@
+mod common;
+
+use std::{env, fs, process, sync::Arc};
+
+use piqued::workspace::workspace::Workspace;
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn typecheck_reports_failing_statements() {
+    let root = env::temp_dir().join(format!("piqued_typecheck_{}", process::id()));
+    fs::create_dir_all(&root).unwrap();
+    fs::write(
+        root.join("queries.sql"),
+        "-- @name relation_names\nSELECT relname FROM pg_class;\n\n-- @name missing_column\nSELECT relname,\n    no_such_column\nFROM pg_class;\n",
+    )
+    .unwrap();
+
+    let config = common::test_config(&root).await;
+
+    let workspace_root = config.workspace.root.clone().unwrap();
+    let workspace = Workspace::new(Arc::new(config), workspace_root).await;
+    let failures = workspace.typecheck().await;
+    fs::remove_dir_all(&root).unwrap();
+
+    let failures = failures.unwrap();
+    assert_eq!(failures.len(), 1);
+    assert!(failures[0].path.ends_with("queries.sql"));
+    assert_eq!(failures[0].line, 6);
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use std::{env, process, sync::Arc};
+
+use piqued::workspace::workspace::Workspace;
+use tower_lsp::lsp_types::{DiagnosticSeverity, Position, Range};
+
+const UNKNOWN_COLUMN_QUERIES: &str = include_str!("fixtures/unknown-column-queries.sql");
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn unknown_columns_are_warned_about() {
+    let mut config = common::test_config(&env::temp_dir()).await;
+    let client = common::connect(&config).await;
+
+    let schema = format!("piqued_unknown_columns_{}", process::id());
+    client
+        .batch_execute(&format!(
+            "
+            CREATE SCHEMA {schema};
+            CREATE TABLE {schema}.account (id int4 PRIMARY KEY, display_name text NOT NULL);
+            CREATE TABLE {schema}.invoice (
+                id int4 PRIMARY KEY,
+                account_id int4 NOT NULL,
+                total numeric NOT NULL
+            );
+            "
+        ))
+        .await
+        .unwrap();
+
+    config.postgres.schema = schema.clone();
+
+    let path = "file:///unknown-column-queries.sql";
+    let mut workspace = Workspace::new(Arc::new(config), env::temp_dir()).await;
+    workspace.patch_file(path.to_string(), UNKNOWN_COLUMN_QUERIES.to_string());
+    let diagnostics = workspace.get_diagnostics(path).await;
+
+    client
+        .batch_execute(&format!("DROP SCHEMA {schema} CASCADE;"))
+        .await
+        .unwrap();
+
+    let warnings: Vec<_> = diagnostics
+        .unwrap()
+        .into_iter()
+        .filter(|diagnostic| diagnostic.severity == Some(DiagnosticSeverity::WARNING))
+        .map(|diagnostic| (diagnostic.range, diagnostic.message))
+        .collect();
+
+    assert_eq!(
+        warnings,
+        vec![
+            (
+                Range::new(Position::new(4, 11), Position::new(4, 23)),
+                "Column \"dispaly_name\" doesn't exist on any table in scope".to_string()
+            ),
+            (
+                Range::new(Position::new(7, 13), Position::new(7, 19)),
+                "Column \"totl\" doesn't exist on \"i\"".to_string()
+            ),
+            (
+                Range::new(Position::new(11, 17), Position::new(11, 21)),
+                "Column \"nmae\" doesn't exist on any table in scope".to_string()
+            ),
+        ]
+    );
+}
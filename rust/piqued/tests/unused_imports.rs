@@ -0,0 +1,135 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::PathBuf,
+    process,
+    sync::{Arc, Mutex},
+};
+
+use piqued::{
+    codegen::{
+        codegen::{
+            CodeGenerationContext, CodeGenerator, ImportResult, QueryContext, SerializationResult,
+        },
+        ts::schema::TSGenerator,
+        utils::contains_identifier,
+    },
+    config::config::Config,
+    query::query::{Column, ColumnType, CompositeType, CustomType, EnumType},
+};
+
+// TypeScript, except that every table claims to need an import it never uses
+struct OverReporting {
+    inner: TSGenerator,
+    imported: Mutex<Vec<String>>,
+}
+
+impl CodeGenerator for OverReporting {
+    fn serialize_import(
+        &self,
+        ctx: &CodeGenerationContext,
+        path: &PathBuf,
+        identifiers: &Vec<String>,
+    ) -> ImportResult {
+        self.imported.lock().unwrap().extend(identifiers.clone());
+        self.inner.serialize_import(ctx, path, identifiers)
+    }
+
+    fn resolve_file_path(&self, ctx: &CodeGenerationContext, path: &PathBuf) -> String {
+        self.inner.resolve_file_path(ctx, path)
+    }
+
+    fn serialize_type(
+        &self,
+        ctx: &CodeGenerationContext,
+        type_: &CustomType,
+    ) -> SerializationResult {
+        self.inner.serialize_type(ctx, type_)
+    }
+
+    fn serialize_query(
+        &self,
+        ctx: &CodeGenerationContext,
+        query: &QueryContext,
+    ) -> SerializationResult {
+        self.inner.serialize_query(ctx, query)
+    }
+
+    fn serialize_table_prefix(
+        &self,
+        ctx: &CodeGenerationContext,
+        tables: &Vec<&String>,
+    ) -> Option<String> {
+        self.inner.serialize_table_prefix(ctx, tables)
+    }
+
+    fn serialize_table(&self, ctx: &CodeGenerationContext, table: &String) -> SerializationResult {
+        let mut res = self.inner.serialize_table(ctx, table);
+        res.requires_import.push("CompanyStatus".to_string());
+
+        res
+    }
+}
+
+#[test]
+fn identifiers_only_match_whole_words() {
+    let code = "const status: CompanyStatus = Company.spec.status;";
+
+    assert!(contains_identifier(code, "CompanyStatus"));
+    assert!(contains_identifier(code, "Company"));
+    assert!(!contains_identifier("let x: CompanyStatus;", "Company"));
+    assert!(!contains_identifier("let x: $Company_;", "Company"));
+}
+
+#[tokio::test]
+async fn unreferenced_imports_are_left_out() {
+    let root = env::temp_dir().join(format!("piqued_unused_imports_{}", process::id()));
+    fs::create_dir_all(&root).unwrap();
+
+    let mut config = Config::default();
+    config.workspace.root = Some(root.clone());
+    config.emit.type_file = "./types".to_string();
+    config.emit.table_file = Some("./tables".to_string());
+
+    let columns = vec![Column {
+        name: "id".to_string(),
+        column_type: ColumnType::Named("int4".to_string()),
+        type_oid: 0,
+        nullable: false,
+        generated: false,
+        references: None,
+    }];
+    let company = CustomType::Composite(CompositeType {
+        oid: 1,
+        name: "company".to_string(),
+        fields: columns.clone(),
+    });
+    let status = CustomType::Enum(EnumType {
+        oid: 2,
+        name: "company_status".to_string(),
+        values: vec!["active".to_string()],
+    });
+    let ctx = CodeGenerationContext::from_schema(
+        Arc::new(config),
+        HashMap::from([("company".to_string(), columns)]),
+        HashMap::from([
+            ("company".to_string(), Arc::new(company)),
+            ("company_status".to_string(), Arc::new(status)),
+        ]),
+    );
+
+    let generator = OverReporting {
+        inner: TSGenerator::new(),
+        imported: Mutex::new(vec![]),
+    };
+    ctx.generate_table_file(&generator).await.unwrap();
+    let tables = fs::read_to_string(root.join("tables.ts")).unwrap();
+
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(
+        *generator.imported.lock().unwrap(),
+        vec!["Company".to_string()]
+    );
+    assert!(!tables.contains("CompanyStatus"), "{}", tables);
+}
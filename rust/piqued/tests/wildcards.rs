@@ -0,0 +1,109 @@
+mod common;
+
+use std::{env, process, sync::Arc};
+
+use piqued::{
+    loose_parser::{
+        parse::ParserContext,
+        parse_cf::{ColumnExpression, Expression, LR1Kind},
+    },
+    parser::parser,
+    query::query::Query,
+};
+
+const WILDCARD_QUERIES: &str =
+    include_str!("../../../ts/test-workspace/src/data/wildcard-queries.sql");
+
+#[test]
+fn scoped_wildcards_are_reduced() {
+    let mut context = ParserContext::new(WILDCARD_QUERIES);
+    let parsed = context.parse();
+
+    let wildcards = parsed
+        .states
+        .iter()
+        .flat_map(|state| match &state.kind {
+            LR1Kind::SelectQuery(select_query) => select_query.columns.clone(),
+            _ => vec![],
+        })
+        .filter_map(|column| match column.as_ref() {
+            ColumnExpression::Unnamed(expr) => match expr.as_ref() {
+                Expression::ScopedWildcardLiteral(name) => Some(name.clone()),
+                _ => None,
+            },
+            ColumnExpression::Named(..) => None,
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(wildcards, vec!["c", "webhook_address"]);
+}
+
+#[tokio::test]
+#[ignore = "needs a database"]
+async fn scoped_wildcards_expand_to_relation_columns() {
+    let mut config = common::test_config(&env::temp_dir()).await;
+    let client = common::connect(&config).await;
+
+    let schema = format!("piqued_wildcards_{}", process::id());
+    client
+        .batch_execute(&format!(
+            "
+            CREATE SCHEMA {schema};
+            CREATE TABLE {schema}.company (id int4 PRIMARY KEY, legal_name text NOT NULL);
+            CREATE TABLE {schema}.webhook_address (
+                id int4 PRIMARY KEY,
+                owner_company_id int4 NOT NULL,
+                callback_address text NOT NULL
+            );
+            "
+        ))
+        .await
+        .unwrap();
+
+    config.postgres.schema = schema.clone();
+    let query = Query::new(Arc::new(config)).await.unwrap();
+
+    let parsed = parser::load_file(WILDCARD_QUERIES).unwrap();
+    let mut probed = vec![];
+    for statement in parsed.statements.iter() {
+        let prepared =
+            parser::get_prepared_statement(statement, &parsed.tokens, WILDCARD_QUERIES, || {
+                "query".to_string()
+            })
+            .unwrap();
+        probed.push(query.probe_type(&prepared).await);
+    }
+
+    client
+        .batch_execute(&format!("DROP SCHEMA {schema} CASCADE;"))
+        .await
+        .unwrap();
+
+    let columns = probed
+        .into_iter()
+        .map(|probed| {
+            let probed = probed.unwrap();
+            probed
+                .column_names
+                .into_iter()
+                .zip(probed.column_nullable)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        columns,
+        vec![
+            vec![
+                ("id".to_string(), false),
+                ("legal_name".to_string(), false),
+                ("callback_address".to_string(), true),
+            ],
+            vec![
+                ("id".to_string(), false),
+                ("owner_company_id".to_string(), false),
+                ("callback_address".to_string(), false),
+            ],
+        ]
+    );
+}
@@ -1,4 +1,4 @@
-export { Cursor, EntityQueries, Query, QueryExecutor, QueryExecutors } from "./types";
+export { Cursor, EntityQueries, Query, QueryExecutor, QueryExecutors, memoize, parseRow } from "./types";
 export { PiquedUpgradeControl, PiquedUpgradeInstance } from "./control";
 export { ClientOptions, SmartClient } from "./smart-client";
 export { InterpolatedExpression, Label, TableExpression, BinaryOperation, ColumnExpression as ColumnBuilder, Expression, FunctionOperation, LiteralExpression, Op, StructuredExpression, TableBuilder, UnaryOperation, serializeExpression, label } from "./query-builder/expression-builder"
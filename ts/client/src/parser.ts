@@ -169,9 +169,9 @@ export const parseArray = <OO, OA>(spec: ResultSpec<OO>, row: any[]): OA => {
 export const parseObject = <OO>(spec: ResultSpec<OO>, row: any): OO => {
     return spec.reduce((acc, [name, parseSpec]) => {
         if (parseSpec === undefined) {
-            acc[name] = row[name];
+            acc[name as keyof OO] = row[name];
         } else {
-            acc[name] = parse(parseSpec, row[name]);
+            acc[name as keyof OO] = parse(parseSpec, row[name]);
         }
 
         return acc;
@@ -21,6 +21,10 @@ export class SmartClient {
         this.active = true;
     }
 
+    public get inTransaction() {
+        return this.txDepth > 0;
+    }
+
     public async query<T extends QueryResultRow>(query: string, values?: any[]): Promise<QueryResult<T>> {
         if (this.active === false) {
             throw new Error("This client is in a transaction. Please do not use it until the transaction completes.");
@@ -13,13 +13,20 @@ export type ParseSpec =
     | CustomParseSpec
     ;
 
-export type ResultSpec<OO> = [name: keyof OO, spec: ParseSpec | undefined][]
+// Names aren't tied to `keyof OO`, since scalarized queries output the column's value directly
+export type ResultSpec<OO> = [name: string, spec: ParseSpec | undefined][]
 
 export type Query<IA extends any[], IO, OA, OO> = {
     name: string;
     query: string;
     params: (keyof IO)[];
     spec: ResultSpec<OO>;
+    // Each row is just the value of the query's only column, rather than an object
+    scalar?: boolean;
+    // Set by `@cache ttl=<seconds>`. Results are memoized per executor, keyed by the parameters, but
+    // never inside a transaction. Nothing invalidates them early, so it's on the user to only cache
+    // queries that can be stale.
+    cache?: { ttl: number };
 
     _brand: {
         inputArray: IA;
@@ -47,78 +54,128 @@ export type Cursor<OA, OO> = {
         Promise<Partialify<Partial, OO>[]>;
 }
 
+// Parses a row returned by `client.query` into the query's output object
+export const parseRow = <IA extends any[], IO, OA, OO>(query: Query<IA, IO, OA, OO>, row: any): OO => {
+    const parsed = parseObject<any>(query.spec, row);
+    return query.scalar ? parsed[query.spec[0][0]] : parsed;
+};
+
+// Memoizes promises by key for `ttl` seconds. Failures are dropped straight away, rather than
+// sticking around until the ttl is up.
+const ttlCache = (ttl: number) => {
+    const cache = new Map<string, { expires: number, result: Promise<unknown> }>();
+
+    return <T>(key: string, run: () => Promise<T>): Promise<T> => {
+        const cached = cache.get(key);
+        if (cached && cached.expires > Date.now()) {
+            return cached.result as Promise<T>;
+        }
+
+        const result = run();
+        cache.set(key, { expires: Date.now() + ttl * 1000, result });
+        result.catch(() => cache.delete(key));
+
+        return result;
+    };
+};
+
+// Wraps the generated runner of a `@cache ttl=<seconds>` query, keyed by its serialized parameters.
+// A transaction can see rows nobody else can yet, so runs inside one skip the cache entirely.
+export const memoize = <A extends unknown[], R>(
+    ttl: number,
+    run: (client: SmartClient, ...args: A) => Promise<R>,
+): ((client: SmartClient, ...args: A) => Promise<R>) => {
+    const cached = ttlCache(ttl);
+
+    return (client, ...args) => client.inTransaction
+        ? run(client, ...args)
+        : cached(JSON.stringify(args), () => run(client, ...args));
+};
+
 export const QueryExecutor = <IA extends any[], IO, OA, OO>(
     query: Query<IA, IO, OA, OO>,
     pool: Pool
-): QueryExecutor<IA, IO, OA, OO> => (args) => {
-    const argsAsArray: unknown[] =
-        Array.isArray(args) ? args :
-        query.params.map((param) => args[param]);
-
-    const q = <T>(fn: (client: SmartClient) => Promise<T>) =>
-        async <IS_PARTIAL extends boolean = false>(
-            client?: SmartClient
-        ): Promise<IS_PARTIAL extends true ? Partial<T> : T> => {
-            const foundClient = client ?? getCurrentClient();
-            if (foundClient) {
-                return await fn(foundClient);
-            }
-
-            const freshClient = await pool.connect();
-            using smartClient = new SmartClient(freshClient);
-
-            return await fn(smartClient);
+): QueryExecutor<IA, IO, OA, OO> => {
+    const cached = query.cache ? ttlCache(query.cache.ttl) : undefined;
+
+    return (args) => {
+        const argsAsArray: unknown[] =
+            Array.isArray(args) ? args :
+            query.params.map((param) => args[param]);
+
+        const q = <T>(method: keyof Cursor<OA, OO>, fn: (client: SmartClient) => Promise<T>) =>
+            async <IS_PARTIAL extends boolean = false>(
+                client?: SmartClient
+            ): Promise<IS_PARTIAL extends true ? Partial<T> : T> => {
+                const foundClient = client ?? getCurrentClient();
+                const run = async () => {
+                    if (foundClient) {
+                        return await fn(foundClient);
+                    }
+
+                    const freshClient = await pool.connect();
+                    using smartClient = new SmartClient(freshClient);
+
+                    return await fn(smartClient);
+                };
+
+                if (!cached || foundClient?.inTransaction) {
+                    return await run();
+                }
+
+                return await cached(`${method}:${JSON.stringify(argsAsArray)}`, run);
+            };
+
+        const result: Cursor<OA, OO> =  {
+            optTuple: q("optTuple", async (client) => {
+                const result = await client.queryArray(query.query, argsAsArray);
+                if (result.rows.length === 0) {
+                    return undefined;
+                }
+
+                return parseArray<OO, OA>(query.spec, result.rows[0]);
+            }),
+
+            oneTuple: q("oneTuple", async (client) => {
+                const result = await client.queryArray(query.query, argsAsArray);
+                if (result.rows.length === 0) {
+                    throw new Error("No results");
+                }
+
+                return parseArray<OO, OA>(query.spec, result.rows[0]);
+            }),
+
+            manyTuples: q("manyTuples", async (client) => {
+                const result = await client.queryArray(query.query,  argsAsArray );
+                return result.rows.map((row) => parseArray<OO, OA>(query.spec, row));
+            }),
+
+            opt: q("opt", async (client) => {
+                const result = await client.query(query.query, argsAsArray);
+                if (result.rows.length === 0) {
+                    return undefined;
+                }
+
+                return parseRow(query, result.rows[0]);
+            }),
+
+            one: q("one", async (client) => {
+                const result = await client.query(query.query, argsAsArray);
+                if (result.rows.length === 0) {
+                    throw new Error("No results");
+                }
+
+                return parseRow(query, result.rows[0]);
+            }),
+
+            many: q("many", async (client) => {
+                const result = await client.query(query.query, argsAsArray);
+                return result.rows.map((row) => parseRow(query, row));
+            }),
         };
 
-    const result: Cursor<OA, OO> =  {
-        optTuple: q(async (client) => {
-            const result = await client.queryArray(query.query, argsAsArray);
-            if (result.rows.length === 0) {
-                return undefined;
-            }
-
-            return parseArray<OO, OA>(query.spec, result.rows[0]);
-        }),
-
-        oneTuple: q(async (client) => {
-            const result = await client.queryArray(query.query, argsAsArray);
-            if (result.rows.length === 0) {
-                throw new Error("No results");
-            }
-
-            return parseArray<OO, OA>(query.spec, result.rows[0]);
-        }),
-
-        manyTuples: q(async (client) => {
-            const result = await client.queryArray(query.query,  argsAsArray );
-            return result.rows.map((row) => parseArray<OO, OA>(query.spec, row));
-        }),
-
-        opt: q(async (client) => {
-            const result = await client.query(query.query, argsAsArray);
-            if (result.rows.length === 0) {
-                return undefined;
-            }
-
-            return parseObject<OO>(query.spec, result.rows[0]);
-        }),
-
-        one: q(async (client) => {
-            const result = await client.query(query.query, argsAsArray);
-            if (result.rows.length === 0) {
-                throw new Error("No results");
-            }
-
-            return parseObject<OO>(query.spec, result.rows[0]);
-        }),
-
-        many: q(async (client) => {
-            const result = await client.query(query.query, argsAsArray);
-            return result.rows.map((row) => parseObject<OO>(query.spec, row));
-        }),
+        return result;
     };
-
-    return result;
 }
 
 export interface QueryExecutor<IA extends any[], IO, OA, OO> {
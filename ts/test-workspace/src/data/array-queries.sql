@@ -0,0 +1,4 @@
+-- @name companies_by_names
+-- @params legal_names
+PREPARE companies_by_names (text[]) AS
+    SELECT id, ARRAY[legal_name, 'placeholder'] AS names FROM company WHERE legal_name = ANY($1);
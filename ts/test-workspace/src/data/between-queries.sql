@@ -0,0 +1,9 @@
+-- @name small_companies
+SELECT id, legal_name
+FROM company
+WHERE id BETWEEN 1 AND 10 AND legal_name <> '';
+
+-- @name company_outliers
+SELECT id, id NOT BETWEEN 10 AND 10 * 2 AS outlier
+FROM company
+WHERE length(legal_name) BETWEEN 1 AND 100 OR id = 0;
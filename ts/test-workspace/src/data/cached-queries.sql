@@ -0,0 +1,9 @@
+-- @name cached_company_count
+-- Counted often, but it's fine if it lags behind a little.
+-- @cache ttl=30
+SELECT count(*) AS total FROM company;
+
+-- @name company_by_id
+-- @cache
+-- @params id
+SELECT id, legal_name FROM company WHERE id = $1;
@@ -0,0 +1,8 @@
+-- @name company_webhooks_unconditioned
+-- Missing its ON clause, so every company is paired with every webhook address
+SELECT c.id, w.callback_address
+FROM company c JOIN webhook_address w;
+
+-- @name company_webhooks_using
+SELECT c.id, w.callback_address
+FROM company c INNER JOIN webhook_address w USING (id);
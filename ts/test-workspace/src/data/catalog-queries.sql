@@ -0,0 +1,4 @@
+-- @name tables_with_schemas
+SELECT c.relname, n.nspname
+FROM pg_class c LEFT JOIN pg_namespace n ON n.oid = c.relnamespace
+WHERE c.relkind = 'r';
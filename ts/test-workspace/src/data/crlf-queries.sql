@@ -0,0 +1,7 @@
+-- @name callback_owners
+-- Saved with Windows line endings
+SELECT w.id,
+    w.callback_address,
+    c.legal_name
+FROM webhook_address w
+JOIN company c ON c.id = w.owner_company_id;
@@ -0,0 +1,15 @@
+-- @name archive_company
+-- @params id
+WITH archived AS (
+    DELETE FROM company WHERE id = $1 RETURNING *
+)
+SELECT company, 1 FROM archived AS company;
+
+-- @name recent_company_webhooks
+WITH recent AS (
+    SELECT id, legal_name FROM company WHERE created_at > now() - interval '1 day'
+), webhooks AS (
+    SELECT owner_company_id, callback_address FROM webhook_address
+)
+SELECT recent.legal_name, webhooks.callback_address
+FROM recent JOIN webhooks ON webhooks.owner_company_id = recent.id;
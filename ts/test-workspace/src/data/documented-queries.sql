@@ -0,0 +1,6 @@
+-- @name documented_reflect
+-- Echoes its input back, with a friendly suffix.
+--
+-- Useful for checking that the connection is alive.
+-- @params input
+SELECT $1::text || ' from postgres!' AS output;
@@ -0,0 +1,11 @@
+-- @name utc_now
+SELECT CURRENT_TIMESTAMP AT TIME ZONE 'UTC' AS utc_now;
+
+-- @name company_exists
+SELECT FROM company LIMIT 1;
+
+-- @name company_count
+SELECT count(*) AS total FROM company;
+
+-- @name company_names
+SELECT company FROM company;
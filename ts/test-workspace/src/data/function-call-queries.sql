@@ -0,0 +1,9 @@
+-- @name company_name_stats
+-- @params name
+SELECT count(*), max(length(c.legal_name)) AS longest
+FROM company c
+WHERE lower(c.legal_name) = lower($1);
+
+-- @name webhook_callbacks
+SELECT coalesce(callback_address, 'none') AS callback, now()
+FROM webhook_address;
@@ -0,0 +1,7 @@
+-- @name upsert_company_returning
+-- Postgres 17 syntax, which the bundled grammar doesn't know about yet
+MERGE INTO company c
+USING (SELECT $1::int4 AS id, $2::text AS legal_name) s ON c.id = s.id
+WHEN MATCHED THEN UPDATE SET legal_name = s.legal_name
+WHEN NOT MATCHED THEN INSERT (id, legal_name) VALUES (s.id, s.legal_name)
+RETURNING merge_action(), c.id;
@@ -0,0 +1,7 @@
+-- @name class_by_oid
+-- @index pg_class_oid_index
+SELECT relname FROM pg_class WHERE oid = $1;
+
+-- @name class_by_name
+-- @index pg_class_oid_index
+SELECT oid FROM pg_class WHERE relname = $1;
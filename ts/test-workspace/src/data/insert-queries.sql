@@ -0,0 +1,7 @@
+-- @name create_company
+-- @params id, legal_name
+INSERT INTO company AS c (id, legal_name) VALUES ($1, $2)
+RETURNING c.id, legal_name;
+
+-- @name create_default_company
+INSERT INTO company DEFAULT VALUES RETURNING *;
@@ -0,0 +1,14 @@
+-- @name unnamed_companies
+SELECT id, legal_name IS NULL AS unnamed
+FROM company
+WHERE legal_name IS NOT NULL AND id > 0;
+
+-- @name company_flags
+SELECT (id > 10) IS TRUE, (id > 10) IS NOT TRUE, (id > 10) IS FALSE, (id > 10) IS NOT FALSE
+FROM company
+WHERE (id < 10) IS UNKNOWN OR (id < 10) IS NOT UNKNOWN;
+
+-- @name company_precedence
+SELECT id
+FROM company
+WHERE NOT id + 1 IS NULL;
@@ -0,0 +1,5 @@
+-- @name sibling_webhooks
+SELECT c.legal_name, w.callback_address, s.callback_address AS sibling_address
+FROM company c
+JOIN webhook_address w ON w.owner_company_id = c.id
+JOIN webhook_address s ON s.owner_company_id = c.id AND s.id <> w.id AND legal_name <> '';
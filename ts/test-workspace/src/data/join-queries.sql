@@ -0,0 +1,8 @@
+-- @name company_webhooks
+-- Companies with their webhook addresses, if any
+SELECT c.id, w.callback_address, coalesce(w.id, 0) AS webhook_id
+FROM company c LEFT JOIN webhook_address w ON w.owner_company_id = c.id;
+
+-- @name company_domain_addresses
+SELECT company_id, d.domain_name, a.address_id
+FROM company_domain_name d JOIN company_ordering_address a USING (company_id);
@@ -0,0 +1,17 @@
+-- @name json_field
+SELECT '{"a": {"b": 1}}'::jsonb -> 'a' AS field;
+
+-- @name json_field_text
+SELECT '{"a": "b"}'::jsonb ->> 'a' AS field;
+
+-- @name json_path
+SELECT '{"a": {"b": 1}}'::jsonb #> '{a,b}' AS field;
+
+-- @name json_path_text
+SELECT '{"a": {"b": 1}}'::jsonb #>> '{a,b}' AS field;
+
+-- @name json_contains
+SELECT '{"a": 1, "b": 2}'::jsonb @> '{"a": 1}'::jsonb AS contains, '{"a": 1}'::jsonb <@ '{"a": 1, "b": 2}'::jsonb AS contained;
+
+-- @name json_has_keys
+SELECT '{"a": 1}'::jsonb ? 'a' AS has_key, '{"a": 1}'::jsonb ?| array['a', 'b'] AS has_any, '{"a": 1}'::jsonb ?& array['a', 'b'] AS has_all;
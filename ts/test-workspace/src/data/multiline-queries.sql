@@ -0,0 +1,10 @@
+-- @name multiline_company
+-- Spans several lines
+SELECT
+    id,
+    legal_name
+FROM company
+WHERE id = $1;;
+
+-- @name after_multiline
+SELECT 'café' AS drink;
@@ -0,0 +1,10 @@
+-- @name webhook_owners
+-- Every webhook address, along with the company that owns it (if there is one)
+SELECT
+    w.id,
+    c.legal_name,
+    coalesce(c.legal_name, 'unowned') AS owner_name,
+    nullif(w.callback_address, '') AS callback_address,
+    greatest(c.id, 0) AS owner_id,
+    least(c.id, c.id) AS nullable_owner_id
+FROM webhook_address w LEFT JOIN company c ON c.id = w.owner_company_id;
@@ -0,0 +1,2 @@
+-- @name companies_per_name
+SELECT legal_name, count(*) AS total FROM company GROUP BY legal_name ORDER BY total DESC NULLS LAST, legal_name;
@@ -0,0 +1,3 @@
+-- @name company_by_optional_id
+-- @params id
+SELECT c.legal_name FROM company c WHERE $1 IS NULL OR c.id = $1;
@@ -0,0 +1,6 @@
+-- @name pages_at_least
+SELECT $1::int AS min_pages, relname FROM pg_class WHERE relpages > $1;
+
+-- @name pages_between
+-- @params min_pages kind
+SELECT relname FROM pg_class WHERE relpages >= $1 AND relkind = $2 AND relpages < $1 * 10;
@@ -0,0 +1,6 @@
+-- @name qualified_company
+SELECT company.id, c.legal_name
+FROM public.company JOIN public.company c ON c.id = company.id;
+
+-- @name create_qualified_company
+INSERT INTO public.company (id, legal_name) VALUES ($1, $2);
@@ -0,0 +1,2 @@
+-- @name company_names_quoted
+SELECT C.ID, "legal_name" AS "legalName" FROM Company C;
@@ -0,0 +1,4 @@
+-- @name visible_company_names
+-- Probed as the application's role, so that row-level security applies
+-- @role app_user
+SELECT legal_name FROM company;
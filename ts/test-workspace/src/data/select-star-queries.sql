@@ -0,0 +1,9 @@
+-- @name all_companies
+SELECT * FROM company;
+
+-- @name company_total
+SELECT count(*) FROM company;
+
+-- @name companies_with_callbacks
+SELECT c.*, w.callback_address
+FROM company c JOIN webhook_address w ON w.owner_company_id = c.id;
@@ -0,0 +1,4 @@
+-- @name company_and_webhook_labels
+SELECT id, legal_name AS label FROM company
+UNION ALL
+SELECT id, callback_address FROM webhook_address;
@@ -0,0 +1,4 @@
+-- @name renamed_company_names
+SELECT named.id, named.company_name
+FROM (SELECT id, legal_name AS company_name FROM company) AS named
+ORDER BY named.company_name;
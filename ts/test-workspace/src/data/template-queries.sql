@@ -0,0 +1,11 @@
+-- @name companies_by_id
+-- @xtemplate ids (uuid_generate_v4(), uuid_generate_v4())
+SELECT company FROM company WHERE id IN :ids;
+
+-- @name companies_by_uid
+-- @xarray uids uuid
+SELECT company FROM company WHERE uid IN :uids;
+
+-- @name companies_by_either_id
+-- @xarray ids uuid
+SELECT company FROM company WHERE id IN :ids OR uid NOT IN :ids;
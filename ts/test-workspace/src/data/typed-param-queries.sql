@@ -0,0 +1,11 @@
+-- @name classes_page
+-- @params page_size:int8
+SELECT relname FROM pg_class ORDER BY relname LIMIT $1;
+
+-- @name class_by_relname
+-- @params relname:text
+SELECT oid FROM pg_class WHERE relname = $1;
+
+-- @name class_by_namespace
+-- @params namespace relname:name
+SELECT oid FROM pg_class WHERE relnamespace = $1 AND relname = $2;
@@ -0,0 +1,6 @@
+-- @name companies_with_webhooks
+SELECT c.*, w.callback_address
+FROM company c LEFT JOIN webhook_address w ON w.owner_company_id = c.id;
+
+-- @name all_webhook_addresses
+SELECT webhook_address.* FROM webhook_address;